@@ -1,53 +1,67 @@
-use pkcore::analysis::store::db::headsup_preflop_result::HUPResult;
-use pkcore::analysis::store::db::sqlite::Sqlable;
-use pkcore::arrays::matchups::masked::Masked;
-use pkcore::util::csv::distinct_shus_from_csv_as_masked_vec;
-use pkcore::util::terminal::Terminal;
-use rand::prelude::SliceRandom;
+use clap::Parser;
+use pkcore::analysis::store::db::hup_audit::{HupAudit, Recompute};
+use pkcore::PKError;
 use rusqlite::Connection;
 
-/// `cargo run --example audit`
-fn main() {
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(short = 'd', long, default_value = "generated/hups.db")]
+    db: String,
+
+    /// Calculate this many random matchups again and compare them with what's stored.
+    #[clap(short = 's', long)]
+    sample: Option<usize>,
+
+    /// Calculate every matchup again. This takes as long as generating them did.
+    #[clap(short = 'a', long)]
+    all: bool,
+
+    /// Don't look for gaps.
+    #[clap(short = 'g', long)]
+    skip_gaps: bool,
+
+    /// Write the full report as JSON to this file.
+    #[clap(short = 'j', long)]
+    json: Option<String>,
+
+    /// Fix what was found. Anything that can't be trusted is deleted so that `hup_job`
+    /// calculates it again.
+    #[clap(short = 'r', long)]
+    repair: bool,
+}
+
+/// Audits the heads up results in a `SQLite` file, and optionally repairs them.
+///
+/// `cargo run --release --example audit -- -d generated/hups.db -s 100 -j audit.json`
+fn main() -> Result<(), PKError> {
     env_logger::init();
-    let distinct = distinct_shus_from_csv_as_masked_vec();
+    let args = Args::parse();
 
-    let conn = Connection::open("generated/hups.db").unwrap();
-    loop {
-        read_input(&conn, &distinct);
+    let recompute = if args.all {
+        Recompute::All
+    } else {
+        args.sample.map_or(Recompute::None, Recompute::Sample)
+    };
+    let mut audit = HupAudit::new().recompute(recompute);
+    if args.skip_gaps {
+        audit = audit.skip_gaps();
     }
-}
 
-fn read_input(conn: &Connection, distinct: &Vec<Masked>) {
-    let mut x = 0usize;
-    let i = Terminal::receive_usize("How many audits? ");
-    println!("Auditing {i} hands.");
-
-    while x < i {
-        let masked = random(&distinct).unwrap();
-        match HUPResult::select(&conn, &masked.shu) {
-            None => println!("{x} - Not in DB: {}", &masked.shu),
-            Some(actual) => {
-                println!("{x} - Auditing: {}", &masked.shu);
-                let expected = HUPResult::from(&masked.shu);
-                if expected == actual {
-                    println!("   {} passes!", &masked);
-                } else {
-                    println!("   {} fails audit", &masked);
-                    println!("      expected: {expected}");
-                    println!("      actual: {actual}");
-                    assert_eq!(expected, actual);
-                }
-            }
-        };
-        if HUPResult::exists(&conn, &masked.shu) {
-        } else {
-            println!("Not in DB: {}", &masked.shu);
-        }
-        x = x + 1;
+    let conn = Connection::open(&args.db).map_err(|_| PKError::SqlError)?;
+    let report = audit.run(&conn)?;
+    for issue in &report.issues {
+        println!("{issue}");
     }
-}
+    println!("{}: {report}", args.db);
 
-fn random(distinct: &Vec<Masked>) -> Option<&Masked> {
-    let mut rng = rand::thread_rng();
-    distinct.choose(&mut rng)
+    if let Some(path) = args.json {
+        std::fs::write(&path, report.to_json()?).map_err(|_| PKError::Fubar)?;
+        println!("Report written to {path}");
+    }
+
+    if args.repair && !report.is_clean() {
+        println!("Repaired: {}", report.repair(&conn)?);
+    }
+    Ok(())
 }
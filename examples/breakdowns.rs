@@ -0,0 +1,39 @@
+use clap::Parser;
+use pkcore::analysis::store::breakdown::Breakdown;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// The breakdown file to replay.
+    #[clap(short = 'f', long, default_value = "docs/hands/DNegsWSOP2022/2022-01.md")]
+    file: String,
+
+    /// Work out the odds before the flop as well. Slow.
+    #[clap(short = 'p', long)]
+    preflop: bool,
+}
+
+/// Replays the hands written down in one of the breakdown files under `docs/hands`, with the
+/// odds at every street for everybody whose cards we know.
+///
+/// `cargo run --release --example breakdowns`
+///
+/// `cargo run --release --example breakdowns -- -p -f docs/hands/DNegsWSOP2022/2022-01.md`
+fn main() {
+    let args = Args::parse();
+
+    let hands = match Breakdown::from_file(&args.file) {
+        Ok(hands) => hands,
+        Err(e) => {
+            println!("Unable to read {}: {e}", args.file);
+            return;
+        }
+    };
+
+    for hand in hands {
+        match hand.annotate(args.preflop) {
+            Ok(s) => println!("{s}"),
+            Err(e) => println!("Unable to replay {}: {e}", hand.title.unwrap_or_default()),
+        }
+    }
+}
@@ -10,10 +10,14 @@ use std::str::FromStr;
 #[clap(author, version, about, long_about = None)]
 struct Args {
     #[clap(short = 'd', long)]
-    dealt: String,
+    dealt: Option<String>,
 
     #[clap(short = 'b', long)]
-    board: String,
+    board: Option<String>,
+
+    /// The whole hand in game notation, like "HE: 6♠ 6♥ 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ 8♠"
+    #[clap(short = 'g', long)]
+    game: Option<String>,
 
     #[clap(short = 'n', long)]
     nuts: bool,
@@ -113,18 +117,33 @@ struct Args {
 /// cargo run --example calc -- -d "8♦ 5♦ K♦ J♥ 2♠ 2♥" -b "9♥ 2♦ K♥ 4♥ J♠" HSP S09E05 Tilly/Hultman
 /// cargo run --example calc -- -d "J♥ J♦ A♠ K♦ T♣ 9♣" -b "7♦ K♠ 2♥ 7♣ A♦" HSP S09E05 Liu/Tilly/Menon
 /// cargo run --example calc -- -d "7s 6c js 4d" -b "8h 5h 9d" -- Hand with KDog
+///
+/// ## Game Notation
+///
+/// The whole hand can also be passed in with the compact notation from the serialization epic,
+/// names, dead cards and all:
+///
+/// cargo run --example calc -- -g "HE: Negreanu: 6♠ 6♥ Hansen: 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ 8♠"
+/// cargo run --example calc -- -g "HE: A♠ K♥ 8♦ 6♣ - A♣ 8♥ 7♥ DEAD: 9♠"
 fn main() -> Result<(), PKError> {
     let now = std::time::Instant::now();
     env_logger::init();
 
     let args = Args::parse();
 
-    let game = Game::new(HoleCards::from_str(&*args.dealt)?, Board::from_str(&*args.board)?);
+    let game = match (args.game, args.dealt, args.board) {
+        (Some(notation), _, _) => Game::from_str(&notation)?,
+        (None, Some(dealt), Some(board)) => Game::new(HoleCards::from_str(&dealt)?, Board::from_str(&board)?),
+        _ => {
+            eprintln!("Either pass in the game (-g) or the dealt cards (-d) and the board (-b).");
+            return Err(PKError::NotDealt);
+        }
+    };
 
     println!("{}", game);
 
     println!();
-    let flop_eval = FlopEval::try_from(game.clone()).unwrap();
+    let flop_eval = FlopEval::try_from(game.clone())?;
     println!("{}", flop_eval);
 
     if args.nuts {
@@ -151,9 +170,5 @@ fn main() -> Result<(), PKError> {
 }
 
 fn command(game: Game) -> String {
-    format!(
-        "cargo run --example calc -- -d  \"{}\" -b \"{}\"",
-        game.hands.cards(),
-        game.board.cards()
-    )
+    format!("cargo run --example calc -- -g \"{game}\"")
 }
@@ -3,7 +3,7 @@ use rusqlite::Connection;
 
 fn main() {
     let conn = Connection::open("generated/hups.db").unwrap();
-    let distinct = HUPResult::distinct_remaining(&conn);
+    let distinct = HUPResult::distinct_remaining(&conn).unwrap();
 
     for shu in distinct.clone() {
         println!("{shu}");
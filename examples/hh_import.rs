@@ -0,0 +1,37 @@
+use clap::Parser;
+use pkcore::analysis::store::db::histories::{HandQuery, StoredHand};
+use pkcore::analysis::store::db::sqlite::Sqlable;
+use pkcore::play::Position6Max;
+use rusqlite::Connection;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// A directory of Pluribus ACPC logs.
+    #[clap(short = 'p', long, default_value = "data/pluribus/raw")]
+    pluribus: String,
+
+    #[clap(short = 't', long, default_value = "generated/hands.db")]
+    to: String,
+}
+
+/// Loads every Pluribus hand into a hand history database, and then asks it a question.
+///
+/// `cargo run --release --example hh_import -- -t "generated/hands.db"`
+fn main() {
+    let args = Args::parse();
+
+    let conn = Connection::open(&args.to).unwrap();
+    StoredHand::create_table(&conn).unwrap();
+    let count = StoredHand::import_pluribus_logs(&conn, &args.pluribus).unwrap();
+    println!("Imported {count} hands into {}", args.to);
+
+    let query = HandQuery::default()
+        .player("Pluribus")
+        .position(Position6Max::BTN)
+        .three_bet(true)
+        .went_to_showdown(true);
+    for stored in StoredHand::query(&conn, &query).unwrap() {
+        println!("{}\n", stored.hand);
+    }
+}
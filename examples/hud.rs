@@ -0,0 +1,44 @@
+use clap::Parser;
+use pkcore::analysis::hud::{Hud, HudFilter};
+use pkcore::play::Position6Max;
+use strum::IntoEnumIterator;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Only count hands played from these positions, like `BTN`.
+    #[clap(short = 'p', long)]
+    position: Vec<String>,
+
+    /// Only count hands from these sessions, like `102`.
+    #[clap(short = 's', long)]
+    session: Vec<usize>,
+}
+
+/// Prints the HUD stats for every player in the Pluribus experiments, over every hand in
+/// `data/pluribus/raw`.
+///
+/// `cargo run --release --example hud`
+///
+/// `cargo run --release --example hud -- -p BTN -p CO -s 102`
+fn main() {
+    let args = Args::parse();
+    let mut filter = HudFilter::default();
+    for position in &args.position {
+        match Position6Max::iter().find(|p| p.to_string().eq_ignore_ascii_case(position)) {
+            Some(position) => filter = filter.position(position),
+            None => {
+                println!("Unknown position {position}");
+                return;
+            }
+        }
+    }
+    for session in args.session {
+        filter = filter.session(session);
+    }
+
+    match Hud::from_pluribus_logs("data/pluribus/raw", filter) {
+        Ok(hud) => print!("{hud}"),
+        Err(e) => println!("{e}"),
+    }
+}
@@ -0,0 +1,55 @@
+use clap::Parser;
+use pkcore::analysis::store::db::hup_job::HupJob;
+use rusqlite::Connection;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(short = 'd', long, default_value = "generated/hups.db")]
+    db: String,
+
+    /// Which shard this process works on, starting at 0.
+    #[clap(short = 's', long, default_value_t = 0)]
+    shard: usize,
+
+    /// How many shards the work is split into.
+    #[clap(short = 'n', long, default_value_t = 1)]
+    shards: usize,
+
+    #[clap(short = 'b', long, default_value_t = HupJob::DEFAULT_BATCH_SIZE)]
+    batch: usize,
+
+    /// Stop after calculating this many results.
+    #[clap(short = 'l', long)]
+    limit: Option<usize>,
+}
+
+/// Works through a shard of the distinct heads up preflop matchups, saving the results as it
+/// goes. Kill it whenever you like, and start it again with the same arguments to pick up where
+/// it left off.
+///
+/// `cargo run --release --example hup_job -- -s 0 -n 4`
+fn main() {
+    env_logger::init();
+    let args = Args::parse();
+
+    let conn = Connection::open(&args.db).unwrap();
+    HupJob::prepare(&conn).unwrap();
+
+    let mut job = HupJob::distinct(args.shard, args.shards)
+        .unwrap()
+        .batch_size(args.batch);
+    if let Some(limit) = args.limit {
+        job = job.limit(limit);
+    }
+
+    let progress = job.run(&conn, |progress| println!("{progress}")).unwrap();
+    println!("Finished {progress}");
+
+    for checkpoint in HupJob::checkpoints(&conn).unwrap() {
+        println!(
+            "shard {}/{}: {}/{} as of {}",
+            checkpoint.shard, checkpoint.shards, checkpoint.done, checkpoint.total, checkpoint.updated_at
+        );
+    }
+}
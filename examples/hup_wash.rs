@@ -202,7 +202,7 @@ fn main() {
     HUPResult::create_table(&conn).unwrap();
 
     let mut hs = HashSet::new();
-    let hups = HUPResult::select_all(&conn);
+    let hups = HUPResult::select_all(&conn).unwrap();
     for (i, hup) in hups.iter().enumerate() {
         hs.insert(hup);
         println!("{i} {hup}");
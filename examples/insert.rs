@@ -38,7 +38,7 @@ fn read_input(conn: &Connection, distinct: &mut HashSet<Masked>) {
             } else {
                 match HUPResult::insert(&conn, &hupr) {
                     Ok(_) => {
-                        let remaining = HUPResult::distinct_remaining(&conn).len();
+                        let remaining = HUPResult::distinct_remaining(&conn).unwrap().len();
                         println!("... inserted... {remaining} remaining");
                     }
                     Err(e) => {
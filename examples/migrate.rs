@@ -0,0 +1,47 @@
+use clap::Parser;
+use pkcore::analysis::store::bcm::binary_card_map::SevenFiveBCM;
+use pkcore::analysis::store::db::headsup_preflop_result::HUPResult;
+use pkcore::analysis::store::storage::{BackendSpec, MigrationReport, Record};
+use pkcore::PKError;
+use std::str::FromStr;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// What's being copied: `hup` for heads up results, or `bcm` for the binary card map.
+    #[clap(short = 'r', long, default_value = "hup")]
+    record: String,
+
+    /// Where it's coming from, like `sqlite:data/hups.db`.
+    #[clap(short = 'f', long)]
+    from: String,
+
+    /// Where it's going, like `bin:generated/hups.bin` or `sled:generated/sleigh`.
+    #[clap(short = 't', long)]
+    to: String,
+}
+
+/// Copies precomputed results from one storage backend to another, and checks that the counts
+/// and checksums match on the other side.
+///
+/// `cargo run --release --example migrate -- -f sqlite:data/hups.db -t bin:generated/hups.bin`
+fn main() -> Result<(), PKError> {
+    env_logger::init();
+    let args = Args::parse();
+
+    let from = BackendSpec::from_str(&args.from)?;
+    let to = BackendSpec::from_str(&args.to)?;
+    let report = match args.record.as_str() {
+        "hup" => migrate::<HUPResult>(&from, &to)?,
+        "bcm" => migrate::<SevenFiveBCM>(&from, &to)?,
+        record => return Err(PKError::StorageError(format!("unknown record {record}"))),
+    };
+    println!("{from} -> {to}: {report}");
+    Ok(())
+}
+
+fn migrate<T: Record + 'static>(from: &BackendSpec, to: &BackendSpec) -> Result<MigrationReport, PKError> {
+    let source = from.open::<T>()?;
+    let mut target = to.open::<T>()?;
+    MigrationReport::migrate(source.as_ref(), target.as_mut())
+}
@@ -0,0 +1,50 @@
+use clap::Parser;
+use pkcore::analysis::store::db::multiway_lookup::MultiwayLookup;
+use pkcore::analysis::store::db::multiway_preflop_result::MultiwayResult;
+use pkcore::analysis::store::db::sqlite::Sqlable;
+use pkcore::arrays::two::Two;
+use pkcore::play::hole_cards::HoleCards;
+use rusqlite::Connection;
+use std::str::FromStr;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(short = 'd', long, default_value = "generated/multiway.db")]
+    db: String,
+
+    /// A file of matchups to calculate, one per line, like `A♠ A♥ K♠ K♥ Q♠ Q♥`.
+    #[clap(short = 'f', long)]
+    file: Option<String>,
+
+    /// A matchup to look up, calculating it if it isn't there yet.
+    hands: Option<String>,
+}
+
+/// Fills the multiway preflop table, and looks up matchups in it.
+///
+/// `cargo run --release --example multiway -- -f matchups.txt "A♠ A♥ K♦ K♣ Q♦ Q♥"`
+fn main() {
+    env_logger::init();
+    let args = Args::parse();
+
+    let conn = Connection::open(&args.db).unwrap();
+    MultiwayResult::create_table(&conn).unwrap();
+
+    if let Some(file) = args.file {
+        let matchups: Vec<Vec<Two>> = std::fs::read_to_string(file)
+            .unwrap()
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| HoleCards::from_str(line).unwrap().iter().copied().collect())
+            .collect();
+        let saved = MultiwayResult::generate(&conn, &matchups, |result| println!("{result}")).unwrap();
+        println!("Saved {saved} new results");
+    }
+
+    if let Some(hands) = args.hands {
+        let hands: Vec<Two> = HoleCards::from_str(&hands).unwrap().iter().copied().collect();
+        let odds = MultiwayLookup::preload(&conn).unwrap().lookup(&conn, &hands).unwrap();
+        println!("{odds}");
+    }
+}
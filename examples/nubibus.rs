@@ -1,12 +1,31 @@
 use pkcore::analysis::store::nubibus::pluribus::Pluribus;
 use pkcore::analysis::store::nubibus::Nubibus;
-use std::str::FromStr;
 
+/// Replays every hand in the Pluribus logs, and reports every one where the final stacks don't
+/// match the winnings in the log.
+///
+/// The logs split odd chips in half, so a winner can be down for `112.5`. `Pluribus` hands those
+/// odd chips out in seat order, the same way `Pot::split()` does, so split pots should match too.
+/// Anything that shows up here is a hand that the replay really does get wrong.
+///
+/// `cargo run --release --example nubibus`
 fn main() {
-    let row = "STATE:52:fr200fffr1100c/r2225c/cc/r5600f:8h3c|7d6d|5dTd|7c7s|Ah4c|Ts9d/3s6c8c/Ad/8s:-50|2275|0|-2225|0|0:Bill|Pluribus|MrWhite|Gogo|Budd|Eddie";
+    let logs = Pluribus::logs("data/pluribus/raw").expect("data/pluribus/raw");
 
-    let plur = Pluribus::from_str(row);
-    let nub = Nubibus::from_pluribus(&plur.as_ref().unwrap());
-    assert!(plur.is_ok());
-    println!("{nub}");
+    let mut total = 0;
+    let mut mismatched = 0;
+    for (_, path) in logs {
+        let path = path.to_string_lossy().to_string();
+        match Nubibus::verify_log(&path) {
+            Ok((checked, mismatches)) => {
+                total += checked;
+                mismatched += mismatches.len();
+                for mismatch in mismatches {
+                    println!("{path} {mismatch}");
+                }
+            }
+            Err(e) => println!("{path}: {e}"),
+        }
+    }
+    println!("{mismatched} of {total} hands don't match their winnings.");
 }
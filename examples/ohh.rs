@@ -0,0 +1,15 @@
+use pkcore::analysis::store::ohh::OpenHandHistory;
+
+/// Converts every Pluribus session in `data/pluribus/raw` into Open Hand History files in
+/// `generated/ohh`.
+///
+/// The ACPC logs don't have dates, so every hand is stamped with the day the sessions were
+/// played.
+///
+/// `cargo run --release --example ohh`
+fn main() {
+    match OpenHandHistory::convert_pluribus_logs("data/pluribus/raw", "generated/ohh", "2019-07-11T12:00:00Z") {
+        Ok(count) => println!("Converted {count} hands to generated/ohh"),
+        Err(e) => println!("{e}"),
+    }
+}
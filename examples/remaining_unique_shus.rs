@@ -13,7 +13,7 @@ fn main() -> Result<(), PKError> {
         return Err(PKError::SqlError);
     };
 
-    let hups = HUPResult::select_all(&conn);
+    let hups = HUPResult::select_all(&conn).map_err(|_| PKError::SqlError)?;
     println!("{} shus processed", hups.len());
 
     for hup in hups {
@@ -0,0 +1,50 @@
+use clap::Parser;
+use pkcore::analysis::store::bcm::binary_card_map::SevenFiveBCM;
+use pkcore::analysis::store::db::headsup_preflop_result::HUPResult;
+use pkcore::analysis::store::db::histories::StoredHand;
+use pkcore::analysis::store::db::multiway_preflop_result::MultiwayResult;
+use pkcore::analysis::store::db::schema::{Schema, Versioned};
+use rusqlite::Connection;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    /// Upgrade every store in the file to the latest version, rather than just reporting on it.
+    #[clap(short = 'u', long)]
+    upgrade: bool,
+
+    /// The `SQLite` files to look at.
+    #[clap(required = true)]
+    dbs: Vec<String>,
+}
+
+/// Reports which schema version each store in a `SQLite` file is at, and upgrades them in place
+/// with `-u`. Stores that aren't in a file are skipped rather than created.
+///
+/// `cargo run --example schema -- -u data/hups.db data/dhups.db`
+fn main() {
+    env_logger::init();
+    let args = Args::parse();
+
+    for db in &args.dbs {
+        let conn = Connection::open(db).unwrap();
+        println!("{db}");
+        report::<HUPResult>(&conn, args.upgrade);
+        report::<SevenFiveBCM>(&conn, args.upgrade);
+        report::<MultiwayResult>(&conn, args.upgrade);
+        report::<StoredHand>(&conn, args.upgrade);
+    }
+}
+
+fn report<T: Versioned>(conn: &Connection, upgrade: bool) {
+    let (version, detected) = Schema::version::<T>(conn).unwrap();
+    if version == 0 {
+        return;
+    }
+    if upgrade {
+        println!("  {}", Schema::upgrade::<T>(conn).unwrap());
+    } else {
+        let detected = if detected { " (detected)" } else { "" };
+        println!("  {}: v{version}{detected} of v{}", T::STORE, Schema::latest::<T>());
+    }
+}
@@ -0,0 +1,39 @@
+use clap::Parser;
+use pkcore::web::server::EquityServer;
+use pkcore::web::service::EquityService;
+use pkcore::PKError;
+use std::sync::Arc;
+
+#[derive(Parser, Debug)]
+#[clap(author, version, about, long_about = None)]
+struct Args {
+    #[clap(short = 'd', long, default_value = "generated/hups.db")]
+    db: String,
+
+    #[clap(short = 'p', long, default_value_t = 8080)]
+    port: u16,
+}
+
+/// Serves hand evaluations, odds, heads up lookups, range equity and outs as JSON on localhost.
+/// The socket's up straight away, and `/ready` turns to `200` once the tables are in memory.
+/// Files from an older version of the schema need `cargo run --example schema -- -u` first.
+///
+/// `cargo run --release --example serve -- -d generated/hups.db -p 8080`
+///
+/// ```txt
+/// curl localhost:8080/ready
+/// curl -d '{"hero": "A♠ A♥", "villain": "K♦ K♣"}' localhost:8080/hup
+/// curl -d '{"game": "HE: 6♠ 6♥ 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠"}' localhost:8080/odds
+/// curl -d '{"hand": "J♠ T♠", "range": "QQ+,AK", "board": "9♠ 8♦ 2♣"}' localhost:8080/range
+/// ```
+fn main() -> Result<(), PKError> {
+    env_logger::init();
+    let args = Args::parse();
+
+    let service = Arc::new(EquityService::open(&args.db)?);
+    service.preload_in_background();
+    let server = EquityServer::bind(service, args.port)?;
+    println!("Listening on http://127.0.0.1:{}", server.port()?);
+    server.serve();
+    Ok(())
+}
@@ -0,0 +1,17 @@
+use pkcore::analysis::store::uci::UciReport;
+
+/// Checks every hand in the UCI poker hand dataset against our lookup tables.
+///
+/// `cargo run --release --example uci`
+///
+/// `cargo run --release --example uci -- data/UCI/poker-hand-testing.data`
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "data/UCI/poker-hand-training-true.data".to_string());
+
+    match UciReport::from_file(&path) {
+        Ok(report) => println!("{report}"),
+        Err(e) => println!("Unable to check {path}: {e}"),
+    }
+}
@@ -4,8 +4,10 @@ use crate::arrays::three::Three;
 use crate::arrays::two::Two;
 use crate::play::hole_cards::HoleCards;
 use crate::util::wincounter::wins::Wins;
-use crate::Pile;
+use crate::util::wincounter::PlayerFlag;
+use crate::{Cards, Pile};
 use log::info;
+use rayon::iter::ParallelIterator;
 use std::slice::Iter;
 use std::sync::mpsc;
 use std::thread;
@@ -70,6 +72,26 @@ impl CaseEvals {
         case_evals
     }
 
+    /// Every board at the deal, the same as `from_holdem_at_deal()`, but only the `Wins`.
+    /// Holding on to a million and a half cases, and spinning up a thread for each of them, is
+    /// more than my laptop can take for a whole file of hands, so the boards are streamed through
+    /// rayon instead. The `dead` cards are never dealt on any of them.
+    #[must_use]
+    pub fn wins_at_deal(hands: &HoleCards, dead: &Cards) -> Wins {
+        let flags: Vec<PlayerFlag> = hands
+            .remaining_after(dead)
+            .par_combinations(5)
+            .filter_map(|v| Five::try_from(v).ok())
+            .filter_map(|five| CaseEval::from_holdem_at_deal(five, hands).ok())
+            .map(|case_eval| case_eval.win_count())
+            .collect();
+        let mut wins = Wins::default();
+        for flag in flags {
+            wins.add(flag);
+        }
+        wins
+    }
+
     /// Experimental concurrent version of this calculation.
     ///
     /// Calc here takes: `cargo run --example calc -- -d  "6♠ 6♥ 5♦ 5♣" -b "9♣ 6♦ 5♥ 5♠ 8♠"`
@@ -78,13 +100,18 @@ impl CaseEvals {
     /// # Panics
     ///
     /// Oopsie
+    ///
+    /// The `dead` cards are left out of the turns and rivers, so folded hands that we know
+    /// about can't come back to win it for somebody.
     #[must_use]
-    pub fn from_holdem_at_flop_mpsc(board: Three, hands: &HoleCards) -> CaseEvals {
+    pub fn from_holdem_at_flop_mpsc(board: Three, hands: &HoleCards, dead: &Cards) -> CaseEvals {
         let mut case_evals = CaseEvals::default();
 
         let (tx, rx) = mpsc::channel();
 
-        for v in hands.combinations_after(2, &board.cards()) {
+        let mut gone = board.cards();
+        gone.insert_all(dead);
+        for v in hands.combinations_after(2, &gone) {
             let tx = tx.clone();
             let my_hands = hands.clone();
 
@@ -0,0 +1,532 @@
+use crate::analysis::store::nubibus::pluribus::Pluribus;
+use crate::analysis::store::pokerstars::{ActionKind, HandHistory, PlayerAction};
+use crate::play::phases::PhaseHoldem;
+use crate::play::Position6Max;
+use crate::PKError;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+/// Which hands get counted. An empty list lets everything through.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HudFilter {
+    pub positions: Vec<Position6Max>,
+    pub sessions: Vec<usize>,
+}
+
+impl HudFilter {
+    #[must_use]
+    pub fn position(mut self, position: Position6Max) -> Self {
+        self.positions.push(position);
+        self
+    }
+
+    #[must_use]
+    pub fn session(mut self, session: usize) -> Self {
+        self.sessions.push(session);
+        self
+    }
+
+    #[must_use]
+    pub fn allows_position(&self, position: Position6Max) -> bool {
+        self.positions.is_empty() || self.positions.contains(&position)
+    }
+
+    #[must_use]
+    pub fn allows_session(&self, session: usize) -> bool {
+        self.sessions.is_empty() || self.sessions.contains(&session)
+    }
+}
+
+/// The running counts behind a player's stats. Each stat is how many times the player did
+/// something, out of how many times they had the chance to.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerStats {
+    pub hands: usize,
+    pub vpip: usize,
+    pub pfr: usize,
+    pub three_bet_chances: usize,
+    pub three_bets: usize,
+    pub faced_three_bets: usize,
+    pub folds_to_three_bets: usize,
+    pub cbet_chances: usize,
+    pub cbets: usize,
+    pub saw_flop: usize,
+    pub went_to_showdown: usize,
+    pub won_at_showdown: usize,
+    /// Bets and raises after the flop.
+    pub aggressive: usize,
+    /// Calls after the flop.
+    pub passive: usize,
+    pub chips_won: f64,
+    pub big_blinds_won: f64,
+}
+
+impl PlayerStats {
+    /// Voluntarily put money in the pot: called or raised before the flop.
+    #[must_use]
+    pub fn vpip(&self) -> f64 {
+        PlayerStats::percent(self.vpip, self.hands)
+    }
+
+    /// Preflop raise.
+    #[must_use]
+    pub fn pfr(&self) -> f64 {
+        PlayerStats::percent(self.pfr, self.hands)
+    }
+
+    /// Reraised when facing a single raise before the flop.
+    #[must_use]
+    pub fn three_bet(&self) -> f64 {
+        PlayerStats::percent(self.three_bets, self.three_bet_chances)
+    }
+
+    /// Folded after opening the betting and being reraised.
+    #[must_use]
+    pub fn fold_to_three_bet(&self) -> f64 {
+        PlayerStats::percent(self.folds_to_three_bets, self.faced_three_bets)
+    }
+
+    /// Continuation bet: bet the flop after being the last to raise before it, when nobody had
+    /// bet in front of them.
+    #[must_use]
+    pub fn cbet(&self) -> f64 {
+        PlayerStats::percent(self.cbets, self.cbet_chances)
+    }
+
+    /// Went to showdown, out of the hands where they saw the flop.
+    #[must_use]
+    pub fn wtsd(&self) -> f64 {
+        PlayerStats::percent(self.went_to_showdown, self.saw_flop)
+    }
+
+    /// Won money at showdown, out of the times they went to one.
+    #[must_use]
+    pub fn wsd(&self) -> f64 {
+        PlayerStats::percent(self.won_at_showdown, self.went_to_showdown)
+    }
+
+    /// Bets and raises divided by calls after the flop. A player who never calls gets their
+    /// number of bets and raises, rather than infinity.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn aggression_factor(&self) -> f64 {
+        if self.passive == 0 {
+            self.aggressive as f64
+        } else {
+            self.aggressive as f64 / self.passive as f64
+        }
+    }
+
+    /// Big blinds won for every hundred hands played.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn bb_per_100(&self) -> f64 {
+        if self.hands == 0 {
+            0.0
+        } else {
+            self.big_blinds_won * 100.0 / self.hands as f64
+        }
+    }
+
+    pub fn merge(&mut self, other: &PlayerStats) {
+        self.hands += other.hands;
+        self.vpip += other.vpip;
+        self.pfr += other.pfr;
+        self.three_bet_chances += other.three_bet_chances;
+        self.three_bets += other.three_bets;
+        self.faced_three_bets += other.faced_three_bets;
+        self.folds_to_three_bets += other.folds_to_three_bets;
+        self.cbet_chances += other.cbet_chances;
+        self.cbets += other.cbets;
+        self.saw_flop += other.saw_flop;
+        self.went_to_showdown += other.went_to_showdown;
+        self.won_at_showdown += other.won_at_showdown;
+        self.aggressive += other.aggressive;
+        self.passive += other.passive;
+        self.chips_won += other.chips_won;
+        self.big_blinds_won += other.big_blinds_won;
+    }
+
+    /// What a single hand adds to a player's stats.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn from_hand(hand: &HandHistory, name: &str) -> PlayerStats {
+        let mut stats = PlayerStats {
+            hands: 1,
+            ..PlayerStats::default()
+        };
+        let is_raise = |action: &PlayerAction| matches!(action.kind, ActionKind::Bet(_) | ActionKind::Raise { .. });
+
+        // Preflop
+        let mut raises = 0;
+        let mut opener: Option<&str> = None;
+        let mut aggressor: Option<&str> = None;
+        let mut three_bet_decided = false;
+        let mut faced_three_bet_decided = false;
+        for action in hand.actions(PhaseHoldem::Preflop) {
+            if action.player == name {
+                match action.kind {
+                    ActionKind::Call(_) | ActionKind::Bet(_) | ActionKind::Raise { .. } => stats.vpip = 1,
+                    _ => {}
+                }
+                if is_raise(action) {
+                    stats.pfr = 1;
+                }
+                if raises == 1 && opener != Some(name) && !three_bet_decided {
+                    three_bet_decided = true;
+                    stats.three_bet_chances = 1;
+                    stats.three_bets = usize::from(is_raise(action));
+                }
+                if raises == 2 && opener == Some(name) && !faced_three_bet_decided {
+                    faced_three_bet_decided = true;
+                    stats.faced_three_bets = 1;
+                    stats.folds_to_three_bets = usize::from(action.kind == ActionKind::Fold);
+                }
+            }
+            if is_raise(action) {
+                raises += 1;
+                opener = opener.or(Some(action.player.as_str()));
+                aggressor = Some(action.player.as_str());
+            }
+        }
+
+        // Postflop
+        let folded_preflop = hand
+            .actions(PhaseHoldem::Preflop)
+            .iter()
+            .any(|action| action.player == name && action.kind == ActionKind::Fold);
+        if hand.street(PhaseHoldem::Flop).is_none() || folded_preflop {
+            return stats.with_result(hand, name);
+        }
+        stats.saw_flop = 1;
+        if aggressor == Some(name) {
+            for action in hand.actions(PhaseHoldem::Flop) {
+                if action.player == name {
+                    stats.cbet_chances = 1;
+                    stats.cbets = usize::from(is_raise(action));
+                    break;
+                }
+                if is_raise(action) {
+                    break;
+                }
+            }
+        }
+        for phase in [PhaseHoldem::Flop, PhaseHoldem::Turn, PhaseHoldem::River] {
+            for action in hand.actions(phase).iter().filter(|action| action.player == name) {
+                if is_raise(action) {
+                    stats.aggressive += 1;
+                } else if matches!(action.kind, ActionKind::Call(_)) {
+                    stats.passive += 1;
+                }
+            }
+        }
+        if hand.showdown && hand.showed.iter().any(|showed| showed.name == name) {
+            stats.went_to_showdown = 1;
+            stats.won_at_showdown = usize::from(hand.collected.iter().any(|(winner, _)| winner == name));
+        }
+        stats.with_result(hand, name)
+    }
+
+    /// Adds how much the player won or lost in the hand.
+    #[allow(clippy::cast_precision_loss)]
+    fn with_result(mut self, hand: &HandHistory, name: &str) -> PlayerStats {
+        let mut put_in = 0;
+        for street in &hand.streets {
+            let mut in_street = 0;
+            for action in street.actions.iter().filter(|action| action.player == name) {
+                in_street = match action.kind {
+                    ActionKind::SmallBlind(amount)
+                    | ActionKind::BigBlind(amount)
+                    | ActionKind::Call(amount)
+                    | ActionKind::Bet(amount) => in_street + amount,
+                    ActionKind::Raise { to, .. } => to,
+                    ActionKind::Fold | ActionKind::Check => in_street,
+                };
+            }
+            put_in += in_street;
+        }
+        if let Some((player, amount)) = &hand.uncalled {
+            if player == name {
+                put_in -= amount;
+            }
+        }
+        let collected: f64 = hand
+            .collected
+            .iter()
+            .filter(|(player, _)| player == name)
+            .map(|(_, amount)| amount)
+            .sum();
+        self.chips_won = collected - put_in as f64;
+        if hand.big_blind > 0 {
+            self.big_blinds_won = self.chips_won / hand.big_blind as f64;
+        }
+        self
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn percent(count: usize, chances: usize) -> f64 {
+        if chances == 0 {
+            0.0
+        } else {
+            count as f64 * 100.0 / chances as f64
+        }
+    }
+}
+
+/// # HUD
+///
+/// The stats that a heads up display shows for every player at the table, added up over as many
+/// hands as you care to throw at it. Anything that can be turned into a `PokerStars`
+/// `HandHistory` can be counted, which means the Pluribus ACPC logs, `PokerStars` files, and
+/// Open Hand History files.
+///
+/// The `HandHistory` format doesn't have sessions, so each hand is added with the session it
+/// came from. For the Pluribus logs that's the number in the file name.
+///
+/// Positions are worked out from the blinds and the button: the player who posts the small blind
+/// is the `SB`, then the `BB`, and counting back from the button for everyone else, so at a
+/// five handed table there's no `UTG`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Hud {
+    pub filter: HudFilter,
+    pub players: BTreeMap<String, PlayerStats>,
+}
+
+impl Hud {
+    #[must_use]
+    pub fn new(filter: HudFilter) -> Hud {
+        Hud {
+            filter,
+            players: BTreeMap::new(),
+        }
+    }
+
+    /// The HUD for every hand in a directory of Pluribus ACPC logs, like `data/pluribus/raw`.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::Fubar` if a log can't be read.
+    /// * `PKError::InvalidPluribusIndex` if a hand in a log can't be made sense of.
+    pub fn from_pluribus_logs(dir: &str, filter: HudFilter) -> Result<Hud, PKError> {
+        let mut hud = Hud::new(filter);
+        for (session, path) in Pluribus::logs(dir)? {
+            if hud.filter.allows_session(session) {
+                for pluribus in Pluribus::from_log(&path.to_string_lossy())? {
+                    hud.add(&HandHistory::from_pluribus(&pluribus, session, "")?, session);
+                }
+            }
+        }
+        Ok(hud)
+    }
+
+    /// Counts a hand, if it passes the filter.
+    pub fn add(&mut self, hand: &HandHistory, session: usize) {
+        if !self.filter.allows_session(session) {
+            return;
+        }
+        for (name, position) in Hud::positions(hand) {
+            if self.filter.allows_position(position) {
+                self.players
+                    .entry(name.clone())
+                    .or_default()
+                    .merge(&PlayerStats::from_hand(hand, &name));
+            }
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&PlayerStats> {
+        self.players.get(name)
+    }
+
+    /// Where everyone was sitting for the hand.
+    #[must_use]
+    pub fn positions(hand: &HandHistory) -> Vec<(String, Position6Max)> {
+        let blind = |small: bool| {
+            hand.actions(PhaseHoldem::Preflop)
+                .iter()
+                .find_map(|action| match action.kind {
+                    ActionKind::SmallBlind(_) if small => Some(action.player.clone()),
+                    ActionKind::BigBlind(_) if !small => Some(action.player.clone()),
+                    _ => None,
+                })
+        };
+        let (small, big) = (blind(true), blind(false));
+
+        let mut seats = hand.seats.iter().collect::<Vec<_>>();
+        seats.sort_by_key(|seat| seat.seat);
+        let button = seats
+            .iter()
+            .position(|seat| seat.seat == hand.button)
+            .unwrap_or_default();
+        let count = seats.len().max(1);
+        seats.rotate_left((button + 1) % count);
+
+        let mut late = [Position6Max::BTN, Position6Max::CO, Position6Max::MP, Position6Max::UTG].into_iter();
+        let mut positions = seats
+            .iter()
+            .rev()
+            .map(|seat| {
+                let position = if small.as_ref() == Some(&seat.name) {
+                    Position6Max::SB
+                } else if big.as_ref() == Some(&seat.name) {
+                    Position6Max::BB
+                } else {
+                    late.next().unwrap_or(Position6Max::UTG)
+                };
+                (seat.name.clone(), position)
+            })
+            .collect::<Vec<_>>();
+        positions.reverse();
+        positions
+    }
+}
+
+impl Display for Hud {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<12} {:>7} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>6} {:>5} {:>8}",
+            "Player", "Hands", "VPIP", "PFR", "3Bet", "F3Bet", "CBet", "WTSD", "W$SD", "AF", "bb/100"
+        )?;
+        for (name, stats) in &self.players {
+            writeln!(
+                f,
+                "{:<12} {:>7} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>6.1} {:>5.2} {:>8.2}",
+                name,
+                stats.hands,
+                stats.vpip(),
+                stats.pfr(),
+                stats.three_bet(),
+                stats.fold_to_three_bet(),
+                stats.cbet(),
+                stats.wtsd(),
+                stats.wsd(),
+                stats.aggression_factor(),
+                stats.bb_per_100()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__hud_tests {
+    use super::*;
+    use crate::analysis::store::pokerstars::analysis__store__pokerstars_tests::HAND;
+    use std::str::FromStr;
+
+    fn hand() -> HandHistory {
+        HandHistory::from_str(HAND).unwrap()
+    }
+
+    fn stats(name: &str) -> PlayerStats {
+        PlayerStats::from_hand(&hand(), name)
+    }
+
+    #[test]
+    fn from_hand__preflop() {
+        let white = stats("MrWhite");
+        let blue = stats("MrBlue");
+        let pink = stats("MrPink");
+
+        assert_eq!((1, 1), (white.vpip, white.pfr));
+        assert_eq!((1, 1), (white.three_bet_chances, white.three_bets));
+        assert_eq!((1, 0), (blue.faced_three_bets, blue.folds_to_three_bets));
+        assert_eq!((0, 0), (pink.vpip, pink.pfr));
+        assert_eq!(0, pink.three_bet_chances);
+        assert_eq!(0, stats("MrBrown").three_bet_chances);
+        assert_eq!(
+            (1, 0),
+            (stats("MrBlonde").three_bet_chances, stats("MrBlonde").three_bets)
+        );
+    }
+
+    #[test]
+    fn from_hand__postflop() {
+        let white = stats("MrWhite");
+        let blue = stats("MrBlue");
+        let blonde = stats("MrBlonde");
+
+        assert_eq!((1, 0), (white.cbet_chances, white.cbets));
+        assert_eq!((1, 0), (white.saw_flop, white.went_to_showdown));
+        assert_eq!((1, 1, 0), (blue.saw_flop, blue.went_to_showdown, blue.won_at_showdown));
+        assert_eq!((1, 1), (blue.aggressive, blue.passive));
+        assert_eq!((1, 1), (blonde.went_to_showdown, blonde.won_at_showdown));
+        assert_eq!((1, 1), (blonde.aggressive, blonde.passive));
+    }
+
+    #[test]
+    fn from_hand__chips_won() {
+        assert!((stats("MrBlonde").chips_won - 11_350.0).abs() < f64::EPSILON);
+        assert!((stats("MrBlue").chips_won + 10_000.0).abs() < f64::EPSILON);
+        assert!((stats("MrWhite").big_blinds_won + 12.5).abs() < f64::EPSILON);
+        assert!((stats("MrPink").chips_won + 100.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn positions() {
+        assert_eq!(
+            vec![
+                ("MrWhite".to_string(), Position6Max::SB),
+                ("MrPink".to_string(), Position6Max::BB),
+                ("MrBrown".to_string(), Position6Max::UTG),
+                ("Pluribus".to_string(), Position6Max::MP),
+                ("MrBlue".to_string(), Position6Max::CO),
+                ("MrBlonde".to_string(), Position6Max::BTN),
+            ],
+            Hud::positions(&hand())
+        );
+    }
+
+    #[test]
+    fn add__filter() {
+        let mut hud = Hud::new(HudFilter::default().position(Position6Max::BTN).session(100));
+
+        hud.add(&hand(), 100);
+        hud.add(&hand(), 101);
+
+        assert_eq!(vec!["MrBlonde"], hud.players.keys().collect::<Vec<&String>>());
+        assert_eq!(1, hud.get("MrBlonde").unwrap().hands);
+    }
+
+    /// What everyone won over a session adds up to what the ACPC logs say they won.
+    #[test]
+    fn from_pluribus_logs() {
+        let hud = Hud::from_pluribus_logs("data/pluribus/raw", HudFilter::default().session(102)).unwrap();
+        let mut expected: BTreeMap<String, f64> = BTreeMap::new();
+        for path in [
+            "data/pluribus/raw/sample_game_102.log",
+            "data/pluribus/raw/sample_game_102b.log",
+        ] {
+            for line in std::fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .filter(|l| l.starts_with("STATE"))
+            {
+                let fields = line.split(':').collect::<Vec<&str>>();
+                for (name, won) in fields[5].split('|').zip(fields[4].split('|')) {
+                    *expected.entry(name.to_string()).or_default() += won.parse::<f64>().unwrap();
+                }
+            }
+        }
+
+        assert_eq!(expected.len(), hud.players.len());
+        for (name, won) in expected {
+            assert!((won - hud.get(&name).unwrap().chips_won).abs() < 0.01, "{name}");
+        }
+        assert!(hud.players.values().map(|stats| stats.chips_won).sum::<f64>().abs() < 0.01);
+    }
+
+    #[test]
+    fn display() {
+        let mut hud = Hud::default();
+        hud.add(&hand(), 100);
+
+        let display = hud.to_string();
+
+        assert!(display.starts_with("Player"));
+        assert!(display.contains("MrBlonde           1  100.0    0.0"), "{display}");
+    }
+}
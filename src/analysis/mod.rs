@@ -9,6 +9,7 @@ pub mod class;
 pub mod eval;
 pub mod evals;
 pub mod hand_rank;
+pub mod hud;
 pub mod matchups;
 pub mod name;
 pub mod outs;
@@ -1,4 +1,5 @@
 use crate::analysis::hand_rank::HandRankValue;
+use crate::analysis::store::db::schema::{Migration, Schema, Versioned};
 use crate::analysis::store::db::sqlite::Sqlable;
 use crate::arrays::five::Five;
 use crate::arrays::seven::Seven;
@@ -10,7 +11,7 @@ use crate::{PKError, Pile};
 use csv::Reader;
 use csv::WriterBuilder;
 use lazy_static::lazy_static;
-use rusqlite::{named_params, Connection};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::error::Error;
@@ -172,59 +173,79 @@ impl SevenFiveBCM {
     }
 }
 
-impl Sqlable<SevenFiveBCM, Bard> for SevenFiveBCM {
-    fn create_table(conn: &Connection) -> rusqlite::Result<usize> {
-        conn.execute(
-            "create table if not exists bcm (
+impl Versioned for SevenFiveBCM {
+    const STORE: &'static str = "bcm";
+    const MIGRATIONS: &'static [Migration] = &[Migration {
+        version: 1,
+        description: "binary card map table",
+        creates: "bcm",
+        sql: "create table if not exists bcm (
             bc integer primary key,
             best integer not null,
             rank integer not null
          )",
-            [],
-        )
+    }];
+}
+
+impl Sqlable<SevenFiveBCM, Bard> for SevenFiveBCM {
+    /// Returns how many migrations it took to bring the table up to date.
+    fn create_table(conn: &Connection) -> rusqlite::Result<usize> {
+        Schema::upgrade::<SevenFiveBCM>(conn).map(|upgrade| upgrade.applied())
     }
 
-    fn exists(_conn: &Connection, _record: &Bard) -> bool {
-        todo!()
+    fn exists(conn: &Connection, bc: &Bard) -> bool {
+        matches!(SevenFiveBCM::select(conn, bc), Ok(Some(_)))
     }
 
+    /// Returns false if there's already a row for the `Bard`.
     fn insert(conn: &Connection, bcm: &SevenFiveBCM) -> rusqlite::Result<bool> {
-        let mut stmt = conn.prepare("INSERT INTO bcm (bc, best, rank) VALUES (:bc, :best, :rank)")?;
-        stmt.execute(named_params! {
+        let mut stmt = conn.prepare_cached("INSERT OR IGNORE INTO bcm (bc, best, rank) VALUES (:bc, :best, :rank)")?;
+        let inserted = stmt.execute(named_params! {
             ":bc": bcm.bc.as_u64(),
             ":best": bcm.best.as_u64(),
             ":rank": u64::from(bcm.rank)
         })?;
-        Ok(true)
+        Ok(inserted > 0)
     }
 
-    fn insert_many(_conn: &Connection, _records: Vec<&SevenFiveBCM>) -> rusqlite::Result<usize> {
-        todo!()
+    fn insert_many(conn: &Connection, records: Vec<&SevenFiveBCM>) -> rusqlite::Result<usize> {
+        let mut inserted = 0;
+        for bcm in records {
+            if SevenFiveBCM::insert(conn, bcm)? {
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
     }
 
-    fn select(conn: &Connection, bc: &Bard) -> Option<SevenFiveBCM> {
-        let mut stmt = conn.prepare("SELECT bc, best, rank FROM bcm WHERE bc=:bc").ok()?;
-
-        let bcm = stmt
-            .query_row(named_params! {":bc": bc.as_u64()}, |row| {
-                let bc: u64 = row.get(0)?;
-                let best: u64 = row.get(1)?;
-                let rank: u16 = row.get(2)?;
-
-                let bcm = SevenFiveBCM {
-                    bc: Bard::from(bc),
-                    best: Bard::from(best),
-                    rank,
-                };
-                Ok(bcm)
-            })
-            .ok()?;
-
-        Some(bcm)
+    fn select(conn: &Connection, bc: &Bard) -> rusqlite::Result<Option<SevenFiveBCM>> {
+        let mut stmt = conn.prepare("SELECT bc, best, rank FROM bcm WHERE bc=:bc")?;
+
+        stmt.query_row(named_params! {":bc": bc.as_u64()}, |row| {
+            let bc: u64 = row.get(0)?;
+            let best: u64 = row.get(1)?;
+            let rank: u16 = row.get(2)?;
+
+            let bcm = SevenFiveBCM {
+                bc: Bard::from(bc),
+                best: Bard::from(best),
+                rank,
+            };
+            Ok(bcm)
+        })
+        .optional()
     }
 
-    fn select_all(_conn: &Connection) -> Vec<SevenFiveBCM> {
-        todo!()
+    fn select_all(conn: &Connection) -> rusqlite::Result<Vec<SevenFiveBCM>> {
+        let mut stmt = conn.prepare("SELECT bc, best, rank FROM bcm ORDER BY bc")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(SevenFiveBCM {
+                bc: Bard::from(row.get::<_, u64>(0)?),
+                best: Bard::from(row.get::<_, u64>(1)?),
+                rank: row.get(2)?,
+            })
+        })?;
+        rows.collect()
     }
 }
 
@@ -318,7 +339,24 @@ mod analysis__store__bcm__binary_card_map_tests {
         SevenFiveBCM::create_table(&conn).unwrap();
         SevenFiveBCM::insert(&conn, &TestData::spades_royal_flush_bcm()).unwrap();
 
-        assert!(SevenFiveBCM::select(&conn, &TestData::spades_royal_flush_bcm().bc).is_some());
-        assert!(SevenFiveBCM::select(&conn, &TestData::spades_king_high_flush_bcm().bc).is_none());
+        assert!(SevenFiveBCM::select(&conn, &TestData::spades_royal_flush_bcm().bc)
+            .unwrap()
+            .is_some());
+        assert!(SevenFiveBCM::select(&conn, &TestData::spades_king_high_flush_bcm().bc)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn sqlite__insert_many() {
+        let conn = Connect::in_memory_connection().unwrap().connection;
+        SevenFiveBCM::create_table(&conn).unwrap();
+        let royal = TestData::spades_royal_flush_bcm();
+        let king = TestData::spades_king_high_flush_bcm();
+
+        assert_eq!(2, SevenFiveBCM::insert_many(&conn, vec![&royal, &king]).unwrap());
+        assert!(!SevenFiveBCM::insert(&conn, &royal).unwrap());
+        assert!(SevenFiveBCM::exists(&conn, &king.bc));
+        assert_eq!(2, SevenFiveBCM::select_all(&conn).unwrap().len());
     }
 }
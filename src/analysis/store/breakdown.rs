@@ -0,0 +1,691 @@
+use crate::analysis::case_evals::CaseEvals;
+use crate::analysis::store::pokerstars::{ActionKind, PlayerAction, Street};
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::play::board::Board;
+use crate::play::game::Game;
+use crate::play::hole_cards::HoleCards;
+use crate::play::phases::PhaseHoldem;
+use crate::play::stages::flop_eval::FlopEval;
+use crate::util::wincounter::results::Results;
+use crate::{PKError, Pile};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::str::FromStr;
+
+lazy_static! {
+    static ref STREET: Regex = Regex::new(r"(?i)^(FLOP|TURN|RIVER|BOARD):?\s+(.+)$").unwrap();
+    static ref HEADER_PLAYER: Regex = Regex::new(r"^(.+?)\s*\((\w+)\)(\s+[\d,]+)?$").unwrap();
+    static ref AMOUNT: Regex = Regex::new(r"\d[\d,]*").unwrap();
+    static ref COMMENT: Regex = Regex::new(r"\s*\(.*\)$").unwrap();
+}
+
+/// The position labels that show up in the breakdowns. Anything else to the left of a colon is
+/// either a player's name, or shorthand for one, like `DN` for `DNegs`.
+pub const POSITIONS: [&str; 13] = [
+    "SB", "BB", "UTG", "UTG1", "UTG2", "EP", "MP", "LJ", "HJ", "CO", "D", "BTN", "BU",
+];
+
+/// A player in a `Breakdown`. The position is whatever label they were given in the notes, if
+/// any, and the cards are empty until they're shown.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BreakdownPlayer {
+    pub name: String,
+    pub position: Option<String>,
+    pub cards: Cards,
+}
+
+impl BreakdownPlayer {
+    #[must_use]
+    pub fn new(name: &str, position: Option<&str>) -> Self {
+        BreakdownPlayer {
+            name: name.to_string(),
+            position: position.map(str::to_uppercase),
+            cards: Cards::default(),
+        }
+    }
+
+    fn is_position(&self, position: &str) -> bool {
+        self.position.as_deref() == Some(position)
+    }
+}
+
+/// The odds for every player with known cards at one street of a `Breakdown`. The results are in
+/// the same order as the hands in `Breakdown::game()`.
+#[derive(Clone, Debug, Default)]
+pub struct StreetEquity {
+    pub phase: PhaseHoldem,
+    pub results: Results,
+}
+
+/// # Hand Breakdowns
+///
+/// When I'm watching a stream, like Daniel Negreanu's WSOP vlogs, and a hand comes up that I
+/// want to dig into later, I scribble it down in a markdown file under `docs/hands`. There's no
+/// real format to it. It's just whatever I could type fast enough to keep up:
+///
+/// ```txt
+/// ## Breakdown 1
+///
+/// Dan Smith (SB) 1,000
+/// Chris Brewer (BB) 2,000
+/// DNEGS (D) 5,000
+///
+/// DNEGS JH 7H
+///
+/// SB: 1000
+/// BB: 2000
+/// DN:  5000
+///
+/// FLOP: QS JC 7C
+///
+/// BB: CHECK
+/// SB: CHECK
+/// DN: 12,000
+/// ```
+///
+/// This is my attempt to make sense out of it after the fact, so that the hands can be replayed
+/// with the odds at every street. The rules, such as they are:
+///
+/// * A `## heading` or a line of `=`s starts a new hand. A `<link>` belongs to the hand it's in,
+///   or the next one if it comes before the heading.
+/// * Players are introduced as `Name (POS) chips`, `POS: Name`, or `POS: Name CARDS`. The chip
+///   counts in the header lines are all over the place, so I ignore them.
+/// * Cards are shown with `Name CARDS`, `Name: CARDS` or `POS: CARDS`.
+/// * `FLOP:`, `TURN:` and `RIVER:` deal the board, and `BOARD:` runs it all out at once after an
+///   all in. I forget the colon sometimes, so it's optional.
+/// * Actions are `LABEL: CHECK`, `LABEL: FOLD`, or `LABEL: amount`, where the amount is what the
+///   player has in front of them for the street, not how much they added. `CALL +25,000 = 35,000`
+///   means the same as `35,000`. The first amounts from the blinds are their blinds, and
+///   anything after a `+` there is the big blind ante. Anything in parentheses at the end is a
+///   comment.
+/// * The label can be a position, a name, or the start of a name, so `DN` is `DNegs`. `UG` is
+///   close enough to `UTG` as well. Labels that don't match anyone become a new player.
+///
+/// Actions are turned into the same `PlayerAction`s that the `PokerStars` hand histories use.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Breakdown {
+    pub title: Option<String>,
+    pub link: Option<String>,
+    pub players: Vec<BreakdownPlayer>,
+    pub ante: usize,
+    pub streets: Vec<Street>,
+    pub board: Cards,
+}
+
+impl Breakdown {
+    /// Parses every hand in a breakdown file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the file can't be read, or `PKError::InvalidHandHistory`
+    /// with the line number of the first line that doesn't make sense.
+    pub fn from_file(path: &str) -> Result<Vec<Breakdown>, PKError> {
+        let s = fs::read_to_string(path).map_err(|_| PKError::Fubar)?;
+        Breakdown::parse_all(&s)
+    }
+
+    /// Parses a string holding any number of hands.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidHandHistory` with the line number of the first line that doesn't
+    /// make sense.
+    pub fn parse_all(s: &str) -> Result<Vec<Breakdown>, PKError> {
+        let mut hands = Vec::new();
+        let mut parser = BreakdownParser::default();
+
+        for (i, raw) in s.lines().enumerate() {
+            let line = raw.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.starts_with("===") {
+                hands.extend(parser.finish());
+            } else if let Some(title) = line.strip_prefix("##") {
+                if !parser.hand.is_blank() {
+                    hands.extend(parser.finish());
+                }
+                parser.hand.title = Some(title.trim().to_string());
+            } else if let Some(link) = line.strip_prefix('<').and_then(|l| l.strip_suffix('>')) {
+                parser.hand.link = Some(link.to_string());
+            } else {
+                parser
+                    .line(line)
+                    .map_err(|reason| PKError::InvalidHandHistory(i + 1, reason))?;
+            }
+        }
+        hands.extend(parser.finish());
+
+        Ok(hands)
+    }
+
+    fn is_blank(&self) -> bool {
+        self.title.is_none() && self.players.is_empty() && self.streets.is_empty() && self.board.is_empty()
+    }
+
+    /// Looks up a player by the label used in the notes. See the rules above.
+    #[must_use]
+    pub fn player(&self, label: &str) -> Option<&BreakdownPlayer> {
+        self.player_index(label).and_then(|i| self.players.get(i))
+    }
+
+    fn player_index(&self, label: &str) -> Option<usize> {
+        let label = label.to_uppercase();
+        let close_to = |position: &str| {
+            let mut chars = position.chars();
+            label.chars().next() == position.chars().next() && label.chars().all(|c| chars.any(|p| p == c))
+        };
+
+        self.players
+            .iter()
+            .position(|p| p.is_position(&label))
+            .or_else(|| self.players.iter().position(|p| p.name.to_uppercase() == label))
+            .or_else(|| {
+                self.players
+                    .iter()
+                    .position(|p| p.position.as_deref().is_some_and(close_to))
+            })
+            .or_else(|| {
+                self.players
+                    .iter()
+                    .position(|p| label.len() > 1 && p.name.to_uppercase().starts_with(&label))
+            })
+    }
+
+    /// Every action on the street.
+    #[must_use]
+    pub fn actions(&self, phase: PhaseHoldem) -> Vec<PlayerAction> {
+        self.streets
+            .iter()
+            .filter(|street| street.phase == phase)
+            .flat_map(|street| street.actions.clone())
+            .collect()
+    }
+
+    /// A `Game` with everybody whose cards we know, in the order they showed up in the notes,
+    /// and as much of the board as got dealt.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::NotEnoughHands` if fewer than two players' cards are known, or the
+    /// usual card errors if the board is a strange size.
+    pub fn game(&self) -> Result<Game, PKError> {
+        let shown: Vec<&BreakdownPlayer> = self.players.iter().filter(|p| p.cards.len() == 2).collect();
+        if shown.len() < 2 {
+            return Err(PKError::NotEnoughHands);
+        }
+
+        let mut twos = Vec::with_capacity(shown.len());
+        for player in &shown {
+            twos.push(Two::try_from(player.cards.clone())?);
+        }
+        let board = if self.board.is_empty() {
+            Board::default()
+        } else {
+            Board::try_from(self.board.clone())?
+        };
+
+        Ok(Game {
+            hands: HoleCards::from(twos),
+            board,
+            names: shown.iter().map(|p| Some(p.name.clone())).collect(),
+            dead: Cards::default(),
+        })
+    }
+
+    /// The odds for everybody with known cards at every street that the board got to.
+    ///
+    /// The preflop odds are optional, since they mean running through every one of the million
+    /// and a half or so boards, which takes a while. As with the `calc` example, this is the
+    /// double dummy view, so a player that folded is still in there.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::NotEnoughHands` if fewer than two players' cards are known.
+    pub fn equities(&self, preflop: bool) -> Result<Vec<StreetEquity>, PKError> {
+        let game = self.game()?;
+        let player_count = game.hands.len();
+        let mut equities = Vec::new();
+
+        if preflop {
+            equities.push(StreetEquity {
+                phase: PhaseHoldem::Preflop,
+                results: game.preflop_results(),
+            });
+        }
+        if game.board.flop.is_dealt() {
+            equities.push(StreetEquity {
+                phase: PhaseHoldem::Flop,
+                results: FlopEval::try_from(game.clone())?.results,
+            });
+        }
+        if game.board.turn.is_dealt() {
+            let (_, _, results, _) = game.turn_calculations();
+            equities.push(StreetEquity {
+                phase: PhaseHoldem::Turn,
+                results,
+            });
+        }
+        if game.board.river.is_dealt() {
+            let wins = CaseEvals::from(vec![game.river_case_eval()?]).wins();
+            equities.push(StreetEquity {
+                phase: PhaseHoldem::River,
+                results: Results::from_wins(&wins, player_count),
+            });
+        }
+
+        Ok(equities)
+    }
+
+    /// Replays the hand, street by street, with the odds for everybody whose cards we know.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `equities()`, other than there not being enough hands to
+    /// compare, in which case the hand is replayed without them.
+    pub fn annotate(&self, preflop: bool) -> Result<String, PKError> {
+        let equities = match self.equities(preflop) {
+            Ok(equities) => equities,
+            Err(PKError::NotEnoughHands) => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        let game = self.game().ok();
+
+        let mut s = String::new();
+        if let Some(title) = &self.title {
+            let _ = writeln!(s, "## {title}");
+        }
+        if let Some(link) = &self.link {
+            let _ = writeln!(s, "<{link}>");
+        }
+        for player in &self.players {
+            let _ = write!(s, "{}", player.name);
+            if let Some(position) = &player.position {
+                let _ = write!(s, " ({position})");
+            }
+            if !player.cards.is_empty() {
+                let _ = write!(s, " {}", player.cards);
+            }
+            s.push('\n');
+        }
+
+        for (phase, board_cards) in [
+            (PhaseHoldem::Preflop, 0),
+            (PhaseHoldem::Flop, 3),
+            (PhaseHoldem::Turn, 4),
+            (PhaseHoldem::River, 5),
+        ] {
+            let actions = self.actions(phase);
+            let equity = equities.iter().find(|e| e.phase == phase);
+            if actions.is_empty() && equity.is_none() {
+                continue;
+            }
+
+            s.push('\n');
+            if phase == PhaseHoldem::Preflop {
+                s.push_str("PREFLOP\n");
+            } else {
+                let cards: Vec<Card> = self.board.iter().take(board_cards).copied().collect();
+                let _ = writeln!(s, "{}: {}", phase.to_string().to_uppercase(), Cards::from(cards));
+            }
+            if let (Some(game), Some(equity)) = (&game, equity) {
+                for (i, two) in game.hands.iter().enumerate() {
+                    let name = game.name(i).unwrap_or_default();
+                    let _ = writeln!(s, "  {name} [{two}] {}", equity.results.player_to_string(i));
+                }
+            }
+            for action in actions {
+                let _ = writeln!(s, "  {action}");
+            }
+        }
+
+        Ok(s)
+    }
+}
+
+/// Keeps track of where things are at while working through a hand.
+#[derive(Debug, Default)]
+struct BreakdownParser {
+    hand: Breakdown,
+    /// What each player has in front of them on the current street.
+    committed: HashMap<String, usize>,
+    bet: usize,
+    opened: bool,
+}
+
+impl BreakdownParser {
+    fn finish(&mut self) -> Option<Breakdown> {
+        let hand = std::mem::take(&mut self.hand);
+        self.committed.clear();
+        self.bet = 0;
+        self.opened = false;
+        if hand.is_blank() {
+            None
+        } else {
+            Some(hand)
+        }
+    }
+
+    fn line(&mut self, line: &str) -> Result<(), String> {
+        if let Some(captures) = STREET.captures(line) {
+            return self.street(&captures[1].to_uppercase(), &captures[2]);
+        }
+        if let Some(captures) = HEADER_PLAYER.captures(line) {
+            self.declare(&captures[1], Some(&captures[2]), &Cards::default());
+            return Ok(());
+        }
+        let line = COMMENT.replace(line, "");
+
+        let Some((label, rest)) = line.split_once(':') else {
+            let (name, cards) = split_cards(&line)?;
+            if name.is_empty() || cards.is_empty() {
+                return Err(format!("don't know what to do with {line}"));
+            }
+            self.show(&name, &cards);
+            return Ok(());
+        };
+
+        let label = label.trim();
+        let rest = rest.trim();
+        let (name, cards) = split_cards(rest)?;
+        let upper = name.to_uppercase();
+        if name.is_empty() {
+            self.show(label, &cards);
+        } else if cards.is_empty()
+            && (rest.starts_with(|c: char| c.is_ascii_digit())
+                || ["CHECK", "FOLD", "CALL", "BET", "RAISE"]
+                    .iter()
+                    .any(|a| upper.starts_with(a)))
+        {
+            return self.action(label, &upper);
+        } else {
+            self.declare(&name, Some(label), &cards);
+        }
+        Ok(())
+    }
+
+    fn street(&mut self, street: &str, cards: &str) -> Result<(), String> {
+        let (rest, cards) = split_cards(cards)?;
+        if !rest.is_empty() {
+            return Err(format!("{rest} aren't cards"));
+        }
+        let (phase, count) = match street {
+            "FLOP" => (PhaseHoldem::Flop, 3),
+            "TURN" => (PhaseHoldem::Turn, 1),
+            "RIVER" => (PhaseHoldem::River, 1),
+            _ => {
+                if !(3..=5).contains(&cards.len()) {
+                    return Err(format!("a board has 3 to 5 cards, not {}", cards.len()));
+                }
+                self.hand.board = cards;
+                return Ok(());
+            }
+        };
+        if cards.len() != count {
+            return Err(format!("the {street} has {count} cards, not {}", cards.len()));
+        }
+
+        self.hand.board.insert_all(&cards);
+        self.hand.streets.push(Street {
+            phase,
+            cards,
+            actions: Vec::new(),
+        });
+        self.committed.clear();
+        self.bet = 0;
+        Ok(())
+    }
+
+    /// Finds the player for a label, adding them if they're new.
+    fn find_or_add(&mut self, label: &str) -> usize {
+        if let Some(i) = self.hand.player_index(label) {
+            return i;
+        }
+        let upper = label.to_uppercase();
+        let position = POSITIONS.contains(&upper.as_str()).then_some(upper.as_str());
+        self.hand.players.push(BreakdownPlayer::new(label, position));
+        self.hand.players.len() - 1
+    }
+
+    fn declare(&mut self, name: &str, position: Option<&str>, cards: &Cards) {
+        let i = if let Some(i) = self.hand.players.iter().position(|p| p.name.eq_ignore_ascii_case(name)) {
+            i
+        } else {
+            self.hand.players.push(BreakdownPlayer::new(name, None));
+            self.hand.players.len() - 1
+        };
+        let player = &mut self.hand.players[i];
+        if let Some(position) = position {
+            player.position = Some(position.to_uppercase());
+        }
+        if !cards.is_empty() {
+            player.cards = cards.clone();
+        }
+    }
+
+    fn show(&mut self, label: &str, cards: &Cards) {
+        let i = self.find_or_add(label);
+        self.hand.players[i].cards = cards.clone();
+    }
+
+    fn action(&mut self, label: &str, action: &str) -> Result<(), String> {
+        let i = self.find_or_add(label);
+        let player = self.hand.players[i].clone();
+        if self.hand.streets.is_empty() {
+            self.hand.streets.push(Street {
+                phase: PhaseHoldem::Preflop,
+                ..Default::default()
+            });
+        }
+        let preflop = self.hand.streets.len() == 1;
+        let committed = self.committed.get(&player.name).copied().unwrap_or_default();
+
+        let amounts = AMOUNT
+            .find_iter(action)
+            .map(|m| m.as_str().replace(',', "").parse::<usize>())
+            .collect::<Result<Vec<usize>, _>>()
+            .map_err(|_| format!("bad amount in {action}"))?;
+
+        let kind = if action.starts_with("CHECK") {
+            ActionKind::Check
+        } else if action.starts_with("FOLD") {
+            ActionKind::Fold
+        } else if preflop
+            && !self.opened
+            && committed == 0
+            && !action.starts_with("CALL")
+            && (player.is_position("SB") || player.is_position("BB"))
+            && !amounts.is_empty()
+        {
+            let blind = amounts[0];
+            self.committed.insert(player.name.clone(), blind);
+            self.bet = self.bet.max(blind);
+            if player.is_position("SB") {
+                ActionKind::SmallBlind(blind)
+            } else {
+                self.hand.ante = amounts[1..].iter().sum();
+                ActionKind::BigBlind(blind)
+            }
+        } else {
+            let to = if action.contains('=') || !action.starts_with("CALL") {
+                amounts.last().copied()
+            } else {
+                amounts.first().copied().or(Some(self.bet))
+            }
+            .ok_or_else(|| format!("no amount in {action}"))?;
+
+            self.committed.insert(player.name.clone(), to);
+            if to <= committed {
+                ActionKind::Check
+            } else if to <= self.bet {
+                ActionKind::Call(to - committed)
+            } else {
+                let kind = if self.bet == 0 {
+                    ActionKind::Bet(to)
+                } else {
+                    ActionKind::Raise { by: to - self.bet, to }
+                };
+                self.bet = to;
+                self.opened = true;
+                kind
+            }
+        };
+
+        if let Some(street) = self.hand.streets.last_mut() {
+            street.actions.push(PlayerAction {
+                player: player.name,
+                kind,
+                all_in: false,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Splits the cards off of the end of a line, leaving whatever came before them, which is
+/// usually a name.
+fn split_cards(s: &str) -> Result<(String, Cards), String> {
+    let tokens: Vec<&str> = s.split_whitespace().collect();
+    let card_count = tokens
+        .iter()
+        .rev()
+        .take_while(|t| t.chars().count() == 2 && Card::from_str(t).is_ok())
+        .count();
+    let split = tokens.len() - card_count;
+
+    let mut cards = Cards::default();
+    for token in &tokens[split..] {
+        let card = Card::from_str(token).map_err(|_| format!("{token} isn't a card"))?;
+        if !cards.insert(card) {
+            return Err(format!("{card} is there twice"));
+        }
+    }
+    Ok((tokens[..split].join(" "), cards))
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__breakdown_tests {
+    use super::*;
+
+    const PATH: &str = "docs/hands/DNegsWSOP2022/2022-01.md";
+
+    fn hands() -> Vec<Breakdown> {
+        Breakdown::from_file(PATH).unwrap()
+    }
+
+    fn action(player: &str, kind: ActionKind) -> PlayerAction {
+        PlayerAction {
+            player: player.to_string(),
+            kind,
+            all_in: false,
+        }
+    }
+
+    #[test]
+    fn from_file() {
+        let hands = hands();
+
+        assert_eq!(6, hands.len());
+        assert_eq!(Some("Breakdown 1".to_string()), hands[0].title);
+        assert_eq!(Some("All In 1".to_string()), hands[2].title);
+        assert_eq!(Some("https://youtu.be/kb_Bho5cM7A?t=1420".to_string()), hands[2].link);
+        assert_eq!(None, hands[3].title);
+        assert_eq!(Some("https://youtu.be/kb_Bho5cM7A?t=1651".to_string()), hands[5].link);
+    }
+
+    #[test]
+    fn parse_all__breakdown_1() {
+        let hand = &hands()[0];
+
+        assert_eq!(3, hand.players.len());
+        assert_eq!("J♥ 7♥", hand.player("DN").unwrap().cards.to_string());
+        assert_eq!(Some("D".to_string()), hand.player("DN").unwrap().position);
+        assert_eq!("Q♠ J♣ 7♣ 6♠ 8♦", hand.board.to_string());
+        assert_eq!(
+            vec![
+                action("Dan Smith", ActionKind::SmallBlind(1000)),
+                action("Chris Brewer", ActionKind::BigBlind(2000)),
+                action("DNEGS", ActionKind::Raise { by: 3000, to: 5000 }),
+                action("Dan Smith", ActionKind::Call(4000)),
+                action("Chris Brewer", ActionKind::Call(3000)),
+            ],
+            hand.actions(PhaseHoldem::Preflop)
+        );
+        assert_eq!(
+            vec![
+                action("Chris Brewer", ActionKind::Check),
+                action("DNEGS", ActionKind::Bet(125_000)),
+                action("Chris Brewer", ActionKind::Fold),
+            ],
+            hand.actions(PhaseHoldem::River)
+        );
+    }
+
+    #[test]
+    fn parse_all__labels_antes_and_calls() {
+        let hands = hands();
+        let second = &hands[1];
+        let fifth = &hands[4];
+
+        assert_eq!("Eric Seidel", second.player("D").unwrap().name);
+        assert_eq!("DNegs", second.player("DN").unwrap().name);
+        assert_eq!("9♥ 9♦", second.player("DN").unwrap().cards.to_string());
+        assert_eq!(5_000, second.ante);
+        assert_eq!(
+            action("Mikita Badziakouski", ActionKind::Call(7_000)),
+            second.actions(PhaseHoldem::Preflop)[5]
+        );
+        assert_eq!("David Peters", hands[3].player("UG").unwrap().name);
+        assert_eq!("5♣ 5♦", hands[3].player("UG").unwrap().cards.to_string());
+        assert_eq!(
+            action("Chris Brewer", ActionKind::Call(25_000)),
+            fifth.actions(PhaseHoldem::Preflop)[3]
+        );
+        assert_eq!("7♥ 6♦ 2♥ 6♠ 4♥", fifth.board.to_string());
+    }
+
+    #[test]
+    fn equities() {
+        let hand = &hands()[2];
+
+        let equities = hand.equities(false).unwrap();
+
+        assert_eq!(3, equities.len());
+        assert_eq!(Some("Brian Rast"), hand.game().unwrap().name(1));
+        assert_eq!(PhaseHoldem::River, equities[2].phase);
+        assert_eq!((0, 0), equities[2].results.wins_and_ties(0));
+        assert_eq!((1, 0), equities[2].results.wins_and_ties(1));
+        assert!(equities[0].results.wins_total_percentage(0) > 50.0);
+        assert_eq!(PKError::NotEnoughHands, hands()[1].equities(false).unwrap_err());
+    }
+
+    #[test]
+    fn annotate() {
+        let hand = &hands()[4];
+
+        let s = hand.annotate(false).unwrap();
+
+        assert!(s.contains("DNegs (CO) K♥ K♦\n"));
+        assert!(s.contains("\nFLOP: 7♥ 6♦ 2♥\n  DNegs [K♥ K♦] "));
+        assert!(s.contains("\nRIVER: 7♥ 6♦ 2♥ 6♠ 4♥\n"));
+        assert!(s.contains("  DNegs: raises 20000 to 35000\n"));
+    }
+
+    #[test]
+    fn parse_all__errors() {
+        assert_eq!(
+            PKError::InvalidHandHistory(2, "the FLOP has 3 cards, not 2".to_string()),
+            Breakdown::parse_all("SB: 100\nFLOP: QS JC").unwrap_err()
+        );
+        assert_eq!(
+            PKError::InvalidHandHistory(1, "don't know what to do with Something happened".to_string()),
+            Breakdown::parse_all("Something happened").unwrap_err()
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use crate::analysis::store::db::schema::{Migration, Schema, Versioned};
 use crate::analysis::store::db::sqlite::Sqlable;
 use crate::arrays::matchups::masked::{Masked, MASKED_DISTINCT};
 use crate::arrays::matchups::sorted_heads_up::SortedHeadsUp;
@@ -6,7 +7,7 @@ use crate::util::wincounter::win::Win;
 use crate::util::wincounter::wins::Wins;
 use crate::{PKError, Pile, Shifty, SuitShift};
 use csv::{Reader, WriterBuilder};
-use rusqlite::{named_params, Connection};
+use rusqlite::{named_params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::fmt::{Display, Formatter};
@@ -24,19 +25,36 @@ pub struct HUPResult {
 }
 
 impl HUPResult {
-    pub fn db_count(conn: &Connection) -> (usize, usize) {
-        let all = HUPResult::select_all(conn);
+    /// # Errors
+    ///
+    /// Throws an error if rusqlite isn't able to read the table.
+    pub fn db_count(conn: &Connection) -> rusqlite::Result<(usize, usize)> {
+        let all = HUPResult::select_all(conn)?;
         let len = all.len();
         let mut hs = HashSet::new();
         for hup in all {
             hs.insert(hup);
         }
-        (len, hs.len())
+        Ok((len, hs.len()))
+    }
+
+    /// How many matchups have more than one row. Anything other than zero and the version 3
+    /// upgrade won't go through.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if rusqlite isn't able to read the table.
+    pub fn duplicated(conn: &Connection) -> rusqlite::Result<usize> {
+        conn.query_row(
+            "SELECT count(*) FROM (SELECT 1 FROM nlh_headsup_result GROUP BY higher, lower HAVING count(*) > 1)",
+            [],
+            |row| row.get(0),
+        )
     }
 
+    /// A table that can't be read isn't valid either.
     pub fn db_is_valid(conn: &Connection) -> bool {
-        let (v, hs) = HUPResult::db_count(conn);
-        v == hs
+        matches!(HUPResult::db_count(conn), Ok((v, hs)) if v == hs)
     }
 
     /// `assert_eq!(first_ties, second_ties);`
@@ -146,16 +164,16 @@ impl HUPResult {
     /// Unable to close connection
     pub fn read_db(path: &str) -> rusqlite::Result<Vec<HUPResult>> {
         let conn = Connection::open(path)?;
-        let hups = HUPResult::select_all(&conn);
+        let hups = HUPResult::select_all(&conn)?;
         conn.close().unwrap();
         Ok(hups)
     }
 
     /// # Errors
     ///
-    /// Returns error if db contains duplicate entries..
+    /// Returns error if db contains duplicate entries, or can't be read.
     pub fn check_db(conn: &Connection) -> Result<usize, PKError> {
-        let (v, hs) = HUPResult::db_count(conn);
+        let (v, hs) = HUPResult::db_count(conn).map_err(|_| PKError::SqlError)?;
         if v == hs {
             Ok(v)
         } else {
@@ -163,25 +181,28 @@ impl HUPResult {
         }
     }
 
-    pub fn select_from_shifts(conn: &Connection, masked: &Masked) -> Option<HUPResult> {
+    /// # Errors
+    ///
+    /// Throws an error if rusqlite isn't able to read the table.
+    pub fn select_from_shifts(conn: &Connection, masked: &Masked) -> rusqlite::Result<Option<HUPResult>> {
         for shift in masked.shifts() {
-            match HUPResult::select(conn, &shift.shu) {
-                None => {}
-                Some(hupr) => {
-                    return Some(hupr);
-                }
+            if let Some(hupr) = HUPResult::select(conn, &shift.shu)? {
+                return Ok(Some(hupr));
             }
         }
-        None
+        Ok(None)
     }
 
-    pub fn distinct_remaining(conn: &Connection) -> HashSet<Masked> {
+    /// # Errors
+    ///
+    /// Throws an error if rusqlite isn't able to read the table.
+    pub fn distinct_remaining(conn: &Connection) -> rusqlite::Result<HashSet<Masked>> {
         let mut distinct = MASKED_DISTINCT.clone();
-        let hups = HUPResult::select_all(conn);
+        let hups = HUPResult::select_all(conn)?;
         for hup in hups {
             distinct.remove(&Masked::from(hup));
         }
-        distinct
+        Ok(distinct)
     }
 
     #[must_use]
@@ -307,11 +328,20 @@ impl From<&SortedHeadsUp> for HUPResult {
     }
 }
 
-impl Sqlable<HUPResult, SortedHeadsUp> for HUPResult {
-    fn create_table(conn: &Connection) -> rusqlite::Result<usize> {
-        log::debug!("HUPResult::create_table({:?})", conn);
-        conn.execute(
-            "create table if not exists nlh_headsup_result
+/// Version 1 is the table as it's always been. The indexes were always meant to be there, but
+/// `create_table()` used `execute()`, which only runs the first statement, so none of the files
+/// in `data/` have them. Version 3 makes sure there's only ever one row per matchup. It doesn't
+/// throw anything away to get there: if a file has more than one row for a matchup the unique
+/// index can't be built, and the whole upgrade is rolled back. `pk hup audit --repair` is how to
+/// clean them up first, and it says what it deleted.
+impl Versioned for HUPResult {
+    const STORE: &'static str = "nlh_headsup_result";
+    const MIGRATIONS: &'static [Migration] = &[
+        Migration {
+            version: 1,
+            description: "heads up results table",
+            creates: "nlh_headsup_result",
+            sql: "create table if not exists nlh_headsup_result
             (
                 id          integer not null
                     constraint nlh_headsup_result_pk
@@ -321,15 +351,42 @@ impl Sqlable<HUPResult, SortedHeadsUp> for HUPResult {
                 higher_wins integer not null,
                 lower_wins  integer not null,
                 ties        integer not null
-            );
-
-            create index if not exists nlh_headsup_result_higher_index
+            );",
+        },
+        Migration {
+            version: 2,
+            description: "indexes on higher and lower",
+            creates: "nlh_headsup_result_lower_index",
+            sql: "create index if not exists nlh_headsup_result_higher_index
                 on nlh_headsup_result (higher);
 
             create index if not exists nlh_headsup_result_lower_index
                 on nlh_headsup_result (lower);",
-            [],
-        )
+        },
+        Migration {
+            version: 3,
+            description: "one row per matchup",
+            creates: "nlh_headsup_result_matchup_index",
+            sql: "create unique index if not exists nlh_headsup_result_matchup_index
+                on nlh_headsup_result (higher, lower);",
+        },
+    ];
+}
+
+impl Sqlable<HUPResult, SortedHeadsUp> for HUPResult {
+    /// Returns how many migrations it took to bring the table up to date. If the file has more
+    /// than one row for a matchup, the upgrade is refused and nothing changes.
+    fn create_table(conn: &Connection) -> rusqlite::Result<usize> {
+        log::debug!("HUPResult::create_table({:?})", conn);
+        Schema::upgrade::<HUPResult>(conn)
+            .map(|upgrade| upgrade.applied())
+            .inspect_err(|_| {
+                if let Ok(duplicated @ 1..) = HUPResult::duplicated(conn) {
+                    log::error!(
+                        "HUPResult::create_table() {duplicated} matchups have more than one row. Run `pk hup audit --repair` first."
+                    );
+                }
+            })
     }
 
     /// This was written to Paul van Dyk's
@@ -339,7 +396,7 @@ impl Sqlable<HUPResult, SortedHeadsUp> for HUPResult {
     ///
     /// Oops. Little miss on the sig. Fixed now.
     fn exists(conn: &Connection, shu: &SortedHeadsUp) -> bool {
-        HUPResult::select(conn, shu).is_some()
+        matches!(HUPResult::select(conn, shu), Ok(Some(_)))
     }
 
     /// Refactoring this to only insert if the record isn't already there.
@@ -371,46 +428,47 @@ impl Sqlable<HUPResult, SortedHeadsUp> for HUPResult {
         }
     }
 
-    fn insert_many(_conn: &Connection, _records: Vec<&HUPResult>) -> rusqlite::Result<usize> {
-        todo!()
+    fn insert_many(conn: &Connection, records: Vec<&HUPResult>) -> rusqlite::Result<usize> {
+        let mut inserted = 0;
+        for hup in records {
+            if HUPResult::insert(conn, hup)? {
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
     }
 
-    fn select(conn: &Connection, key: &SortedHeadsUp) -> Option<HUPResult> {
+    fn select(conn: &Connection, key: &SortedHeadsUp) -> rusqlite::Result<Option<HUPResult>> {
         log::debug!("HUPResult::select({:?})", conn);
-        let mut stmt = conn
-            .prepare(
-                "SELECT higher_wins, lower_wins, ties \
+        let mut stmt = conn.prepare(
+            "SELECT higher_wins, lower_wins, ties \
             FROM nlh_headsup_result WHERE higher=:higher and lower=:lower",
-            )
-            .ok()?;
+        )?;
 
         let hb = key.higher().bard();
         let lb = key.lower().bard();
 
-        let hup = stmt
-            .query_row(
-                named_params! {
-                    ":higher": hb.as_u64(),
-                    ":lower": lb.as_u64(),
-                },
-                |row| {
-                    let hw = row.get(0)?;
-                    let lw = row.get(1)?;
-                    let ties = row.get(2)?;
-
-                    let r = HUPResult {
-                        higher: hb,
-                        lower: lb,
-                        higher_wins: hw,
-                        lower_wins: lw,
-                        ties,
-                    };
-                    Ok(r)
-                },
-            )
-            .ok()?;
-
-        Some(hup)
+        stmt.query_row(
+            named_params! {
+                ":higher": hb.as_u64(),
+                ":lower": lb.as_u64(),
+            },
+            |row| {
+                let hw = row.get(0)?;
+                let lw = row.get(1)?;
+                let ties = row.get(2)?;
+
+                let r = HUPResult {
+                    higher: hb,
+                    lower: lb,
+                    higher_wins: hw,
+                    lower_wins: lw,
+                    ties,
+                };
+                Ok(r)
+            },
+        )
+        .optional()
     }
 
     /// OK, so these results are completely foobared.
@@ -445,19 +503,19 @@ impl Sqlable<HUPResult, SortedHeadsUp> for HUPResult {
     ///  Q♦ J♦ (1198761) 9♠ 4♥ (498275) ties: (15268)
     /// ...
     /// ```
-    fn select_all(conn: &Connection) -> Vec<HUPResult> {
+    fn select_all(conn: &Connection) -> rusqlite::Result<Vec<HUPResult>> {
         log::debug!("HUPResult::select_all({:?})", conn);
 
-        let mut stmt = conn.prepare("SELECT * FROM nlh_headsup_result").ok().unwrap();
+        let mut stmt = conn.prepare("SELECT * FROM nlh_headsup_result")?;
 
         let mut r: Vec<HUPResult> = Vec::new();
-        let mut hups = stmt.query(()).unwrap();
-        while let Some(row) = hups.next().unwrap() {
-            let higher: u64 = row.get(1).unwrap();
-            let lower: u64 = row.get(2).unwrap();
-            let higher_wins: u64 = row.get(3).unwrap();
-            let lower_wins: u64 = row.get(4).unwrap();
-            let ties: u64 = row.get(5).unwrap();
+        let mut hups = stmt.query(())?;
+        while let Some(row) = hups.next()? {
+            let higher: u64 = row.get(1)?;
+            let lower: u64 = row.get(2)?;
+            let higher_wins: u64 = row.get(3)?;
+            let lower_wins: u64 = row.get(4)?;
+            let ties: u64 = row.get(5)?;
             let hup = HUPResult {
                 higher: Bard::from(higher),
                 lower: Bard::from(lower),
@@ -467,7 +525,7 @@ impl Sqlable<HUPResult, SortedHeadsUp> for HUPResult {
             };
             r.push(hup);
         }
-        r
+        Ok(r)
     }
 }
 
@@ -594,7 +652,7 @@ mod analysis__store__db__hupresult_tests {
     #[test]
     fn db_count() {
         let conn = Connection::open(SAMPLE_DB_PATH).unwrap();
-        let (v, hs) = HUPResult::db_count(&conn);
+        let (v, hs) = HUPResult::db_count(&conn).unwrap();
         assert_eq!(v, hs);
         conn.close().unwrap();
     }
@@ -722,6 +780,36 @@ mod analysis__store__db__hupresult_tests {
         conn.close().unwrap();
     }
 
+    /// Builds a file the way the old `create_table()` did, duplicate and all. The upgrade won't
+    /// touch it until the duplicate's been cleaned up.
+    #[test]
+    fn sqlable__create_table__upgrades_old_files() {
+        let conn = Connect::in_memory_connection().unwrap().connection;
+        conn.execute_batch(HUPResult::MIGRATIONS[0].sql).unwrap();
+        let the_hand = TestData::the_hand_as_hup_result();
+        HUPResult::insert(&conn, &the_hand).unwrap();
+        conn.execute(
+            "INSERT INTO nlh_headsup_result (higher, lower, higher_wins, lower_wins, ties) VALUES (?1, ?2, 0, 0, 0)",
+            [the_hand.higher.as_u64(), the_hand.lower.as_u64()],
+        )
+        .unwrap();
+        assert_eq!((1, true), Schema::version::<HUPResult>(&conn).unwrap());
+        assert_eq!(1, HUPResult::duplicated(&conn).unwrap());
+
+        assert!(HUPResult::create_table(&conn).is_err());
+        assert_eq!((1, true), Schema::version::<HUPResult>(&conn).unwrap());
+        assert_eq!(2, HUPResult::select_all(&conn).unwrap().len());
+
+        conn.execute("DELETE FROM nlh_headsup_result WHERE higher_wins = 0", [])
+            .unwrap();
+        assert_eq!(0, HUPResult::duplicated(&conn).unwrap());
+        assert_eq!(2, HUPResult::create_table(&conn).unwrap());
+
+        assert_eq!((3, false), Schema::version::<HUPResult>(&conn).unwrap());
+        assert_eq!(vec![the_hand], HUPResult::select_all(&conn).unwrap());
+        assert_eq!(0, HUPResult::create_table(&conn).unwrap());
+    }
+
     #[test]
     fn sqlable__exists() {
         // Preamble
@@ -770,8 +858,8 @@ mod analysis__store__db__hupresult_tests {
         HUPResult::create_table(&conn).unwrap();
         HUPResult::insert(&conn, &TestData::the_hand_as_hup_result()).unwrap();
 
-        let actual = HUPResult::select(&conn, &TestData::the_hand_sorted_headsup());
-        let nope = HUPResult::select(&conn, &SortedHeadsUp::new(Two::HAND_6S_6H, Two::HAND_5S_5D));
+        let actual = HUPResult::select(&conn, &TestData::the_hand_sorted_headsup()).unwrap();
+        let nope = HUPResult::select(&conn, &SortedHeadsUp::new(Two::HAND_6S_6H, Two::HAND_5S_5D)).unwrap();
 
         assert!(actual.is_some());
         assert_eq!(TestData::the_hand_as_hup_result(), actual.unwrap());
@@ -785,7 +873,7 @@ mod analysis__store__db__hupresult_tests {
         HUPResult::create_table(&conn).unwrap();
         HUPResult::insert(&conn, &TestData::the_hand_as_hup_result()).unwrap();
 
-        let actual = HUPResult::select_all(&conn);
+        let actual = HUPResult::select_all(&conn).unwrap();
 
         assert_eq!(actual.len(), 1);
         assert_eq!(&TestData::the_hand_as_hup_result(), actual.get(0).unwrap());
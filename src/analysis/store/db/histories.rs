@@ -0,0 +1,821 @@
+use crate::analysis::hud::{Hud, PlayerStats};
+use crate::analysis::store::db::schema::{Migration, Schema, Versioned};
+use crate::analysis::store::db::sqlite::Sqlable;
+use crate::analysis::store::nubibus::pluribus::Pluribus;
+use crate::analysis::store::pokerstars::{ActionKind, HandHistory, HistorySeat, PlayerAction, Showed, Street};
+use crate::cards::Cards;
+use crate::play::phases::PhaseHoldem;
+use crate::play::Position6Max;
+use crate::PKError;
+use rusqlite::types::ToSql;
+use rusqlite::{named_params, params_from_iter, Connection, OptionalExtension};
+use std::str::FromStr;
+
+/// # Stored Hands
+///
+/// A home for parsed hand histories. Each `HandHistory` is broken out into a normalised schema:
+///
+/// * `hh_hands` - one row for every hand, with the session it came from.
+/// * `hh_players` - every player's name, once.
+/// * `hh_seats` - who sat where, with how many chips, and what they were dealt.
+/// * `hh_actions` - every action, in order, with the street it happened on.
+/// * `hh_boards` - the cards dealt on each street.
+/// * `hh_results` - what each player collected and showed, along with the stats that `Hud`
+///   works out for them, so that questions like "who 3-bet from the button and went to showdown"
+///   are a simple `WHERE` clause.
+///
+/// Hands come back out of the database exactly as they went in, so anything you can do with a
+/// `HandHistory`, like writing it back out as a `PokerStars` log, you can do with a query
+/// result.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StoredHand {
+    pub session: usize,
+    pub hand: HandHistory,
+}
+
+impl StoredHand {
+    #[must_use]
+    pub fn new(session: usize, hand: HandHistory) -> StoredHand {
+        StoredHand { session, hand }
+    }
+
+    /// Imports every hand in a directory of Pluribus ACPC logs, like `data/pluribus/raw`.
+    /// Returns the number of hands that weren't already there.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::Fubar` if a log can't be read.
+    /// * `PKError::InvalidPluribusIndex` if a hand in a log can't be made sense of.
+    /// * `PKError::SqlError` if the hands can't be written.
+    pub fn import_pluribus_logs(conn: &Connection, dir: &str) -> Result<usize, PKError> {
+        let mut count = 0;
+        for (session, path) in Pluribus::logs(dir)? {
+            let hands = Pluribus::from_log(&path.to_string_lossy())?
+                .iter()
+                .map(|pluribus| {
+                    Ok(StoredHand::new(
+                        session,
+                        HandHistory::from_pluribus(pluribus, session, "")?,
+                    ))
+                })
+                .collect::<Result<Vec<StoredHand>, PKError>>()?;
+            count += StoredHand::insert_many(conn, hands.iter().collect()).map_err(|_| PKError::SqlError)?;
+        }
+        Ok(count)
+    }
+
+    /// Imports every hand in a `PokerStars` hand history file. Returns the number of hands that
+    /// weren't already there.
+    ///
+    /// # Errors
+    ///
+    /// * Anything `HandHistory::from_file()` returns.
+    /// * `PKError::SqlError` if the hands can't be written.
+    pub fn import_pokerstars(conn: &Connection, path: &str, session: usize) -> Result<usize, PKError> {
+        let hands = HandHistory::from_file(path)?
+            .into_iter()
+            .map(|hand| StoredHand::new(session, hand))
+            .collect::<Vec<StoredHand>>();
+        StoredHand::insert_many(conn, hands.iter().collect()).map_err(|_| PKError::SqlError)
+    }
+
+    /// Every hand that matches the query, in order.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if rusqlite isn't able to run the query.
+    pub fn query(conn: &Connection, query: &HandQuery) -> rusqlite::Result<Vec<StoredHand>> {
+        let (sql, params) = query.sql();
+        let mut stmt = conn.prepare(&sql)?;
+        let ids = stmt
+            .query_map(params_from_iter(params.iter()), |row| row.get::<_, usize>(0))?
+            .collect::<rusqlite::Result<Vec<usize>>>()?;
+        ids.iter()
+            .filter_map(|id| StoredHand::select(conn, id).transpose())
+            .collect()
+    }
+
+    fn player_id(conn: &Connection, name: &str) -> rusqlite::Result<i64> {
+        conn.execute(
+            "INSERT OR IGNORE INTO hh_players (name) VALUES (:name)",
+            named_params! {":name": name},
+        )?;
+        conn.query_row(
+            "SELECT id FROM hh_players WHERE name = :name",
+            named_params! {":name": name},
+            |row| row.get(0),
+        )
+    }
+
+    fn cards(s: Option<String>) -> Cards {
+        s.and_then(|s| Cards::from_str(&s).ok()).unwrap_or_default()
+    }
+
+    fn letters(cards: &Cards) -> Option<String> {
+        (!cards.is_empty()).then(|| cards.to_letters().join(" "))
+    }
+
+    fn phase(street: i64) -> PhaseHoldem {
+        match street {
+            2 => PhaseHoldem::Flop,
+            3 => PhaseHoldem::Turn,
+            4 => PhaseHoldem::River,
+            _ => PhaseHoldem::Preflop,
+        }
+    }
+
+    fn kind(kind: &str, amount: usize, to: usize) -> ActionKind {
+        match kind {
+            "small blind" => ActionKind::SmallBlind(amount),
+            "big blind" => ActionKind::BigBlind(amount),
+            "call" => ActionKind::Call(amount),
+            "bet" => ActionKind::Bet(amount),
+            "raise" => ActionKind::Raise { by: amount, to },
+            "check" => ActionKind::Check,
+            _ => ActionKind::Fold,
+        }
+    }
+
+    fn kind_columns(kind: ActionKind) -> (&'static str, usize, usize) {
+        match kind {
+            ActionKind::SmallBlind(amount) => ("small blind", amount, 0),
+            ActionKind::BigBlind(amount) => ("big blind", amount, 0),
+            ActionKind::Fold => ("fold", 0, 0),
+            ActionKind::Check => ("check", 0, 0),
+            ActionKind::Call(amount) => ("call", amount, 0),
+            ActionKind::Bet(amount) => ("bet", amount, 0),
+            ActionKind::Raise { by, to } => ("raise", by, to),
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn write(conn: &Connection, stored: &StoredHand) -> rusqlite::Result<()> {
+        let hand = &stored.hand;
+        let (uncalled_player, uncalled_amount) = match &hand.uncalled {
+            Some((name, amount)) => (Some(StoredHand::player_id(conn, name)?), *amount),
+            None => (None, 0),
+        };
+        conn.execute(
+            "INSERT INTO hh_hands \
+            (id, session, small_blind, big_blind, date, table_name, max_seats, play_money, button, \
+            uncalled_player, uncalled_amount, showdown, total_pot, rake, summary_board) VALUES \
+            (:id, :session, :small_blind, :big_blind, :date, :table_name, :max_seats, :play_money, :button, \
+            :uncalled_player, :uncalled_amount, :showdown, :total_pot, :rake, :summary_board)",
+            named_params! {
+                ":id": hand.id,
+                ":session": stored.session,
+                ":small_blind": hand.small_blind,
+                ":big_blind": hand.big_blind,
+                ":date": hand.date,
+                ":table_name": hand.table,
+                ":max_seats": hand.max_seats,
+                ":play_money": hand.play_money,
+                ":button": hand.button,
+                ":uncalled_player": uncalled_player,
+                ":uncalled_amount": uncalled_amount,
+                ":showdown": hand.showdown,
+                ":total_pot": hand.total_pot,
+                ":rake": hand.rake,
+                ":summary_board": StoredHand::letters(&hand.summary_board),
+            },
+        )?;
+
+        let positions = Hud::positions(hand);
+        for seat in &hand.seats {
+            let player = StoredHand::player_id(conn, &seat.name)?;
+            let dealt = hand
+                .dealt
+                .iter()
+                .find(|(name, _)| *name == seat.name)
+                .map(|(_, cards)| cards);
+            conn.execute(
+                "INSERT INTO hh_seats (hand_id, seat, player_id, stack, hole_cards) \
+                VALUES (:hand_id, :seat, :player_id, :stack, :hole_cards)",
+                named_params! {
+                    ":hand_id": hand.id,
+                    ":seat": seat.seat,
+                    ":player_id": player,
+                    ":stack": seat.stack,
+                    ":hole_cards": dealt.and_then(StoredHand::letters),
+                },
+            )?;
+
+            let stats = PlayerStats::from_hand(hand, &seat.name);
+            let position = positions
+                .iter()
+                .find(|(name, _)| *name == seat.name)
+                .map(|(_, position)| position.to_string());
+            let collected = hand
+                .collected
+                .iter()
+                .filter(|(name, _)| *name == seat.name)
+                .map(|(_, amount)| *amount)
+                .reduce(|a, b| a + b);
+            let shows = hand
+                .shows
+                .iter()
+                .find(|(name, _)| *name == seat.name)
+                .map(|(_, cards)| cards);
+            let showed = hand.showed.iter().find(|showed| showed.name == seat.name);
+            conn.execute(
+                "INSERT INTO hh_results \
+                (hand_id, player_id, position, collected, shows, showed, won, vpip, pfr, three_bet, \
+                folded_to_three_bet, cbet, saw_flop, went_to_showdown, won_at_showdown, chips_won) VALUES \
+                (:hand_id, :player_id, :position, :collected, :shows, :showed, :won, :vpip, :pfr, :three_bet, \
+                :folded_to_three_bet, :cbet, :saw_flop, :went_to_showdown, :won_at_showdown, :chips_won)",
+                named_params! {
+                    ":hand_id": hand.id,
+                    ":player_id": player,
+                    ":position": position,
+                    ":collected": collected,
+                    ":shows": shows.and_then(StoredHand::letters),
+                    ":showed": showed.and_then(|showed| StoredHand::letters(&showed.cards)),
+                    ":won": showed.and_then(|showed| showed.won),
+                    ":vpip": stats.vpip,
+                    ":pfr": stats.pfr,
+                    ":three_bet": stats.three_bets,
+                    ":folded_to_three_bet": stats.folds_to_three_bets,
+                    ":cbet": stats.cbets,
+                    ":saw_flop": stats.saw_flop,
+                    ":went_to_showdown": stats.went_to_showdown,
+                    ":won_at_showdown": stats.won_at_showdown,
+                    ":chips_won": stats.chips_won,
+                },
+            )?;
+        }
+
+        let mut number = 0;
+        for street in &hand.streets {
+            let phase = street.phase as i64;
+            if street.phase != PhaseHoldem::Preflop {
+                conn.execute(
+                    "INSERT INTO hh_boards (hand_id, street, cards) VALUES (:hand_id, :street, :cards)",
+                    named_params! {
+                        ":hand_id": hand.id,
+                        ":street": phase,
+                        ":cards": StoredHand::letters(&street.cards),
+                    },
+                )?;
+            }
+            for action in &street.actions {
+                let (kind, amount, to) = StoredHand::kind_columns(action.kind);
+                conn.execute(
+                    "INSERT INTO hh_actions (hand_id, number, street, player_id, kind, amount, raise_to, all_in) \
+                    VALUES (:hand_id, :number, :street, :player_id, :kind, :amount, :raise_to, :all_in)",
+                    named_params! {
+                        ":hand_id": hand.id,
+                        ":number": number,
+                        ":street": phase,
+                        ":player_id": StoredHand::player_id(conn, &action.player)?,
+                        ":kind": kind,
+                        ":amount": amount,
+                        ":raise_to": to,
+                        ":all_in": action.all_in,
+                    },
+                )?;
+                number += 1;
+            }
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn read(conn: &Connection, id: usize) -> rusqlite::Result<Option<StoredHand>> {
+        let Some((session, mut hand, uncalled)) = conn
+            .query_row(
+                "SELECT h.session, h.small_blind, h.big_blind, h.date, h.table_name, h.max_seats, h.play_money, \
+                h.button, p.name, h.uncalled_amount, h.showdown, h.total_pot, h.rake, h.summary_board \
+                FROM hh_hands h LEFT JOIN hh_players p ON p.id = h.uncalled_player WHERE h.id = :id",
+                named_params! {":id": id},
+                |row| {
+                    let hand = HandHistory {
+                        id,
+                        small_blind: row.get(1)?,
+                        big_blind: row.get(2)?,
+                        date: row.get(3)?,
+                        table: row.get(4)?,
+                        max_seats: row.get(5)?,
+                        play_money: row.get(6)?,
+                        button: row.get(7)?,
+                        showdown: row.get(10)?,
+                        total_pot: row.get(11)?,
+                        rake: row.get(12)?,
+                        summary_board: StoredHand::cards(row.get(13)?),
+                        ..HandHistory::default()
+                    };
+                    let uncalled = row
+                        .get::<_, Option<String>>(8)?
+                        .map(|name| (name, row.get(9).unwrap_or_default()));
+                    Ok((row.get(0)?, hand, uncalled))
+                },
+            )
+            .optional()?
+        else {
+            return Ok(None);
+        };
+        hand.uncalled = uncalled;
+
+        let mut stmt = conn.prepare(
+            "SELECT s.seat, p.name, s.stack, s.hole_cards, r.collected, r.shows, r.showed, r.won \
+            FROM hh_seats s JOIN hh_players p ON p.id = s.player_id \
+            JOIN hh_results r ON r.hand_id = s.hand_id AND r.player_id = s.player_id \
+            WHERE s.hand_id = :id ORDER BY s.seat",
+        )?;
+        let mut rows = stmt.query(named_params! {":id": id})?;
+        while let Some(row) = rows.next()? {
+            let seat: usize = row.get(0)?;
+            let name: String = row.get(1)?;
+            hand.seats.push(HistorySeat {
+                seat,
+                name: name.clone(),
+                stack: row.get(2)?,
+            });
+            if let Some(cards) = row.get::<_, Option<String>>(3)? {
+                hand.dealt.push((name.clone(), StoredHand::cards(Some(cards))));
+            }
+            if let Some(collected) = row.get::<_, Option<f64>>(4)? {
+                hand.collected.push((name.clone(), collected));
+            }
+            if let Some(cards) = row.get::<_, Option<String>>(5)? {
+                hand.shows.push((name.clone(), StoredHand::cards(Some(cards))));
+            }
+            if let Some(cards) = row.get::<_, Option<String>>(6)? {
+                hand.showed.push(Showed {
+                    seat,
+                    name,
+                    cards: StoredHand::cards(Some(cards)),
+                    won: row.get(7)?,
+                });
+            }
+        }
+
+        hand.streets.push(Street {
+            phase: PhaseHoldem::Preflop,
+            ..Street::default()
+        });
+        let mut stmt = conn.prepare("SELECT street, cards FROM hh_boards WHERE hand_id = :id ORDER BY street")?;
+        let mut rows = stmt.query(named_params! {":id": id})?;
+        while let Some(row) = rows.next()? {
+            hand.streets.push(Street {
+                phase: StoredHand::phase(row.get(0)?),
+                cards: StoredHand::cards(row.get(1)?),
+                actions: Vec::new(),
+            });
+        }
+
+        let mut stmt = conn.prepare(
+            "SELECT a.street, p.name, a.kind, a.amount, a.raise_to, a.all_in \
+            FROM hh_actions a JOIN hh_players p ON p.id = a.player_id \
+            WHERE a.hand_id = :id ORDER BY a.number",
+        )?;
+        let mut rows = stmt.query(named_params! {":id": id})?;
+        while let Some(row) = rows.next()? {
+            let phase = StoredHand::phase(row.get(0)?);
+            let kind: String = row.get(2)?;
+            let action = PlayerAction {
+                player: row.get(1)?,
+                kind: StoredHand::kind(&kind, row.get(3)?, row.get(4)?),
+                all_in: row.get(5)?,
+            };
+            if let Some(street) = hand.streets.iter_mut().find(|street| street.phase == phase) {
+                street.actions.push(action);
+            }
+        }
+
+        Ok(Some(StoredHand { session, hand }))
+    }
+}
+
+/// The hand history tables are versioned together, since a hand is written across all of them.
+impl Versioned for StoredHand {
+    const STORE: &'static str = "hh";
+    const MIGRATIONS: &'static [Migration] = &[Migration {
+        version: 1,
+        description: "hand history tables",
+        creates: "hh_results_player_index",
+        sql: "create table if not exists hh_hands
+            (
+                id              integer not null
+                    constraint hh_hands_pk
+                        primary key,
+                session         integer not null,
+                small_blind     integer not null,
+                big_blind       integer not null,
+                date            text    not null,
+                table_name      text    not null,
+                max_seats       integer not null,
+                play_money      integer not null,
+                button          integer not null,
+                uncalled_player integer,
+                uncalled_amount integer not null,
+                showdown        integer not null,
+                total_pot       integer not null,
+                rake            integer not null,
+                summary_board   text
+            );
+
+            create index if not exists hh_hands_session_index
+                on hh_hands (session);
+
+            create table if not exists hh_players
+            (
+                id   integer not null
+                    constraint hh_players_pk
+                        primary key autoincrement,
+                name text    not null unique
+            );
+
+            create table if not exists hh_seats
+            (
+                hand_id    integer not null,
+                seat       integer not null,
+                player_id  integer not null,
+                stack      integer not null,
+                hole_cards text,
+                constraint hh_seats_pk
+                    primary key (hand_id, seat)
+            );
+
+            create table if not exists hh_actions
+            (
+                hand_id   integer not null,
+                number    integer not null,
+                street    integer not null,
+                player_id integer not null,
+                kind      text    not null,
+                amount    integer not null,
+                raise_to  integer not null,
+                all_in    integer not null,
+                constraint hh_actions_pk
+                    primary key (hand_id, number)
+            );
+
+            create table if not exists hh_boards
+            (
+                hand_id integer not null,
+                street  integer not null,
+                cards   text,
+                constraint hh_boards_pk
+                    primary key (hand_id, street)
+            );
+
+            create table if not exists hh_results
+            (
+                hand_id             integer not null,
+                player_id           integer not null,
+                position            text,
+                collected           real,
+                shows               text,
+                showed              text,
+                won                 real,
+                vpip                integer not null,
+                pfr                 integer not null,
+                three_bet           integer not null,
+                folded_to_three_bet integer not null,
+                cbet                integer not null,
+                saw_flop            integer not null,
+                went_to_showdown    integer not null,
+                won_at_showdown     integer not null,
+                chips_won           real    not null,
+                constraint hh_results_pk
+                    primary key (hand_id, player_id)
+            );
+
+            create index if not exists hh_results_player_index
+                on hh_results (player_id);",
+    }];
+}
+
+impl Sqlable<StoredHand, usize> for StoredHand {
+    /// Returns how many migrations it took to bring the table up to date.
+    fn create_table(conn: &Connection) -> rusqlite::Result<usize> {
+        log::debug!("StoredHand::create_table({conn:?})");
+        Schema::upgrade::<StoredHand>(conn).map(|upgrade| upgrade.applied())
+    }
+
+    fn exists(conn: &Connection, id: &usize) -> bool {
+        conn.query_row(
+            "SELECT 1 FROM hh_hands WHERE id = :id",
+            named_params! {":id": id},
+            |_| Ok(()),
+        )
+        .is_ok()
+    }
+
+    /// Returns true if the hand wasn't already there. Everything about the hand is written in a
+    /// single transaction, so a hand is never half there.
+    fn insert(conn: &Connection, record: &StoredHand) -> rusqlite::Result<bool> {
+        log::debug!("StoredHand::insert({})", record.hand.id);
+        if StoredHand::exists(conn, &record.hand.id) {
+            return Ok(false);
+        }
+        let tx = conn.unchecked_transaction()?;
+        StoredHand::write(&tx, record)?;
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Writes all of the hands in a single transaction, which is a whole lot faster than one at a
+    /// time.
+    fn insert_many(conn: &Connection, records: Vec<&StoredHand>) -> rusqlite::Result<usize> {
+        let tx = conn.unchecked_transaction()?;
+        let mut count = 0;
+        for record in records {
+            if !StoredHand::exists(&tx, &record.hand.id) {
+                StoredHand::write(&tx, record)?;
+                count += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(count)
+    }
+
+    fn select(conn: &Connection, id: &usize) -> rusqlite::Result<Option<StoredHand>> {
+        StoredHand::read(conn, *id)
+    }
+
+    fn select_all(conn: &Connection) -> rusqlite::Result<Vec<StoredHand>> {
+        let mut stmt = conn.prepare("SELECT id FROM hh_hands ORDER BY id")?;
+        let ids = stmt
+            .query_map([], |row| row.get::<_, usize>(0))?
+            .collect::<rusqlite::Result<Vec<usize>>>()?;
+        ids.iter()
+            .filter_map(|id| StoredHand::select(conn, id).transpose())
+            .collect()
+    }
+}
+
+/// A typed query over the stored hands. Everything about a player, like their position and
+/// what they did, has to be true for the same player in the same hand. Leave the player out,
+/// and any player will do.
+///
+/// ```
+/// use pkcore::analysis::store::db::histories::HandQuery;
+/// use pkcore::play::Position6Max;
+///
+/// // All the hands where Pluribus 3-bet from the button and went to showdown.
+/// let query = HandQuery::default()
+///     .player("Pluribus")
+///     .position(Position6Max::BTN)
+///     .three_bet(true)
+///     .went_to_showdown(true);
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HandQuery {
+    pub player: Option<String>,
+    pub positions: Vec<Position6Max>,
+    pub sessions: Vec<usize>,
+    pub vpip: Option<bool>,
+    pub pfr: Option<bool>,
+    pub three_bet: Option<bool>,
+    pub folded_to_three_bet: Option<bool>,
+    pub cbet: Option<bool>,
+    pub saw_flop: Option<bool>,
+    pub went_to_showdown: Option<bool>,
+    pub won_at_showdown: Option<bool>,
+    pub limit: Option<usize>,
+}
+
+impl HandQuery {
+    #[must_use]
+    pub fn player(mut self, name: &str) -> Self {
+        self.player = Some(name.to_string());
+        self
+    }
+
+    #[must_use]
+    pub fn position(mut self, position: Position6Max) -> Self {
+        self.positions.push(position);
+        self
+    }
+
+    #[must_use]
+    pub fn session(mut self, session: usize) -> Self {
+        self.sessions.push(session);
+        self
+    }
+
+    #[must_use]
+    pub fn vpip(mut self, vpip: bool) -> Self {
+        self.vpip = Some(vpip);
+        self
+    }
+
+    #[must_use]
+    pub fn pfr(mut self, pfr: bool) -> Self {
+        self.pfr = Some(pfr);
+        self
+    }
+
+    #[must_use]
+    pub fn three_bet(mut self, three_bet: bool) -> Self {
+        self.three_bet = Some(three_bet);
+        self
+    }
+
+    #[must_use]
+    pub fn folded_to_three_bet(mut self, folded: bool) -> Self {
+        self.folded_to_three_bet = Some(folded);
+        self
+    }
+
+    #[must_use]
+    pub fn cbet(mut self, cbet: bool) -> Self {
+        self.cbet = Some(cbet);
+        self
+    }
+
+    #[must_use]
+    pub fn saw_flop(mut self, saw_flop: bool) -> Self {
+        self.saw_flop = Some(saw_flop);
+        self
+    }
+
+    #[must_use]
+    pub fn went_to_showdown(mut self, went: bool) -> Self {
+        self.went_to_showdown = Some(went);
+        self
+    }
+
+    #[must_use]
+    pub fn won_at_showdown(mut self, won: bool) -> Self {
+        self.won_at_showdown = Some(won);
+        self
+    }
+
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The SQL for the ids of the matching hands, and the values to bind to it.
+    fn sql(&self) -> (String, Vec<Box<dyn ToSql>>) {
+        let mut clauses: Vec<String> = Vec::new();
+        let mut params: Vec<Box<dyn ToSql>> = Vec::new();
+        if let Some(player) = &self.player {
+            clauses.push("p.name = ?".to_string());
+            params.push(Box::new(player.clone()));
+        }
+        if !self.positions.is_empty() {
+            clauses.push(format!(
+                "r.position IN ({})",
+                vec!["?"; self.positions.len()].join(", ")
+            ));
+            params.extend(
+                self.positions
+                    .iter()
+                    .map(|position| Box::new(position.to_string()) as Box<dyn ToSql>),
+            );
+        }
+        if !self.sessions.is_empty() {
+            clauses.push(format!("h.session IN ({})", vec!["?"; self.sessions.len()].join(", ")));
+            params.extend(self.sessions.iter().map(|session| Box::new(*session) as Box<dyn ToSql>));
+        }
+        for (column, flag) in [
+            ("vpip", self.vpip),
+            ("pfr", self.pfr),
+            ("three_bet", self.three_bet),
+            ("folded_to_three_bet", self.folded_to_three_bet),
+            ("cbet", self.cbet),
+            ("saw_flop", self.saw_flop),
+            ("went_to_showdown", self.went_to_showdown),
+            ("won_at_showdown", self.won_at_showdown),
+        ] {
+            if let Some(flag) = flag {
+                clauses.push(format!("r.{column} = ?"));
+                params.push(Box::new(flag));
+            }
+        }
+
+        let mut sql = "SELECT DISTINCT h.id FROM hh_hands h \
+            JOIN hh_results r ON r.hand_id = h.id \
+            JOIN hh_players p ON p.id = r.player_id"
+            .to_string();
+        if !clauses.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&clauses.join(" AND "));
+        }
+        sql.push_str(" ORDER BY h.id");
+        if let Some(limit) = self.limit {
+            sql.push_str(" LIMIT ");
+            sql.push_str(&limit.to_string());
+        }
+        (sql, params)
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__db__histories_tests {
+    use super::*;
+    use crate::analysis::hud::HudFilter;
+    use crate::analysis::store::pokerstars::analysis__store__pokerstars_tests::HAND;
+    use crate::util::data::TestData;
+
+    fn stored() -> StoredHand {
+        StoredHand::new(100, HandHistory::from_str(HAND).unwrap())
+    }
+
+    #[test]
+    fn insert() {
+        let conn = TestData::connection::<StoredHand, _>(&[]);
+
+        assert!(StoredHand::insert(&conn, &stored()).unwrap());
+        assert!(!StoredHand::insert(&conn, &stored()).unwrap());
+        assert!(StoredHand::exists(&conn, &100_062));
+        assert!(!StoredHand::exists(&conn, &100_063));
+    }
+
+    #[test]
+    fn select() {
+        let conn = TestData::connection::<StoredHand, _>(&[]);
+        StoredHand::insert(&conn, &stored()).unwrap();
+
+        assert_eq!(Some(stored()), StoredHand::select(&conn, &100_062).unwrap());
+        assert_eq!(None, StoredHand::select(&conn, &100_063).unwrap());
+        conn.execute("DROP TABLE hh_actions", []).unwrap();
+        assert!(StoredHand::select(&conn, &100_062).is_err());
+    }
+
+    /// Every hand in a converted log comes back out of the database byte for byte.
+    #[test]
+    fn import_pokerstars() {
+        let conn = TestData::connection::<StoredHand, _>(&[]);
+        let path = "data/pluribus/converted_logs/pluribus_102.txt";
+
+        let count = StoredHand::import_pokerstars(&conn, path, 102).unwrap();
+        let hands = StoredHand::select_all(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|stored| stored.hand)
+            .collect::<Vec<HandHistory>>();
+
+        assert_eq!(HandHistory::from_file(path).unwrap().len(), count);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), HandHistory::to_log(&hands));
+        assert_eq!(0, StoredHand::import_pokerstars(&conn, path, 102).unwrap());
+    }
+
+    #[test]
+    fn query() {
+        let conn = TestData::connection::<StoredHand, _>(&[]);
+        StoredHand::insert(&conn, &stored()).unwrap();
+
+        let found = |query: HandQuery| {
+            StoredHand::query(&conn, &query)
+                .unwrap()
+                .iter()
+                .map(|stored| stored.hand.id)
+                .collect::<Vec<usize>>()
+        };
+
+        assert_eq!(vec![100_062], found(HandQuery::default()));
+        assert_eq!(
+            vec![100_062],
+            found(
+                HandQuery::default()
+                    .player("MrWhite")
+                    .position(Position6Max::SB)
+                    .three_bet(true)
+            )
+        );
+        assert!(found(HandQuery::default().player("MrWhite").went_to_showdown(true)).is_empty());
+        assert_eq!(
+            vec![100_062],
+            found(
+                HandQuery::default()
+                    .position(Position6Max::BTN)
+                    .went_to_showdown(true)
+                    .won_at_showdown(true)
+            )
+        );
+        assert!(found(HandQuery::default().session(101)).is_empty());
+    }
+
+    /// Every time Pluribus 3-bet in a session, the same as the HUD counts.
+    #[test]
+    fn import_pluribus_logs() {
+        let raw = std::env::temp_dir().join("pkcore_histories_raw");
+        std::fs::create_dir_all(&raw).unwrap();
+        for log in ["sample_game_102.log", "sample_game_102b.log"] {
+            std::fs::copy(format!("data/pluribus/raw/{log}"), raw.join(log)).unwrap();
+        }
+        let conn = TestData::connection::<StoredHand, _>(&[]);
+
+        let count = StoredHand::import_pluribus_logs(&conn, &raw.to_string_lossy()).unwrap();
+        let hands = StoredHand::query(
+            &conn,
+            &HandQuery::default().player("Pluribus").three_bet(true).session(102),
+        )
+        .unwrap();
+        let hud = Hud::from_pluribus_logs(&raw.to_string_lossy(), HudFilter::default()).unwrap();
+
+        assert_eq!(hud.get("Pluribus").unwrap().hands, count);
+        assert_eq!(hud.get("Pluribus").unwrap().three_bets, hands.len());
+        for stored in hands {
+            assert_eq!(102, stored.session);
+            assert_eq!(1, PlayerStats::from_hand(&stored.hand, "Pluribus").three_bets);
+        }
+        std::fs::remove_dir_all(raw).unwrap();
+    }
+}
@@ -0,0 +1,684 @@
+use crate::analysis::store::db::headsup_preflop_result::HUPResult;
+use crate::analysis::store::db::hup_lookup::HupLookup;
+use crate::analysis::store::db::sqlite::Sqlable;
+use crate::arrays::matchups::sorted_heads_up::SortedHeadsUp;
+use crate::arrays::two::Two;
+use crate::bard::Bard;
+use crate::play::stages::deal_eval::DealEval;
+use crate::{PKError, Pile};
+use rand::seq::SliceRandom;
+use rayon::prelude::*;
+use rusqlite::{named_params, Connection};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// The `higher` and `lower` of a row, which is what's supposed to be unique.
+type Matchup = (Bard, Bard);
+
+/// A stored result, along with what it looks like under its canonical suit permutation.
+type Canonicalized = (HUPResult, HUPResult);
+
+/// How many of the stored results to calculate again from scratch.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recompute {
+    /// Just the checks that don't need any calculating.
+    #[default]
+    None,
+    /// This many, picked at random.
+    Sample(usize),
+    /// Every last one of them. Bring a book.
+    All,
+}
+
+/// Something wrong, or at least fishy, with what's in `nlh_headsup_result`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HupAuditIssue {
+    /// The row isn't two different, valid hands.
+    Invalid { result: HUPResult },
+    /// More than one row for the same matchup.
+    Duplicate { rows: Vec<HUPResult> },
+    /// The lower hand was stored as the higher one, so `HUPResult::select()` will never find it.
+    Unsorted { result: HUPResult },
+    /// Both sides of a matchup are stored, and their wins aren't the other's flipped around.
+    MirrorMismatch { result: HUPResult, mirror: HUPResult },
+    /// The wins and ties don't add up to every possible board.
+    BadTotal { result: HUPResult, total: u64 },
+    /// Two suit shifts of the same matchup are stored with different results. One of them has
+    /// to be wrong.
+    ShiftMismatch { result: HUPResult, shift: HUPResult },
+    /// Different matchups with exactly the same results. The old `gaps` example found a bunch of these
+    /// that turned out to be rows that got shifted onto the wrong hands.
+    Gap { results: Vec<HUPResult> },
+    /// Calculating the matchup again gave us something else.
+    Mismatch { stored: HUPResult, expected: HUPResult },
+}
+
+impl HupAuditIssue {
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            HupAuditIssue::Invalid { .. } => "invalid",
+            HupAuditIssue::Duplicate { .. } => "duplicate",
+            HupAuditIssue::Unsorted { .. } => "unsorted",
+            HupAuditIssue::MirrorMismatch { .. } => "mirror_mismatch",
+            HupAuditIssue::BadTotal { .. } => "bad_total",
+            HupAuditIssue::ShiftMismatch { .. } => "shift_mismatch",
+            HupAuditIssue::Gap { .. } => "gap",
+            HupAuditIssue::Mismatch { .. } => "mismatch",
+        }
+    }
+}
+
+impl Display for HupAuditIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HupAuditIssue::Invalid { result } | HupAuditIssue::Unsorted { result } => {
+                write!(f, "{}: {result}", self.kind())
+            }
+            HupAuditIssue::Duplicate { rows } => write!(f, "{}: {} rows for {}", self.kind(), rows.len(), rows[0]),
+            HupAuditIssue::MirrorMismatch { result, mirror } => write!(f, "{}: {result} vs {mirror}", self.kind()),
+            HupAuditIssue::BadTotal { result, total } => write!(f, "{}: {result} adds up to {total}", self.kind()),
+            HupAuditIssue::ShiftMismatch { result, shift } => write!(f, "{}: {result} vs {shift}", self.kind()),
+            HupAuditIssue::Gap { results } => {
+                let results: Vec<String> = results.iter().map(ToString::to_string).collect();
+                write!(f, "{}: {}", self.kind(), results.join(" | "))
+            }
+            HupAuditIssue::Mismatch { stored, expected } => {
+                write!(f, "{}: stored {stored} expected {expected}", self.kind())
+            }
+        }
+    }
+}
+
+/// # Heads Up Audit
+///
+/// `examples/audit.rs`, `gaps.rs`, `hup_check.rs` and `HUPResult::check_db()` each did a piece
+/// of checking `hups.db`, and none of them did all of it. This does all of it in one pass:
+///
+/// * Rows that aren't two different, valid hands.
+/// * More than one row for the same matchup.
+/// * Rows stored lower hand first, and if the other way around is stored too, whether the two
+///   agree.
+/// * Wins and ties that don't add up to every possible board.
+/// * Suit shifts of the same matchup stored with different results. `Shifty` misses some of
+///   them, so this goes by `HupLookup::canonical()` instead.
+/// * Gaps, where different matchups have exactly the same results.
+/// * Calculating some or all of the matchups again, and comparing.
+///
+/// The report serializes to JSON, and `HupAuditReport::repair()` can clean up what it found.
+///
+/// ```txt
+/// cargo run --release --example audit -- -d generated/hups.db -s 100 -j audit.json
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HupAudit {
+    pub recompute: Recompute,
+    /// Skip the gap check. It's the one most likely to turn up things that are fine.
+    pub skip_gaps: bool,
+}
+
+impl HupAudit {
+    #[must_use]
+    pub fn new() -> HupAudit {
+        HupAudit::default()
+    }
+
+    #[must_use]
+    pub fn recompute(mut self, recompute: Recompute) -> Self {
+        self.recompute = recompute;
+        self
+    }
+
+    #[must_use]
+    pub fn skip_gaps(mut self) -> Self {
+        self.skip_gaps = true;
+        self
+    }
+
+    /// Audits every row in `nlh_headsup_result`, calculating the ones to recompute with
+    /// `HUPResult::from()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the table can't be read.
+    pub fn run(&self, conn: &Connection) -> Result<HupAuditReport, PKError> {
+        self.run_with(conn, |shu| HUPResult::from(shu))
+    }
+
+    /// Same as `run()`, but with the passed in calculation, so that it can be tested without
+    /// waiting around for the real thing.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the table can't be read.
+    pub fn run_with<F>(&self, conn: &Connection, calc: F) -> Result<HupAuditReport, PKError>
+    where
+        F: Fn(&SortedHeadsUp) -> HUPResult + Sync,
+    {
+        let rows = HupAudit::rows(conn)?;
+        log::info!("HupAudit::run() {} rows", rows.len());
+        Ok(self.audit(&rows, calc))
+    }
+
+    /// Does the actual auditing on rows that have already been read in.
+    pub fn audit<F>(&self, rows: &[HUPResult], calc: F) -> HupAuditReport
+    where
+        F: Fn(&SortedHeadsUp) -> HUPResult + Sync,
+    {
+        let mut report = HupAuditReport {
+            rows: rows.len(),
+            ..HupAuditReport::default()
+        };
+
+        let mut by_key: BTreeMap<Matchup, Vec<HUPResult>> = BTreeMap::new();
+        for row in rows {
+            by_key.entry((row.higher, row.lower)).or_default().push(*row);
+        }
+        report.matchups = by_key.len();
+
+        let mut good: Vec<HUPResult> = Vec::new();
+        for ((higher, lower), rows) in &by_key {
+            let result = rows[0];
+            if rows.len() > 1 {
+                report.issues.push(HupAuditIssue::Duplicate { rows: rows.clone() });
+            }
+            if !HupAudit::is_valid(&result) {
+                report.issues.push(HupAuditIssue::Invalid { result });
+                continue;
+            }
+            if !HupAudit::is_sorted(&result) {
+                report.issues.push(HupAuditIssue::Unsorted { result });
+                if let Some(mirror) = by_key.get(&(*lower, *higher)).map(|rows| rows[0]) {
+                    if !HupAudit::mirror(&result).matches(&mirror) {
+                        report.issues.push(HupAuditIssue::MirrorMismatch { result, mirror });
+                    }
+                }
+                continue;
+            }
+            let total = HupAudit::total(&result);
+            if total != DealEval::HEADSUP_PREFLOP_COMBO_COUNT as u64 {
+                report.issues.push(HupAuditIssue::BadTotal { result, total });
+            }
+            good.push(result);
+        }
+
+        let canonicals: Vec<Canonicalized> = good
+            .par_iter()
+            .filter_map(|result| Some((*result, HupAudit::canonical(result)?)))
+            .collect();
+        report.issues.extend(HupAudit::shift_mismatches(&canonicals));
+        if !self.skip_gaps {
+            report.issues.extend(HupAudit::gaps(&canonicals));
+        }
+
+        let sample: Vec<HUPResult> = match self.recompute {
+            Recompute::None => Vec::new(),
+            Recompute::Sample(n) => good.choose_multiple(&mut rand::thread_rng(), n).copied().collect(),
+            Recompute::All => good,
+        };
+        report.recomputed = sample.len();
+        let mismatches: Vec<HupAuditIssue> = sample
+            .par_iter()
+            .filter_map(|stored| {
+                let expected = calc(&stored.get_sorted_heads_up()?);
+                (expected != *stored).then_some(HupAuditIssue::Mismatch {
+                    stored: *stored,
+                    expected,
+                })
+            })
+            .collect();
+        report.issues.extend(mismatches);
+
+        report
+    }
+
+    fn rows(conn: &Connection) -> Result<Vec<HUPResult>, PKError> {
+        let mut stmt = conn
+            .prepare("SELECT higher, lower, higher_wins, lower_wins, ties FROM nlh_headsup_result")
+            .map_err(|_| PKError::SqlError)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok(HUPResult {
+                    higher: Bard::from(row.get::<_, u64>(0)?),
+                    lower: Bard::from(row.get::<_, u64>(1)?),
+                    higher_wins: row.get(2)?,
+                    lower_wins: row.get(3)?,
+                    ties: row.get(4)?,
+                })
+            })
+            .map_err(|_| PKError::SqlError)?;
+        rows.collect::<rusqlite::Result<Vec<HUPResult>>>()
+            .map_err(|_| PKError::SqlError)
+    }
+
+    fn is_valid(result: &HUPResult) -> bool {
+        let dealt = |bard: Bard| Two::try_from(bard).is_ok_and(|two| two.is_dealt());
+        dealt(result.higher) && dealt(result.lower) && (result.higher & result.lower) == Bard::BLANK
+    }
+
+    /// Sorted the way `SortedHeadsUp` sorts them, which isn't the same as by `Bard`.
+    fn is_sorted(result: &HUPResult) -> bool {
+        result
+            .get_sorted_heads_up()
+            .is_some_and(|shu| shu.higher_as_bard() == result.higher)
+    }
+
+    fn total(result: &HUPResult) -> u64 {
+        result.higher_wins + result.lower_wins + result.ties
+    }
+
+    /// The same matchup from the other side of the table.
+    fn mirror(result: &HUPResult) -> HUPResult {
+        HUPResult {
+            higher: result.lower,
+            lower: result.higher,
+            higher_wins: result.lower_wins,
+            lower_wins: result.higher_wins,
+            ties: result.ties,
+        }
+    }
+
+    /// The result for the canonical suit permutation of its matchup, with the wins flipped
+    /// around if the hands trade places.
+    ///
+    /// This is what `Shifty::shifts()` is for, but the `Masked` shifts don't cover every suit
+    /// permutation, so matchups that are obviously the same thing, like `T♦ 9♦ T♣ 2♦` and
+    /// `T♥ 9♥ T♦ 2♥`, came back as different. `HupLookup::canonical()` tries all 24.
+    fn canonical(result: &HUPResult) -> Option<HUPResult> {
+        let shu = result.get_sorted_heads_up()?;
+        let (canonical, higher) = HupLookup::canonical(shu.higher(), shu.lower());
+        let (higher_wins, lower_wins) = if canonical.higher() == higher {
+            (result.higher_wins, result.lower_wins)
+        } else {
+            (result.lower_wins, result.higher_wins)
+        };
+        Some(HUPResult {
+            higher: canonical.higher_as_bard(),
+            lower: canonical.lower_as_bard(),
+            higher_wins,
+            lower_wins,
+            ties: result.ties,
+        })
+    }
+
+    /// Every suit permutation of a matchup should have the same results. The first row for each
+    /// canonical matchup is compared with the rest of them.
+    fn shift_mismatches(canonicals: &[Canonicalized]) -> Vec<HupAuditIssue> {
+        let mut by_matchup: BTreeMap<Matchup, Vec<&Canonicalized>> = BTreeMap::new();
+        for pair in canonicals {
+            by_matchup.entry((pair.1.higher, pair.1.lower)).or_default().push(pair);
+        }
+
+        let mut mismatches = Vec::new();
+        for pairs in by_matchup.values() {
+            let (result, canonical) = pairs[0];
+            for (shift, shift_canonical) in &pairs[1..] {
+                if !canonical.matches(shift_canonical) {
+                    mismatches.push(HupAuditIssue::ShiftMismatch {
+                        result: *result,
+                        shift: *shift,
+                    });
+                }
+            }
+        }
+        mismatches
+    }
+
+    /// Groups the results by their wins and ties, and flags any group with more than one
+    /// canonical matchup in it. The old `gaps` example only went by the higher wins.
+    fn gaps(canonicals: &[Canonicalized]) -> Vec<HupAuditIssue> {
+        let mut by_wins: BTreeMap<(u64, u64, u64), Vec<&Canonicalized>> = BTreeMap::new();
+        for pair in canonicals {
+            let canonical = pair.1;
+            by_wins
+                .entry((canonical.higher_wins, canonical.lower_wins, canonical.ties))
+                .or_default()
+                .push(pair);
+        }
+
+        by_wins
+            .into_values()
+            .filter(|pairs| {
+                let matchups: HashSet<Matchup> = pairs.iter().map(|(_, c)| (c.higher, c.lower)).collect();
+                matchups.len() > 1
+            })
+            .map(|pairs| HupAuditIssue::Gap {
+                results: pairs.iter().map(|(result, _)| *result).collect(),
+            })
+            .collect()
+    }
+}
+
+/// What `HupAudit` found.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct HupAuditReport {
+    pub rows: usize,
+    pub matchups: usize,
+    pub recomputed: usize,
+    pub issues: Vec<HupAuditIssue>,
+}
+
+/// What `HupAuditReport::repair()` did.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct HupRepair {
+    pub deleted: usize,
+    pub inserted: usize,
+}
+
+impl Display for HupRepair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} rows deleted, {} inserted", self.deleted, self.inserted)
+    }
+}
+
+impl HupAuditReport {
+    #[must_use]
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// How many of each kind of issue there are.
+    #[must_use]
+    pub fn counts(&self) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for issue in &self.issues {
+            *counts.entry(issue.kind()).or_default() += 1;
+        }
+        counts
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if serde can't manage it, which it always should.
+    pub fn to_json(&self) -> Result<String, PKError> {
+        serde_json::to_string_pretty(self).map_err(|_| PKError::Fubar)
+    }
+
+    /// Fixes what can be fixed, and deletes what can't be trusted so that `HupJob` will calculate
+    /// it again the next time it runs. All in one transaction.
+    ///
+    /// * Invalid rows, bad totals and both sides of a shift mismatch are deleted.
+    /// * Duplicates are cut down to one row if they all agree, and deleted if they don't.
+    /// * Unsorted rows are deleted, and stored the right way around if they aren't already.
+    /// * Mismatches are replaced with what was calculated.
+    /// * Gaps are left alone, since they're only suspicious.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the database gives us trouble, in which case nothing is
+    /// changed.
+    pub fn repair(&self, conn: &Connection) -> Result<HupRepair, PKError> {
+        let key = |result: &HUPResult| (result.higher, result.lower);
+        let mut deletes: HashSet<Matchup> = HashSet::new();
+        let mut distrusted: HashSet<Matchup> = HashSet::new();
+        let mut inserts: HashMap<Matchup, HUPResult> = HashMap::new();
+
+        for issue in &self.issues {
+            match issue {
+                HupAuditIssue::Invalid { result } | HupAuditIssue::BadTotal { result, .. } => {
+                    distrusted.insert(key(result));
+                }
+                HupAuditIssue::Duplicate { rows } => {
+                    deletes.insert(key(&rows[0]));
+                    if rows.iter().all(|row| row.matches(&rows[0])) {
+                        inserts.insert(key(&rows[0]), rows[0]);
+                    } else {
+                        distrusted.insert(key(&rows[0]));
+                    }
+                }
+                HupAuditIssue::Unsorted { result } => {
+                    // If the sorted one is already there, inserting it again doesn't do anything.
+                    let mirror = HupAudit::mirror(result);
+                    deletes.insert(key(result));
+                    inserts.insert(key(&mirror), mirror);
+                }
+                HupAuditIssue::MirrorMismatch { result, mirror } => {
+                    distrusted.insert(key(result));
+                    distrusted.insert(key(mirror));
+                }
+                HupAuditIssue::ShiftMismatch { result, shift } => {
+                    distrusted.insert(key(result));
+                    distrusted.insert(key(shift));
+                }
+                HupAuditIssue::Gap { .. } | HupAuditIssue::Mismatch { .. } => {}
+            }
+        }
+        inserts.retain(|k, _| !distrusted.contains(k));
+        deletes.extend(distrusted);
+
+        // Calculated results win over everything else.
+        for issue in &self.issues {
+            if let HupAuditIssue::Mismatch { stored, expected } = issue {
+                deletes.insert(key(stored));
+                inserts.insert(key(expected), *expected);
+            }
+        }
+
+        HupAuditReport::apply(conn, &deletes, &inserts).map_err(|_| PKError::SqlError)
+    }
+
+    fn apply(
+        conn: &Connection,
+        deletes: &HashSet<Matchup>,
+        inserts: &HashMap<Matchup, HUPResult>,
+    ) -> rusqlite::Result<HupRepair> {
+        let tx = conn.unchecked_transaction()?;
+        let mut repair = HupRepair::default();
+        for (higher, lower) in deletes {
+            repair.deleted += tx.execute(
+                "DELETE FROM nlh_headsup_result WHERE higher=:higher AND lower=:lower",
+                named_params! {":higher": higher.as_u64(), ":lower": lower.as_u64()},
+            )?;
+        }
+        for hup in inserts.values() {
+            if HUPResult::insert(&tx, hup)? {
+                repair.inserted += 1;
+            }
+        }
+        tx.commit()?;
+        log::info!("HupAuditReport::repair() {repair}");
+        Ok(repair)
+    }
+}
+
+impl Display for HupAuditReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} rows, {} matchups, {} recomputed",
+            self.rows, self.matchups, self.recomputed
+        )?;
+        if self.is_clean() {
+            return write!(f, ": clean");
+        }
+        let counts: Vec<String> = self
+            .counts()
+            .iter()
+            .map(|(kind, count)| format!("{count} {kind}"))
+            .collect();
+        write!(f, ": {}", counts.join(", "))
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__db__hup_audit_tests {
+    use super::*;
+    use crate::analysis::store::db::schema::Versioned;
+    use crate::util::data::TestData;
+    use crate::SuitShift;
+
+    fn the_hand() -> HUPResult {
+        TestData::the_hand_as_hup_result()
+    }
+
+    fn no_calc(shu: &SortedHeadsUp) -> HUPResult {
+        panic!("{shu} shouldn't have been calculated")
+    }
+
+    /// A file from before there was a unique index, so that we can put duplicates in it.
+    fn old_db(rows: &[HUPResult]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(HUPResult::MIGRATIONS[0].sql).unwrap();
+        for row in rows {
+            conn.execute(
+                "INSERT INTO nlh_headsup_result (higher, lower, higher_wins, lower_wins, ties) VALUES (?1, ?2, ?3, ?4, ?5)",
+                [row.higher.as_u64(), row.lower.as_u64(), row.higher_wins, row.lower_wins, row.ties],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn audit__clean() {
+        let rows = vec![the_hand(), the_hand().shift_suit_up()];
+
+        let report = HupAudit::new().audit(&rows, no_calc);
+
+        assert!(report.is_clean());
+        assert_eq!("2 rows, 2 matchups, 0 recomputed: clean", report.to_string());
+    }
+
+    #[test]
+    fn audit__invalid_unsorted_and_mirror() {
+        let mut shared = the_hand();
+        shared.lower = Two::HAND_6S_5D.bard();
+        let mut mirror = HupAudit::mirror(&the_hand());
+        mirror.ties += 1;
+
+        let report = HupAudit::new().audit(&[shared, the_hand(), mirror], no_calc);
+
+        assert_eq!(
+            vec![
+                HupAuditIssue::Unsorted { result: mirror },
+                HupAuditIssue::MirrorMismatch {
+                    result: mirror,
+                    mirror: the_hand()
+                },
+                HupAuditIssue::Invalid { result: shared },
+            ],
+            report.issues
+        );
+    }
+
+    #[test]
+    fn audit__duplicate_and_bad_total() {
+        let mut off = the_hand();
+        off.ties += 1;
+
+        let report = HupAudit::new().audit(&[off, the_hand()], no_calc);
+
+        assert_eq!(
+            vec![
+                HupAuditIssue::Duplicate {
+                    rows: vec![off, the_hand()]
+                },
+                HupAuditIssue::BadTotal {
+                    result: off,
+                    total: 1_712_305
+                },
+            ],
+            report.issues
+        );
+        assert_eq!(
+            "2 rows, 1 matchups, 0 recomputed: 1 bad_total, 1 duplicate",
+            report.to_string()
+        );
+    }
+
+    #[test]
+    fn audit__shift_mismatch() {
+        let mut shifted = the_hand().shift_suit_up();
+        shifted.higher_wins -= 1;
+        shifted.lower_wins += 1;
+
+        let report = HupAudit::new().audit(&[the_hand(), shifted], no_calc);
+
+        assert_eq!(1, report.issues.len());
+        assert_eq!("shift_mismatch", report.issues[0].kind());
+    }
+
+    /// `TestData::known_hups()` has the same results for AA against 77, 66 and 55.
+    #[test]
+    fn audit__gap() {
+        let report = HupAudit::new().audit(&TestData::known_hups(), no_calc);
+
+        assert_eq!(
+            vec!["duplicate", "gap"],
+            report.issues.iter().map(HupAuditIssue::kind).collect::<Vec<_>>()
+        );
+        assert!(
+            HupAudit::new()
+                .skip_gaps()
+                .audit(&TestData::known_hups(), no_calc)
+                .issues
+                .len()
+                == 1
+        );
+    }
+
+    #[test]
+    fn audit__recompute() {
+        let rows = vec![the_hand(), the_hand().shift_suit_up()];
+        let calc = |shu: &SortedHeadsUp| {
+            let mut expected = HUPResult::from_sorted_heads_up(shu, &TestData::the_hand_as_wins());
+            if *shu != TestData::the_hand_sorted_headsup() {
+                expected.ties = 0;
+            }
+            expected
+        };
+
+        let report = HupAudit::new().recompute(Recompute::All).audit(&rows, calc);
+
+        assert_eq!(2, report.recomputed);
+        assert_eq!(1, report.issues.len());
+        assert_eq!("mismatch", report.issues[0].kind());
+        assert_eq!(
+            1,
+            HupAudit::new()
+                .recompute(Recompute::Sample(1))
+                .audit(&rows, |shu| calc(shu))
+                .recomputed
+        );
+    }
+
+    #[test]
+    fn repair() {
+        let mut shared = the_hand();
+        shared.lower = Two::HAND_6S_5D.bard();
+        let shifted = the_hand().shift_suit_up();
+        let conn = old_db(&[the_hand(), the_hand(), shared, HupAudit::mirror(&shifted)]);
+        let report = HupAudit::new().run_with(&conn, no_calc).unwrap();
+        assert_eq!(
+            vec!["duplicate", "invalid", "unsorted"],
+            report.counts().keys().copied().collect::<Vec<_>>()
+        );
+
+        let repair = report.repair(&conn).unwrap();
+
+        assert_eq!(
+            HupRepair {
+                deleted: 4,
+                inserted: 2
+            },
+            repair
+        );
+        assert!(HupAudit::new().run_with(&conn, no_calc).unwrap().is_clean());
+        assert_eq!(2, HupAudit::rows(&conn).unwrap().len());
+        assert_eq!(2, HUPResult::create_table(&conn).unwrap());
+    }
+
+    #[test]
+    fn to_json() {
+        let report = HupAudit::new().audit(&TestData::known_hups(), no_calc);
+
+        let json = report.to_json().unwrap();
+
+        assert!(json.contains("\"kind\": \"gap\""));
+        assert!(json.contains("\"rows\": 4"));
+    }
+}
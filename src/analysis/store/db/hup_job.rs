@@ -0,0 +1,441 @@
+use crate::analysis::store::db::headsup_preflop_result::HUPResult;
+use crate::analysis::store::db::sqlite::Sqlable;
+use crate::arrays::matchups::masked::{Masked, MASKED_DISTINCT};
+use crate::arrays::matchups::sorted_heads_up::SortedHeadsUp;
+use crate::PKError;
+use rayon::prelude::*;
+use rusqlite::{named_params, Connection, Transaction, TransactionBehavior};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::time::{Duration, Instant};
+
+/// # Heads Up Preflop Batch Job
+///
+/// Filling `nlh_headsup_result` with every distinct heads up matchup takes days. Up until now
+/// I've been doing it with the `insert` examples, typing in how many runs I want, and hoping that
+/// I remember where I left off. This is the grown up version:
+///
+/// * The work is the distinct `Masked` set, sorted so that every process agrees on the order.
+/// * It can be split into shards, so that several processes can work on the same `SQLite` file at
+///   once. Shard `n` of `m` takes every `m`th matchup starting at `n`, so they never step on
+///   each other.
+/// * Each batch is calculated across all the cores with rayon, and then written in a single
+///   `IMMEDIATE` transaction, along with the shard's checkpoint in `nlh_headsup_job`. The
+///   database is put into WAL mode with a busy timeout, so the other shards wait their turn
+///   instead of failing.
+/// * The results themselves are the real checkpoint. When a job starts, it skips anything that's
+///   already in the table, so killing it loses at most the batch that was in flight.
+///
+/// ```txt
+/// cargo run --release --example hup_job -- -s 0 -n 4 &
+/// cargo run --release --example hup_job -- -s 1 -n 4 &
+/// ...
+/// ```
+#[derive(Clone, Debug)]
+pub struct HupJob {
+    pub shard: usize,
+    pub shards: usize,
+    pub batch_size: usize,
+    /// Stop after calculating this many results, for when I just want it to run for a bit.
+    pub limit: Option<usize>,
+    work: Vec<Masked>,
+}
+
+/// A row from `nlh_headsup_job`: how far along a shard was the last time it wrote a batch.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HupCheckpoint {
+    pub shard: usize,
+    pub shards: usize,
+    pub done: usize,
+    pub total: usize,
+    pub updated_at: String,
+}
+
+/// Where a running job is at, and how fast it's getting there.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HupJobProgress {
+    pub shard: usize,
+    pub shards: usize,
+    pub total: usize,
+    /// Done, including the ones that were already in the database when the job started.
+    pub done: usize,
+    /// Calculated by this run.
+    pub calculated: usize,
+    pub elapsed: Duration,
+}
+
+impl HupJobProgress {
+    /// Results calculated per second by this run.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn rate(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            self.calculated as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// How much longer the shard should take at the current rate, if there's any rate to go on.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn eta(&self) -> Option<Duration> {
+        let rate = self.rate();
+        if rate > 0.0 {
+            Some(Duration::from_secs_f64((self.total - self.done) as f64 / rate))
+        } else {
+            None
+        }
+    }
+
+    #[must_use]
+    pub fn is_done(&self) -> bool {
+        self.done >= self.total
+    }
+}
+
+impl Display for HupJobProgress {
+    #[allow(clippy::cast_precision_loss)]
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let percentage = if self.total == 0 {
+            100.0
+        } else {
+            self.done as f64 * 100.0 / self.total as f64
+        };
+        write!(
+            f,
+            "shard {}/{}: {}/{} ({percentage:.2}%) {:.2}/s",
+            self.shard,
+            self.shards,
+            self.done,
+            self.total,
+            self.rate()
+        )?;
+        match self.eta() {
+            Some(eta) => {
+                let secs = eta.as_secs();
+                write!(f, " ETA {}h{:02}m{:02}s", secs / 3600, secs / 60 % 60, secs % 60)
+            }
+            None => write!(f, " ETA ?"),
+        }
+    }
+}
+
+impl HupJob {
+    pub const DEFAULT_BATCH_SIZE: usize = 64;
+    pub const BUSY_TIMEOUT: Duration = Duration::from_secs(120);
+
+    /// A job over the passed in matchups. Most of the time you want `HupJob::distinct()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidIndex` if there are no shards, or the shard isn't one of them.
+    pub fn new(work: &HashSet<Masked>, shard: usize, shards: usize) -> Result<HupJob, PKError> {
+        if shard >= shards {
+            return Err(PKError::InvalidIndex);
+        }
+        let mut sorted: Vec<Masked> = work.iter().copied().collect();
+        sorted.sort();
+        let work = sorted
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| i % shards == shard)
+            .map(|(_, masked)| masked)
+            .collect();
+
+        Ok(HupJob {
+            shard,
+            shards,
+            batch_size: HupJob::DEFAULT_BATCH_SIZE,
+            limit: None,
+            work,
+        })
+    }
+
+    /// A job over every distinct heads up matchup.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidIndex` if there are no shards, or the shard isn't one of them.
+    pub fn distinct(shard: usize, shards: usize) -> Result<HupJob, PKError> {
+        HupJob::new(&MASKED_DISTINCT, shard, shards)
+    }
+
+    #[must_use]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    #[must_use]
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The matchups in this job's shard.
+    #[must_use]
+    pub fn work(&self) -> &[Masked] {
+        &self.work
+    }
+
+    /// Gets a connection ready to be shared with the other shards, and creates the tables if
+    /// they aren't there yet.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SQLite`.
+    pub fn prepare(conn: &Connection) -> rusqlite::Result<()> {
+        conn.busy_timeout(HupJob::BUSY_TIMEOUT)?;
+        // In memory databases answer `memory`, which is fine.
+        let _: String = conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
+        HUPResult::create_table(conn)?;
+        conn.execute_batch(
+            "create table if not exists nlh_headsup_job
+            (
+                shard      integer not null,
+                shards     integer not null,
+                done       integer not null,
+                total      integer not null,
+                updated_at text    not null default current_timestamp,
+                primary key (shard, shards)
+            );",
+        )
+    }
+
+    /// Every shard's last checkpoint, so you can see how all the processes are doing.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SQLite`.
+    pub fn checkpoints(conn: &Connection) -> rusqlite::Result<Vec<HupCheckpoint>> {
+        let mut stmt =
+            conn.prepare("SELECT shard, shards, done, total, updated_at FROM nlh_headsup_job ORDER BY shards, shard")?;
+        let rows = stmt.query_map([], |row| {
+            Ok(HupCheckpoint {
+                shard: row.get(0)?,
+                shards: row.get(1)?,
+                done: row.get(2)?,
+                total: row.get(3)?,
+                updated_at: row.get(4)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// The matchups in this shard that aren't in the database yet.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SQLite`.
+    pub fn remaining(&self, conn: &Connection) -> rusqlite::Result<Vec<Masked>> {
+        let mut stmt = conn.prepare("SELECT higher, lower FROM nlh_headsup_result")?;
+        let existing = stmt
+            .query_map([], |row| {
+                let higher: u64 = row.get(0)?;
+                let lower: u64 = row.get(1)?;
+                Ok((higher, lower))
+            })?
+            .collect::<rusqlite::Result<HashSet<(u64, u64)>>>()?;
+
+        Ok(self
+            .work
+            .iter()
+            .filter(|masked| !existing.contains(&HupJob::key(&masked.shu)))
+            .copied()
+            .collect())
+    }
+
+    fn key(shu: &SortedHeadsUp) -> (u64, u64) {
+        (shu.higher_as_bard().as_u64(), shu.lower_as_bard().as_u64())
+    }
+
+    /// Works through whatever is left in the shard, a batch at a time, calling `on_batch` with
+    /// the progress after every batch is saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the database gives us trouble. Everything up to the last
+    /// finished batch is saved, so the job can just be started again.
+    pub fn run<F>(&self, conn: &Connection, mut on_batch: F) -> Result<HupJobProgress, PKError>
+    where
+        F: FnMut(&HupJobProgress),
+    {
+        HupJob::prepare(conn).map_err(|_| PKError::SqlError)?;
+
+        let now = Instant::now();
+        let mut remaining = self.remaining(conn).map_err(|_| PKError::SqlError)?;
+        let total = self.work.len();
+        let mut progress = HupJobProgress {
+            shard: self.shard,
+            shards: self.shards,
+            total,
+            done: total - remaining.len(),
+            calculated: 0,
+            elapsed: Duration::default(),
+        };
+        if let Some(limit) = self.limit {
+            remaining.truncate(limit);
+        }
+        log::info!(
+            "HupJob::run() {} remaining in shard {}/{}",
+            remaining.len(),
+            self.shard,
+            self.shards
+        );
+        self.checkpoint(conn, &progress).map_err(|_| PKError::SqlError)?;
+
+        for batch in remaining.chunks(self.batch_size) {
+            let results: Vec<HUPResult> = batch.par_iter().map(|masked| HUPResult::from(&masked.shu)).collect();
+
+            let inserted = self.save(conn, &results, &progress).map_err(|_| PKError::SqlError)?;
+            progress.done += inserted;
+            progress.calculated += results.len();
+            progress.elapsed = now.elapsed();
+            self.checkpoint(conn, &progress).map_err(|_| PKError::SqlError)?;
+
+            on_batch(&progress);
+        }
+
+        progress.elapsed = now.elapsed();
+        Ok(progress)
+    }
+
+    /// Writes a batch in a single transaction, so that either all of it makes it or none of it
+    /// does. `IMMEDIATE` grabs the write lock up front, which is what keeps the shards from
+    /// deadlocking on each other.
+    fn save(&self, conn: &Connection, results: &[HUPResult], progress: &HupJobProgress) -> rusqlite::Result<usize> {
+        let tx = Transaction::new_unchecked(conn, TransactionBehavior::Immediate)?;
+        let mut inserted = 0;
+        for hup in results {
+            if HUPResult::insert(&tx, hup)? {
+                inserted += 1;
+            }
+        }
+        self.upsert_checkpoint(&tx, progress.done + inserted, progress.total)?;
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    fn checkpoint(&self, conn: &Connection, progress: &HupJobProgress) -> rusqlite::Result<usize> {
+        self.upsert_checkpoint(conn, progress.done, progress.total)
+    }
+
+    fn upsert_checkpoint(&self, conn: &Connection, done: usize, total: usize) -> rusqlite::Result<usize> {
+        conn.execute(
+            "INSERT INTO nlh_headsup_job (shard, shards, done, total) VALUES (:shard, :shards, :done, :total)
+            ON CONFLICT (shard, shards) DO UPDATE SET done = excluded.done, total = excluded.total,
+            updated_at = current_timestamp",
+            named_params! {
+                ":shard": self.shard,
+                ":shards": self.shards,
+                ":done": done,
+                ":total": total,
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__db__hup_job_tests {
+    use super::*;
+    use crate::util::data::TestData;
+
+    fn known() -> HashSet<Masked> {
+        TestData::known_hups().iter().map(Masked::from).collect()
+    }
+
+    fn conn_with_known() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        HupJob::prepare(&conn).unwrap();
+        for hup in TestData::known_hups() {
+            HUPResult::insert(&conn, &hup).unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn new__shards() {
+        let work = known();
+
+        let shards: Vec<HupJob> = (0..2).map(|shard| HupJob::new(&work, shard, 2).unwrap()).collect();
+
+        assert_eq!(3, work.len());
+        assert_eq!(2, shards[0].work().len());
+        assert_eq!(1, shards[1].work().len());
+        assert!(!shards[0].work().contains(&shards[1].work()[0]));
+        assert_eq!(PKError::InvalidIndex, HupJob::new(&work, 2, 2).unwrap_err());
+        assert_eq!(PKError::InvalidIndex, HupJob::new(&work, 0, 0).unwrap_err());
+    }
+
+    #[test]
+    fn remaining() {
+        let conn = conn_with_known();
+        let mut work = known();
+        work.insert(Masked::from(TestData::the_hand_sorted_headsup()));
+        let job = HupJob::new(&work, 0, 1).unwrap();
+
+        let remaining = job.remaining(&conn).unwrap();
+
+        assert_eq!(1, remaining.len());
+        assert_eq!(TestData::the_hand_sorted_headsup(), remaining[0].shu);
+    }
+
+    /// Everything is already there, so this is what happens when a finished job is restarted.
+    #[test]
+    fn run__resume() {
+        let conn = conn_with_known();
+        let job = HupJob::new(&known(), 0, 1).unwrap();
+        let mut batches = 0;
+
+        let progress = job.run(&conn, |_| batches += 1).unwrap();
+
+        assert_eq!(0, batches);
+        assert_eq!(3, progress.done);
+        assert_eq!(0, progress.calculated);
+        assert!(progress.is_done());
+        let checkpoints = HupJob::checkpoints(&conn).unwrap();
+        assert_eq!(1, checkpoints.len());
+        assert_eq!(3, checkpoints[0].done);
+        assert_eq!(3, checkpoints[0].total);
+    }
+
+    #[test]
+    fn progress() {
+        let progress = HupJobProgress {
+            shard: 1,
+            shards: 4,
+            total: 100,
+            done: 50,
+            calculated: 20,
+            elapsed: Duration::from_secs(10),
+        };
+
+        assert!((progress.rate() - 2.0).abs() < f64::EPSILON);
+        assert_eq!(Some(Duration::from_secs(25)), progress.eta());
+        assert_eq!("shard 1/4: 50/100 (50.00%) 2.00/s ETA 0h00m25s", progress.to_string());
+        assert_eq!(None, HupJobProgress::default().eta());
+    }
+
+    /// Slow in debug, since it's a million and a half or so hands, and `HUPResult` needs the
+    /// binary card map from the `generate_bcm` example.
+    #[test]
+    #[ignore = "slow, and needs the binary card map"]
+    fn run() {
+        let conn = conn_with_known();
+        let mut work = known();
+        work.insert(Masked::from(TestData::the_hand_sorted_headsup()));
+        let job = HupJob::new(&work, 0, 1).unwrap().batch_size(1);
+
+        let progress = job.run(&conn, |_| {}).unwrap();
+
+        assert_eq!(1, progress.calculated);
+        assert_eq!(4, progress.done);
+        assert_eq!(
+            Some(TestData::the_hand_as_hup_result()),
+            HUPResult::select(&conn, &TestData::the_hand_sorted_headsup()).unwrap()
+        );
+    }
+}
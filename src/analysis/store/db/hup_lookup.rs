@@ -0,0 +1,458 @@
+use crate::analysis::store::db::headsup_preflop_result::HUPResult;
+use crate::analysis::store::db::sqlite::Sqlable;
+use crate::arrays::matchups::sorted_heads_up::SortedHeadsUp;
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::suit::Suit;
+use crate::util::wincounter::results::Results;
+use crate::{PKError, Pile};
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+lazy_static! {
+    /// Every way of swapping the four suits around. Index 0 is spades, 1 hearts, 2 diamonds and
+    /// 3 clubs, and the value is the suit that it turns into.
+    pub static ref SUIT_PERMUTATIONS: Vec<[Suit; 4]> = HupLookup::SUITS
+        .iter()
+        .copied()
+        .permutations(4)
+        .map(|v| [v[0], v[1], v[2], v[3]])
+        .collect();
+}
+
+/// # Any Suit Heads Up Lookup
+///
+/// The heads up table only has one row for each distinct matchup. `A♠ A♥` against `7♦ 7♣` is in
+/// there, but `A♦ A♣` against `7♠ 7♥` isn't, since it's the same thing with the suits swapped.
+/// `HUPResult::select_from_shifts()` will find it for you, but then you've got to work out which
+/// of the two hands in the row is yours, which is exactly the sort of thing I get wrong.
+///
+/// So this does it all in one go. Pass in your hand and the other player's, in whatever order and
+/// suits, and you get back your wins, losses and ties:
+///
+/// * Both hands are canonicalised by running them through every one of the 24 suit permutations
+///   and keeping the lowest `SortedHeadsUp`. Every matchup that's the same except for the suits
+///   ends up with the same key.
+/// * The lookup is against an in memory index keyed by the canonical matchup if you preload one,
+///   and the database if you don't.
+/// * If it isn't stored anywhere, it's calculated, saved in its canonical form, and added to the
+///   index, so the next time is instant.
+#[derive(Clone, Debug, Default)]
+pub struct HupLookup {
+    index: HashMap<SortedHeadsUp, HUPResult>,
+}
+
+/// How a heads up matchup turns out from the point of view of the hero.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct HupOdds {
+    pub hero: Two,
+    pub villain: Two,
+    pub wins: u64,
+    pub losses: u64,
+    pub ties: u64,
+}
+
+impl HupOdds {
+    /// Reads the odds out of a stored result, if the hero is one of its hands.
+    #[must_use]
+    pub fn from_result(result: &HUPResult, hero: Two, villain: Two) -> Option<HupOdds> {
+        let (wins, losses) = if result.higher == hero.bard() && result.lower == villain.bard() {
+            (result.higher_wins, result.lower_wins)
+        } else if result.lower == hero.bard() && result.higher == villain.bard() {
+            (result.lower_wins, result.higher_wins)
+        } else {
+            return None;
+        };
+        Some(HupOdds {
+            hero,
+            villain,
+            wins,
+            losses,
+            ties: result.ties,
+        })
+    }
+
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.wins + self.losses + self.ties
+    }
+
+    #[must_use]
+    pub fn win_percentage(&self) -> f32 {
+        self.percentage(self.wins)
+    }
+
+    #[must_use]
+    pub fn loss_percentage(&self) -> f32 {
+        self.percentage(self.losses)
+    }
+
+    #[must_use]
+    pub fn tie_percentage(&self) -> f32 {
+        self.percentage(self.ties)
+    }
+
+    #[allow(clippy::cast_precision_loss)]
+    fn percentage(&self, count: u64) -> f32 {
+        match self.total() {
+            0 => 0.0,
+            total => count as f32 * 100.0 / total as f32,
+        }
+    }
+}
+
+impl Display for HupOdds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} vs {}: {:.2}% wins {:.2}% losses {:.2}% ties",
+            self.hero,
+            self.villain,
+            self.win_percentage(),
+            self.loss_percentage(),
+            self.tie_percentage()
+        )
+    }
+}
+
+/// So that the stored odds can go anywhere freshly calculated ones can. The hero is the first
+/// player, and since it's heads up, the ties are the same for both.
+impl From<&HupOdds> for Results {
+    fn from(odds: &HupOdds) -> Self {
+        let count = |n: u64| usize::try_from(n).unwrap_or_default();
+        Results {
+            case_count: count(odds.total()),
+            player_count: 2,
+            v: vec![
+                (count(odds.wins), count(odds.ties)),
+                (count(odds.losses), count(odds.ties)),
+            ],
+        }
+    }
+}
+
+impl HupLookup {
+    pub const SUITS: [Suit; 4] = [Suit::SPADES, Suit::HEARTS, Suit::DIAMONDS, Suit::CLUBS];
+
+    /// A lookup that goes to the database every time.
+    #[must_use]
+    pub fn new() -> HupLookup {
+        HupLookup::default()
+    }
+
+    /// Loads every stored result into memory, so that lookups never have to touch the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the table can't be read.
+    pub fn preload(conn: &Connection) -> Result<HupLookup, PKError> {
+        Ok(HupLookup::from_results(
+            &HUPResult::select_all(conn).map_err(|_| PKError::SqlError)?,
+        ))
+    }
+
+    #[must_use]
+    pub fn from_results(results: &[HUPResult]) -> HupLookup {
+        let mut lookup = HupLookup::default();
+        for result in results {
+            lookup.add(result);
+        }
+        lookup
+    }
+
+    /// How many canonical matchups are in the in memory index.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Adds a result to the in memory index under its canonical matchup. Anything that isn't a
+    /// real heads up matchup is ignored.
+    pub fn add(&mut self, result: &HUPResult) {
+        let Ok(shu) = SortedHeadsUp::try_from(result) else {
+            return;
+        };
+        let (canonical, higher) = HupLookup::canonical(shu.higher, shu.lower);
+        if let Some(odds) = HupOdds::from_result(result, shu.higher, shu.lower) {
+            self.index
+                .insert(canonical, HupLookup::to_result(&canonical, higher, &odds));
+        }
+    }
+
+    /// Swaps the suits of a hand around. The permutation is indexed the same way as
+    /// `HupLookup::SUITS`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCard` if the hand has a blank card in it.
+    pub fn permute(two: Two, permutation: &[Suit; 4]) -> Result<Two, PKError> {
+        let permute = |card: Card| -> Result<Card, PKError> {
+            let i = HupLookup::SUITS
+                .iter()
+                .position(|suit| *suit == card.get_suit())
+                .ok_or(PKError::InvalidCard)?;
+            Ok(Card::new(card.get_rank(), permutation[i]))
+        };
+        Two::new(permute(two.first())?, permute(two.second())?)
+    }
+
+    /// The canonical version of a matchup, along with what the hero's hand turns into in it.
+    ///
+    /// Hands with blank cards can't be shifted, so they're passed back as is. `lookup()` checks
+    /// for them before it ever gets here.
+    #[must_use]
+    pub fn canonical(hero: Two, villain: Two) -> (SortedHeadsUp, Two) {
+        SUIT_PERMUTATIONS
+            .iter()
+            .filter_map(|permutation| {
+                let hero = HupLookup::permute(hero, permutation).ok()?;
+                let villain = HupLookup::permute(villain, permutation).ok()?;
+                Some((SortedHeadsUp::new(hero, villain), hero))
+            })
+            .min()
+            .unwrap_or((SortedHeadsUp::new(hero, villain), hero))
+    }
+
+    /// Looks up the odds without calculating anything, first in the in memory index, and then
+    /// under every suit permutation in the database.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCard` if either hand isn't dealt, `PKError::Duplicate` if they
+    /// share a card, and `PKError::SqlError` if the stored results can't be read.
+    pub fn find(&self, conn: Option<&Connection>, hero: Two, villain: Two) -> Result<Option<HupOdds>, PKError> {
+        HupLookup::check(hero, villain)?;
+        let (canonical, canonical_hero) = HupLookup::canonical(hero, villain);
+        if let Some(result) = self.index.get(&canonical) {
+            return Ok(HupLookup::reorient(result, canonical_hero, hero, villain));
+        }
+
+        let Some(conn) = conn else {
+            return Ok(None);
+        };
+        for permutation in SUIT_PERMUTATIONS.iter() {
+            let shifted_hero = HupLookup::permute(hero, permutation)?;
+            let shifted_villain = HupLookup::permute(villain, permutation)?;
+            if let Some(result) = HUPResult::select(conn, &SortedHeadsUp::new(shifted_hero, shifted_villain))
+                .map_err(|_| PKError::SqlError)?
+            {
+                return Ok(HupLookup::reorient(&result, shifted_hero, hero, villain));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the odds for the hero, calculating and saving them if they aren't stored yet.
+    ///
+    /// Calculating means running through all 1.7 million or so boards, so a miss takes a while.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCard` if either hand isn't dealt, `PKError::Duplicate` if they
+    /// share a card, and `PKError::SqlError` if the new result can't be saved.
+    pub fn lookup(&mut self, conn: &Connection, hero: Two, villain: Two) -> Result<HupOdds, PKError> {
+        if let Some(odds) = self.find(Some(conn), hero, villain)? {
+            return Ok(odds);
+        }
+
+        let (canonical, canonical_hero) = HupLookup::canonical(hero, villain);
+        log::info!("HupLookup::lookup() calculating {canonical}");
+        let result = HUPResult::from(&canonical);
+        HUPResult::insert(conn, &result).map_err(|_| PKError::SqlError)?;
+        self.index.insert(canonical, result);
+
+        HupLookup::reorient(&result, canonical_hero, hero, villain).ok_or(PKError::Fubar)
+    }
+
+    fn check(hero: Two, villain: Two) -> Result<(), PKError> {
+        if !hero.is_dealt() || !villain.is_dealt() {
+            return Err(PKError::InvalidCard);
+        }
+        if hero.first() == villain.first()
+            || hero.first() == villain.second()
+            || hero.second() == villain.first()
+            || hero.second() == villain.second()
+        {
+            return Err(PKError::Duplicate);
+        }
+        Ok(())
+    }
+
+    /// The stored result is for some suit shifted version of the hero's hand. Finding which side
+    /// that is gives us the odds, which we hand back with the hero's real cards.
+    fn reorient(result: &HUPResult, shifted_hero: Two, hero: Two, villain: Two) -> Option<HupOdds> {
+        let shifted_villain = if result.higher == shifted_hero.bard() {
+            Two::try_from(result.lower).ok()?
+        } else {
+            Two::try_from(result.higher).ok()?
+        };
+        let odds = HupOdds::from_result(result, shifted_hero, shifted_villain)?;
+        Some(HupOdds { hero, villain, ..odds })
+    }
+
+    fn to_result(canonical: &SortedHeadsUp, hero: Two, odds: &HupOdds) -> HUPResult {
+        let (higher_wins, lower_wins) = if canonical.higher == hero {
+            (odds.wins, odds.losses)
+        } else {
+            (odds.losses, odds.wins)
+        };
+        HUPResult {
+            higher: canonical.higher_as_bard(),
+            lower: canonical.lower_as_bard(),
+            higher_wins,
+            lower_wins,
+            ties: odds.ties,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__db__hup_lookup_tests {
+    use super::*;
+    use crate::util::data::TestData;
+
+    #[test]
+    fn suit_permutations() {
+        assert_eq!(24, SUIT_PERMUTATIONS.len());
+    }
+
+    #[test]
+    fn canonical() {
+        let (canonical, hero) = HupLookup::canonical(Two::HAND_6S_6H, Two::HAND_5D_5C);
+        let (shifted, shifted_hero) = HupLookup::canonical(Two::HAND_5S_5H, Two::HAND_6D_6C);
+
+        assert_eq!(canonical, shifted);
+        assert_eq!(canonical.higher, hero);
+        assert_eq!(canonical.lower, shifted_hero);
+    }
+
+    #[test]
+    fn find__database() {
+        let conn = TestData::connection(&[TestData::the_hand_as_hup_result()]);
+        let lookup = HupLookup::new();
+
+        let odds = lookup
+            .find(Some(&conn), Two::HAND_5S_5H, Two::HAND_6D_6C)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(Two::HAND_5S_5H, odds.hero);
+        assert_eq!(Two::HAND_6D_6C, odds.villain);
+        assert_eq!(314_904, odds.wins);
+        assert_eq!(1_365_284, odds.losses);
+        assert_eq!(32_116, odds.ties);
+        assert_eq!(
+            None,
+            lookup.find(Some(&conn), Two::HAND_AS_AH, Two::HAND_6D_6C).unwrap()
+        );
+    }
+
+    #[test]
+    fn find__index() {
+        let lookup = HupLookup::preload(&TestData::connection(&[TestData::the_hand_as_hup_result()])).unwrap();
+
+        let odds = lookup.find(None, Two::HAND_6D_6C, Two::HAND_5S_5H).unwrap().unwrap();
+
+        assert_eq!(1, lookup.len());
+        assert_eq!(1_365_284, odds.wins);
+        assert_eq!(314_904, odds.losses);
+        assert_eq!("6♦ 6♣ vs 5♠ 5♥: 79.73% wins 18.39% losses 1.88% ties", odds.to_string());
+    }
+
+    /// Every suit permutation of every known result, with the hands flipped, should come back the
+    /// same from the database and from the index.
+    #[test]
+    fn find__every_permutation() {
+        let conn = Connection::open_in_memory().unwrap();
+        HUPResult::create_table(&conn).unwrap();
+        for hup in TestData::known_hups() {
+            HUPResult::insert(&conn, &hup).unwrap();
+        }
+        let lookup = HupLookup::preload(&conn).unwrap();
+
+        for result in TestData::known_hups() {
+            let shu = SortedHeadsUp::try_from(&result).unwrap();
+            for permutation in SUIT_PERMUTATIONS.iter() {
+                let hero = HupLookup::permute(shu.lower, permutation).unwrap();
+                let villain = HupLookup::permute(shu.higher, permutation).unwrap();
+
+                let from_db = lookup.find(Some(&conn), hero, villain).unwrap().unwrap();
+                let from_index = lookup.find(None, hero, villain).unwrap().unwrap();
+
+                assert_eq!(from_db, from_index);
+                assert_eq!(result.lower_wins, from_db.wins);
+                assert_eq!(result.higher_wins, from_db.losses);
+            }
+        }
+    }
+
+    #[test]
+    fn results__from_hup_odds() {
+        let odds = HupLookup::preload(&TestData::connection(&[TestData::the_hand_as_hup_result()]))
+            .unwrap()
+            .find(None, Two::HAND_6D_6C, Two::HAND_5S_5H)
+            .unwrap()
+            .unwrap();
+
+        let results = Results::from(&odds);
+
+        assert_eq!(1_712_304, results.case_count);
+        assert_eq!((1_365_284, 32_116), results.wins_and_ties(0));
+        assert_eq!((314_904, 32_116), results.wins_and_ties(1));
+        assert_eq!("81.6% (79.73%/1.88%) [1365284/32116]", results.player_to_string(0));
+    }
+
+    #[test]
+    fn find__errors() {
+        let lookup = HupLookup::new();
+
+        assert_eq!(
+            PKError::Duplicate,
+            lookup.find(None, Two::HAND_6D_6C, Two::HAND_6D_5H).unwrap_err()
+        );
+        assert_eq!(
+            PKError::InvalidCard,
+            lookup.find(None, Two::default(), Two::HAND_6D_5H).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn lookup__stored() {
+        let conn = TestData::connection(&[TestData::the_hand_as_hup_result()]);
+        let mut lookup = HupLookup::new();
+
+        let odds = lookup.lookup(&conn, Two::HAND_5D_5C, Two::HAND_6S_6H).unwrap();
+
+        assert_eq!(314_904, odds.wins);
+        assert!(lookup.is_empty());
+    }
+
+    /// Calculates a matchup that isn't stored, which needs the binary card map from the
+    /// `generate_bcm` example.
+    #[test]
+    #[ignore = "slow, and needs the binary card map"]
+    fn lookup__calculated() {
+        let conn = TestData::connection(&[TestData::the_hand_as_hup_result()]);
+        let mut lookup = HupLookup::new();
+
+        let odds = lookup.lookup(&conn, Two::HAND_7D_7C, Two::HAND_AS_AH).unwrap();
+
+        assert_eq!(343_300, odds.wins);
+        assert_eq!(1_364_608, odds.losses);
+        assert_eq!(1, lookup.len());
+        assert!(lookup
+            .find(Some(&conn), Two::HAND_7S_7H, Two::HAND_AD_AC)
+            .unwrap()
+            .is_some());
+    }
+}
@@ -1,2 +1,9 @@
 pub mod headsup_preflop_result;
+pub mod histories;
+pub mod hup_audit;
+pub mod hup_job;
+pub mod hup_lookup;
+pub mod multiway_lookup;
+pub mod multiway_preflop_result;
+pub mod schema;
 pub mod sqlite;
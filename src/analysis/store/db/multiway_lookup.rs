@@ -0,0 +1,267 @@
+use crate::analysis::store::db::multiway_preflop_result::{MultiwayResult, Share, SortedMultiway};
+use crate::analysis::store::db::sqlite::Sqlable;
+use crate::arrays::two::Two;
+use crate::util::wincounter::results::Results;
+use crate::PKError;
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// # Multiway Preflop Lookup
+///
+/// The three and four handed version of `HupLookup`. Hands go in in whatever order and suits,
+/// get canonicalised, and are looked up in the in memory index if there is one, and then the
+/// database. A miss gets calculated and saved. Since `MultiwayResult` only ever stores canonical
+/// matchups, there's no need to go hunting through the suit permutations in the database.
+#[derive(Clone, Debug, Default)]
+pub struct MultiwayLookup {
+    index: HashMap<SortedMultiway, MultiwayResult>,
+}
+
+/// How each of the hands does, in the order they were asked for.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiwayOdds {
+    pub hands: Vec<Two>,
+    pub boards: u64,
+    pub shares: Vec<Share>,
+}
+
+impl MultiwayOdds {
+    /// Puts the shares from a canonical result back in the caller's order.
+    #[must_use]
+    pub fn from_result(result: &MultiwayResult, hands: &[Two], seats: &[usize]) -> MultiwayOdds {
+        MultiwayOdds {
+            hands: hands.to_vec(),
+            boards: result.boards,
+            shares: seats
+                .iter()
+                .map(|seat| result.shares.get(*seat).copied().unwrap_or_default())
+                .collect(),
+        }
+    }
+
+    /// The share of the pot for the player in the passed in seat, counting splits, as a
+    /// percentage.
+    #[must_use]
+    pub fn equity(&self, seat: usize) -> f64 {
+        self.shares.get(seat).map_or(0.0, |share| share.equity(self.boards))
+    }
+}
+
+impl Display for MultiwayOdds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (two, share)) in self.hands.iter().zip(self.shares.iter()).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{two} {:.2}% ({:.2}% wins, {} splits)",
+                share.equity(self.boards),
+                share.win_percentage(self.boards),
+                share.ties
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// The wins are outright wins, and the ties are every board the player split, however many ways.
+impl From<&MultiwayOdds> for Results {
+    fn from(odds: &MultiwayOdds) -> Self {
+        let count = |n: u64| usize::try_from(n).unwrap_or_default();
+        Results {
+            case_count: count(odds.boards),
+            player_count: odds.hands.len(),
+            v: odds
+                .shares
+                .iter()
+                .map(|share| (count(share.wins), count(share.ties)))
+                .collect(),
+        }
+    }
+}
+
+impl MultiwayLookup {
+    /// A lookup that goes to the database every time.
+    #[must_use]
+    pub fn new() -> MultiwayLookup {
+        MultiwayLookup::default()
+    }
+
+    /// Loads every stored result into memory.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the table can't be read.
+    pub fn preload(conn: &Connection) -> Result<MultiwayLookup, PKError> {
+        Ok(MultiwayLookup::from_results(
+            MultiwayResult::select_all(conn).map_err(|_| PKError::SqlError)?,
+        ))
+    }
+
+    #[must_use]
+    pub fn from_results(results: Vec<MultiwayResult>) -> MultiwayLookup {
+        MultiwayLookup {
+            index: results
+                .into_iter()
+                .map(|result| (result.hands.clone(), result))
+                .collect(),
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Adds a result to the in memory index. They're always canonical, so there's nothing to
+    /// work out first.
+    pub fn add(&mut self, result: MultiwayResult) {
+        self.index.insert(result.hands.clone(), result);
+    }
+
+    /// Looks up the odds without calculating anything.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SortedMultiway::canonical()`, and returns `PKError::SqlError` if
+    /// the stored results can't be read.
+    pub fn find(&self, conn: Option<&Connection>, hands: &[Two]) -> Result<Option<MultiwayOdds>, PKError> {
+        let (canonical, seats) = SortedMultiway::canonical(hands)?;
+        if let Some(result) = self.index.get(&canonical) {
+            return Ok(Some(MultiwayOdds::from_result(result, hands, &seats)));
+        }
+        let Some(conn) = conn else {
+            return Ok(None);
+        };
+        Ok(MultiwayResult::select(conn, &canonical)
+            .map_err(|_| PKError::SqlError)?
+            .map(|result| MultiwayOdds::from_result(&result, hands, &seats)))
+    }
+
+    /// Returns the odds for every hand, calculating and saving them if they aren't stored yet.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SortedMultiway::canonical()`, and returns `PKError::SqlError` if
+    /// the new result can't be saved.
+    pub fn lookup(&mut self, conn: &Connection, hands: &[Two]) -> Result<MultiwayOdds, PKError> {
+        if let Some(odds) = self.find(Some(conn), hands)? {
+            return Ok(odds);
+        }
+
+        let (canonical, seats) = SortedMultiway::canonical(hands)?;
+        log::info!("MultiwayLookup::lookup() calculating {canonical}");
+        let result = MultiwayResult::from(&canonical);
+        MultiwayResult::insert(conn, &result).map_err(|_| PKError::SqlError)?;
+        let odds = MultiwayOdds::from_result(&result, hands, &seats);
+        self.index.insert(canonical, result);
+        Ok(odds)
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__db__multiway_lookup_tests {
+    use super::*;
+    use crate::util::data::TestData;
+
+    #[test]
+    fn find__database() {
+        let conn = TestData::connection(&[TestData::aces_kings_queens()]);
+        let lookup = MultiwayLookup::new();
+
+        let odds = lookup
+            .find(Some(&conn), &[Two::HAND_QD_QC, Two::HAND_AD_AC, Two::HAND_KD_KC])
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(1, odds.shares[0].wins);
+        assert_eq!(6, odds.shares[1].wins);
+        assert_eq!(2, odds.shares[2].wins);
+        assert!((odds.equity(1) - 190.0 / 3.0).abs() < 0.000_001);
+        assert_eq!(
+            None,
+            lookup
+                .find(Some(&conn), &[Two::HAND_QD_QC, Two::HAND_AD_AC, Two::HAND_KH_KD])
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn find__index() {
+        let lookup = MultiwayLookup::preload(&TestData::connection(&[TestData::aces_kings_queens()])).unwrap();
+
+        let odds = lookup
+            .find(None, &[Two::HAND_KS_KH, Two::HAND_QS_QH, Two::HAND_AS_AH])
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(1, lookup.len());
+        assert_eq!(
+            "K♠ K♥ 23.33% (20.00% wins, 1 splits)\nQ♠ Q♥ 13.33% (10.00% wins, 1 splits)\nA♠ A♥ 63.33% (60.00% wins, 1 splits)",
+            odds.to_string()
+        );
+    }
+
+    #[test]
+    fn add() {
+        let mut lookup = MultiwayLookup::new();
+
+        lookup.add(TestData::aces_kings_queens());
+        lookup.add(TestData::aces_kings_queens());
+
+        assert_eq!(1, lookup.len());
+        assert!(lookup
+            .find(None, &[Two::HAND_AD_AC, Two::HAND_QD_QC, Two::HAND_KD_KC])
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    fn results__from_multiway_odds() {
+        let odds = MultiwayLookup::preload(&TestData::connection(&[TestData::aces_kings_queens()]))
+            .unwrap()
+            .find(None, &[Two::HAND_KS_KH, Two::HAND_QS_QH, Two::HAND_AS_AH])
+            .unwrap()
+            .unwrap();
+
+        let results = Results::from(&odds);
+
+        assert_eq!(10, results.case_count);
+        assert_eq!(3, results.player_count);
+        assert_eq!((6, 1), results.wins_and_ties(2));
+    }
+
+    #[test]
+    fn find__errors() {
+        assert_eq!(
+            PKError::NotEnoughHands,
+            MultiwayLookup::new()
+                .find(None, &[Two::HAND_AS_AH, Two::HAND_KS_KH])
+                .unwrap_err()
+        );
+    }
+
+    #[test]
+    fn lookup__stored() {
+        let conn = TestData::connection(&[TestData::aces_kings_queens()]);
+        let mut lookup = MultiwayLookup::new();
+
+        let odds = lookup
+            .lookup(&conn, &[Two::HAND_KD_KC, Two::HAND_QD_QC, Two::HAND_AD_AC])
+            .unwrap();
+
+        assert_eq!(
+            vec![2, 1, 6],
+            odds.shares.iter().map(|share| share.wins).collect::<Vec<u64>>()
+        );
+        assert!(lookup.is_empty());
+    }
+}
@@ -0,0 +1,529 @@
+use crate::analysis::case_eval::CaseEval;
+use crate::analysis::store::db::hup_lookup::{HupLookup, SUIT_PERMUTATIONS};
+use crate::analysis::store::db::schema::{Migration, Schema, Versioned};
+use crate::analysis::store::db::sqlite::Sqlable;
+use crate::arrays::five::Five;
+use crate::arrays::two::Two;
+use crate::bard::Bard;
+use crate::play::hole_cards::HoleCards;
+use crate::util::wincounter::win::Win;
+use crate::util::wincounter::PlayerFlag;
+use crate::{PKError, Pile};
+use itertools::Itertools;
+use rayon::iter::ParallelIterator;
+use rusqlite::{named_params, Connection};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+/// # Multiway Preflop Results
+///
+/// `SortedHeadsUp` and `HUPResult` stop at two players, but the all in decisions that make me
+/// sweat are the ones where somebody else is already in behind. This is the same idea for three
+/// and four handed pots.
+///
+/// There are far too many multiway matchups to calculate all of them up front, so instead of
+/// working through a distinct set like `Masked`, each one is reduced to its canonical form when
+/// it's first asked for: every suit permutation is tried, the hands in each are sorted, and the
+/// lowest one wins. Any two matchups that are the same except for the suits, or the order of the
+/// players, end up with the same key.
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct SortedMultiway {
+    pub hands: Vec<Two>,
+}
+
+impl SortedMultiway {
+    pub const MIN_PLAYERS: usize = 3;
+    pub const MAX_PLAYERS: usize = 4;
+
+    /// # Errors
+    ///
+    /// * `PKError::NotEnoughHands` for fewer than three hands, and `PKError::TooManyHands` for more
+    ///   than four.
+    /// * `PKError::InvalidCard` if a hand isn't dealt, and `PKError::Duplicate` if two hands share
+    ///   a card.
+    pub fn new(hands: &[Two]) -> Result<SortedMultiway, PKError> {
+        SortedMultiway::check(hands)?;
+        let mut hands = hands.to_vec();
+        hands.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(SortedMultiway { hands })
+    }
+
+    /// The canonical matchup, along with where each of the passed in hands ended up in it.
+    ///
+    /// # Errors
+    ///
+    /// Same as `SortedMultiway::new()`.
+    pub fn canonical(hands: &[Two]) -> Result<(SortedMultiway, Vec<usize>), PKError> {
+        SortedMultiway::check(hands)?;
+        let mut best: Option<(SortedMultiway, Vec<Two>)> = None;
+        for permutation in SUIT_PERMUTATIONS.iter() {
+            let shifted = hands
+                .iter()
+                .map(|two| HupLookup::permute(*two, permutation))
+                .collect::<Result<Vec<Two>, PKError>>()?;
+            let sorted = SortedMultiway::new(&shifted)?;
+            if best.as_ref().map_or(true, |(b, _)| sorted < *b) {
+                best = Some((sorted, shifted));
+            }
+        }
+        let (canonical, shifted) = best.ok_or(PKError::Fubar)?;
+        let seats = shifted
+            .iter()
+            .map(|two| canonical.seat(two).ok_or(PKError::Fubar))
+            .collect::<Result<Vec<usize>, PKError>>()?;
+        Ok((canonical, seats))
+    }
+
+    #[must_use]
+    pub fn is_canonical(&self) -> bool {
+        SortedMultiway::canonical(&self.hands).is_ok_and(|(canonical, _)| &canonical == self)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hands.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.hands.is_empty()
+    }
+
+    /// Where a hand sits in the matchup.
+    #[must_use]
+    pub fn seat(&self, two: &Two) -> Option<usize> {
+        self.hands.iter().position(|hand| hand == two)
+    }
+
+    /// The key the matchup is stored under: the `Bard` of every hand, in order.
+    #[must_use]
+    pub fn key(&self) -> String {
+        self.hands.iter().map(|two| two.bard().as_u64()).join("-")
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidBinaryFormat` if the key isn't a list of hands.
+    pub fn from_key(key: &str) -> Result<SortedMultiway, PKError> {
+        let hands = key
+            .split('-')
+            .map(|bard| {
+                let bard = bard.parse::<u64>().map_err(|_| PKError::InvalidBinaryFormat)?;
+                Two::try_from(Bard::from(bard)).map_err(|_| PKError::InvalidBinaryFormat)
+            })
+            .collect::<Result<Vec<Two>, PKError>>()?;
+        SortedMultiway::new(&hands)
+    }
+
+    fn check(hands: &[Two]) -> Result<(), PKError> {
+        if hands.len() < SortedMultiway::MIN_PLAYERS {
+            return Err(PKError::NotEnoughHands);
+        }
+        if hands.len() > SortedMultiway::MAX_PLAYERS {
+            return Err(PKError::TooManyHands);
+        }
+        let mut seen = HashSet::new();
+        for two in hands {
+            if !two.is_dealt() {
+                return Err(PKError::InvalidCard);
+            }
+            if !seen.insert(two.first()) || !seen.insert(two.second()) {
+                return Err(PKError::Duplicate);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Display for SortedMultiway {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.hands.iter().join(", "))
+    }
+}
+
+/// How one player does across every board.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Share {
+    /// Boards the player wins outright.
+    pub wins: u64,
+    /// Boards the player splits with somebody.
+    pub ties: u64,
+    /// How many pots the splits add up to. Splitting three ways is a third of a pot.
+    pub split: f64,
+}
+
+impl Share {
+    /// The player's share of the pot, counting the splits, as a percentage.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn equity(&self, boards: u64) -> f64 {
+        if boards == 0 {
+            0.0
+        } else {
+            (self.wins as f64 + self.split) * 100.0 / boards as f64
+        }
+    }
+
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn win_percentage(&self, boards: u64) -> f64 {
+        if boards == 0 {
+            0.0
+        } else {
+            self.wins as f64 * 100.0 / boards as f64
+        }
+    }
+}
+
+/// The result of running every board out for a canonical multiway matchup. The shares are in the
+/// same order as the hands.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiwayResult {
+    pub hands: SortedMultiway,
+    pub boards: u64,
+    pub shares: Vec<Share>,
+}
+
+impl MultiwayResult {
+    /// Tallies up who won each board.
+    #[must_use]
+    pub fn from_flags(hands: SortedMultiway, flags: &[PlayerFlag]) -> MultiwayResult {
+        let mut shares = vec![Share::default(); hands.len()];
+        for flag in flags {
+            let winners = flag.count_ones();
+            for (i, share) in shares.iter_mut().enumerate() {
+                if flag & Win::from_index(i) == 0 {
+                    continue;
+                }
+                if winners == 1 {
+                    share.wins += 1;
+                } else {
+                    share.ties += 1;
+                    share.split += 1.0 / f64::from(winners);
+                }
+            }
+        }
+        MultiwayResult {
+            hands,
+            boards: flags.len() as u64,
+            shares,
+        }
+    }
+
+    /// Works out the canonical version of every matchup that isn't stored yet, and calculates
+    /// and saves them one at a time, calling `on_result` after each one is saved. Each
+    /// calculation is spread across all the cores, so there's no point in running them side by
+    /// side.
+    ///
+    /// Returns how many new results were saved.
+    ///
+    /// # Errors
+    ///
+    /// Returns any `PKError` from canonicalising a matchup, and `PKError::SqlError` if a result
+    /// can't be saved.
+    pub fn generate<F>(conn: &Connection, matchups: &[Vec<Two>], mut on_result: F) -> Result<usize, PKError>
+    where
+        F: FnMut(&MultiwayResult),
+    {
+        let mut work: Vec<SortedMultiway> = Vec::new();
+        for hands in matchups {
+            let (canonical, _) = SortedMultiway::canonical(hands)?;
+            if !work.contains(&canonical) && !MultiwayResult::exists(conn, &canonical) {
+                work.push(canonical);
+            }
+        }
+        log::info!("MultiwayResult::generate() {} to calculate", work.len());
+
+        let mut saved = 0;
+        for canonical in work {
+            let result = MultiwayResult::from(&canonical);
+            if MultiwayResult::insert(conn, &result).map_err(|_| PKError::SqlError)? {
+                saved += 1;
+            }
+            on_result(&result);
+        }
+        Ok(saved)
+    }
+}
+
+impl Display for MultiwayResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let players = self
+            .hands
+            .hands
+            .iter()
+            .zip(self.shares.iter())
+            .map(|(two, share)| format!("{two} {:.2}%", share.equity(self.boards)))
+            .join(", ");
+        write!(f, "{players} ({} boards)", self.boards)
+    }
+}
+
+impl From<&SortedMultiway> for MultiwayResult {
+    /// Runs out every board, which is a little over a million of them for three players.
+    fn from(sorted: &SortedMultiway) -> Self {
+        let hole_cards = HoleCards::from(sorted.hands.clone());
+        let flags: Vec<PlayerFlag> = hole_cards
+            .par_combinations_remaining(5)
+            .filter_map(|v| Five::try_from(v).ok())
+            .filter_map(|five| CaseEval::from_holdem_at_deal(five, &hole_cards).ok())
+            .map(|case_eval| case_eval.win_count())
+            .collect();
+        MultiwayResult::from_flags(sorted.clone(), &flags)
+    }
+}
+
+/// Each player gets their own row, keyed by the matchup and their seat in it, which means that
+/// three and four handed results can share the table.
+impl Versioned for MultiwayResult {
+    const STORE: &'static str = "nlh_multiway_result";
+    const MIGRATIONS: &'static [Migration] = &[Migration {
+        version: 1,
+        description: "multiway results table",
+        creates: "nlh_multiway_result",
+        sql: "create table if not exists nlh_multiway_result
+            (
+                matchup text    not null,
+                seat    integer not null,
+                hand    integer not null,
+                boards  integer not null,
+                wins    integer not null,
+                ties    integer not null,
+                split   real    not null,
+                primary key (matchup, seat)
+            )",
+    }];
+}
+
+impl Sqlable<MultiwayResult, SortedMultiway> for MultiwayResult {
+    /// Returns how many migrations it took to bring the table up to date.
+    fn create_table(conn: &Connection) -> rusqlite::Result<usize> {
+        log::debug!("MultiwayResult::create_table({conn:?})");
+        Schema::upgrade::<MultiwayResult>(conn).map(|upgrade| upgrade.applied())
+    }
+
+    fn exists(conn: &Connection, key: &SortedMultiway) -> bool {
+        conn.query_row(
+            "SELECT count(*) FROM nlh_multiway_result WHERE matchup=:matchup",
+            named_params! {":matchup": key.key()},
+            |row| row.get::<_, usize>(0),
+        )
+        .is_ok_and(|count| count > 0)
+    }
+
+    /// Returns false if the matchup is already there.
+    fn insert(conn: &Connection, record: &MultiwayResult) -> rusqlite::Result<bool> {
+        log::debug!("MultiwayResult::insert({record})");
+
+        if MultiwayResult::exists(conn, &record.hands) {
+            return Ok(false);
+        }
+        let tx = conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO nlh_multiway_result \
+                (matchup, seat, hand, boards, wins, ties, split) VALUES \
+                (:matchup, :seat, :hand, :boards, :wins, :ties, :split)",
+            )?;
+            for (seat, (two, share)) in record.hands.hands.iter().zip(record.shares.iter()).enumerate() {
+                stmt.execute(named_params! {
+                    ":matchup": record.hands.key(),
+                    ":seat": seat,
+                    ":hand": two.bard().as_u64(),
+                    ":boards": record.boards,
+                    ":wins": share.wins,
+                    ":ties": share.ties,
+                    ":split": share.split,
+                })?;
+            }
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    fn insert_many(conn: &Connection, records: Vec<&MultiwayResult>) -> rusqlite::Result<usize> {
+        let mut inserted = 0;
+        for record in records {
+            if MultiwayResult::insert(conn, record)? {
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// A matchup without a row for every seat isn't there.
+    fn select(conn: &Connection, key: &SortedMultiway) -> rusqlite::Result<Option<MultiwayResult>> {
+        let mut stmt = conn.prepare(
+            "SELECT boards, wins, ties, split FROM nlh_multiway_result WHERE matchup=:matchup ORDER BY seat",
+        )?;
+        let rows = stmt
+            .query_map(named_params! {":matchup": key.key()}, |row| {
+                Ok((
+                    row.get::<_, u64>(0)?,
+                    Share {
+                        wins: row.get(1)?,
+                        ties: row.get(2)?,
+                        split: row.get(3)?,
+                    },
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<(u64, Share)>>>()?;
+        if rows.len() != key.len() {
+            return Ok(None);
+        }
+
+        Ok(Some(MultiwayResult {
+            hands: key.clone(),
+            boards: rows[0].0,
+            shares: rows.into_iter().map(|(_, share)| share).collect(),
+        }))
+    }
+
+    /// A matchup that can't be read back in is an error, not something to skip over.
+    fn select_all(conn: &Connection) -> rusqlite::Result<Vec<MultiwayResult>> {
+        log::debug!("MultiwayResult::select_all({conn:?})");
+
+        let mut stmt = conn.prepare("SELECT matchup FROM nlh_multiway_result WHERE seat = 0 ORDER BY matchup")?;
+        let keys = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+
+        keys.iter()
+            .map(|key| {
+                SortedMultiway::from_key(key)
+                    .map_err(|e| rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, Box::new(e)))
+            })
+            .filter_map(|sorted| {
+                sorted
+                    .and_then(|sorted| MultiwayResult::select(conn, &sorted))
+                    .transpose()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__db__multiway_preflop_result_tests {
+    use super::*;
+    use crate::util::data::TestData;
+
+    fn hands() -> Vec<Two> {
+        vec![Two::HAND_AS_AH, Two::HAND_KS_KH, Two::HAND_QS_QH]
+    }
+
+    #[test]
+    fn new__errors() {
+        assert_eq!(PKError::NotEnoughHands, SortedMultiway::new(&hands()[..2]).unwrap_err());
+        assert_eq!(
+            PKError::TooManyHands,
+            SortedMultiway::new(&[
+                Two::HAND_AS_AH,
+                Two::HAND_KS_KH,
+                Two::HAND_QS_QH,
+                Two::HAND_JS_JH,
+                Two::HAND_TS_TH
+            ])
+            .unwrap_err()
+        );
+        assert_eq!(
+            PKError::Duplicate,
+            SortedMultiway::new(&[Two::HAND_AS_AH, Two::HAND_KS_KH, Two::HAND_AS_QH]).unwrap_err()
+        );
+        assert_eq!(
+            PKError::InvalidCard,
+            SortedMultiway::new(&[Two::HAND_AS_AH, Two::HAND_KS_KH, Two::default()]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn canonical() {
+        let (canonical, seats) = SortedMultiway::canonical(&hands()).unwrap();
+        let (shifted, shifted_seats) =
+            SortedMultiway::canonical(&[Two::HAND_QD_QC, Two::HAND_AD_AC, Two::HAND_KD_KC]).unwrap();
+
+        assert_eq!(canonical, shifted);
+        assert!(canonical.is_canonical());
+        assert_eq!(vec![0, 1, 2], seats);
+        assert_eq!(vec![2, 0, 1], shifted_seats);
+        assert!(
+            !SortedMultiway::new(&[Two::HAND_AS_AH, Two::HAND_KD_KC, Two::HAND_QS_QH])
+                .unwrap()
+                .is_canonical()
+        );
+    }
+
+    #[test]
+    fn key() {
+        let sorted = SortedMultiway::new(&hands()).unwrap();
+
+        assert_eq!(sorted, SortedMultiway::from_key(&sorted.key()).unwrap());
+        assert_eq!(
+            PKError::InvalidBinaryFormat,
+            SortedMultiway::from_key("1-x").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn from_flags() {
+        let flags = vec![
+            Win::FIRST,
+            Win::FIRST,
+            Win::SECOND,
+            Win::FIRST | Win::SECOND,
+            Win::FIRST | Win::SECOND | Win::THIRD,
+        ];
+
+        let result = MultiwayResult::from_flags(SortedMultiway::new(&hands()).unwrap(), &flags);
+
+        assert_eq!(5, result.boards);
+        assert_eq!(2, result.shares[0].wins);
+        assert_eq!(2, result.shares[0].ties);
+        assert!((result.shares[0].split - 5.0 / 6.0).abs() < f64::EPSILON);
+        assert_eq!(1, result.shares[1].wins);
+        assert_eq!(0, result.shares[2].wins);
+        assert_eq!(1, result.shares[2].ties);
+        assert!((result.shares[0].equity(5) - 56.666_666).abs() < 0.001);
+    }
+
+    #[test]
+    fn sqlable() {
+        let conn = Connection::open_in_memory().unwrap();
+        MultiwayResult::create_table(&conn).unwrap();
+        let result = TestData::aces_kings_queens();
+
+        assert!(MultiwayResult::insert(&conn, &result).unwrap());
+        assert!(!MultiwayResult::insert(&conn, &result).unwrap());
+        assert!(MultiwayResult::exists(&conn, &result.hands));
+        assert_eq!(
+            Some(result.clone()),
+            MultiwayResult::select(&conn, &result.hands).unwrap()
+        );
+        assert_eq!(vec![result], MultiwayResult::select_all(&conn).unwrap());
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            "A♦ A♣ 63.33%, K♦ K♣ 23.33%, Q♦ Q♣ 13.33% (10 boards)",
+            TestData::aces_kings_queens().to_string()
+        );
+    }
+
+    /// About 1.4 million boards, so run it with `--release`.
+    #[test]
+    #[ignore = "slow"]
+    fn generate() {
+        let conn = Connection::open_in_memory().unwrap();
+        MultiwayResult::create_table(&conn).unwrap();
+        let shifted = vec![Two::HAND_QD_QC, Two::HAND_AD_AC, Two::HAND_KD_KC];
+
+        let saved = MultiwayResult::generate(&conn, &[hands(), shifted], |_| {}).unwrap();
+        let result = MultiwayResult::select_all(&conn).unwrap().pop().unwrap();
+
+        assert_eq!(1, saved);
+        assert_eq!(1_370_754, result.boards);
+        assert!(result.shares[0].equity(result.boards) > result.shares[1].equity(result.boards));
+        assert!(result.shares[1].equity(result.boards) > result.shares[2].equity(result.boards));
+    }
+}
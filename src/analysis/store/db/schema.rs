@@ -0,0 +1,296 @@
+use rusqlite::{named_params, Connection, OptionalExtension};
+use std::fmt::{Display, Formatter};
+
+/// One step in a store's schema. Versions start at 1 and go up by one each time.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    /// The table or index that the step creates. If it's in `sqlite_master`, the step has
+    /// already been done, which is how we work out the version of a file from before there was a
+    /// `schema_version` table.
+    pub creates: &'static str,
+    pub sql: &'static str,
+}
+
+/// A store whose tables are created and upgraded through an ordered list of `Migration`s.
+pub trait Versioned {
+    /// The name the store's version is recorded under in `schema_version`.
+    const STORE: &'static str;
+
+    const MIGRATIONS: &'static [Migration];
+}
+
+/// # Schema Versions
+///
+/// Every one of the hups databases in `data/` was made with `create table if not exists`, so
+/// there's no telling which version of the code made which file. Now each store has an ordered
+/// list of migrations, and the version that a file is at is kept in a `schema_version` table.
+///
+/// For files from before `schema_version`, the version is worked out by looking for what each
+/// migration creates in `sqlite_master`. Upgrading runs whatever's left, one version at a time,
+/// inside a savepoint, so a file either gets all the way there or stays where it was.
+///
+/// Each store's `Sqlable::create_table()` is just `Schema::upgrade()`, so a brand new file goes
+/// through the exact same steps as an old one.
+pub struct Schema;
+
+/// What `Schema::upgrade()` did.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SchemaUpgrade {
+    pub store: String,
+    pub from: u32,
+    pub to: u32,
+    /// Whether `from` was worked out from what's in the file, rather than read from
+    /// `schema_version`.
+    pub detected: bool,
+}
+
+impl SchemaUpgrade {
+    #[must_use]
+    pub fn upgraded(&self) -> bool {
+        self.from != self.to
+    }
+
+    /// How many migrations were run.
+    #[must_use]
+    pub fn applied(&self) -> usize {
+        (self.from..self.to).len()
+    }
+}
+
+impl Display for SchemaUpgrade {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let detected = if self.detected { " (detected)" } else { "" };
+        if self.upgraded() {
+            write!(f, "{}: v{}{detected} -> v{}", self.store, self.from, self.to)
+        } else {
+            write!(f, "{}: v{}{detected}", self.store, self.from)
+        }
+    }
+}
+
+impl Schema {
+    /// # Errors
+    ///
+    /// Throws an error if rusqlite isn't able to create the table.
+    pub fn create_table(conn: &Connection) -> rusqlite::Result<usize> {
+        conn.execute(
+            "create table if not exists schema_version
+            (
+                store      text    not null
+                    constraint schema_version_pk
+                        primary key,
+                version    integer not null,
+                updated_at text    not null default current_timestamp
+            )",
+            [],
+        )
+    }
+
+    /// The newest version of a store's schema.
+    #[must_use]
+    pub fn latest<T: Versioned>() -> u32 {
+        T::MIGRATIONS.iter().map(|m| m.version).max().unwrap_or_default()
+    }
+
+    /// The version in `schema_version`, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SQLite` other than the table not being there.
+    pub fn recorded<T: Versioned>(conn: &Connection) -> rusqlite::Result<Option<u32>> {
+        if !Schema::has_object(conn, "schema_version")? {
+            return Ok(None);
+        }
+        conn.query_row(
+            "SELECT version FROM schema_version WHERE store=:store",
+            named_params! {":store": T::STORE},
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    /// Works out the version from what's in the file. Each migration counts if what it creates
+    /// is there, stopping at the first one that isn't. A file without any of the store's tables
+    /// is at version 0.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SQLite`.
+    pub fn detect<T: Versioned>(conn: &Connection) -> rusqlite::Result<u32> {
+        let mut version = 0;
+        for migration in T::MIGRATIONS {
+            if !Schema::has_object(conn, migration.creates)? {
+                break;
+            }
+            version = migration.version;
+        }
+        Ok(version)
+    }
+
+    /// The recorded version if there is one, and the detected one if there isn't, along with
+    /// whether it was detected.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SQLite`.
+    pub fn version<T: Versioned>(conn: &Connection) -> rusqlite::Result<(u32, bool)> {
+        match Schema::recorded::<T>(conn)? {
+            Some(version) => Ok((version, false)),
+            None => Ok((Schema::detect::<T>(conn)?, true)),
+        }
+    }
+
+    /// Brings a store up to the latest version in place.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `SQLite`. Nothing from the failed upgrade is kept.
+    pub fn upgrade<T: Versioned>(conn: &Connection) -> rusqlite::Result<SchemaUpgrade> {
+        let (from, detected) = Schema::version::<T>(conn)?;
+        let mut upgrade = SchemaUpgrade {
+            store: T::STORE.to_string(),
+            from,
+            to: from,
+            detected,
+        };
+        if from >= Schema::latest::<T>() && !detected {
+            return Ok(upgrade);
+        }
+
+        conn.execute_batch("SAVEPOINT schema_upgrade")?;
+        match Schema::apply::<T>(conn, from) {
+            Ok(to) => {
+                conn.execute_batch("RELEASE schema_upgrade")?;
+                log::info!("Schema::upgrade() {} v{from} -> v{to}", T::STORE);
+                upgrade.to = to;
+                Ok(upgrade)
+            }
+            Err(e) => {
+                conn.execute_batch("ROLLBACK TO schema_upgrade; RELEASE schema_upgrade")?;
+                Err(e)
+            }
+        }
+    }
+
+    fn apply<T: Versioned>(conn: &Connection, from: u32) -> rusqlite::Result<u32> {
+        Schema::create_table(conn)?;
+        let mut version = from;
+        for migration in T::MIGRATIONS.iter().filter(|m| m.version > from) {
+            log::debug!(
+                "Schema::apply() {} v{}: {}",
+                T::STORE,
+                migration.version,
+                migration.description
+            );
+            conn.execute_batch(migration.sql)?;
+            version = migration.version;
+        }
+        conn.execute(
+            "INSERT INTO schema_version (store, version) VALUES (:store, :version)
+            ON CONFLICT (store) DO UPDATE SET version = :version, updated_at = current_timestamp",
+            named_params! {":store": T::STORE, ":version": version},
+        )?;
+        Ok(version)
+    }
+
+    fn has_object(conn: &Connection, name: &str) -> rusqlite::Result<bool> {
+        conn.query_row(
+            "SELECT count(*) FROM sqlite_master WHERE name=:name",
+            named_params! {":name": name},
+            |row| row.get::<_, usize>(0),
+        )
+        .map(|count| count > 0)
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__db__schema_tests {
+    use super::*;
+
+    struct Widgets;
+
+    impl Versioned for Widgets {
+        const STORE: &'static str = "widgets";
+        const MIGRATIONS: &'static [Migration] = &[
+            Migration {
+                version: 1,
+                description: "widgets",
+                creates: "widgets",
+                sql: "create table if not exists widgets (id integer primary key, name text not null)",
+            },
+            Migration {
+                version: 2,
+                description: "index on name",
+                creates: "widgets_name_index",
+                sql: "create index if not exists widgets_name_index on widgets (name)",
+            },
+        ];
+    }
+
+    struct Broken;
+
+    impl Versioned for Broken {
+        const STORE: &'static str = "broken";
+        const MIGRATIONS: &'static [Migration] = &[
+            Migration {
+                version: 1,
+                description: "broken",
+                creates: "broken",
+                sql: "create table broken (id integer primary key)",
+            },
+            Migration {
+                version: 2,
+                description: "oops",
+                creates: "nope",
+                sql: "this isn't sql",
+            },
+        ];
+    }
+
+    #[test]
+    fn upgrade__new() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        let upgrade = Schema::upgrade::<Widgets>(&conn).unwrap();
+
+        assert_eq!(0, upgrade.from);
+        assert_eq!(2, upgrade.to);
+        assert_eq!(2, upgrade.applied());
+        assert_eq!("widgets: v0 (detected) -> v2", upgrade.to_string());
+        assert_eq!(Some(2), Schema::recorded::<Widgets>(&conn).unwrap());
+        assert!(!Schema::upgrade::<Widgets>(&conn).unwrap().upgraded());
+    }
+
+    #[test]
+    fn upgrade__detected() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(Widgets::MIGRATIONS[0].sql).unwrap();
+
+        assert_eq!(None, Schema::recorded::<Widgets>(&conn).unwrap());
+        assert_eq!((1, true), Schema::version::<Widgets>(&conn).unwrap());
+
+        let upgrade = Schema::upgrade::<Widgets>(&conn).unwrap();
+
+        assert_eq!(1, upgrade.from);
+        assert_eq!(2, upgrade.to);
+        assert_eq!((2, false), Schema::version::<Widgets>(&conn).unwrap());
+        assert_eq!("widgets: v2", Schema::upgrade::<Widgets>(&conn).unwrap().to_string());
+    }
+
+    #[test]
+    fn upgrade__rolls_back() {
+        let conn = Connection::open_in_memory().unwrap();
+
+        assert!(Schema::upgrade::<Broken>(&conn).is_err());
+        assert_eq!(0, Schema::detect::<Broken>(&conn).unwrap());
+        assert_eq!(None, Schema::recorded::<Broken>(&conn).unwrap());
+    }
+
+    #[test]
+    fn latest() {
+        assert_eq!(2, Schema::latest::<Widgets>());
+    }
+}
@@ -89,7 +89,15 @@ pub trait Sqlable<T, S> {
     /// throw if the record is already there.
     fn insert_many(conn: &Connection, records: Vec<&T>) -> rusqlite::Result<usize>;
 
-    fn select(conn: &Connection, key: &S) -> Option<T>;
+    /// # Errors
+    ///
+    /// Throws an error if rusqlite isn't able to read the table. A record that isn't there
+    /// isn't an error, it's `None`.
+    fn select(conn: &Connection, key: &S) -> rusqlite::Result<Option<T>>;
 
-    fn select_all(conn: &Connection) -> Vec<T>;
+    /// # Errors
+    ///
+    /// Throws an error if rusqlite isn't able to read the table, or any of the rows in it. A
+    /// table that isn't there is an error, not an empty `Vec`.
+    fn select_all(conn: &Connection) -> rusqlite::Result<Vec<T>>;
 }
@@ -1,4 +1,9 @@
 pub mod bcm;
+pub mod breakdown;
 pub mod db;
 pub mod heads_up;
 pub mod nubibus;
+pub mod ohh;
+pub mod pokerstars;
+pub mod storage;
+pub mod uci;
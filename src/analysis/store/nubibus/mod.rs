@@ -2,10 +2,16 @@ use crate::analysis::store::nubibus::actions::{Action, ActionType};
 use crate::analysis::store::nubibus::pluribus::Pluribus;
 use crate::analysis::store::nubibus::seat::{Seat, SeatSnapshot};
 use crate::arrays::two::Two;
+use crate::cards::Cards;
 use crate::casino::cashier::chips::Chips;
-use crate::{Betting, PKError};
+use crate::play::events::{SeatState, TableState};
+use crate::play::run_it::RunIt;
+use crate::{Betting, PKError, Pile};
 use itertools::Itertools;
 use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::str::FromStr;
 
 pub mod actions;
 pub mod player;
@@ -16,7 +22,7 @@ pub mod seat;
 use log::{debug, info, warn}; // Use log crate when building application
 
 use crate::play::actions::ActionTracker;
-use crate::play::phases::PhaseHoldemTracker;
+use crate::play::phases::{PhaseHoldem, PhaseHoldemTracker};
 use crate::play::positions::Position6MaxPointer;
 use crate::play::Position6Max;
 use std::cell::Cell;
@@ -42,6 +48,8 @@ pub struct Nubibus {
     pub queue_turn: Vec<String>,
     pub queue_river: Vec<String>,
     pub ledger: Vec<Action>,
+    /// The cards that have been dealt to the board so far.
+    pub board: Cards,
 }
 
 impl Nubibus {
@@ -64,6 +72,7 @@ impl Nubibus {
             queue_turn: ActionType::actions_turn_reverse(&pluribus.rounds),
             queue_river: ActionType::actions_river_reverse(&pluribus.rounds),
             ledger: Vec::new(),
+            board: Cards::default(),
         };
         for i in 0..pluribus.players.len() {
             let seat_number = u8::try_from(i + 1).unwrap_or_default();
@@ -156,12 +165,12 @@ impl Nubibus {
     /// # Panics
     ///
     /// ¯\\_(ツ)_/¯
+    ///
+    /// The queues are stored in reverse, so that the next action is the one popped off the end.
+    /// The original version of this walked through the queue from the front, which played the
+    /// round backwards.
     pub fn play_preflop(&mut self) {
-        self.preflop_action(self.queue_preflop.clone());
-    }
-
-    fn preflop_action(&mut self, queue: Vec<String>) {
-        for action in queue {
+        while let Some(action) = self.queue_preflop.pop() {
             self.act(&action);
         }
     }
@@ -171,6 +180,7 @@ impl Nubibus {
     ///
     /// Renamed from `preflop_act` to `act` to make it more generic.
     fn act(&mut self, act: &str) {
+        self.skip_all_in();
         let action_type = ActionType::from(act.chars().next().unwrap());
         match action_type {
             ActionType::FOLD => {
@@ -191,43 +201,192 @@ impl Nubibus {
         }
     }
 
+    /// Plays the next action in the hand. When a round of betting is over, the board cards for
+    /// the next street are dealt before its first action. Returns `None` once every action has
+    /// been played.
     pub fn pop(&mut self) -> Option<()> {
-        if !self.queue_preflop.is_empty() {
-            match self.queue_preflop.pop() {
-                None => None,
-                Some(action) => {
-                    self.act(&action);
-                    Some(())
-                }
+        loop {
+            let queue = match self.phase.current() {
+                PhaseHoldem::Preflop => &mut self.queue_preflop,
+                PhaseHoldem::Flop => &mut self.queue_flop,
+                PhaseHoldem::Turn => &mut self.queue_turn,
+                PhaseHoldem::River => &mut self.queue_river,
+                PhaseHoldem::Init | PhaseHoldem::Over => return None,
+            };
+            if let Some(action) = queue.pop() {
+                self.act(&action);
+                return Some(());
             }
-        } else if !self.queue_flop.is_empty() {
-            match self.queue_flop.pop() {
-                None => None,
-                Some(action) => {
-                    self.act(&action);
-                    Some(())
-                }
+            if !self.next_street() {
+                info!("Hand {} is played out.", self.pluribus.index);
+                return None;
             }
-        } else if !self.queue_turn.is_empty() {
-            match self.queue_turn.pop() {
-                None => None,
-                Some(action) => {
-                    self.act(&action);
-                    Some(())
-                }
+        }
+    }
+
+    /// Ends the current round of betting, and deals the next street, as long as there are at
+    /// least two players left in the hand. Returns false if there are no more streets to play.
+    fn next_street(&mut self) -> bool {
+        self.end_round();
+        if self.position.is_over() || self.phase.current() == PhaseHoldem::River {
+            return false;
+        }
+        self.phase.increment();
+        let board = self.pluribus.board;
+        let action = match self.phase.current() {
+            PhaseHoldem::Flop => Action::flops(board.flop),
+            PhaseHoldem::Turn => Action::turn(board.turn),
+            _ => Action::river(board.river),
+        };
+        let cards = match self.phase.current() {
+            PhaseHoldem::Flop => board.flop.cards(),
+            PhaseHoldem::Turn => Cards::from(vec![board.turn]),
+            _ => Cards::from(vec![board.river]),
+        };
+        if !cards.is_dealt() {
+            warn!("Hand {} is missing its {}", self.pluribus.index, self.phase.current());
+            return false;
+        }
+        info!("{action}");
+        self.board.insert_all(&cards);
+        self.ledger.push(action);
+        true
+    }
+
+    /// Players who are all in don't have any more decisions to make, so they don't show up in
+    /// the Pluribus actions.
+    fn skip_all_in(&self) {
+        for _ in 0..self.seats.len() {
+            let seat = self.seat_from_position(self.current_position());
+            if seat.is_active() && seat.stack_size() == 0 {
+                self.position.increment();
+            } else {
+                return;
             }
-        } else if !self.queue_river.is_empty() {
-            match self.queue_river.pop() {
-                None => None,
-                Some(action) => {
-                    self.act(&action);
-                    Some(())
-                }
+        }
+    }
+
+    /// Plays the whole hand through to the end, awarding the pot.
+    ///
+    /// # Errors
+    ///
+    /// Returns any errors from `do_showdown()`.
+    pub fn play(&mut self) -> Result<(), PKError> {
+        self.do_init();
+        while self.pop().is_some() {}
+        self.do_showdown()?;
+        Ok(())
+    }
+
+    /// Awards the pot, and any side pots, to the winners. If everyone but one player has folded,
+    /// they get it all without having to show their cards.
+    ///
+    /// The work is done by `RunIt::showdown()`, which is why the seats are turned into a
+    /// `TableState` first.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::NotEnoughCards` if it comes to a showdown and the board isn't complete.
+    pub fn do_showdown(&mut self) -> Result<Vec<usize>, PKError> {
+        for seat in &self.seats {
+            seat.end_round();
+        }
+        let state = self.table_state();
+        let awards = RunIt::showdown(&state)?;
+        for (seat, award) in self.seats.iter().zip(awards.iter()) {
+            if *award > 0 {
+                seat.stack.set(seat.stack.get() + Chips::new(*award));
+                info!("{} wins {award}", seat.desc());
             }
+        }
+        self.pot.set(Chips::default());
+        while !self.phase.is_over() {
+            self.phase.increment();
+        }
+        Ok(awards)
+    }
+
+    /// How much each player won or lost, in the same order as `Pluribus::winnings`.
+    #[must_use]
+    pub fn winnings(&self) -> Vec<isize> {
+        self.seats
+            .iter()
+            .take(self.pluribus.players.len())
+            .map(|seat| {
+                let stack = isize::try_from(seat.stack_size()).unwrap_or_default();
+                stack - isize::try_from(Pluribus::STARTING_STACK).unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Replays a `Pluribus` hand, and checks the final stacks against the logged winnings.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `Mismatch` if the hand can't be replayed, or if the winnings don't line up.
+    pub fn verify(pluribus: &Pluribus) -> Result<Nubibus, Mismatch> {
+        let mut nubibus = Nubibus::from_pluribus(pluribus);
+        let result = match nubibus.play() {
+            Ok(()) => Ok(nubibus.winnings()),
+            Err(e) => Err(e),
+        };
+        if result.as_ref() == Ok(&pluribus.winnings) {
+            Ok(nubibus)
         } else {
-            info!("Hand {} is played out.", self.pluribus.index);
-            None
+            Err(Mismatch {
+                index: pluribus.index,
+                raw: pluribus.raw.clone(),
+                expected: pluribus.winnings.clone(),
+                actual: result,
+            })
+        }
+    }
+
+    /// Replays every hand in a Pluribus log file, returning the number of hands checked, and
+    /// every hand that didn't match.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the file can't be read.
+    pub fn verify_log(path: &str) -> Result<(usize, Vec<Mismatch>), PKError> {
+        let file = File::open(path).map_err(|_| PKError::Fubar)?;
+        let mut checked = 0;
+        let mut mismatches = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|_| PKError::Fubar)?;
+            if let Ok(pluribus) = Pluribus::from_str(&line) {
+                checked += 1;
+                if let Err(mismatch) = Nubibus::verify(&pluribus) {
+                    mismatches.push(mismatch);
+                }
+            }
         }
+        Ok((checked, mismatches))
+    }
+
+    /// The seats, turned into the state of the table that the rest of the library knows how to
+    /// work with.
+    #[must_use]
+    pub fn table_state(&self) -> TableState {
+        let mut state = TableState::new(
+            self.seats
+                .iter()
+                .take(self.pluribus.players.len())
+                .map(|seat| SeatState {
+                    name: seat.name.clone(),
+                    stack: seat.stack_size(),
+                    in_round: seat.chips_in_play_size(),
+                    in_pot: seat.chips_in_pot_size() + seat.chips_in_play_size(),
+                    cards: seat.holding(),
+                    folded: !seat.is_active(),
+                    won: 0,
+                })
+                .collect(),
+        );
+        state.board = self.board.clone();
+        state.pot = state.seats.iter().map(|seat| seat.in_pot).sum();
+        state.phase = self.phase.current();
+        state
     }
 
     /// # Errors
@@ -267,7 +426,7 @@ impl Nubibus {
     pub fn do_call(&mut self) {
         let seat = self.seat_from_position(self.current_position());
 
-        let amount = self.floor.get();
+        let amount = self.floor.get().min(seat.chips_in_play_size() + seat.stack_size());
         seat.bet(amount);
 
         let action = Action::call(amount);
@@ -278,8 +437,13 @@ impl Nubibus {
     }
 
     /// Gawd this code is a horrible mess.
+    ///
+    /// The amount of a Pluribus raise is the total the player has put in over the whole hand,
+    /// so after the flop the chips already in the pot need to be taken off to get the amount for
+    /// the round.
     pub fn do_raise(&mut self, amount: usize) {
         let seat = self.seat_from_position(self.current_position());
+        let amount = amount.saturating_sub(seat.chips_in_pot_size());
         let floor_before = self.floor.get();
         self.floor.set(amount);
         seat.bet(amount);
@@ -339,11 +503,22 @@ impl Nubibus {
     }
 
     pub fn end_preflop_round(&mut self) {
+        self.end_round();
+    }
+
+    /// Moves everyone's chips into the pot, and passes the action to the first player still in
+    /// the hand after the button.
+    pub fn end_round(&mut self) {
         // I'm surprised how easy my code makes this.
+        let mut pot = Chips::default();
         for seat in &self.seats {
             seat.end_round();
-            self.add_to_pot(seat.chips_in_pot.get());
+            pot += Chips::new(seat.chips_in_pot.get());
         }
+        self.pot.set(pot);
+        self.floor.set(0);
+        self.position.set(Position6Max::BTN);
+        self.position.increment();
 
         let action = Action::end_round(self.phase.current());
         info!("{} Phase over {} in pot\n", action.detail, self.pot.get());
@@ -391,6 +566,29 @@ impl Nubibus {
     }
 }
 
+/// A Pluribus hand where replaying it didn't come out the way the log says it should have.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Mismatch {
+    pub index: usize,
+    pub raw: String,
+    pub expected: Vec<isize>,
+    /// The winnings from the replay, or the error that stopped it.
+    pub actual: Result<Vec<isize>, PKError>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.actual {
+            Ok(actual) => write!(
+                f,
+                "Hand #{}: expected {:?} got {:?}\n  {}",
+                self.index, self.expected, actual, self.raw
+            ),
+            Err(e) => write!(f, "Hand #{}: {e}\n  {}", self.index, self.raw),
+        }
+    }
+}
+
 impl Display for Nubibus {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let joined = Itertools::join(&mut self.seats.iter(), "\n");
@@ -862,6 +1060,54 @@ mod store_nubibus_tests {
         assert!(pop.is_some());
     }
 
+    #[rstest]
+    #[case(ROW_52)]
+    #[case("STATE:7:fr225fffc/cr475c/cr1225c/cc:5hJc|Jd9h|6s5c|Ah7h|2s2d|3hTs/3sJh2h/Tc/Ks:-50|1275|0|-1225|0|0:Gogo|Budd|Eddie|Bill|Pluribus|MrWhite")]
+    #[case("STATE:16:fr200fccc/cccc/ccr700ffr1600r3550r7300f:4c4s|7dTd|Qh2d|6s7s|Qd8s|3h3s/5hTs8h/Tc:-200|3950|0|-3550|0|-200:Bill|Pluribus|MrWhite|Gogo|Budd|Eddie")]
+    #[case("STATE:27:r200ffcfc/cr850cf/cr1825r3775c/r10000c:Qc4h|Tc9c|8sAs|Qh7c|JcQd|5h5d/3h7s5c/Qs/6c:-50|-200|-10000|0|0|10250:Eddie|Bill|Pluribus|MrWhite|Gogo|Budd")]
+    #[case("STATE:75:fffr225fr1100r2558r6655r10000c///:4s6d|KsKh|Jh5h|9dQc|4hJs|QsQh/5dKdTh/Ac/7d:-50|10050|0|0|0|-10000:MrPink|MrOrange|Pluribus|MrBlue|MrBlonde|MrWhite")]
+    #[case("STATE:13:fffff:Kh4d|8d9s|5hQd|TsKc|3h7h|Jc2d:-50|50|0|0|0|0:Gogo|Budd|Eddie|Bill|Pluribus|MrWhite")]
+    fn verify(#[case] row: &str) {
+        let pluribus = Pluribus::from_str(row).unwrap();
+
+        let nubibus = Nubibus::verify(&pluribus).unwrap();
+
+        assert_eq!(pluribus.winnings, nubibus.winnings());
+        assert_eq!(PhaseHoldem::Over, nubibus.phase.current());
+        assert_eq!(0, nubibus.pot.get().size());
+    }
+
+    /// The Pluribus logs split odd chips in half, and the replay gives the odd chip to the first
+    /// winner.
+    #[test]
+    fn verify__split_pot() {
+        let pluribus = Pluribus::from_str("STATE:53:fr225ffcc/ccr562cf/cc/cc:JdKd|8s9h|7dQh|JcKc|8d9c|4s3s/KsAsTh/8c/3c:112.5|-225|0|112.5|0|0:Pluribus|MrOrange|MrWhite|MrBlue|Budd|Bill").unwrap();
+
+        let nubibus = Nubibus::verify(&pluribus).unwrap();
+
+        assert_eq!(vec![113, -225, 0, 112, 0, 0], nubibus.winnings());
+    }
+
+    #[test]
+    fn verify_log() {
+        let (checked, mismatches) = Nubibus::verify_log("data/pluribus/raw/sample_game_100.log").unwrap();
+
+        assert_eq!(71, checked);
+        assert!(mismatches.is_empty());
+    }
+
+    #[test]
+    fn pop__deals_board() {
+        let mut nub = parse_row_52();
+        nub.do_init();
+
+        while nub.pop().is_some() {}
+
+        assert_eq!("3♠ 6♣ 8♣ A♦ 8♠", nub.board.to_string());
+        assert_eq!(PhaseHoldem::River, nub.phase.current());
+        assert_eq!(7_875, nub.pot.get().size());
+    }
+
     #[test]
     fn seat_check() {
         let mut nub = parse_row_52();
@@ -2,11 +2,18 @@ use crate::play::board::Board;
 use crate::play::hole_cards::HoleCards;
 use crate::util::Util;
 use crate::{PKError, Plurable};
+use lazy_static::lazy_static;
 use regex::Regex;
 use std::fmt::{Display, Formatter};
+use std::fs;
 use std::ops::Index;
+use std::path::PathBuf;
 use std::str::FromStr;
 
+lazy_static! {
+    static ref ACPC_LOG: Regex = Regex::new(r"^sample_game_(\d+)b?\.log$").unwrap();
+}
+
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Pluribus {
     pub index: usize,
@@ -21,9 +28,68 @@ pub struct Pluribus {
 impl Pluribus {
     pub const SMALL_BLIND: usize = 50;
     pub const BIG_BLIND: usize = 100;
+    pub const STARTING_STACK: usize = 10_000;
+
+    /// Every ACPC log in a directory like `data/pluribus/raw`, sorted by name, along with the
+    /// session number from the file name. `sample_game_102.log` and `sample_game_102b.log` are
+    /// both session `102`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the directory can't be read.
+    pub fn logs(dir: &str) -> Result<Vec<(usize, PathBuf)>, PKError> {
+        let mut logs = fs::read_dir(dir)
+            .map_err(|_| PKError::Fubar)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter_map(|path| {
+                let name = path.file_name()?.to_string_lossy().to_string();
+                let session = ACPC_LOG.captures(&name)?[1].parse::<usize>().ok()?;
+                Some((session, path))
+            })
+            .collect::<Vec<(usize, PathBuf)>>();
+        logs.sort_by(|a, b| a.1.cmp(&b.1));
+        Ok(logs)
+    }
 
-    fn parse_isizes(s: &str) -> Vec<isize> {
-        s.split('|').map(|raw| raw.parse::<isize>().unwrap_or(0)).collect()
+    /// Every hand in an ACPC log.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::Fubar` if the file can't be read.
+    /// * `PKError::InvalidPluribusIndex` if a `STATE:` line can't be parsed.
+    pub fn from_log(path: &str) -> Result<Vec<Pluribus>, PKError> {
+        fs::read_to_string(path)
+            .map_err(|_| PKError::Fubar)?
+            .lines()
+            .filter(|line| line.starts_with("STATE:"))
+            .map(Pluribus::from_str)
+            .collect()
+    }
+
+    /// The logs split an odd chip in a split pot, so a winner can be down for `112.5`. There's
+    /// no such thing as half a chip, so the odd chips go to the split winners in seat order, one
+    /// at a time, which is the same way that `Pot::split()` hands them out.
+    fn parse_isizes(s: &str) -> Result<Vec<isize>, PKError> {
+        let raw = s
+            .split('|')
+            .map(|raw| raw.parse::<f64>().map_err(|_| PKError::InvalidPluribusIndex))
+            .collect::<Result<Vec<f64>, PKError>>()?;
+        let floors = raw.iter().map(|w| w.floor()).collect::<Vec<f64>>();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let mut odd = (raw.iter().sum::<f64>() - floors.iter().sum::<f64>()).round() as usize;
+
+        let mut winnings = Vec::with_capacity(raw.len());
+        for (w, floor) in raw.iter().zip(floors) {
+            #[allow(clippy::cast_possible_truncation)]
+            let mut chips = floor as isize;
+            if odd > 0 && (w - floor).abs() > f64::EPSILON {
+                chips += 1;
+                odd -= 1;
+            }
+            winnings.push(chips);
+        }
+        Ok(winnings)
     }
 
     fn parse_usize(s: &str) -> Result<usize, PKError> {
@@ -84,7 +150,7 @@ impl FromStr for Pluribus {
                     rounds: Util::str_splitter(v.index(2), "/"),
                     hole_cards,
                     board,
-                    winnings: Pluribus::parse_isizes(v.index(4)),
+                    winnings: Pluribus::parse_isizes(v.index(4))?,
                     players: Util::str_splitter(v.index(5), "|"),
                     raw: s.to_string(),
                 })
@@ -124,11 +190,39 @@ mod store_pluribus_tests {
     fn parse_isizes() {
         let expected = vec![-50, -200, -10000, 0, 0, 10250];
 
-        let actual = Pluribus::parse_isizes(Pluribus::parse_string(LOG).unwrap().index(4));
+        let actual = Pluribus::parse_isizes(Pluribus::parse_string(LOG).unwrap().index(4)).unwrap();
 
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn parse_isizes__split_pot() {
+        assert_eq!(
+            vec![113, -225, 0, 112, 0, 0],
+            Pluribus::parse_isizes("112.5|-225|0|112.5|0|0").unwrap()
+        );
+        assert_eq!(vec![-12, 12], Pluribus::parse_isizes("-12.5|12.5").unwrap());
+        assert_eq!(Err(PKError::InvalidPluribusIndex), Pluribus::parse_isizes("100|x"));
+    }
+
+    #[test]
+    fn logs() {
+        let logs = Pluribus::logs("data/pluribus/raw").unwrap();
+
+        assert_eq!(92, logs.len());
+        assert_eq!(100, logs[0].0);
+        assert!(logs[1].1.ends_with("sample_game_100b.log"));
+        assert_eq!(100, logs[1].0);
+    }
+
+    #[test]
+    fn from_log() {
+        let hands = Pluribus::from_log("data/pluribus/raw/sample_game_102.log").unwrap();
+
+        assert_eq!(0, hands[0].index);
+        assert_eq!(6, hands[0].players.len());
+    }
+
     #[test]
     fn parse_usize() {
         assert_eq!(
@@ -193,7 +287,7 @@ mod store_pluribus_tests {
                     rounds: Util::str_splitter(v.index(2), "/"),
                     hole_cards,
                     board,
-                    winnings: Pluribus::parse_isizes(v.index(4)),
+                    winnings: Pluribus::parse_isizes(v.index(4)).unwrap(),
                     players: Util::str_splitter(v.index(5), "|"),
                     raw: row.to_string(),
                 })
@@ -213,7 +307,7 @@ mod store_pluribus_tests {
         let _rounds = Util::str_splitter(v.index(2), "/");
         let _hole_cards = player_cards;
         let _board = board;
-        let _winnings = Pluribus::parse_isizes(v.index(4));
+        let _winnings = Pluribus::parse_isizes(v.index(4)).unwrap();
         let _players = Util::str_splitter(v.index(5), "|");
     }
 }
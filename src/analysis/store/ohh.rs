@@ -0,0 +1,934 @@
+use crate::analysis::store::nubibus::pluribus::Pluribus;
+use crate::analysis::store::pokerstars::{ActionKind, HandHistory, PlayerAction};
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::play::board::Board;
+use crate::play::hole_cards::HoleCards;
+use crate::play::phases::PhaseHoldem;
+use crate::PKError;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+lazy_static! {
+    static ref UTC: Regex = Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?(Z|[+-]\d{2}:\d{2})$").unwrap();
+    static ref ET: Regex = Regex::new(r"^(\d{4})/(\d{2})/(\d{2}) (\d{2}):(\d{2}):(\d{2}) ET$").unwrap();
+}
+
+/// # Open Hand History
+///
+/// The [Open Hand History](https://hh-specs.handhistory.org/) standard is a JSON format for
+/// swapping hands between poker tools. Every hand is its own JSON object, wrapped in an `ohh`
+/// field, and a file is just a bunch of them separated by blank lines.
+///
+/// Streets and actions are kept as the strings the standard uses, like `"Flop"` and `"Post SB"`,
+/// rather than enums, so that a hand from some other tool with a typo in it can still be loaded,
+/// and `validate()` can tell you exactly which field is off.
+///
+/// The `amount` for every action is the number of chips that action puts into the pot, so a
+/// raise from 100 to 300 by a player who already has 100 in is an `amount` of 200. Players are
+/// identified by their index in `players`, and cards are written the usual way, like `Tc`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OpenHandHistory {
+    pub ohh: OhhHand,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OhhHand {
+    pub spec_version: String,
+    pub site_name: String,
+    pub network_name: String,
+    pub internal_version: String,
+    pub tournament: bool,
+    pub game_number: String,
+    pub start_date_utc: String,
+    pub table_name: String,
+    pub table_size: usize,
+    pub game_type: String,
+    pub bet_limit: OhhBetLimit,
+    pub currency: String,
+    pub dealer_seat: usize,
+    pub small_blind_amount: f64,
+    pub big_blind_amount: f64,
+    #[serde(default)]
+    pub ante_amount: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hero_player_id: Option<usize>,
+    pub players: Vec<OhhPlayer>,
+    pub rounds: Vec<OhhRound>,
+    pub pots: Vec<OhhPot>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OhhBetLimit {
+    pub bet_type: String,
+    #[serde(default)]
+    pub bet_cap: f64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OhhPlayer {
+    pub id: usize,
+    pub seat: usize,
+    pub name: String,
+    pub starting_stack: f64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OhhRound {
+    pub id: usize,
+    pub street: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cards: Vec<String>,
+    pub actions: Vec<OhhAction>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OhhAction {
+    pub action_number: usize,
+    pub player_id: usize,
+    pub action: String,
+    #[serde(default)]
+    pub amount: f64,
+    #[serde(default)]
+    pub is_allin: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cards: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OhhPot {
+    pub number: usize,
+    pub amount: f64,
+    #[serde(default)]
+    pub rake: f64,
+    pub player_wins: Vec<OhhWin>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OhhWin {
+    pub player_id: usize,
+    pub win_amount: f64,
+}
+
+impl OpenHandHistory {
+    pub const SPEC_VERSION: &'static str = "1.4.7";
+    pub const STREETS: [&'static str; 5] = ["Preflop", "Flop", "Turn", "River", "Showdown"];
+    pub const ACTIONS: [&'static str; 13] = [
+        "Dealt Cards",
+        "Mucks Cards",
+        "Shows Cards",
+        "Post Ante",
+        "Post SB",
+        "Post BB",
+        "Straddle",
+        "Post Dead",
+        "Fold",
+        "Check",
+        "Bet",
+        "Raise",
+        "Call",
+    ];
+
+    /// Converts a `PokerStars` hand. A date in the `PokerStars` Eastern time format is moved
+    /// to UTC, allowing for daylight saving time, and anything else is passed along as is.
+    ///
+    /// The `PokerStars` format doesn't say how a pot was split up into side pots, so everything
+    /// collected ends up in a single pot.
+    #[must_use]
+    #[allow(clippy::too_many_lines)]
+    pub fn from_hand_history(hand: &HandHistory) -> OpenHandHistory {
+        let id = |name: &str| hand.seats.iter().position(|seat| seat.name == name).unwrap_or_default();
+        let mut action_number = 0;
+        let mut action = |player_id: usize, name: &str, amount: usize, is_allin: bool, cards: &Cards| {
+            action_number += 1;
+            #[allow(clippy::cast_precision_loss)]
+            OhhAction {
+                action_number: action_number - 1,
+                player_id,
+                action: name.to_string(),
+                amount: amount as f64,
+                is_allin,
+                cards: cards.to_letters(),
+            }
+        };
+
+        let mut rounds = Vec::new();
+        for street in &hand.streets {
+            let mut in_street = vec![0; hand.seats.len()];
+            let blinds = street
+                .actions
+                .iter()
+                .take_while(|action| matches!(action.kind, ActionKind::SmallBlind(_) | ActionKind::BigBlind(_)))
+                .count();
+            let mut actions = Vec::new();
+            for (i, player_action) in street.actions.iter().enumerate() {
+                if street.phase == PhaseHoldem::Preflop && i == blinds {
+                    for (name, cards) in &hand.dealt {
+                        actions.push(action(id(name), "Dealt Cards", 0, false, cards));
+                    }
+                }
+                let player_id = id(&player_action.player);
+                let (name, amount) = match player_action.kind {
+                    ActionKind::SmallBlind(amount) => ("Post SB", amount),
+                    ActionKind::BigBlind(amount) => ("Post BB", amount),
+                    ActionKind::Fold => ("Fold", 0),
+                    ActionKind::Check => ("Check", 0),
+                    ActionKind::Call(amount) => ("Call", amount),
+                    ActionKind::Bet(amount) => ("Bet", amount),
+                    ActionKind::Raise { to, .. } => ("Raise", to.saturating_sub(in_street[player_id])),
+                };
+                in_street[player_id] += amount;
+                actions.push(action(player_id, name, amount, player_action.all_in, &Cards::default()));
+            }
+            rounds.push(OhhRound {
+                id: rounds.len(),
+                street: OpenHandHistory::street_name(street.phase).to_string(),
+                cards: street.cards.to_letters(),
+                actions,
+            });
+        }
+        if !hand.showed.is_empty() {
+            let actions = hand
+                .showed
+                .iter()
+                .map(|showed| action(id(&showed.name), "Shows Cards", 0, false, &showed.cards))
+                .collect();
+            rounds.push(OhhRound {
+                id: rounds.len(),
+                street: "Showdown".to_string(),
+                cards: Vec::new(),
+                actions,
+            });
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        OpenHandHistory {
+            ohh: OhhHand {
+                spec_version: OpenHandHistory::SPEC_VERSION.to_string(),
+                site_name: "PokerStars".to_string(),
+                network_name: "PokerStars".to_string(),
+                internal_version: env!("CARGO_PKG_VERSION").to_string(),
+                tournament: false,
+                game_number: hand.id.to_string(),
+                start_date_utc: OpenHandHistory::utc(&hand.date),
+                table_name: hand.table.clone(),
+                table_size: hand.max_seats,
+                game_type: "Holdem".to_string(),
+                bet_limit: OhhBetLimit {
+                    bet_type: "NL".to_string(),
+                    bet_cap: 0.0,
+                },
+                currency: if hand.play_money { "PM" } else { "USD" }.to_string(),
+                dealer_seat: hand.button,
+                small_blind_amount: hand.small_blind as f64,
+                big_blind_amount: hand.big_blind as f64,
+                ante_amount: 0.0,
+                hero_player_id: None,
+                players: hand
+                    .seats
+                    .iter()
+                    .enumerate()
+                    .map(|(id, seat)| OhhPlayer {
+                        id,
+                        seat: seat.seat,
+                        name: seat.name.clone(),
+                        starting_stack: seat.stack as f64,
+                    })
+                    .collect(),
+                rounds,
+                pots: vec![OhhPot {
+                    number: 0,
+                    amount: hand.total_pot as f64,
+                    rake: hand.rake as f64,
+                    player_wins: hand
+                        .collected
+                        .iter()
+                        .map(|(name, amount)| OhhWin {
+                            player_id: id(name),
+                            win_amount: *amount,
+                        })
+                        .collect(),
+                }],
+            },
+        }
+    }
+
+    /// Converts a row from the Pluribus ACPC logs. The logs don't say when a hand was played, so
+    /// the UTC date is passed in. Pluribus is the hero.
+    ///
+    /// # Errors
+    ///
+    /// Anything `HandHistory::from_pluribus()` returns.
+    pub fn from_pluribus(
+        pluribus: &Pluribus,
+        session: usize,
+        start_date_utc: &str,
+    ) -> Result<OpenHandHistory, PKError> {
+        let hand = HandHistory::from_pluribus(pluribus, session, start_date_utc)?;
+        let mut ohh = OpenHandHistory::from_hand_history(&hand);
+        ohh.ohh.hero_player_id = pluribus.players.iter().position(|name| name == "Pluribus");
+        Ok(ohh)
+    }
+
+    /// Converts every `sample_game_NNN.log` in a directory of ACPC logs, like
+    /// `data/pluribus/raw`, into `pluribus_NNN.ohh` files in `out`. The session is the number
+    /// in the file name, the same as in `data/pluribus/converted_logs`. Every hand gets the
+    /// same `start_date_utc`, since there's nothing in the logs to say otherwise.
+    ///
+    /// Returns how many hands were converted.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::Fubar` if a directory or file can't be read or written.
+    /// * `PKError::InvalidPluribusIndex` if a `STATE:` line can't be made sense of.
+    pub fn convert_pluribus_logs(raw: &str, out: &str, start_date_utc: &str) -> Result<usize, PKError> {
+        fs::create_dir_all(out).map_err(|_| PKError::Fubar)?;
+        let mut count = 0;
+        for (session, path) in Pluribus::logs(raw)? {
+            let hands = Pluribus::from_log(&path.to_string_lossy())?
+                .iter()
+                .map(|pluribus| OpenHandHistory::from_pluribus(pluribus, session, start_date_utc))
+                .collect::<Result<Vec<OpenHandHistory>, PKError>>()?;
+            let name = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .replace("sample_game_", "pluribus_");
+            let destination = Path::new(out).join(format!("{name}.ohh"));
+            OpenHandHistory::to_file(&destination.to_string_lossy(), &hands)?;
+            count += hands.len();
+        }
+        Ok(count)
+    }
+
+    /// Loads every hand in a file, and validates each one.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::Fubar` if the file can't be read.
+    /// * `PKError::InvalidOhh` if a hand isn't valid JSON, or fails `validate()`.
+    pub fn from_file(path: &str) -> Result<Vec<OpenHandHistory>, PKError> {
+        OpenHandHistory::parse_all(&fs::read_to_string(path).map_err(|_| PKError::Fubar)?)
+    }
+
+    /// Parses every hand in a string of hands separated by whitespace.
+    ///
+    /// # Errors
+    ///
+    /// `PKError::InvalidOhh` if a hand isn't valid JSON, or fails `validate()`. The hands are
+    /// counted from zero.
+    pub fn parse_all(s: &str) -> Result<Vec<OpenHandHistory>, PKError> {
+        serde_json::Deserializer::from_str(s)
+            .into_iter::<OpenHandHistory>()
+            .enumerate()
+            .map(|(i, hand)| {
+                let hand = hand.map_err(|e| PKError::InvalidOhh(format!("hand {i}: {e}")))?;
+                hand.validate()
+                    .map_err(|e| PKError::InvalidOhh(format!("hand {i}: {}", OpenHandHistory::reason(&e))))?;
+                Ok(hand)
+            })
+            .collect()
+    }
+
+    /// Writes out hands as pretty printed JSON, separated by blank lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the file can't be written.
+    pub fn to_file(path: &str, hands: &[OpenHandHistory]) -> Result<(), PKError> {
+        fs::write(path, OpenHandHistory::to_log(hands)).map_err(|_| PKError::Fubar)
+    }
+
+    #[must_use]
+    pub fn to_log(hands: &[OpenHandHistory]) -> String {
+        hands.iter().map(|hand| hand.to_string() + "\n\n").collect()
+    }
+
+    /// Every betting action for a street, as `PokerStars` style actions. Dealt and shown cards
+    /// are left out.
+    ///
+    /// # Errors
+    ///
+    /// `PKError::InvalidOhh` if an action refers to a player that isn't there, or isn't
+    /// something that `PlayerAction` has room for.
+    pub fn actions(&self, phase: PhaseHoldem) -> Result<Vec<PlayerAction>, PKError> {
+        let street = OpenHandHistory::street_name(phase);
+        let Some(round) = self.ohh.rounds.iter().find(|round| round.street == street) else {
+            return Ok(Vec::new());
+        };
+        let mut in_street = vec![0; self.ohh.players.len()];
+        let mut actions = Vec::new();
+        for (i, action) in round.actions.iter().enumerate() {
+            let field = format!("ohh.rounds[{}].actions[{i}]", round.id);
+            let (seat, player) = self.player(action.player_id, &field)?;
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let amount = action.amount.round() as usize;
+            let max = in_street.iter().max().copied().unwrap_or_default();
+            let kind = match action.action.as_str() {
+                "Dealt Cards" | "Shows Cards" | "Mucks Cards" => continue,
+                "Post SB" => ActionKind::SmallBlind(amount),
+                "Post BB" => ActionKind::BigBlind(amount),
+                "Fold" => ActionKind::Fold,
+                "Check" => ActionKind::Check,
+                "Call" => ActionKind::Call(amount),
+                "Bet" => ActionKind::Bet(amount),
+                "Raise" => {
+                    let to = in_street[seat] + amount;
+                    ActionKind::Raise {
+                        by: to.saturating_sub(max),
+                        to,
+                    }
+                }
+                other => {
+                    return Err(PKError::InvalidOhh(format!(
+                        "{field}.action: unsupported action {other}"
+                    )))
+                }
+            };
+            in_street[seat] += amount;
+            actions.push(PlayerAction {
+                player: player.name.clone(),
+                kind,
+                all_in: action.is_allin,
+            });
+        }
+        Ok(actions)
+    }
+
+    /// The cards on the board at the end of the hand. Empty if the hand ended before the flop.
+    ///
+    /// # Errors
+    ///
+    /// `PKError::InvalidOhh` if the cards on a street can't be parsed.
+    pub fn board(&self) -> Result<Board, PKError> {
+        let mut cards = Cards::default();
+        for round in &self.ohh.rounds {
+            cards.insert_all(&OpenHandHistory::cards(
+                &round.cards,
+                &format!("ohh.rounds[{}].cards", round.id),
+            )?);
+        }
+        if cards.len() < 3 {
+            return Ok(Board::default());
+        }
+        Board::try_from(cards).map_err(|_| PKError::InvalidOhh("ohh.rounds: too many board cards".to_string()))
+    }
+
+    /// The hole cards dealt to each player, in player order.
+    ///
+    /// # Errors
+    ///
+    /// `PKError::InvalidOhh` if a player wasn't dealt exactly two cards.
+    pub fn hole_cards(&self) -> Result<HoleCards, PKError> {
+        let mut dealt = vec![None; self.ohh.players.len()];
+        for round in &self.ohh.rounds {
+            for (i, action) in round.actions.iter().enumerate() {
+                if action.action == "Dealt Cards" {
+                    let field = format!("ohh.rounds[{}].actions[{i}]", round.id);
+                    let (seat, _) = self.player(action.player_id, &field)?;
+                    dealt[seat] = Some(OpenHandHistory::two(&action.cards, &format!("{field}.cards"))?);
+                }
+            }
+        }
+        dealt
+            .into_iter()
+            .enumerate()
+            .map(|(i, two)| two.ok_or_else(|| PKError::InvalidOhh(format!("ohh.players[{i}]: no cards dealt"))))
+            .collect::<Result<Vec<Two>, PKError>>()
+            .map(HoleCards::from)
+    }
+
+    /// Checks that the hand makes sense, returning a `PKError::InvalidOhh` naming the first field
+    /// that doesn't, like `ohh.rounds[1].actions[3].player_id: no player with id 7`.
+    ///
+    /// # Errors
+    ///
+    /// `PKError::InvalidOhh` for the first problem found.
+    #[allow(clippy::too_many_lines)]
+    pub fn validate(&self) -> Result<(), PKError> {
+        let ohh = &self.ohh;
+        let invalid = |field: &str, reason: String| Err(PKError::InvalidOhh(format!("{field}: {reason}")));
+
+        if ohh.spec_version.is_empty() {
+            return invalid("ohh.spec_version", "missing".to_string());
+        }
+        if ohh.game_type != "Holdem" {
+            return invalid(
+                "ohh.game_type",
+                format!("only Holdem is supported, not {}", ohh.game_type),
+            );
+        }
+        if !["NL", "PL", "FL"].contains(&ohh.bet_limit.bet_type.as_str()) {
+            return invalid(
+                "ohh.bet_limit.bet_type",
+                format!("unknown bet type {}", ohh.bet_limit.bet_type),
+            );
+        }
+        if !UTC.is_match(&ohh.start_date_utc) {
+            return invalid(
+                "ohh.start_date_utc",
+                format!("not an ISO 8601 date {}", ohh.start_date_utc),
+            );
+        }
+        for (field, amount) in [
+            ("ohh.small_blind_amount", ohh.small_blind_amount),
+            ("ohh.big_blind_amount", ohh.big_blind_amount),
+            ("ohh.ante_amount", ohh.ante_amount),
+        ] {
+            if !amount.is_finite() || amount < 0.0 {
+                return invalid(field, format!("invalid amount {amount}"));
+            }
+        }
+        if ohh.small_blind_amount > ohh.big_blind_amount {
+            return invalid("ohh.small_blind_amount", "bigger than the big blind".to_string());
+        }
+
+        if ohh.players.len() < 2 || ohh.players.len() > ohh.table_size {
+            return invalid(
+                "ohh.players",
+                format!("{} players at a {} seat table", ohh.players.len(), ohh.table_size),
+            );
+        }
+        let mut seats = HashSet::new();
+        let mut ids = HashSet::new();
+        for (i, player) in ohh.players.iter().enumerate() {
+            if !ids.insert(player.id) {
+                return invalid(&format!("ohh.players[{i}].id"), format!("{} is used twice", player.id));
+            }
+            if player.seat < 1 || player.seat > ohh.table_size || !seats.insert(player.seat) {
+                return invalid(
+                    &format!("ohh.players[{i}].seat"),
+                    format!("invalid seat {}", player.seat),
+                );
+            }
+            if !player.starting_stack.is_finite() || player.starting_stack < 0.0 {
+                return invalid(
+                    &format!("ohh.players[{i}].starting_stack"),
+                    format!("invalid amount {}", player.starting_stack),
+                );
+            }
+        }
+        if !seats.contains(&ohh.dealer_seat) {
+            return invalid("ohh.dealer_seat", format!("nobody is in seat {}", ohh.dealer_seat));
+        }
+
+        let mut street = 0;
+        let mut action_number = None;
+        let mut cards = Cards::default();
+        let mut round_ids = HashSet::new();
+        for (r, round) in ohh.rounds.iter().enumerate() {
+            let field = format!("ohh.rounds[{r}]");
+            if !round_ids.insert(round.id) {
+                return invalid(&format!("{field}.id"), format!("{} is used twice", round.id));
+            }
+            let Some(position) = OpenHandHistory::STREETS.iter().position(|s| *s == round.street) else {
+                return invalid(&format!("{field}.street"), format!("unknown street {}", round.street));
+            };
+            if position < street {
+                return invalid(&format!("{field}.street"), format!("{} is out of order", round.street));
+            }
+            street = position;
+            let dealt = OpenHandHistory::cards(&round.cards, &format!("{field}.cards"))?;
+            let expected = [0, 3, 1, 1, 0][position];
+            if dealt.len() != expected {
+                return invalid(
+                    &format!("{field}.cards"),
+                    format!("expected {expected} cards on the {}", round.street),
+                );
+            }
+            for card in dealt.iter() {
+                if !cards.insert(*card) {
+                    return invalid(&format!("{field}.cards"), format!("{card} was dealt twice"));
+                }
+            }
+
+            for (a, action) in round.actions.iter().enumerate() {
+                let field = format!("{field}.actions[{a}]");
+                if action_number.is_some_and(|n| action.action_number <= n) {
+                    return invalid(
+                        &format!("{field}.action_number"),
+                        format!("{} is out of order", action.action_number),
+                    );
+                }
+                action_number = Some(action.action_number);
+                if !ids.contains(&action.player_id) {
+                    return invalid(
+                        &format!("{field}.player_id"),
+                        format!("no player with id {}", action.player_id),
+                    );
+                }
+                if !OpenHandHistory::ACTIONS.contains(&action.action.as_str()) {
+                    return invalid(&format!("{field}.action"), format!("unknown action {}", action.action));
+                }
+                if !action.amount.is_finite() || action.amount < 0.0 {
+                    return invalid(&format!("{field}.amount"), format!("invalid amount {}", action.amount));
+                }
+                if action.action.ends_with("Cards") {
+                    OpenHandHistory::two(&action.cards, &format!("{field}.cards"))?;
+                }
+            }
+        }
+
+        for (p, pot) in ohh.pots.iter().enumerate() {
+            let field = format!("ohh.pots[{p}]");
+            let mut won = 0.0;
+            for (w, win) in pot.player_wins.iter().enumerate() {
+                if !ids.contains(&win.player_id) {
+                    return invalid(
+                        &format!("{field}.player_wins[{w}].player_id"),
+                        format!("no player with id {}", win.player_id),
+                    );
+                }
+                won += win.win_amount;
+            }
+            if won > pot.amount - pot.rake + f64::EPSILON {
+                return invalid(
+                    &format!("{field}.player_wins"),
+                    format!("{won} won from a pot of {}", pot.amount),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The player with the id, along with where they are in `players`. The spec doesn't say
+    /// that ids have to be anything in particular, just that the actions use them.
+    fn player(&self, id: usize, field: &str) -> Result<(usize, &OhhPlayer), PKError> {
+        self.ohh
+            .players
+            .iter()
+            .enumerate()
+            .find(|(_, player)| player.id == id)
+            .ok_or_else(|| PKError::InvalidOhh(format!("{field}.player_id: no player with id {id}")))
+    }
+
+    fn cards(letters: &[String], field: &str) -> Result<Cards, PKError> {
+        let cards = letters
+            .iter()
+            .map(|s| Card::from_str(s).map_err(|_| PKError::InvalidOhh(format!("{field}: invalid card {s}"))))
+            .collect::<Result<Vec<Card>, PKError>>()?;
+        Ok(Cards::from(cards))
+    }
+
+    fn two(letters: &[String], field: &str) -> Result<Two, PKError> {
+        let cards = OpenHandHistory::cards(letters, field)?;
+        if letters.len() != 2 || cards.len() != 2 {
+            return Err(PKError::InvalidOhh(format!("{field}: expected two cards")));
+        }
+        Two::try_from(cards).map_err(|_| PKError::InvalidOhh(format!("{field}: expected two cards")))
+    }
+
+    /// The reason from an error, without the `Display` prefix.
+    fn reason(e: &PKError) -> String {
+        match e {
+            PKError::InvalidOhh(reason) => reason.clone(),
+            _ => e.to_string(),
+        }
+    }
+
+    fn street_name(phase: PhaseHoldem) -> &'static str {
+        match phase {
+            PhaseHoldem::Flop => "Flop",
+            PhaseHoldem::Turn => "Turn",
+            PhaseHoldem::River => "River",
+            PhaseHoldem::Over => "Showdown",
+            PhaseHoldem::Init | PhaseHoldem::Preflop => "Preflop",
+        }
+    }
+
+    /// `PokerStars` dates are in Eastern time, like `2019/07/12 03:47:42 ET`. Daylight saving
+    /// time runs from 2am on the second Sunday in March until 2am on the first Sunday in
+    /// November.
+    fn utc(date: &str) -> String {
+        let Some(captures) = ET.captures(date) else {
+            return date.to_string();
+        };
+        let n = |i: usize| captures[i].parse::<i64>().unwrap_or_default();
+        let (year, month, day) = (n(1), n(2), n(3));
+        let seconds = n(4) * 3_600 + n(5) * 60 + n(6);
+
+        let sunday = |month: i64, nth: i64| {
+            let first = OpenHandHistory::days_from_civil(year, month, 1);
+            let weekday = (first + 4).rem_euclid(7);
+            first + (7 - weekday) % 7 + 7 * (nth - 1)
+        };
+        let local = OpenHandHistory::days_from_civil(year, month, day) * 86_400 + seconds;
+        let daylight = local >= sunday(3, 2) * 86_400 + 7_200 && local < sunday(11, 1) * 86_400 + 7_200;
+        let utc = local + if daylight { 4 } else { 5 } * 3_600;
+
+        let (year, month, day) = OpenHandHistory::civil_from_days(utc.div_euclid(86_400));
+        let seconds = utc.rem_euclid(86_400);
+        format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            seconds / 3_600,
+            seconds / 60 % 60,
+            seconds % 60
+        )
+    }
+
+    /// Days since 1970-01-01, from Howard Hinnant's `chrono`-compatible date algorithms.
+    fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+        let year = if month <= 2 { year - 1 } else { year };
+        let era = year.div_euclid(400);
+        let yoe = year - era * 400;
+        let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + day - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+
+    fn civil_from_days(days: i64) -> (i64, i64, i64) {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z - era * 146_097;
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = doy - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        (year, month, day)
+    }
+}
+
+impl Display for OpenHandHistory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let json = serde_json::to_string_pretty(self).map_err(|_| std::fmt::Error)?;
+        write!(f, "{json}")
+    }
+}
+
+impl From<&HandHistory> for OpenHandHistory {
+    fn from(hand: &HandHistory) -> Self {
+        OpenHandHistory::from_hand_history(hand)
+    }
+}
+
+impl FromStr for OpenHandHistory {
+    type Err = PKError;
+
+    /// Parses and validates a single hand.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hand: OpenHandHistory = serde_json::from_str(s).map_err(|e| PKError::InvalidOhh(e.to_string()))?;
+        hand.validate()?;
+        Ok(hand)
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__ohh_tests {
+    use super::*;
+    use crate::analysis::store::pokerstars::analysis__store__pokerstars_tests::HAND;
+
+    fn ohh() -> OpenHandHistory {
+        OpenHandHistory::from(&HandHistory::from_str(HAND).unwrap())
+    }
+
+    #[test]
+    fn from_hand_history() {
+        let ohh = ohh().ohh;
+
+        assert_eq!("100062", ohh.game_number);
+        assert_eq!("2019-07-12T07:47:42Z", ohh.start_date_utc);
+        assert_eq!(6, ohh.dealer_seat);
+        assert_eq!(6, ohh.players.len());
+        assert_eq!(
+            vec!["Preflop", "Flop", "Turn", "River", "Showdown"],
+            ohh.rounds
+                .iter()
+                .map(|round| round.street.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(vec!["Qh", "Kc", "5h"], ohh.rounds[1].cards);
+        assert!((ohh.pots[0].amount - 21_350.0).abs() < f64::EPSILON);
+        assert_eq!(5, ohh.pots[0].player_wins[0].player_id);
+        // MrWhite raises to 1250 with the small blind already in.
+        let raise = &ohh.rounds[0].actions[12];
+        assert_eq!(
+            ("Raise", 0, 1_200.0),
+            (raise.action.as_str(), raise.player_id, raise.amount)
+        );
+    }
+
+    #[test]
+    fn actions() {
+        let hand = HandHistory::from_str(HAND).unwrap();
+        let ohh = OpenHandHistory::from(&hand);
+
+        for phase in [
+            PhaseHoldem::Preflop,
+            PhaseHoldem::Flop,
+            PhaseHoldem::Turn,
+            PhaseHoldem::River,
+        ] {
+            assert_eq!(hand.actions(phase), ohh.actions(phase).unwrap().as_slice());
+        }
+    }
+
+    #[test]
+    fn board() {
+        assert_eq!(HandHistory::from_str(HAND).unwrap().board(), ohh().board().unwrap());
+    }
+
+    #[test]
+    fn hole_cards() {
+        assert_eq!(
+            HandHistory::from_str(HAND).unwrap().hole_cards(),
+            ohh().hole_cards().unwrap()
+        );
+    }
+
+    #[test]
+    fn from_str() {
+        let ohh = ohh();
+
+        assert_eq!(ohh, OpenHandHistory::from_str(&ohh.to_string()).unwrap());
+    }
+
+    #[test]
+    fn from_str__missing_field() {
+        let json = ohh().to_string().replace("\"players\"", "\"people\"");
+
+        let err = OpenHandHistory::from_str(&json).unwrap_err().to_string();
+
+        assert!(err.contains("missing field `players`"), "{err}");
+    }
+
+    #[test]
+    fn validate() {
+        let mut ohh = ohh();
+        ohh.ohh.rounds[1].actions[1].player_id = 7;
+
+        assert_eq!(
+            PKError::InvalidOhh("ohh.rounds[1].actions[1].player_id: no player with id 7".to_string()),
+            ohh.validate().unwrap_err()
+        );
+    }
+
+    /// Ids are just names for the players, so they don't have to line up with anything.
+    #[test]
+    fn validate__opaque_ids() {
+        let mut ohh = ohh();
+        for player in &mut ohh.ohh.players {
+            player.id = 100 - player.id;
+        }
+        for round in &mut ohh.ohh.rounds {
+            round.id += 10;
+            for action in &mut round.actions {
+                action.player_id = 100 - action.player_id;
+            }
+        }
+        for pot in &mut ohh.ohh.pots {
+            for win in &mut pot.player_wins {
+                win.player_id = 100 - win.player_id;
+            }
+        }
+
+        assert!(ohh.validate().is_ok());
+        assert_eq!(self::ohh().hole_cards().unwrap(), ohh.hole_cards().unwrap());
+        assert_eq!(
+            self::ohh().actions(PhaseHoldem::Preflop).unwrap(),
+            ohh.actions(PhaseHoldem::Preflop).unwrap()
+        );
+    }
+
+    #[test]
+    fn validate__duplicate_id() {
+        let mut ohh = ohh();
+        ohh.ohh.players[1].id = ohh.ohh.players[0].id;
+        ohh.ohh.rounds[1].id = ohh.ohh.rounds[0].id;
+
+        assert_eq!(
+            PKError::InvalidOhh(format!("ohh.players[1].id: {} is used twice", ohh.ohh.players[0].id)),
+            ohh.validate().unwrap_err()
+        );
+        ohh.ohh.players[1].id = 1;
+        ohh.ohh.players[0].id = 0;
+        assert_eq!(
+            PKError::InvalidOhh(format!("ohh.rounds[1].id: {} is used twice", ohh.ohh.rounds[0].id)),
+            ohh.validate().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn validate__street() {
+        let mut ohh = ohh();
+        ohh.ohh.rounds[2].street = "Fourth Street".to_string();
+
+        assert_eq!(
+            PKError::InvalidOhh("ohh.rounds[2].street: unknown street Fourth Street".to_string()),
+            ohh.validate().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn validate__cards() {
+        let mut ohh = ohh();
+        ohh.ohh.rounds[3].cards = vec!["Kc".to_string()];
+
+        assert_eq!(
+            PKError::InvalidOhh("ohh.rounds[3].cards: K♣ was dealt twice".to_string()),
+            ohh.validate().unwrap_err()
+        );
+    }
+
+    #[test]
+    fn utc() {
+        assert_eq!("2019-07-12T07:47:42Z", OpenHandHistory::utc("2019/07/12 03:47:42 ET"));
+        assert_eq!("2019-01-01T04:59:59Z", OpenHandHistory::utc("2018/12/31 23:59:59 ET"));
+        assert_eq!("2019-03-10T06:59:59Z", OpenHandHistory::utc("2019/03/10 01:59:59 ET"));
+        assert_eq!("2019-03-10T07:00:00Z", OpenHandHistory::utc("2019/03/10 03:00:00 ET"));
+        assert_eq!("2024-02-29T12:00:00Z", OpenHandHistory::utc("2024-02-29T12:00:00Z"));
+    }
+
+    /// Every hand in a session converts, validates, and reads back the same.
+    #[test]
+    fn convert_pluribus_logs() {
+        let raw = std::env::temp_dir().join("pkcore_ohh_raw");
+        let out = std::env::temp_dir().join("pkcore_ohh_out");
+        fs::create_dir_all(&raw).unwrap();
+        fs::copy("data/pluribus/raw/sample_game_102.log", raw.join("sample_game_102.log")).unwrap();
+
+        let count = OpenHandHistory::convert_pluribus_logs(
+            &raw.to_string_lossy(),
+            &out.to_string_lossy(),
+            "2019-07-12T00:00:00Z",
+        )
+        .unwrap();
+        let hands = OpenHandHistory::from_file(&out.join("pluribus_102.ohh").to_string_lossy()).unwrap();
+        let converted = HandHistory::from_file("data/pluribus/converted_logs/pluribus_102.txt").unwrap();
+
+        assert_eq!(count, hands.len());
+        assert_eq!(converted.len(), hands.len());
+        for (ohh, hand) in hands.iter().zip(converted.iter()) {
+            assert_eq!(hand.id.to_string(), ohh.ohh.game_number);
+            assert_eq!(hand.hole_cards(), ohh.hole_cards().unwrap());
+            assert_eq!(hand.board(), ohh.board().unwrap());
+            assert_eq!(
+                hand.actions(PhaseHoldem::River),
+                ohh.actions(PhaseHoldem::River).unwrap().as_slice()
+            );
+        }
+        fs::remove_dir_all(raw).unwrap();
+        fs::remove_dir_all(out).unwrap();
+    }
+
+    #[test]
+    fn parse_all__invalid() {
+        let mut bad = ohh();
+        bad.ohh.pots[0].player_wins[0].player_id = 9;
+        let log = OpenHandHistory::to_log(&[ohh(), bad]);
+
+        assert_eq!(
+            PKError::InvalidOhh("hand 1: ohh.pots[0].player_wins[0].player_id: no player with id 9".to_string()),
+            OpenHandHistory::parse_all(&log).unwrap_err()
+        );
+    }
+}
@@ -0,0 +1,959 @@
+use crate::analysis::store::nubibus::pluribus::Pluribus;
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::play::board::Board;
+use crate::play::hole_cards::HoleCards;
+use crate::play::phases::PhaseHoldem;
+use crate::play::pots::Pots;
+use crate::util::Util;
+use crate::{PKError, Pile};
+use lazy_static::lazy_static;
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::str::FromStr;
+
+lazy_static! {
+    static ref HEADER: Regex =
+        Regex::new(r"^PokerStars Hand #(\d+): Hold'em No Limit \((\d+)/(\d+)\) - (.+)$").unwrap();
+    static ref TABLE: Regex =
+        Regex::new(r"^Table '(.+)' (\d+)-max (\(Play Money\) )?Seat #(\d+) is the button$").unwrap();
+    static ref SEAT: Regex = Regex::new(r"^Seat (\d+): (.+) \((\d+) in chips\)$").unwrap();
+    static ref POST: Regex = Regex::new(r"^(.+): posts (small|big) blind (\d+)$").unwrap();
+    static ref DEALT: Regex = Regex::new(r"^Dealt to (.+) \[(.+)\]$").unwrap();
+    static ref STREET: Regex = Regex::new(r"^\*\*\* (FLOP|TURN|RIVER) \*\*\* .*\[([^\]]+)\]$").unwrap();
+    static ref ACTION: Regex =
+        Regex::new(r"^(.+): (folds|checks|calls (\d+)|bets (\d+)|raises (\d+) to (\d+))( and is all-in)?$").unwrap();
+    static ref UNCALLED: Regex = Regex::new(r"^Uncalled bet \((\d+)\) returned to (.+)$").unwrap();
+    static ref SHOWS: Regex = Regex::new(r"^(.+): shows \[(.+)\]$").unwrap();
+    static ref COLLECTED: Regex = Regex::new(r"^(.+) collected ([\d.]+) from pot$").unwrap();
+    static ref TOTAL: Regex = Regex::new(r"^Total pot (\d+) \| Rake (\d+)$").unwrap();
+    static ref BOARD: Regex = Regex::new(r"^Board \[(.+)\]$").unwrap();
+    static ref SHOWED: Regex = Regex::new(r"^Seat (\d+): (.+) showed \[(.+)\] and (won \(([\d.]+)\)|lost)$").unwrap();
+}
+
+/// A player sitting at the table when the hand started.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistorySeat {
+    pub seat: usize,
+    pub name: String,
+    pub stack: usize,
+}
+
+/// What a player did. Call, bet, and raise amounts are exactly as they show up in the history:
+/// a call is the number of chips it takes to call, and a raise is how much the bet went up `by`,
+/// and what it went up `to`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ActionKind {
+    SmallBlind(usize),
+    BigBlind(usize),
+    Fold,
+    Check,
+    Call(usize),
+    Bet(usize),
+    Raise { by: usize, to: usize },
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PlayerAction {
+    pub player: String,
+    pub kind: ActionKind,
+    pub all_in: bool,
+}
+
+/// A single street of the hand: the cards dealt to the board at the start of it, and every
+/// action taken during it. The blinds are the first actions of the preflop street.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Street {
+    pub phase: PhaseHoldem,
+    pub cards: Cards,
+    pub actions: Vec<PlayerAction>,
+}
+
+/// A player whose cards were shown in the summary, and what they won.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Showed {
+    pub seat: usize,
+    pub name: String,
+    pub cards: Cards,
+    pub won: Option<f64>,
+}
+
+/// # `PokerStars` Hand Histories
+///
+/// The Pluribus sessions in `data/pluribus/converted_logs` are in the `PokerStars` text format,
+/// which is about as close as the poker world has to a lingua franca. Where the ACPC `STATE:`
+/// lines cram a whole hand onto a single line, a `PokerStars` history spells everything out, one
+/// line at a time, which makes it a lot easier to read, and a lot more work to parse.
+///
+/// Cards are kept in the order they appear in the history. `hole_cards()` sorts them the way the
+/// rest of the library expects.
+///
+/// Amounts collected from the pot are `f64`s, since an odd chip split between two players shows
+/// up in the history as half a chip each.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct HandHistory {
+    pub id: usize,
+    pub small_blind: usize,
+    pub big_blind: usize,
+    pub date: String,
+    pub table: String,
+    pub max_seats: usize,
+    pub play_money: bool,
+    /// The seat number with the dealer button.
+    pub button: usize,
+    pub seats: Vec<HistorySeat>,
+    pub dealt: Vec<(String, Cards)>,
+    pub streets: Vec<Street>,
+    pub uncalled: Option<(String, usize)>,
+    pub showdown: bool,
+    pub shows: Vec<(String, Cards)>,
+    pub collected: Vec<(String, f64)>,
+    pub total_pot: usize,
+    pub rake: usize,
+    pub summary_board: Cards,
+    pub showed: Vec<Showed>,
+}
+
+impl HandHistory {
+    /// Parses every hand in a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the file can't be read, or `PKError::InvalidHandHistory`
+    /// with the line number in the file of the first line that can't be parsed.
+    pub fn from_file(path: &str) -> Result<Vec<HandHistory>, PKError> {
+        let s = fs::read_to_string(path).map_err(|_| PKError::Fubar)?;
+        HandHistory::parse_all(&s)
+    }
+
+    /// Parses a string holding any number of hands, separated by blank lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidHandHistory` with the line number of the first line that can't
+    /// be parsed, counting from one.
+    pub fn parse_all(s: &str) -> Result<Vec<HandHistory>, PKError> {
+        let mut hands = Vec::new();
+        let mut lines: Vec<(usize, &str)> = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim_end();
+            if line.is_empty() {
+                if !lines.is_empty() {
+                    hands.push(HandHistory::parse(&lines)?);
+                    lines.clear();
+                }
+            } else {
+                lines.push((i + 1, line));
+            }
+        }
+        if !lines.is_empty() {
+            hands.push(HandHistory::parse(&lines)?);
+        }
+        Ok(hands)
+    }
+
+    /// Parses a single hand from its numbered lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidHandHistory` for the first line that can't be parsed.
+    #[allow(clippy::too_many_lines)]
+    pub fn parse(lines: &[(usize, &str)]) -> Result<HandHistory, PKError> {
+        let mut lines = lines.iter();
+        let mut hand = HandHistory::default();
+
+        let (n, line) = lines
+            .next()
+            .ok_or(PKError::InvalidHandHistory(0, "empty hand".to_string()))?;
+        let caps = HEADER
+            .captures(line)
+            .ok_or_else(|| HandHistory::error(*n, "expected a PokerStars Hand header", line))?;
+        hand.id = HandHistory::number(*n, &caps[1])?;
+        hand.small_blind = HandHistory::number(*n, &caps[2])?;
+        hand.big_blind = HandHistory::number(*n, &caps[3])?;
+        hand.date = caps[4].to_string();
+
+        let (n, line) = lines
+            .next()
+            .ok_or(PKError::InvalidHandHistory(*n + 1, "missing Table line".to_string()))?;
+        let caps = TABLE
+            .captures(line)
+            .ok_or_else(|| HandHistory::error(*n, "expected a Table line", line))?;
+        hand.table = caps[1].to_string();
+        hand.max_seats = HandHistory::number(*n, &caps[2])?;
+        hand.play_money = caps.get(3).is_some();
+        hand.button = HandHistory::number(*n, &caps[4])?;
+
+        let mut summary = false;
+        for (n, line) in lines {
+            let n = *n;
+            if *line == "*** HOLE CARDS ***" {
+                if hand.streets.is_empty() {
+                    hand.streets.push(Street {
+                        phase: PhaseHoldem::Preflop,
+                        ..Street::default()
+                    });
+                }
+            } else if *line == "*** SHOWDOWN ***" {
+                hand.showdown = true;
+            } else if *line == "*** SUMMARY ***" {
+                summary = true;
+            } else if let Some(caps) = SHOWED.captures(line) {
+                hand.showed.push(Showed {
+                    seat: HandHistory::number(n, &caps[1])?,
+                    name: caps[2].to_string(),
+                    cards: HandHistory::hole(n, &caps[3])?,
+                    won: match caps.get(5) {
+                        Some(won) => Some(HandHistory::chips(n, won.as_str())?),
+                        None => None,
+                    },
+                });
+            } else if let Some(caps) = SEAT.captures(line) {
+                hand.seats.push(HistorySeat {
+                    seat: HandHistory::number(n, &caps[1])?,
+                    name: caps[2].to_string(),
+                    stack: HandHistory::number(n, &caps[3])?,
+                });
+            } else if let Some(caps) = POST.captures(line) {
+                let amount = HandHistory::number(n, &caps[3])?;
+                let kind = if &caps[2] == "small" {
+                    ActionKind::SmallBlind(amount)
+                } else {
+                    ActionKind::BigBlind(amount)
+                };
+                // The blinds are posted before the hole cards are dealt.
+                if hand.streets.is_empty() {
+                    hand.streets.push(Street {
+                        phase: PhaseHoldem::Preflop,
+                        ..Street::default()
+                    });
+                }
+                hand.push_action(n, line, &caps[1], kind, false)?;
+            } else if let Some(caps) = DEALT.captures(line) {
+                let name = hand.player(n, line, &caps[1])?;
+                hand.dealt.push((name, HandHistory::hole(n, &caps[2])?));
+            } else if let Some(caps) = STREET.captures(line) {
+                let phase = match &caps[1] {
+                    "FLOP" => PhaseHoldem::Flop,
+                    "TURN" => PhaseHoldem::Turn,
+                    _ => PhaseHoldem::River,
+                };
+                let cards =
+                    Cards::from_str(&caps[2]).map_err(|_| HandHistory::error(n, "invalid board cards", line))?;
+                hand.streets.push(Street {
+                    phase,
+                    cards,
+                    actions: Vec::new(),
+                });
+            } else if let Some(caps) = ACTION.captures(line) {
+                let kind = HandHistory::action_kind(n, &caps)?;
+                hand.push_action(n, line, &caps[1], kind, caps.get(7).is_some())?;
+            } else if let Some(caps) = UNCALLED.captures(line) {
+                let name = hand.player(n, line, &caps[2])?;
+                hand.uncalled = Some((name, HandHistory::number(n, &caps[1])?));
+            } else if let Some(caps) = SHOWS.captures(line) {
+                let name = hand.player(n, line, &caps[1])?;
+                hand.shows.push((name, HandHistory::hole(n, &caps[2])?));
+            } else if let Some(caps) = COLLECTED.captures(line) {
+                let name = hand.player(n, line, &caps[1])?;
+                hand.collected.push((name, HandHistory::chips(n, &caps[2])?));
+            } else if let Some(caps) = TOTAL.captures(line).filter(|_| summary) {
+                hand.total_pot = HandHistory::number(n, &caps[1])?;
+                hand.rake = HandHistory::number(n, &caps[2])?;
+            } else if let Some(caps) = BOARD.captures(line).filter(|_| summary) {
+                hand.summary_board =
+                    Cards::from_str(&caps[1]).map_err(|_| HandHistory::error(n, "invalid board cards", line))?;
+            } else {
+                return Err(HandHistory::error(n, "unrecognized line", line));
+            }
+        }
+
+        Ok(hand)
+    }
+
+    /// Every action for a street. Empty if the hand never got there.
+    #[must_use]
+    pub fn actions(&self, phase: PhaseHoldem) -> &[PlayerAction] {
+        self.street(phase).map_or(&[], |street| street.actions.as_slice())
+    }
+
+    /// The cards on the board at the end of the hand.
+    #[must_use]
+    pub fn board(&self) -> Board {
+        Board::try_from(self.board_cards()).unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn board_cards(&self) -> Cards {
+        let mut cards = Cards::default();
+        for street in &self.streets {
+            cards.insert_all(&street.cards);
+        }
+        cards
+    }
+
+    /// The hole cards dealt to each player, in seat order.
+    #[must_use]
+    pub fn hole_cards(&self) -> HoleCards {
+        HoleCards::from(
+            self.dealt
+                .iter()
+                .map(|(_, cards)| Two::try_from(cards.clone()).unwrap_or_default())
+                .collect::<Vec<Two>>(),
+        )
+    }
+
+    #[must_use]
+    pub fn players(&self) -> Vec<String> {
+        self.seats.iter().map(|seat| seat.name.clone()).collect()
+    }
+
+    #[must_use]
+    pub fn street(&self, phase: PhaseHoldem) -> Option<&Street> {
+        self.streets.iter().find(|street| street.phase == phase)
+    }
+
+    /// Builds the history for a hand from a row in the Pluribus ACPC logs, laid out the same way
+    /// as the hands in `data/pluribus/converted_logs`. The logs don't say when a hand was played,
+    /// so the date is passed in. A `Nubibus` hand can be exported by passing in its `pluribus`.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::InvalidPluribusIndex` if the cards or actions in the row can't be made sense of.
+    /// * `PKError::NotEnoughCards` if the hand needs a showdown and the board isn't complete.
+    #[allow(clippy::too_many_lines)]
+    pub fn from_pluribus(pluribus: &Pluribus, session: usize, date: &str) -> Result<HandHistory, PKError> {
+        let cards = pluribus.raw.split(':').nth(3).ok_or(PKError::InvalidPluribusIndex)?;
+        let mut groups = cards.split('/');
+        let dealt = groups
+            .next()
+            .unwrap_or_default()
+            .split('|')
+            .map(|s| HandHistory::hole(0, &Util::str_len_splitter(s, 2)))
+            .collect::<Result<Vec<Cards>, PKError>>()
+            .map_err(|_| PKError::InvalidPluribusIndex)?;
+        let board = groups
+            .map(|s| Cards::from_str(&Util::str_len_splitter(s, 2)))
+            .collect::<Result<Vec<Cards>, PKError>>()
+            .map_err(|_| PKError::InvalidPluribusIndex)?;
+        let count = pluribus.players.len();
+        if count != dealt.len() || count < 2 {
+            return Err(PKError::InvalidPluribusIndex);
+        }
+
+        let mut hand = HandHistory {
+            id: session * 1_000 + pluribus.index,
+            small_blind: Pluribus::SMALL_BLIND,
+            big_blind: Pluribus::BIG_BLIND,
+            date: date.to_string(),
+            table: format!("Pluribus Session {session}"),
+            max_seats: count,
+            play_money: true,
+            button: count,
+            seats: pluribus
+                .players
+                .iter()
+                .enumerate()
+                .map(|(i, name)| HistorySeat {
+                    seat: i + 1,
+                    name: name.clone(),
+                    stack: Pluribus::STARTING_STACK,
+                })
+                .collect(),
+            dealt: pluribus.players.iter().cloned().zip(dealt.iter().cloned()).collect(),
+            ..HandHistory::default()
+        };
+
+        let mut in_pot = vec![0; count];
+        let mut folded = vec![false; count];
+        in_pot[0] = Pluribus::SMALL_BLIND;
+        in_pot[1] = Pluribus::BIG_BLIND;
+        hand.streets.push(Street {
+            phase: PhaseHoldem::Preflop,
+            cards: Cards::default(),
+            actions: vec![
+                hand.action(0, ActionKind::SmallBlind(Pluribus::SMALL_BLIND), false),
+                hand.action(1, ActionKind::BigBlind(Pluribus::BIG_BLIND), false),
+            ],
+        });
+        for (i, cards) in board.iter().enumerate() {
+            hand.streets.push(Street {
+                phase: [PhaseHoldem::Flop, PhaseHoldem::Turn, PhaseHoldem::River]
+                    .get(i)
+                    .copied()
+                    .ok_or(PKError::InvalidPluribusIndex)?,
+                cards: cards.clone(),
+                actions: Vec::new(),
+            });
+        }
+        for street in 0..hand.streets.len() {
+            let round = pluribus.rounds.get(street).map_or("", String::as_str);
+            hand.betting_round(street, round, &mut in_pot, &mut folded)?;
+        }
+
+        // Whatever the last bettor put in that nobody matched goes back to them.
+        let top = (0..count).rev().max_by_key(|i| in_pot[*i]).unwrap_or_default();
+        let called = (0..count)
+            .filter(|i| *i != top)
+            .map(|i| in_pot[i])
+            .max()
+            .unwrap_or_default();
+        if in_pot[top] > called {
+            hand.uncalled = Some((pluribus.players[top].clone(), in_pot[top] - called));
+            in_pot[top] = called;
+        }
+
+        let hands = dealt
+            .iter()
+            .map(|cards| Two::try_from(cards.clone()))
+            .collect::<Result<Vec<Two>, PKError>>()?;
+        let mut collected = vec![0.0_f64; count];
+        for pot in Pots::from_contributions(&in_pot, &folded).iter() {
+            let winners = if pot.eligible.len() > 1 {
+                pot.winners(&hands, &Board::try_from(hand.board_cards())?)
+            } else {
+                pot.eligible.clone()
+            };
+            for winner in &winners {
+                #[allow(clippy::cast_precision_loss)]
+                let share = pot.amount as f64 / winners.len() as f64;
+                collected[*winner] += share;
+            }
+        }
+        hand.collected = (0..count)
+            .filter(|i| collected[*i] > 0.0)
+            .map(|i| (pluribus.players[i].clone(), collected[i]))
+            .collect();
+        hand.total_pot = in_pot.iter().sum();
+        hand.summary_board = hand.board_cards();
+
+        // The converted logs only have a showdown if the hand didn't end with a fold, and only
+        // count a player as having won it if they came out ahead.
+        let active = (0..count).filter(|i| !folded[*i]).collect::<Vec<usize>>();
+        let last = hand.streets.iter().flat_map(|street| street.actions.iter()).last();
+        if active.len() > 1 && last.is_some_and(|action| action.kind != ActionKind::Fold) {
+            hand.showdown = true;
+            for i in active {
+                #[allow(clippy::cast_precision_loss)]
+                let won = collected[i] > in_pot[i] as f64;
+                if won {
+                    hand.shows.push((pluribus.players[i].clone(), dealt[i].clone()));
+                }
+                hand.showed.push(Showed {
+                    seat: i + 1,
+                    name: pluribus.players[i].clone(),
+                    cards: dealt[i].clone(),
+                    won: if won { Some(collected[i]) } else { None },
+                });
+            }
+        }
+
+        Ok(hand)
+    }
+
+    /// The hand written out in `PokerStars` format, one line at a time.
+    #[must_use]
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "PokerStars Hand #{}: Hold'em No Limit ({}/{}) - {}",
+                self.id, self.small_blind, self.big_blind, self.date
+            ),
+            format!(
+                "Table '{}' {}-max {}Seat #{} is the button",
+                self.table,
+                self.max_seats,
+                if self.play_money { "(Play Money) " } else { "" },
+                self.button
+            ),
+        ];
+        for seat in &self.seats {
+            lines.push(format!("Seat {}: {} ({} in chips)", seat.seat, seat.name, seat.stack));
+        }
+
+        let mut board: Vec<String> = Vec::new();
+        for street in &self.streets {
+            if street.phase == PhaseHoldem::Preflop {
+                let blinds = street
+                    .actions
+                    .iter()
+                    .take_while(|action| matches!(action.kind, ActionKind::SmallBlind(_) | ActionKind::BigBlind(_)))
+                    .count();
+                lines.extend(street.actions[..blinds].iter().map(ToString::to_string));
+                lines.push("*** HOLE CARDS ***".to_string());
+                for (name, two) in &self.dealt {
+                    lines.push(format!(
+                        "Dealt to {name} [{}]",
+                        HandHistory::letters(&Cards::from(two.to_vec()))
+                    ));
+                }
+                lines.extend(street.actions[blinds..].iter().map(ToString::to_string));
+            } else {
+                board.push(format!("[{}]", HandHistory::letters(&street.cards)));
+                lines.push(format!(
+                    "*** {} *** {}",
+                    street.phase.to_string().to_uppercase(),
+                    board.join(" ")
+                ));
+                lines.extend(street.actions.iter().map(ToString::to_string));
+            }
+        }
+
+        if let Some((name, amount)) = &self.uncalled {
+            lines.push(format!("Uncalled bet ({amount}) returned to {name}"));
+        }
+        if self.showdown {
+            lines.push("*** SHOWDOWN ***".to_string());
+        }
+        for (name, two) in &self.shows {
+            lines.push(format!(
+                "{name}: shows [{}]",
+                HandHistory::letters(&Cards::from(two.to_vec()))
+            ));
+        }
+        for (name, amount) in &self.collected {
+            lines.push(format!("{name} collected {amount:?} from pot"));
+        }
+        lines.push("*** SUMMARY ***".to_string());
+        lines.push(format!("Total pot {} | Rake {}", self.total_pot, self.rake));
+        if !self.summary_board.is_empty() {
+            lines.push(format!("Board [{}]", HandHistory::letters(&self.summary_board)));
+        }
+        for showed in &self.showed {
+            let result = match showed.won {
+                Some(won) => format!("won ({won:?})"),
+                None => "lost".to_string(),
+            };
+            lines.push(format!(
+                "Seat {}: {} showed [{}] and {result}",
+                showed.seat,
+                showed.name,
+                HandHistory::letters(&showed.cards)
+            ));
+        }
+        lines
+    }
+
+    /// Writes out hands the way the `PokerStars` client does, with Windows line endings and two
+    /// blank lines between each hand. Parsing a file from `data/pluribus/converted_logs` and
+    /// writing it back out gets you the exact same bytes.
+    #[must_use]
+    pub fn to_log(hands: &[HandHistory]) -> String {
+        hands
+            .iter()
+            .map(|hand| hand.lines().join("\r\n") + "\r\n")
+            .collect::<Vec<String>>()
+            .join("\r\n\r\n")
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the file can't be written.
+    pub fn to_file(path: &str, hands: &[HandHistory]) -> Result<(), PKError> {
+        fs::write(path, HandHistory::to_log(hands)).map_err(|_| PKError::Fubar)
+    }
+
+    fn action(&self, seat: usize, kind: ActionKind, all_in: bool) -> PlayerAction {
+        PlayerAction {
+            player: self.seats[seat].name.clone(),
+            kind,
+            all_in,
+        }
+    }
+
+    /// Plays out a single ACPC betting round, like `r225fcc`. Raises in ACPC are to the total a
+    /// player has put in for the whole hand, so they're turned into what they raise to for the
+    /// street.
+    fn betting_round(
+        &mut self,
+        street: usize,
+        round: &str,
+        in_pot: &mut [usize],
+        folded: &mut [bool],
+    ) -> Result<(), PKError> {
+        let count = in_pot.len();
+        let stack = Pluribus::STARTING_STACK;
+        let preflop = street == 0;
+        let start = if preflop { vec![0; count] } else { in_pot.to_vec() };
+        let mut bet = if preflop { Pluribus::BIG_BLIND } else { 0 };
+        let mut seat = if preflop { 2 % count } else { 0 };
+
+        let mut chars = round.chars().peekable();
+        while let Some(c) = chars.next() {
+            seat = (0..count)
+                .map(|i| (seat + i) % count)
+                .find(|i| !folded[*i] && in_pot[*i] < stack)
+                .ok_or(PKError::InvalidPluribusIndex)?;
+            let kind = match c {
+                'f' => {
+                    folded[seat] = true;
+                    ActionKind::Fold
+                }
+                'c' => {
+                    let to_call = bet.saturating_sub(in_pot[seat] - start[seat]);
+                    if to_call == 0 {
+                        ActionKind::Check
+                    } else {
+                        let amount = to_call.min(stack - in_pot[seat]);
+                        in_pot[seat] += amount;
+                        ActionKind::Call(amount)
+                    }
+                }
+                'r' => {
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+                    let total = digits.parse::<usize>().map_err(|_| PKError::InvalidPluribusIndex)?;
+                    let to = total.saturating_sub(start[seat]);
+                    if to <= bet {
+                        return Err(PKError::InvalidPluribusIndex);
+                    }
+                    in_pot[seat] = total;
+                    let kind = if bet == 0 {
+                        ActionKind::Bet(to)
+                    } else {
+                        ActionKind::Raise { by: to - bet, to }
+                    };
+                    bet = to;
+                    kind
+                }
+                _ => return Err(PKError::InvalidPluribusIndex),
+            };
+            let action = self.action(seat, kind, c != 'f' && in_pot[seat] == stack);
+            self.streets[street].actions.push(action);
+            seat = (seat + 1) % count;
+        }
+        Ok(())
+    }
+
+    /// Cards the way `PokerStars` writes them, like `Tc Qc`.
+    fn letters(cards: &Cards) -> String {
+        cards.to_letters().join(" ")
+    }
+
+    fn action_kind(n: usize, caps: &Captures) -> Result<ActionKind, PKError> {
+        Ok(match &caps[2] {
+            "folds" => ActionKind::Fold,
+            "checks" => ActionKind::Check,
+            _ => {
+                if let Some(amount) = caps.get(3) {
+                    ActionKind::Call(HandHistory::number(n, amount.as_str())?)
+                } else if let Some(amount) = caps.get(4) {
+                    ActionKind::Bet(HandHistory::number(n, amount.as_str())?)
+                } else {
+                    ActionKind::Raise {
+                        by: HandHistory::number(n, &caps[5])?,
+                        to: HandHistory::number(n, &caps[6])?,
+                    }
+                }
+            }
+        })
+    }
+
+    fn push_action(&mut self, n: usize, line: &str, name: &str, kind: ActionKind, all_in: bool) -> Result<(), PKError> {
+        let player = self.player(n, line, name)?;
+        match self.streets.last_mut() {
+            Some(street) => {
+                street.actions.push(PlayerAction { player, kind, all_in });
+                Ok(())
+            }
+            None => Err(HandHistory::error(n, "action before the hole cards", line)),
+        }
+    }
+
+    /// Makes sure that the player is sitting at the table.
+    fn player(&self, n: usize, line: &str, name: &str) -> Result<String, PKError> {
+        if self.seats.iter().any(|seat| seat.name == name) {
+            Ok(name.to_string())
+        } else {
+            Err(HandHistory::error(n, &format!("unknown player {name}"), line))
+        }
+    }
+
+    fn error(n: usize, reason: &str, line: &str) -> PKError {
+        PKError::InvalidHandHistory(n, format!("{reason}: {line}"))
+    }
+
+    fn number(n: usize, s: &str) -> Result<usize, PKError> {
+        s.parse::<usize>()
+            .map_err(|_| PKError::InvalidHandHistory(n, format!("invalid number {s}")))
+    }
+
+    fn chips(n: usize, s: &str) -> Result<f64, PKError> {
+        s.parse::<f64>()
+            .map_err(|_| PKError::InvalidHandHistory(n, format!("invalid amount {s}")))
+    }
+
+    /// Hole cards, in the order they were listed. A `Two` always puts the higher card first, so
+    /// they're kept as `Cards`.
+    fn hole(n: usize, s: &str) -> Result<Cards, PKError> {
+        let cards = s
+            .split_whitespace()
+            .map(Card::from_str)
+            .collect::<Result<Vec<Card>, PKError>>()
+            .map_err(|_| PKError::InvalidHandHistory(n, format!("invalid cards {s}")))?;
+        match cards.as_slice() {
+            [first, second] if first != second => Ok(Cards::from(vec![*first, *second])),
+            _ => Err(PKError::InvalidHandHistory(n, format!("expected two cards {s}"))),
+        }
+    }
+}
+
+impl Display for PlayerAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let player = &self.player;
+        match self.kind {
+            ActionKind::SmallBlind(amount) => write!(f, "{player}: posts small blind {amount}")?,
+            ActionKind::BigBlind(amount) => write!(f, "{player}: posts big blind {amount}")?,
+            ActionKind::Fold => write!(f, "{player}: folds")?,
+            ActionKind::Check => write!(f, "{player}: checks")?,
+            ActionKind::Call(amount) => write!(f, "{player}: calls {amount}")?,
+            ActionKind::Bet(amount) => write!(f, "{player}: bets {amount}")?,
+            ActionKind::Raise { by, to } => write!(f, "{player}: raises {by} to {to}")?,
+        }
+        if self.all_in {
+            write!(f, " and is all-in")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for HandHistory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.lines().join("\n"))
+    }
+}
+
+impl FromStr for HandHistory {
+    type Err = PKError;
+
+    /// Parses a single hand. If there's more than one, it's an error.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut hands = HandHistory::parse_all(s)?;
+        match hands.len() {
+            1 => Ok(hands.remove(0)),
+            0 => Err(PKError::InvalidHandHistory(0, "no hand found".to_string())),
+            _ => Err(PKError::TooManyHands),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+pub(crate) mod analysis__store__pokerstars_tests {
+    use super::*;
+    use crate::analysis::store::nubibus::Nubibus;
+
+    pub const HAND: &str = "PokerStars Hand #100062: Hold'em No Limit (50/100) - 2019/07/12 03:47:42 ET
+Table 'Pluribus Session 100' 6-max (Play Money) Seat #6 is the button
+Seat 1: MrWhite (10000 in chips)
+Seat 2: MrPink (10000 in chips)
+Seat 3: MrBrown (10000 in chips)
+Seat 4: Pluribus (10000 in chips)
+Seat 5: MrBlue (10000 in chips)
+Seat 6: MrBlonde (10000 in chips)
+MrWhite: posts small blind 50
+MrPink: posts big blind 100
+*** HOLE CARDS ***
+Dealt to MrWhite [Ts Th]
+Dealt to MrPink [3c 2d]
+Dealt to MrBrown [2c Ad]
+Dealt to Pluribus [7s Qs]
+Dealt to MrBlue [As Ah]
+Dealt to MrBlonde [5d 5c]
+MrBrown: folds
+Pluribus: folds
+MrBlue: raises 100 to 200
+MrBlonde: calls 200
+MrWhite: raises 1050 to 1250
+MrPink: folds
+MrBlue: calls 1050
+MrBlonde: calls 1050
+*** FLOP *** [Qh Kc 5h]
+MrWhite: checks
+MrBlue: bets 1925
+MrBlonde: calls 1925
+MrWhite: folds
+*** TURN *** [Qh Kc 5h] [Ks]
+MrBlue: checks
+MrBlonde: checks
+*** RIVER *** [Qh Kc 5h] [Ks] [6s]
+MrBlue: checks
+MrBlonde: bets 6825 and is all-in
+MrBlue: calls 6825 and is all-in
+*** SHOWDOWN ***
+MrBlonde: shows [5d 5c]
+MrBlonde collected 21350.0 from pot
+*** SUMMARY ***
+Total pot 21350 | Rake 0
+Board [Qh Kc 5h Ks 6s]
+Seat 5: MrBlue showed [As Ah] and lost
+Seat 6: MrBlonde showed [5d 5c] and won (21350.0)";
+
+    #[test]
+    fn from_str() {
+        let hand = HandHistory::from_str(HAND).unwrap();
+
+        assert_eq!(100062, hand.id);
+        assert_eq!(100, hand.big_blind);
+        assert_eq!("Pluribus Session 100", hand.table);
+        assert_eq!(6, hand.button);
+        assert!(hand.play_money);
+        assert_eq!(6, hand.seats.len());
+        assert_eq!(4, hand.streets.len());
+        assert!(hand.showdown);
+        assert_eq!(vec![("MrBlonde".to_string(), 21350.0)], hand.collected);
+        assert_eq!(21350, hand.total_pot);
+        assert_eq!(2, hand.showed.len());
+        assert_eq!(None, hand.showed[0].won);
+    }
+
+    #[test]
+    fn actions() {
+        let hand = HandHistory::from_str(HAND).unwrap();
+        let preflop = hand.actions(PhaseHoldem::Preflop);
+        let river = hand.actions(PhaseHoldem::River);
+
+        assert_eq!(10, preflop.len());
+        assert_eq!(ActionKind::SmallBlind(50), preflop[0].kind);
+        assert_eq!(ActionKind::Raise { by: 1050, to: 1250 }, preflop[6].kind);
+        assert_eq!(4, hand.actions(PhaseHoldem::Flop).len());
+        assert_eq!(
+            PlayerAction {
+                player: "MrBlonde".to_string(),
+                kind: ActionKind::Bet(6825),
+                all_in: true
+            },
+            river[1]
+        );
+    }
+
+    #[test]
+    fn hole_cards() {
+        let hand = HandHistory::from_str(HAND).unwrap();
+
+        assert_eq!(
+            "[T♠ T♥, 3♣ 2♦, A♦ 2♣, Q♠ 7♠, A♠ A♥, 5♦ 5♣]",
+            hand.hole_cards().to_string()
+        );
+        assert_eq!("FLOP: Q♥ K♣ 5♥, TURN: K♠, RIVER: 6♠", hand.board().to_string());
+        assert_eq!(hand.summary_board.to_string(), hand.board_cards().to_string());
+    }
+
+    #[test]
+    fn parse_all() {
+        let hands = HandHistory::parse_all(&format!("{HAND}\n\n\n{HAND}\n")).unwrap();
+
+        assert_eq!(2, hands.len());
+        assert_eq!(hands[0], hands[1]);
+    }
+
+    #[test]
+    fn parse_all__line_number() {
+        let broken = HAND.replace("MrBlue: checks", "MrBlue: dances");
+
+        let err = HandHistory::parse_all(&format!("{HAND}\n\n{broken}")).unwrap_err();
+
+        assert_eq!(
+            PKError::InvalidHandHistory(78, "unrecognized line: MrBlue: dances".to_string()),
+            err
+        );
+        assert_eq!(
+            "Invalid hand history at line 78: unrecognized line: MrBlue: dances",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn from_str__unknown_player() {
+        let err = HandHistory::from_str(&HAND.replace("Dealt to MrPink", "Dealt to MrOrange")).unwrap_err();
+
+        assert_eq!(
+            PKError::InvalidHandHistory(13, "unknown player MrOrange: Dealt to MrOrange [3c 2d]".to_string()),
+            err
+        );
+    }
+
+    #[test]
+    fn from_str__invalid_header() {
+        assert_eq!(
+            PKError::InvalidHandHistory(1, "expected a PokerStars Hand header: Full Tilt".to_string()),
+            HandHistory::from_str("Full Tilt").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn from_file() {
+        let hands = HandHistory::from_file("data/pluribus/converted_logs/pluribus_102.txt").unwrap();
+        let split = &hands[0];
+
+        assert_eq!(102000, split.id);
+        assert_eq!(2, split.collected.len());
+        assert_eq!("674.5", split.collected[0].1.to_string());
+        assert!(!split.showdown);
+        assert!(hands.iter().all(|hand| hand.seats.len() == 6));
+    }
+
+    #[test]
+    fn to_log__round_trip() {
+        let raw = fs::read_to_string("data/pluribus/converted_logs/pluribus_102.txt").unwrap();
+
+        let hands = HandHistory::parse_all(&raw).unwrap();
+
+        assert_eq!(raw, HandHistory::to_log(&hands));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(HAND, HandHistory::from_str(HAND).unwrap().to_string());
+    }
+
+    /// Every hand in the session, rebuilt from the ACPC logs, matches the converted log.
+    #[test]
+    fn from_pluribus() {
+        let hands = HandHistory::from_file("data/pluribus/converted_logs/pluribus_102.txt").unwrap();
+        let rows = fs::read_to_string("data/pluribus/raw/sample_game_102.log")
+            .unwrap()
+            .lines()
+            .filter_map(|line| Pluribus::from_str(line).ok())
+            .collect::<Vec<Pluribus>>();
+
+        assert_eq!(hands.len(), rows.len());
+        for (hand, row) in hands.iter().zip(rows.iter()) {
+            assert_eq!(*hand, HandHistory::from_pluribus(row, 102, &hand.date).unwrap());
+        }
+    }
+
+    /// A raise has to be to more than what's already been bet.
+    #[test]
+    fn from_pluribus__raise_too_small() {
+        let pluribus = Pluribus::from_str("STATE:27:r200r150ffffc:Qc4h|Tc9c|8sAs|Qh7c|JcQd|5h5d:-50|-100|0|-200|0|350:Eddie|Bill|Pluribus|MrWhite|Gogo|Budd").unwrap();
+
+        assert_eq!(
+            Err(PKError::InvalidPluribusIndex),
+            HandHistory::from_pluribus(&pluribus, 30, "2019/07/11 08:20:27 ET")
+        );
+    }
+
+    #[test]
+    fn from_pluribus__nubibus() {
+        let pluribus = Pluribus::from_str("STATE:27:r200ffcfc/cr850cf/cr1825r3775c/r10000c:Qc4h|Tc9c|8sAs|Qh7c|JcQd|5h5d/3h7s5c/Qs/6c:-50|-200|-10000|0|0|10250:Eddie|Bill|Pluribus|MrWhite|Gogo|Budd").unwrap();
+        let nubibus = Nubibus::from_pluribus(&pluribus);
+
+        let hand = HandHistory::from_pluribus(&nubibus.pluribus, 30, "2019/07/11 08:20:27 ET").unwrap();
+        let lines = hand.lines();
+
+        assert_eq!(
+            "PokerStars Hand #30027: Hold'em No Limit (50/100) - 2019/07/11 08:20:27 ET",
+            lines[0]
+        );
+        assert!(lines.contains(&"Pluribus: bets 6225 and is all-in".to_string()));
+        assert!(lines.contains(&"Budd: calls 6225 and is all-in".to_string()));
+        assert!(lines.contains(&"Budd collected 20250.0 from pot".to_string()));
+        assert_eq!("Seat 6: Budd showed [5h 5d] and won (20250.0)", lines[lines.len() - 1]);
+    }
+}
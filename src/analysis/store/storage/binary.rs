@@ -0,0 +1,173 @@
+use crate::analysis::store::storage::{BackendKind, KeyOrder, Record, Storage};
+use crate::PKError;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+
+/// A flat file: the record's name on its own line, and then every record's bytes back to back,
+/// in key order. No index, no nothing, so it's the smallest and the fastest to load, and adding
+/// to it means writing the whole thing out again.
+pub struct BinaryStorage {
+    path: String,
+}
+
+impl BinaryStorage {
+    /// Doesn't touch the file until there's something to read or write.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the path is a directory.
+    pub fn open(path: &str) -> Result<BinaryStorage, PKError> {
+        if Path::new(path).is_dir() {
+            return Err(PKError::StorageError(format!("{path} is a directory")));
+        }
+        Ok(BinaryStorage { path: path.to_string() })
+    }
+
+    fn header<T: Record>() -> Vec<u8> {
+        format!("{}\n", T::NAME).into_bytes()
+    }
+
+    fn io_error(&self, e: &std::io::Error) -> PKError {
+        PKError::StorageError(format!("{}: {e}", self.path))
+    }
+
+    /// Like `read_exact()`, but says how far it got when the file runs out, so that the end of
+    /// the file can be told apart from a record that's been cut off.
+    fn fill(reader: &mut impl Read, buf: &mut [u8]) -> std::io::Result<usize> {
+        let mut filled = 0;
+        while filled < buf.len() {
+            match reader.read(&mut buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(filled)
+    }
+
+    /// Writes everything to a file next to the real one, and then renames it over the top, so
+    /// that if anything goes wrong halfway through the old file is still there as it was.
+    fn write<'a, T: Record + 'a>(&self, records: impl Iterator<Item = &'a T>) -> Result<(), PKError> {
+        let tmp = format!("{}.tmp", self.path);
+        let file = File::create(&tmp).map_err(|e| self.io_error(&e))?;
+        let mut writer = BufWriter::new(file);
+        writer
+            .write_all(&BinaryStorage::header::<T>())
+            .map_err(|e| self.io_error(&e))?;
+        for record in records {
+            writer.write_all(&record.to_bytes()).map_err(|e| self.io_error(&e))?;
+        }
+        let file = writer.into_inner().map_err(|e| self.io_error(e.error()))?;
+        file.sync_all().map_err(|e| self.io_error(&e))?;
+        fs::rename(&tmp, &self.path).map_err(|e| self.io_error(&e))
+    }
+}
+
+impl<T: Record> Storage<T> for BinaryStorage {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Binary
+    }
+
+    fn insert_many(&mut self, records: &[T]) -> Result<usize, PKError> {
+        let mut stored = BTreeMap::new();
+        self.for_each(&mut |record: T| {
+            stored.insert(record.key(), record);
+            Ok(())
+        })?;
+        let before = stored.len();
+        for record in records {
+            stored.entry(record.key()).or_insert_with(|| record.clone());
+        }
+
+        self.write(stored.values())?;
+        Ok(stored.len() - before)
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(T) -> Result<(), PKError>) -> Result<(), PKError> {
+        if !Path::new(&self.path).exists() {
+            return Ok(());
+        }
+        let mut reader = BufReader::new(File::open(&self.path).map_err(|e| self.io_error(&e))?);
+        let header = BinaryStorage::header::<T>();
+        let mut buf = vec![0; header.len()];
+        if BinaryStorage::fill(&mut reader, &mut buf).map_err(|e| self.io_error(&e))? != header.len() || buf != header {
+            return Err(PKError::StorageError(format!("{} isn't a {} file", self.path, T::NAME)));
+        }
+
+        let mut order = KeyOrder::default();
+        let mut chunk = vec![0; T::SIZE];
+        loop {
+            match BinaryStorage::fill(&mut reader, &mut chunk).map_err(|e| self.io_error(&e))? {
+                0 => return Ok(()),
+                n if n < T::SIZE => return Err(PKError::InvalidBinaryFormat),
+                _ => {}
+            }
+            let record = T::from_bytes(&chunk)?;
+            order.check(&record, &self.path)?;
+            f(record)?;
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__storage__binary_tests {
+    use super::*;
+    use crate::analysis::store::db::headsup_preflop_result::HUPResult;
+    use crate::util::data::TestData;
+
+    fn path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("pkcore_binary_{name}_{}.bin", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn insert_many__replaces_the_file() {
+        let path = path("insert");
+        let mut storage = BinaryStorage::open(&path).unwrap();
+
+        let first = storage.insert_many(&TestData::known_hups()).unwrap();
+        let second = storage.insert_many(&[TestData::the_hand_as_hup_result()]).unwrap();
+        let tmp = Path::new(&format!("{path}.tmp")).exists();
+        let count = Storage::<HUPResult>::count(&storage).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(3, first);
+        assert_eq!(1, second);
+        assert_eq!(4, count);
+        assert!(!tmp);
+    }
+
+    #[test]
+    fn select_all__duplicates() {
+        let path = path("duplicates");
+        let hup = TestData::the_hand_as_hup_result();
+        let bytes = [BinaryStorage::header::<HUPResult>(), hup.to_bytes(), hup.to_bytes()].concat();
+        fs::write(&path, bytes).unwrap();
+
+        let all = Storage::<HUPResult>::select_all(&BinaryStorage::open(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(all, Err(PKError::StorageError(_))));
+    }
+
+    #[test]
+    fn select_all__cut_off() {
+        let path = path("cut_off");
+        let hup = TestData::the_hand_as_hup_result();
+        let bytes = [BinaryStorage::header::<HUPResult>(), hup.to_bytes()[..30].to_vec()].concat();
+        fs::write(&path, bytes).unwrap();
+
+        let all = Storage::<HUPResult>::select_all(&BinaryStorage::open(&path).unwrap());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(Err(PKError::InvalidBinaryFormat), all);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::analysis::store::storage::{BackendKind, Record, Storage};
+use crate::PKError;
+use std::collections::BTreeMap;
+
+/// Nothing but a sorted map. Handy for tests, and for loading everything up front.
+#[derive(Clone, Debug)]
+pub struct MemoryStorage<T> {
+    records: BTreeMap<Vec<u8>, T>,
+}
+
+impl<T> Default for MemoryStorage<T> {
+    fn default() -> Self {
+        MemoryStorage {
+            records: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T: Record> Storage<T> for MemoryStorage<T> {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Memory
+    }
+
+    fn insert_many(&mut self, records: &[T]) -> Result<usize, PKError> {
+        let mut inserted = 0;
+        for record in records {
+            if let std::collections::btree_map::Entry::Vacant(entry) = self.records.entry(record.key()) {
+                entry.insert(record.clone());
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(T) -> Result<(), PKError>) -> Result<(), PKError> {
+        for record in self.records.values() {
+            f(record.clone())?;
+        }
+        Ok(())
+    }
+
+    fn count(&self) -> Result<usize, PKError> {
+        Ok(self.records.len())
+    }
+}
@@ -0,0 +1,545 @@
+pub mod binary;
+pub mod memory;
+pub mod sled_tree;
+pub mod sqlite;
+
+use crate::analysis::store::bcm::binary_card_map::SevenFiveBCM;
+use crate::analysis::store::db::headsup_preflop_result::HUPResult;
+use crate::analysis::store::db::sqlite::Sqlable;
+use crate::analysis::store::storage::binary::BinaryStorage;
+use crate::analysis::store::storage::memory::MemoryStorage;
+use crate::analysis::store::storage::sled_tree::SledStorage;
+use crate::analysis::store::storage::sqlite::SqliteStorage;
+use crate::arrays::matchups::sorted_heads_up::SortedHeadsUp;
+use crate::bard::Bard;
+use crate::PKError;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// # Storage Backends
+///
+/// The precomputed results have been living in `SQLite` through `Sqlable`, in CSV files, and for a
+/// little while in a `sled` tree under `generated/sleigh`, with different code for each. This
+/// pulls them together behind one trait, so that where the data lives can be decided when the
+/// program runs, and moving it from one place to another is a single call.
+///
+/// Every backend stores a `Record` as a key and a fixed width block of bytes. The key sorts the
+/// same way everywhere, which is what lets the checksum come out the same no matter where the
+/// records came from.
+pub trait Record: Sized + Clone + Sqlable<Self, Self::Key> {
+    /// What `Sqlable` looks a record up by.
+    type Key;
+
+    /// The name of the sled tree and the tag at the top of a binary file.
+    const NAME: &'static str;
+
+    /// How many bytes `to_bytes()` returns.
+    const SIZE: usize;
+
+    /// Every row of the table in key order, so that `SqliteStorage` can stream them instead of
+    /// going through `Sqlable::select_all()` and a `Vec`.
+    const SELECT_ALL: &'static str;
+
+    fn key(&self) -> Vec<u8>;
+
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidBinaryFormat` if the bytes aren't `SIZE` long.
+    fn from_bytes(bytes: &[u8]) -> Result<Self, PKError>;
+
+    /// One row from `SELECT_ALL`.
+    ///
+    /// # Errors
+    ///
+    /// Passes on rusqlite's complaints about the row.
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self>;
+}
+
+pub trait Storage<T: Record> {
+    fn kind(&self) -> BackendKind;
+
+    /// Adds the records, skipping any whose key is already there, and returns how many were new.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the backend gives us trouble.
+    fn insert_many(&mut self, records: &[T]) -> Result<usize, PKError>;
+
+    /// Hands every record to `f`, one at a time and in key order, so that nothing has to hold
+    /// on to all hundred and thirty odd million `SevenFiveBCM` rows at once. Reading never
+    /// creates anything; a table or tree that isn't there yet has no records in it.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the backend gives us trouble, including a record that
+    /// can't be read or a key that's in there more than once, and passes on anything from `f`.
+    fn for_each(&self, f: &mut dyn FnMut(T) -> Result<(), PKError>) -> Result<(), PKError>;
+
+    /// Every record, in key order.
+    ///
+    /// # Errors
+    ///
+    /// Passes on anything from `for_each()`.
+    fn select_all(&self) -> Result<Vec<T>, PKError> {
+        let mut records = Vec::new();
+        self.for_each(&mut |record| {
+            records.push(record);
+            Ok(())
+        })?;
+        Ok(records)
+    }
+
+    /// How many records are actually stored, duplicates and all.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the backend gives us trouble.
+    fn count(&self) -> Result<usize, PKError> {
+        let mut count = 0;
+        self.for_each(&mut |_| {
+            count += 1;
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
+    /// A 64 bit FNV-1a hash of every record's bytes, in key order.
+    ///
+    /// # Errors
+    ///
+    /// Passes on anything from `for_each()`.
+    fn checksum(&self) -> Result<u64, PKError> {
+        let mut checksum = Checksum::default();
+        self.for_each(&mut |record| {
+            checksum.add(&record);
+            Ok(())
+        })?;
+        Ok(checksum.value())
+    }
+}
+
+/// A 64 bit FNV-1a hash that the records are fed into one at a time, in key order.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Checksum(u64);
+
+impl Checksum {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    pub fn add<T: Record>(&mut self, record: &T) {
+        self.0 = record.to_bytes().iter().fold(self.0, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(Checksum::PRIME)
+        });
+    }
+
+    #[must_use]
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Checksum {
+    fn default() -> Self {
+        Checksum(Checksum::OFFSET)
+    }
+}
+
+/// Sorts the records by key and hashes them, for records that aren't in key order already.
+#[must_use]
+pub fn checksum<T: Record>(records: &[T]) -> u64 {
+    let mut sorted: Vec<&T> = records.iter().collect();
+    sorted.sort_by_key(|record| record.key());
+    let mut checksum = Checksum::default();
+    for record in sorted {
+        checksum.add(record);
+    }
+    checksum.value()
+}
+
+/// Keeps the backends that can't promise it themselves honest about handing records back in key
+/// order, and only once each.
+#[derive(Debug, Default)]
+struct KeyOrder(Option<Vec<u8>>);
+
+impl KeyOrder {
+    fn check<T: Record>(&mut self, record: &T, source: &str) -> Result<(), PKError> {
+        let key = record.key();
+        if self.0.as_ref().is_some_and(|last| *last >= key) {
+            return Err(PKError::StorageError(format!(
+                "{source} has a {} record that's out of key order or there more than once",
+                T::NAME
+            )));
+        }
+        self.0 = Some(key);
+        Ok(())
+    }
+}
+
+/// Where the records live.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BackendKind {
+    Sqlite,
+    Sled,
+    Binary,
+    Memory,
+}
+
+impl Display for BackendKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            BackendKind::Sqlite => "sqlite",
+            BackendKind::Sled => "sled",
+            BackendKind::Binary => "bin",
+            BackendKind::Memory => "memory",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for BackendKind {
+    type Err = PKError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "sqlite" | "db" => Ok(BackendKind::Sqlite),
+            "sled" => Ok(BackendKind::Sled),
+            "bin" | "binary" => Ok(BackendKind::Binary),
+            "memory" | "mem" => Ok(BackendKind::Memory),
+            _ => Err(PKError::StorageError(format!("unknown backend {s}"))),
+        }
+    }
+}
+
+/// A backend and where to find it, written as `kind:path`, like `sqlite:data/hups.db`,
+/// `sled:generated/sleigh`, or `bin:generated/hups.bin`. The in memory backend is just `memory`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BackendSpec {
+    pub kind: BackendKind,
+    pub path: String,
+}
+
+impl BackendSpec {
+    /// Opens the backend, creating it if it isn't there yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the backend can't be opened.
+    pub fn open<T: Record + 'static>(&self) -> Result<Box<dyn Storage<T>>, PKError> {
+        Ok(match self.kind {
+            BackendKind::Sqlite => Box::new(SqliteStorage::open(&self.path)?),
+            BackendKind::Sled => Box::new(SledStorage::open(&self.path)?),
+            BackendKind::Binary => Box::new(BinaryStorage::open(&self.path)?),
+            BackendKind::Memory => Box::new(MemoryStorage::default()),
+        })
+    }
+}
+
+impl Display for BackendSpec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            BackendKind::Memory => write!(f, "{}", self.kind),
+            _ => write!(f, "{}:{}", self.kind, self.path),
+        }
+    }
+}
+
+impl FromStr for BackendSpec {
+    type Err = PKError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, path) = s.split_once(':').unwrap_or((s, ""));
+        let kind = BackendKind::from_str(kind)?;
+        if path.is_empty() && kind != BackendKind::Memory {
+            return Err(PKError::StorageError(format!(
+                "{kind} needs a path, like {kind}:some/file"
+            )));
+        }
+        Ok(BackendSpec {
+            kind,
+            path: path.to_string(),
+        })
+    }
+}
+
+/// What happened when the records were copied from one backend to another.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MigrationReport {
+    pub read: usize,
+    pub written: usize,
+    pub from_count: usize,
+    pub to_count: usize,
+    pub from_checksum: u64,
+    pub to_checksum: u64,
+}
+
+impl MigrationReport {
+    /// How many records go to `insert_many()` at a time.
+    pub const CHUNK: usize = 100_000;
+
+    /// Copies everything over a chunk at a time, counting and checksumming the records on the
+    /// way past, and then counts and checksums the other side. Only one chunk is ever held in
+    /// memory here, so a full `SevenFiveBCM` is fine, as long as the target doesn't need to
+    /// hold on to everything itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if either backend gives us trouble, or if the counts or
+    /// checksums don't match afterwards.
+    pub fn migrate<T: Record>(from: &dyn Storage<T>, to: &mut dyn Storage<T>) -> Result<MigrationReport, PKError> {
+        let mut read = 0;
+        let mut written = 0;
+        let mut from_checksum = Checksum::default();
+        let mut chunk = Vec::with_capacity(MigrationReport::CHUNK);
+        from.for_each(&mut |record| {
+            read += 1;
+            from_checksum.add(&record);
+            chunk.push(record);
+            if chunk.len() == MigrationReport::CHUNK {
+                written += to.insert_many(&chunk)?;
+                chunk.clear();
+            }
+            Ok(())
+        })?;
+        if !chunk.is_empty() {
+            written += to.insert_many(&chunk)?;
+        }
+
+        let report = MigrationReport {
+            read,
+            written,
+            from_count: read,
+            to_count: to.count()?,
+            from_checksum: from_checksum.value(),
+            to_checksum: to.checksum()?,
+        };
+        log::info!("MigrationReport::migrate() {} to {}: {report}", from.kind(), to.kind());
+
+        if report.verified() {
+            Ok(report)
+        } else {
+            Err(PKError::StorageError(report.to_string()))
+        }
+    }
+
+    #[must_use]
+    pub fn verified(&self) -> bool {
+        self.from_count == self.to_count && self.from_checksum == self.to_checksum
+    }
+}
+
+impl Display for MigrationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "read {} wrote {}, count {} -> {}, checksum {:016x} -> {:016x}",
+            self.read, self.written, self.from_count, self.to_count, self.from_checksum, self.to_checksum
+        )
+    }
+}
+
+fn read_u64(bytes: &[u8], i: usize) -> Result<u64, PKError> {
+    bytes
+        .get(i * 8..(i + 1) * 8)
+        .and_then(|b| b.try_into().ok())
+        .map(u64::from_be_bytes)
+        .ok_or(PKError::InvalidBinaryFormat)
+}
+
+/// Keyed by the two hands, and then the counts.
+impl Record for HUPResult {
+    type Key = SortedHeadsUp;
+    const NAME: &'static str = "nlh_headsup_result";
+    const SIZE: usize = 40;
+    const SELECT_ALL: &'static str =
+        "SELECT higher, lower, higher_wins, lower_wins, ties FROM nlh_headsup_result ORDER BY higher, lower";
+
+    fn key(&self) -> Vec<u8> {
+        [self.higher.as_u64().to_be_bytes(), self.lower.as_u64().to_be_bytes()].concat()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.higher.as_u64(),
+            self.lower.as_u64(),
+            self.higher_wins,
+            self.lower_wins,
+            self.ties,
+        ]
+        .iter()
+        .flat_map(|n| n.to_be_bytes())
+        .collect()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, PKError> {
+        if bytes.len() != HUPResult::SIZE {
+            return Err(PKError::InvalidBinaryFormat);
+        }
+        Ok(HUPResult {
+            higher: Bard::from(read_u64(bytes, 0)?),
+            lower: Bard::from(read_u64(bytes, 1)?),
+            higher_wins: read_u64(bytes, 2)?,
+            lower_wins: read_u64(bytes, 3)?,
+            ties: read_u64(bytes, 4)?,
+        })
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(HUPResult {
+            higher: Bard::from(row.get::<_, u64>(0)?),
+            lower: Bard::from(row.get::<_, u64>(1)?),
+            higher_wins: row.get(2)?,
+            lower_wins: row.get(3)?,
+            ties: row.get(4)?,
+        })
+    }
+}
+
+/// Keyed by the seven (or five) cards, and then the best five and their rank.
+impl Record for SevenFiveBCM {
+    type Key = Bard;
+    const NAME: &'static str = "bcm";
+    const SIZE: usize = 18;
+    const SELECT_ALL: &'static str = "SELECT bc, best, rank FROM bcm ORDER BY bc";
+
+    fn key(&self) -> Vec<u8> {
+        self.bc.as_u64().to_be_bytes().to_vec()
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        [
+            self.bc.as_u64().to_be_bytes().as_slice(),
+            self.best.as_u64().to_be_bytes().as_slice(),
+            self.rank.to_be_bytes().as_slice(),
+        ]
+        .concat()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, PKError> {
+        if bytes.len() != SevenFiveBCM::SIZE {
+            return Err(PKError::InvalidBinaryFormat);
+        }
+        Ok(SevenFiveBCM {
+            bc: Bard::from(read_u64(bytes, 0)?),
+            best: Bard::from(read_u64(bytes, 1)?),
+            rank: u16::from_be_bytes([bytes[16], bytes[17]]),
+        })
+    }
+
+    fn from_row(row: &rusqlite::Row) -> rusqlite::Result<Self> {
+        Ok(SevenFiveBCM {
+            bc: Bard::from(row.get::<_, u64>(0)?),
+            best: Bard::from(row.get::<_, u64>(1)?),
+            rank: row.get(2)?,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__storage_tests {
+    use super::*;
+    use crate::util::data::TestData;
+
+    #[test]
+    fn record__hup_result() {
+        let hup = TestData::the_hand_as_hup_result();
+
+        assert_eq!(HUPResult::SIZE, hup.to_bytes().len());
+        assert_eq!(hup, HUPResult::from_bytes(&hup.to_bytes()).unwrap());
+        assert_eq!(
+            PKError::InvalidBinaryFormat,
+            HUPResult::from_bytes(&[0; 8]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn record__bcm() {
+        let bcm = TestData::spades_royal_flush_bcm();
+
+        assert_eq!(SevenFiveBCM::SIZE, bcm.to_bytes().len());
+        assert_eq!(bcm, SevenFiveBCM::from_bytes(&bcm.to_bytes()).unwrap());
+    }
+
+    #[test]
+    fn checksum__order() {
+        let mut hups = TestData::known_hups();
+        let forward = checksum(&hups);
+        hups.reverse();
+
+        assert_eq!(forward, checksum(&hups));
+        assert_ne!(forward, checksum(&hups[1..]));
+    }
+
+    #[test]
+    fn backend_spec() {
+        let spec = BackendSpec::from_str("sqlite:data/hups.db").unwrap();
+
+        assert_eq!(BackendKind::Sqlite, spec.kind);
+        assert_eq!("data/hups.db", spec.path);
+        assert_eq!("sqlite:data/hups.db", spec.to_string());
+        assert_eq!("memory", BackendSpec::from_str("memory").unwrap().to_string());
+        assert_eq!(BackendKind::Binary, BackendSpec::from_str("binary:x.bin").unwrap().kind);
+        assert!(BackendSpec::from_str("sled").is_err());
+        assert!(BackendSpec::from_str("csv:x.csv").is_err());
+    }
+
+    #[test]
+    fn migrate() {
+        let mut from = MemoryStorage::default();
+        from.insert_many(&TestData::known_hups()).unwrap();
+        let mut to = MemoryStorage::default();
+
+        let report = MigrationReport::migrate(&from, &mut to).unwrap();
+
+        assert_eq!(3, report.read);
+        assert_eq!(3, report.written);
+        assert_eq!(3, report.to_count);
+        assert!(report.verified());
+        assert_eq!(3, MigrationReport::migrate(&from, &mut to).unwrap().to_count);
+    }
+
+    #[test]
+    fn migrate__mismatch() {
+        let mut from = MemoryStorage::default();
+        from.insert_many(&TestData::known_hups()).unwrap();
+        let mut to = MemoryStorage::default();
+        to.insert_many(&[TestData::the_hand_as_hup_result()]).unwrap();
+
+        assert!(MigrationReport::migrate(&from, &mut to).is_err());
+    }
+
+    /// Memory to `SQLite` to a binary file to sled and back to memory, checking along the way.
+    #[test]
+    fn migrate__every_backend() {
+        let dir = std::env::temp_dir().join(format!("pkcore_storage_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let bin = dir.join("hups.bin").to_string_lossy().to_string();
+        let sled = dir.join("sleigh").to_string_lossy().to_string();
+
+        let mut memory = MemoryStorage::default();
+        memory.insert_many(&TestData::known_hups()).unwrap();
+        let mut sqlite = SqliteStorage::from(rusqlite::Connection::open_in_memory().unwrap());
+        let mut binary: Box<dyn Storage<HUPResult>> =
+            BackendSpec::from_str(&format!("bin:{bin}")).unwrap().open().unwrap();
+        let mut sled: Box<dyn Storage<HUPResult>> =
+            BackendSpec::from_str(&format!("sled:{sled}")).unwrap().open().unwrap();
+        let mut back = MemoryStorage::default();
+
+        let reports = [
+            MigrationReport::migrate(&memory, &mut sqlite).unwrap(),
+            MigrationReport::migrate(&sqlite, binary.as_mut()).unwrap(),
+            MigrationReport::migrate(binary.as_ref(), sled.as_mut()).unwrap(),
+            MigrationReport::migrate(sled.as_ref(), &mut back).unwrap(),
+        ];
+        drop(sled);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        for report in &reports {
+            assert_eq!(3, report.to_count);
+            assert_eq!(reports[0].from_checksum, report.to_checksum);
+        }
+        assert_eq!(memory.select_all().unwrap(), back.select_all().unwrap());
+    }
+}
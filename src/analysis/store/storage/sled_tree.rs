@@ -0,0 +1,76 @@
+use crate::analysis::store::storage::{BackendKind, Record, Storage};
+use crate::PKError;
+
+/// A tree for each kind of record in a [sled](https://github.com/spacejam/sled) database. This
+/// is where `generated/sleigh` came from the first time around, back when I gave up on it. It
+/// turns out that it's fine once something else is deciding what the keys and values are.
+pub struct SledStorage {
+    db: sled::Db,
+}
+
+impl SledStorage {
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the database can't be opened.
+    pub fn open(path: &str) -> Result<SledStorage, PKError> {
+        sled::open(path)
+            .map(|db| SledStorage { db })
+            .map_err(|e| PKError::StorageError(format!("{path}: {e}")))
+    }
+
+    fn tree<T: Record>(&self) -> Result<sled::Tree, PKError> {
+        self.db
+            .open_tree(T::NAME)
+            .map_err(|e| PKError::StorageError(e.to_string()))
+    }
+
+    /// `open_tree()` creates the tree if it isn't there, which reading shouldn't do.
+    fn existing_tree<T: Record>(&self) -> Result<Option<sled::Tree>, PKError> {
+        if self
+            .db
+            .tree_names()
+            .iter()
+            .any(|name| name.as_ref() == T::NAME.as_bytes())
+        {
+            self.tree::<T>().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: Record> Storage<T> for SledStorage {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Sled
+    }
+
+    fn insert_many(&mut self, records: &[T]) -> Result<usize, PKError> {
+        let tree = self.tree::<T>()?;
+        let mut inserted = 0;
+        for record in records {
+            let swapped = tree
+                .compare_and_swap(record.key(), None as Option<&[u8]>, Some(record.to_bytes()))
+                .map_err(|e| PKError::StorageError(e.to_string()))?;
+            if swapped.is_ok() {
+                inserted += 1;
+            }
+        }
+        tree.flush().map_err(|e| PKError::StorageError(e.to_string()))?;
+        Ok(inserted)
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(T) -> Result<(), PKError>) -> Result<(), PKError> {
+        let Some(tree) = self.existing_tree::<T>()? else {
+            return Ok(());
+        };
+        for value in tree.iter().values() {
+            let value = value.map_err(|e| PKError::StorageError(e.to_string()))?;
+            f(T::from_bytes(&value)?)?;
+        }
+        Ok(())
+    }
+
+    fn count(&self) -> Result<usize, PKError> {
+        Ok(self.existing_tree::<T>()?.map_or(0, |tree| tree.len()))
+    }
+}
@@ -0,0 +1,151 @@
+use crate::analysis::store::storage::{BackendKind, KeyOrder, Record, Storage};
+use crate::PKError;
+use rusqlite::Connection;
+
+/// The tables that we already have, through each record's `Sqlable` implementation.
+pub struct SqliteStorage {
+    conn: Connection,
+}
+
+impl SqliteStorage {
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the database can't be opened.
+    pub fn open(path: &str) -> Result<SqliteStorage, PKError> {
+        Connection::open(path)
+            .map(SqliteStorage::from)
+            .map_err(|e| PKError::StorageError(format!("{path}: {e}")))
+    }
+
+    #[must_use]
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Asks without creating anything, unlike `Sqlable::create_table()`.
+    fn has_table<T: Record>(&self) -> rusqlite::Result<bool> {
+        self.conn.query_row(
+            "SELECT count(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = ?1",
+            [T::NAME],
+            |row| row.get(0),
+        )
+    }
+}
+
+impl From<Connection> for SqliteStorage {
+    fn from(conn: Connection) -> Self {
+        SqliteStorage { conn }
+    }
+}
+
+impl<T: Record> Storage<T> for SqliteStorage {
+    fn kind(&self) -> BackendKind {
+        BackendKind::Sqlite
+    }
+
+    /// Everything goes in in one transaction, which makes a big difference with `SQLite`.
+    fn insert_many(&mut self, records: &[T]) -> Result<usize, PKError> {
+        let to_error = |e: rusqlite::Error| PKError::StorageError(e.to_string());
+        T::create_table(&self.conn).map_err(to_error)?;
+        let tx = self.conn.transaction().map_err(to_error)?;
+        let inserted = T::insert_many(&tx, records.iter().collect()).map_err(to_error)?;
+        tx.commit().map_err(to_error)?;
+        Ok(inserted)
+    }
+
+    /// Some of the older tables have the odd duplicate row in them. Rather than quietly pick one
+    /// of them, that's an error, and the table needs repairing before it goes anywhere.
+    fn for_each(&self, f: &mut dyn FnMut(T) -> Result<(), PKError>) -> Result<(), PKError> {
+        let to_error = |e: rusqlite::Error| PKError::StorageError(format!("{}: {e}", T::NAME));
+        if !self.has_table::<T>().map_err(to_error)? {
+            return Ok(());
+        }
+        let mut stmt = self.conn.prepare(T::SELECT_ALL).map_err(to_error)?;
+        let mut rows = stmt.query([]).map_err(to_error)?;
+        let mut order = KeyOrder::default();
+        let mut i = 0;
+        while let Some(row) = rows.next().map_err(to_error)? {
+            i += 1;
+            let record = T::from_row(row).map_err(|e| PKError::StorageError(format!("{} row {i}: {e}", T::NAME)))?;
+            order.check(&record, T::NAME)?;
+            f(record)?;
+        }
+        Ok(())
+    }
+
+    /// The rows that are actually there, so a table with duplicates doesn't look fine.
+    fn count(&self) -> Result<usize, PKError> {
+        let to_error = |e: rusqlite::Error| PKError::StorageError(format!("{}: {e}", T::NAME));
+        if !self.has_table::<T>().map_err(to_error)? {
+            return Ok(0);
+        }
+        self.conn
+            .query_row(&format!("SELECT count(*) FROM {}", T::NAME), [], |row| row.get(0))
+            .map_err(to_error)
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__storage__sqlite_tests {
+    use super::*;
+    use crate::analysis::store::db::headsup_preflop_result::HUPResult;
+    use crate::analysis::store::db::schema::Versioned;
+    use crate::util::data::TestData;
+
+    /// The first version of the table, before there was anything to stop duplicates.
+    fn storage() -> SqliteStorage {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(HUPResult::MIGRATIONS[0].sql).unwrap();
+        SqliteStorage::from(conn)
+    }
+
+    fn insert(storage: &SqliteStorage, higher: &str) {
+        let hup = TestData::the_hand_as_hup_result();
+        storage
+            .connection()
+            .execute(
+                "INSERT INTO nlh_headsup_result (higher, lower, higher_wins, lower_wins, ties) VALUES (?1, ?2, 1, 2, 3)",
+                rusqlite::params![higher, hup.lower.as_u64()],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn select_all__leaves_the_database_alone() {
+        let storage = SqliteStorage::from(Connection::open_in_memory().unwrap());
+
+        let records: Vec<HUPResult> = storage.select_all().unwrap();
+
+        assert!(records.is_empty());
+        assert!(!storage.has_table::<HUPResult>().unwrap());
+    }
+
+    #[test]
+    fn select_all__duplicates() {
+        let storage = storage();
+        let higher = TestData::the_hand_as_hup_result().higher.as_u64().to_string();
+        insert(&storage, &higher);
+        insert(&storage, &higher);
+
+        assert_eq!(2, Storage::<HUPResult>::count(&storage).unwrap());
+        assert!(matches!(
+            Storage::<HUPResult>::select_all(&storage),
+            Err(PKError::StorageError(_))
+        ));
+        assert!(Storage::<HUPResult>::checksum(&storage).is_err());
+    }
+
+    #[test]
+    fn select_all__unreadable_row() {
+        let storage = storage();
+        insert(&storage, "not a number");
+
+        assert_eq!(
+            Err(PKError::StorageError(
+                "nlh_headsup_result row 1: Invalid column type Text at index: 0, name: higher".to_string()
+            )),
+            Storage::<HUPResult>::select_all(&storage)
+        );
+    }
+}
@@ -0,0 +1,321 @@
+use crate::analysis::hand_rank::HandRank;
+use crate::analysis::name::Name;
+use crate::arrays::five::Five;
+use crate::arrays::HandRanker;
+use crate::card::Card;
+use crate::rank::Rank;
+use crate::suit::Suit;
+use crate::PKError;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::str::FromStr;
+
+/// # UCI Poker Hands
+///
+/// The [UCI Machine Learning Repository](https://archive.ics.uci.edu/dataset/158/poker+hand) has
+/// a dataset of five card poker hands, each one labeled with what kind of hand it is. It was put
+/// together to see if machine learning could figure out poker hands, but what I like about it is
+/// that it's a list of hands that somebody else classified. That makes it a nice independent check
+/// on our lookup tables.
+///
+/// Each row is ten integers for the five cards, suit then rank, followed by the class label:
+///
+/// ```txt
+/// 1,10,1,11,1,13,1,12,1,1,9
+/// ```
+///
+/// Suits are 1 to 4 for hearts, spades, diamonds and clubs, ranks are 1 to 13 for ace through
+/// king, and the class is 0 for nothing through 9 for a royal flush. The file and its
+/// description are in `data/UCI`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UciHand {
+    pub five: Five,
+    /// The UCI class label, from 0 to 9.
+    pub class: u8,
+}
+
+impl UciHand {
+    pub const CLASSES: [&'static str; 10] = [
+        "Nothing in hand",
+        "One pair",
+        "Two pairs",
+        "Three of a kind",
+        "Straight",
+        "Flush",
+        "Full house",
+        "Four of a kind",
+        "Straight flush",
+        "Royal flush",
+    ];
+
+    /// Reads every hand in a UCI data file.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the file can't be read, or `PKError::InvalidUci` with the
+    /// line number of the first row that doesn't make sense.
+    pub fn from_file(path: &str) -> Result<Vec<UciHand>, PKError> {
+        let s = fs::read_to_string(path).map_err(|_| PKError::Fubar)?;
+        UciHand::parse_all(&s)
+    }
+
+    /// Reads every row in a string, skipping blank lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidUci` with the line number of the first row that doesn't make
+    /// sense.
+    pub fn parse_all(s: &str) -> Result<Vec<UciHand>, PKError> {
+        s.lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                UciHand::from_str(line).map_err(|e| match e {
+                    PKError::InvalidUci(_, reason) => PKError::InvalidUci(i + 1, reason),
+                    e => e,
+                })
+            })
+            .collect()
+    }
+
+    /// The `Name` that goes with a UCI class label. We don't have a separate name for a royal
+    /// flush, since it's just the best straight flush.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidUci` if the label isn't from 0 to 9.
+    pub fn name_from_class(class: u8) -> Result<Name, PKError> {
+        match class {
+            0 => Ok(Name::HighCard),
+            1 => Ok(Name::Pair),
+            2 => Ok(Name::TwoPair),
+            3 => Ok(Name::ThreeOfAKind),
+            4 => Ok(Name::Straight),
+            5 => Ok(Name::Flush),
+            6 => Ok(Name::FullHouse),
+            7 => Ok(Name::FourOfAKind),
+            8 | 9 => Ok(Name::StraightFlush),
+            _ => Err(PKError::InvalidUci(0, format!("unknown class {class}"))),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidUci` if the number isn't from 1 to 4.
+    pub fn suit_from_uci(n: u8) -> Result<Suit, PKError> {
+        match n {
+            1 => Ok(Suit::HEARTS),
+            2 => Ok(Suit::SPADES),
+            3 => Ok(Suit::DIAMONDS),
+            4 => Ok(Suit::CLUBS),
+            _ => Err(PKError::InvalidUci(0, format!("unknown suit {n}"))),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidUci` if the number isn't from 1 to 13.
+    pub fn rank_from_uci(n: u8) -> Result<Rank, PKError> {
+        let rank = match n {
+            1 => Rank::ACE,
+            2 => Rank::DEUCE,
+            3 => Rank::TREY,
+            4 => Rank::FOUR,
+            5 => Rank::FIVE,
+            6 => Rank::SIX,
+            7 => Rank::SEVEN,
+            8 => Rank::EIGHT,
+            9 => Rank::NINE,
+            10 => Rank::TEN,
+            11 => Rank::JACK,
+            12 => Rank::QUEEN,
+            13 => Rank::KING,
+            _ => return Err(PKError::InvalidUci(0, format!("unknown rank {n}"))),
+        };
+        Ok(rank)
+    }
+
+    /// What the label says the hand is. The class is checked when the hand is parsed, so this
+    /// only comes back `Invalid` for a `UciHand` that was put together by hand.
+    #[must_use]
+    pub fn name(&self) -> Name {
+        UciHand::name_from_class(self.class).unwrap_or_default()
+    }
+
+    /// Returns the `HandRank` from our lookup tables if it disagrees with the label. A royal
+    /// flush has to be the best hand there is, and a straight flush anything but.
+    #[must_use]
+    pub fn disagreement(&self) -> Option<HandRank> {
+        let hand_rank = self.five.hand_rank();
+        let agrees = hand_rank.name == self.name()
+            && match self.class {
+                8 => hand_rank.value != 1,
+                9 => hand_rank.value == 1,
+                _ => true,
+            };
+        if agrees {
+            None
+        } else {
+            Some(hand_rank)
+        }
+    }
+}
+
+impl Display for UciHand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let class = UciHand::CLASSES.get(self.class as usize).copied().unwrap_or_default();
+        write!(f, "{} ({class})", self.five)
+    }
+}
+
+impl FromStr for UciHand {
+    type Err = PKError;
+
+    /// Parses a single row. The line number in any error is 0, since `parse_all()` is the one
+    /// that knows where the row came from.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let numbers = s
+            .trim()
+            .split(',')
+            .map(|n| n.trim().parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| PKError::InvalidUci(0, format!("{s} isn't all numbers")))?;
+        if numbers.len() != 11 {
+            return Err(PKError::InvalidUci(
+                0,
+                format!("expected 11 numbers, not {}", numbers.len()),
+            ));
+        }
+
+        let mut cards = [Card::BLANK; 5];
+        for (i, pair) in numbers[..10].chunks(2).enumerate() {
+            let card = Card::new(UciHand::rank_from_uci(pair[1])?, UciHand::suit_from_uci(pair[0])?);
+            if cards.contains(&card) {
+                return Err(PKError::InvalidUci(0, format!("{card} is there twice")));
+            }
+            cards[i] = card;
+        }
+        let class = numbers[10];
+        UciHand::name_from_class(class)?;
+
+        Ok(UciHand {
+            five: Five::from(cards),
+            class,
+        })
+    }
+}
+
+/// What happened when every hand in a UCI file was checked against our `HandRanker`.
+#[derive(Clone, Debug, Default)]
+pub struct UciReport {
+    pub checked: usize,
+    /// How many hands had each label.
+    pub classes: BTreeMap<u8, usize>,
+    /// Every hand where we disagree with the label, with its line number and what we think
+    /// it is.
+    pub disagreements: Vec<(usize, UciHand, HandRank)>,
+}
+
+impl UciReport {
+    /// Checks every hand against the lookup tables. The line numbers assume that the hands came
+    /// from a file with one on each line, which is how the UCI files are.
+    #[must_use]
+    pub fn verify(hands: &[UciHand]) -> UciReport {
+        let mut report = UciReport::default();
+        for (i, hand) in hands.iter().enumerate() {
+            report.checked += 1;
+            *report.classes.entry(hand.class).or_default() += 1;
+            if let Some(hand_rank) = hand.disagreement() {
+                report.disagreements.push((i + 1, *hand, hand_rank));
+            }
+        }
+        report
+    }
+
+    /// Reads and checks a whole UCI file.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `UciHand::from_file()`.
+    pub fn from_file(path: &str) -> Result<UciReport, PKError> {
+        Ok(UciReport::verify(&UciHand::from_file(path)?))
+    }
+
+    #[must_use]
+    pub fn agrees(&self) -> bool {
+        self.disagreements.is_empty()
+    }
+}
+
+impl Display for UciReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Checked {} hands", self.checked)?;
+        for (class, count) in &self.classes {
+            let name = UciHand::CLASSES.get(*class as usize).copied().unwrap_or_default();
+            writeln!(f, "  {class}: {name:<16} {count:>7}")?;
+        }
+        if self.agrees() {
+            write!(f, "No disagreements")
+        } else {
+            writeln!(f, "{} disagreements:", self.disagreements.len())?;
+            for (line, hand, hand_rank) in &self.disagreements {
+                writeln!(f, "  line {line}: {hand} is {hand_rank}")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod analysis__store__uci_tests {
+    use super::*;
+    use crate::analysis::class::Class;
+
+    const PATH: &str = "data/UCI/poker-hand-training-true.data";
+
+    #[test]
+    fn from_str() {
+        let hand = UciHand::from_str("1,10,1,11,1,13,1,12,1,1,9").unwrap();
+
+        assert_eq!(Five::from_str("T♥ J♥ K♥ Q♥ A♥").unwrap(), hand.five);
+        assert_eq!(9, hand.class);
+        assert_eq!(Name::StraightFlush, hand.name());
+        assert_eq!("T♥ J♥ K♥ Q♥ A♥ (Royal flush)", hand.to_string());
+    }
+
+    #[test]
+    fn from_str__errors() {
+        assert!(UciHand::from_str("1,10,1,11,1,13,1,12,1,1").is_err());
+        assert!(UciHand::from_str("5,10,1,11,1,13,1,12,1,1,9").is_err());
+        assert!(UciHand::from_str("1,14,1,11,1,13,1,12,1,1,9").is_err());
+        assert!(UciHand::from_str("1,10,1,10,1,13,1,12,1,1,9").is_err());
+        assert!(UciHand::from_str("1,10,1,11,1,13,1,12,1,1,10").is_err());
+        assert_eq!(
+            PKError::InvalidUci(2, "x,10 isn't all numbers".to_string()),
+            UciHand::parse_all("1,10,1,11,1,13,1,12,1,1,9\nx,10").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn disagreement() {
+        let royal = UciHand::from_str("1,10,1,11,1,13,1,12,1,1,9").unwrap();
+        let mislabeled = UciHand::from_str("1,10,1,11,1,13,1,12,1,1,8").unwrap();
+        let pair = UciHand::from_str("1,10,2,10,1,13,1,12,1,1,0").unwrap();
+
+        assert_eq!(None, royal.disagreement());
+        assert_eq!(Class::RoyalFlush, mislabeled.disagreement().unwrap().class);
+        assert_eq!(Name::Pair, pair.disagreement().unwrap().name);
+    }
+
+    #[test]
+    fn verify() {
+        let report = UciReport::from_file(PATH).unwrap();
+
+        assert_eq!(25_010, report.checked);
+        assert_eq!(Some(&12_493), report.classes.get(&0));
+        assert_eq!(Some(&5), report.classes.get(&9));
+        assert!(report.agrees(), "{report}");
+    }
+}
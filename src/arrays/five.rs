@@ -1,4 +1,5 @@
 use crate::analysis::hand_rank::{HandRankValue, NO_HAND_RANK_VALUE};
+use crate::arrays::seven::Seven;
 use crate::arrays::three::Three;
 use crate::arrays::two::Two;
 use crate::arrays::HandRanker;
@@ -294,8 +295,31 @@ impl Pile for Five {
         ])
     }
 
+    /// Treats the `Five` as a finished board, so it's every two hole cards that are left, and the
+    /// best five out of the seven.
     fn the_nuts(&self) -> TheNuts {
-        todo!()
+        if !self.is_dealt() {
+            return TheNuts::default();
+        }
+
+        let mut the_nuts = TheNuts::default();
+
+        for v in self.remaining().combinations(2) {
+            let hole_cards = Two::from(v);
+            let hand = Seven::from([
+                hole_cards.first(),
+                hole_cards.second(),
+                self.first(),
+                self.second(),
+                self.third(),
+                self.forth(),
+                self.fifth(),
+            ]);
+            the_nuts.push(hand.eval());
+        }
+        the_nuts.sort_in_place();
+
+        the_nuts
     }
 
     fn to_vec(&self) -> Vec<Card> {
@@ -2464,6 +2488,21 @@ mod arrays__five_tests {
         assert_eq!("A♦ K♦ Q♦ J♦ T♦", Five::from(ROYAL_FLUSH).cards().to_string());
     }
 
+    #[test]
+    fn pile__the_nuts__blank() {
+        assert_eq!(TheNuts::default(), Five::default().the_nuts());
+    }
+
+    #[test]
+    fn pile__the_nuts() {
+        let five = Five::from_str("9♣ 6♦ 5♥ 5♠ 8♠").unwrap();
+
+        let the_nuts = five.the_nuts();
+
+        assert_eq!("5♠ 5♥ 5♦ 5♣ 9♣ - 124-FourFives", the_nuts.get(0).unwrap().to_string());
+        assert_eq!(25, the_nuts.to_evals().len());
+    }
+
     #[test]
     fn pile__clean() {
         let full_house = Five::from([
@@ -1,4 +1,7 @@
+use crate::arrays::six::Six;
 use crate::arrays::three::Three;
+use crate::arrays::two::Two;
+use crate::arrays::HandRanker;
 use crate::{Card, Pile, TheNuts};
 use std::fmt;
 use std::fmt::{Display, Formatter};
@@ -107,8 +110,22 @@ impl Pile for Four {
         ])
     }
 
+    /// Same as the flop, only with the turn card thrown in, so it's the best five out of six.
     fn the_nuts(&self) -> TheNuts {
-        todo!()
+        if !self.is_dealt() {
+            return TheNuts::default();
+        }
+
+        let flop = Three::from([self.first(), self.second(), self.third()]);
+        let mut the_nuts = TheNuts::default();
+
+        for v in self.remaining().combinations(2) {
+            let hand = Six::from_2and3and1(Two::from(v), flop, self.forth());
+            the_nuts.push(hand.eval());
+        }
+        the_nuts.sort_in_place();
+
+        the_nuts
     }
 
     fn to_vec(&self) -> Vec<Card> {
@@ -135,4 +152,26 @@ mod arrays__four_tests {
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn pile__the_nuts__blank() {
+        let four = Four::from([Card::BLANK, Card::SIX_DIAMONDS, Card::FIVE_HEARTS, Card::FIVE_SPADES]);
+
+        assert_eq!(TheNuts::default(), four.the_nuts());
+    }
+
+    #[test]
+    fn pile__the_nuts() {
+        let four = Four::from([
+            Card::NINE_CLUBS,
+            Card::SIX_DIAMONDS,
+            Card::FIVE_HEARTS,
+            Card::FIVE_SPADES,
+        ]);
+
+        let the_nuts = four.the_nuts();
+
+        assert_eq!("5♠ 5♥ 5♦ 5♣ 9♣ - 124-FourFives", the_nuts.get(0).unwrap().to_string());
+        assert_eq!(21, the_nuts.to_evals().len());
+    }
 }
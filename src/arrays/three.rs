@@ -5,11 +5,12 @@ use crate::card::Card;
 use crate::cards::Cards;
 use crate::util::Util;
 use crate::{PKError, Pile, Plurable, TheNuts};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
 pub struct Three([Card; 3]);
 
 impl Three {
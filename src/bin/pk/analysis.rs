@@ -0,0 +1,262 @@
+use crate::config::Config;
+use crate::output::{percent, render, Format, Report};
+use clap::Args;
+use pkcore::cards::Cards;
+use pkcore::play::hole_cards::HoleCards;
+use pkcore::web::api::{EvalRequest, EvalResponse, GameRequest, OddsResponse, RangeRequest, RangeResponse};
+use pkcore::web::service::EquityService;
+use pkcore::PKError;
+use std::str::FromStr;
+
+#[derive(Args, Debug)]
+pub struct EvalArgs {
+    /// Five to seven cards, like "A♠ K♠ Q♠ J♠ T♠".
+    cards: String,
+}
+
+#[derive(Args, Debug)]
+pub struct CalcArgs {
+    /// The whole hand in game notation, like "HE: 6♠ 6♥ 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ 8♠".
+    #[clap(short = 'g', long)]
+    game: Option<String>,
+
+    /// Everybody's hole cards, like "6♠ 6♥ 5♦ 5♣".
+    #[clap(short = 'd', long)]
+    dealt: Option<String>,
+
+    #[clap(short = 'b', long)]
+    board: Option<String>,
+
+    /// Include the odds before the flop. Heads up and three or four handed matchups come from the
+    /// stored results if they're there.
+    #[clap(short = 'p', long)]
+    preflop: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct RangeArgs {
+    hand: String,
+
+    /// Like "QQ+,AK,76s".
+    range: String,
+
+    #[clap(short = 'b', long)]
+    board: Option<String>,
+
+    /// Before the flop, give the equity against just the stored matchups rather than an error
+    /// when some of them are missing.
+    #[clap(short = 'p', long)]
+    partial: bool,
+}
+
+/// # Errors
+///
+/// Returns `PKError::InvalidCard` or one of the card count errors if the cards don't make sense,
+/// and `PKError::Duplicate` if the same card is in there twice, which `Cards` would otherwise
+/// quietly drop.
+pub fn eval(args: &EvalArgs, format: Format) -> Result<String, PKError> {
+    let cards = Cards::from_str(&args.cards)?;
+    if cards.len() != args.cards.split_whitespace().count() {
+        return Err(PKError::Duplicate);
+    }
+    let request = EvalRequest { cards };
+    render(&EquityService::eval(&request)?, format)
+}
+
+/// # Errors
+///
+/// Returns `PKError::NotDealt` if there's neither a game nor dealt cards, and passes on anything
+/// wrong with the cards.
+pub fn calc(args: &CalcArgs, config: &Config, format: Format) -> Result<String, PKError> {
+    let request = match (&args.game, &args.dealt) {
+        (Some(game), _) => GameRequest {
+            game: Some(game.clone()),
+            preflop: args.preflop,
+            ..Default::default()
+        },
+        (None, Some(dealt)) => GameRequest {
+            hands: HoleCards::from_str(dealt)?.iter().copied().collect(),
+            board: args.board.as_deref().map(Cards::from_str).transpose()?,
+            preflop: args.preflop,
+            ..Default::default()
+        },
+        (None, None) => return Err(PKError::NotDealt),
+    };
+    render(&config.service()?.odds(&request)?, format)
+}
+
+/// # Errors
+///
+/// Passes on anything wrong with the hand, range or board.
+pub fn range(args: &RangeArgs, config: &Config, format: Format) -> Result<String, PKError> {
+    let request = RangeRequest {
+        hand: pkcore::arrays::two::Two::from_str(&args.hand)?,
+        range: args.range.clone(),
+        board: args.board.as_deref().map(Cards::from_str).transpose()?,
+        partial: args.partial,
+    };
+    render(&config.service()?.range(&request)?, format)
+}
+
+impl Report for EvalResponse {
+    fn header(&self) -> Vec<&'static str> {
+        vec!["cards", "best", "rank", "name", "class"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.cards.to_string(),
+            self.best.to_string(),
+            self.rank.to_string(),
+            self.name.clone(),
+            self.class.clone(),
+        ]]
+    }
+}
+
+/// One row for each player at each street.
+impl Report for OddsResponse {
+    fn header(&self) -> Vec<&'static str> {
+        vec![
+            "street",
+            "board",
+            "hand",
+            "name",
+            "wins",
+            "ties",
+            "win_percentage",
+            "tie_percentage",
+            "outs",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.streets
+            .iter()
+            .flat_map(|street| {
+                street.players.iter().map(|player| {
+                    vec![
+                        street.street.to_string(),
+                        street.board.to_string(),
+                        player.hand.to_string(),
+                        player.name.clone().unwrap_or_default(),
+                        player.wins.to_string(),
+                        player.ties.to_string(),
+                        percent(player.win_percentage),
+                        percent(player.tie_percentage),
+                        player.outs.as_ref().map(ToString::to_string).unwrap_or_default(),
+                    ]
+                })
+            })
+            .collect()
+    }
+}
+
+impl Report for RangeResponse {
+    fn header(&self) -> Vec<&'static str> {
+        vec![
+            "hand",
+            "range",
+            "board",
+            "combos",
+            "blocked",
+            "missing",
+            "partial",
+            "win_percentage",
+            "tie_percentage",
+            "equity",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.hand.to_string(),
+            self.range.clone(),
+            self.board.to_string(),
+            self.combos.to_string(),
+            self.blocked.to_string(),
+            self.missing.to_string(),
+            self.partial.to_string(),
+            percent(self.win_percentage),
+            percent(self.tie_percentage),
+            percent(self.equity),
+        ]]
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod pk__analysis_tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config::default().with_flags(Some("no/such/hups.db".to_string()), None, None)
+    }
+
+    #[test]
+    fn eval() {
+        let args = EvalArgs {
+            cards: "9♠ A♠ K♠ 2♦ Q♠ J♠ T♠".to_string(),
+        };
+
+        assert_eq!(
+            "A♠ K♠ Q♠ J♠ T♠ (1-RoyalFlush)",
+            super::eval(&args, Format::Text).unwrap()
+        );
+        assert_eq!(
+            "cards,best,rank,name,class\n9♠ A♠ K♠ 2♦ Q♠ J♠ T♠,A♠ K♠ Q♠ J♠ T♠,1,StraightFlush,RoyalFlush\n",
+            super::eval(&args, Format::Csv).unwrap()
+        );
+    }
+
+    #[test]
+    fn eval__duplicate() {
+        let args = EvalArgs {
+            cards: "A♠ A♠ K♠ Q♠ J♠ T♠".to_string(),
+        };
+
+        assert_eq!(Err(PKError::Duplicate), super::eval(&args, Format::Text));
+    }
+
+    #[test]
+    fn calc() {
+        let args = CalcArgs {
+            game: None,
+            dealt: Some("6♠ 6♥ 5♦ 5♣".to_string()),
+            board: Some("9♣ 6♦ 5♥ 5♠".to_string()),
+            preflop: false,
+        };
+
+        let csv = super::calc(&args, &config(), Format::Csv).unwrap();
+
+        assert_eq!(5, csv.lines().count());
+        assert!(csv.contains("Turn,9♣ 6♦ 5♥ 5♠,6♠ 6♥,,1,0,2.27,0.00,6♣"));
+    }
+
+    #[test]
+    fn calc__not_dealt() {
+        let args = CalcArgs {
+            game: None,
+            dealt: None,
+            board: None,
+            preflop: false,
+        };
+
+        assert_eq!(Err(PKError::NotDealt), super::calc(&args, &config(), Format::Text));
+    }
+
+    #[test]
+    fn range() {
+        let args = RangeArgs {
+            hand: "5♦ 5♣".to_string(),
+            range: "66".to_string(),
+            board: Some("9♣ 6♦ 5♥".to_string()),
+            partial: false,
+        };
+
+        let json: RangeResponse = serde_json::from_str(&super::range(&args, &config(), Format::Json).unwrap()).unwrap();
+
+        assert_eq!(3, json.combos);
+        assert_eq!(3, json.blocked);
+    }
+}
@@ -0,0 +1,81 @@
+use crate::config::Config;
+use crate::output::{render, Format, Report};
+use clap::{Args, Subcommand};
+use pkcore::analysis::store::bcm::binary_card_map::SevenFiveBCM;
+use pkcore::PKError;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::time::Instant;
+
+#[derive(Subcommand, Debug)]
+pub enum BcmCommand {
+    /// Writes out every five and seven card hand's best five cards. Takes a long while, and
+    /// the file is big.
+    Generate(GenerateArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    /// Where to write the CSV. Defaults to the configured BCM path.
+    #[clap(short = 'o', long)]
+    output: Option<String>,
+}
+
+/// # Errors
+///
+/// Returns `PKError::StorageError` if the file can't be written.
+pub fn run(command: BcmCommand, config: &Config, format: Format) -> Result<String, PKError> {
+    match command {
+        BcmCommand::Generate(args) => render(&generate(&args, config)?, format),
+    }
+}
+
+fn generate(args: &GenerateArgs, config: &Config) -> Result<Generated, PKError> {
+    let path = args.output.clone().unwrap_or_else(|| config.bcm.clone());
+    let now = Instant::now();
+    SevenFiveBCM::generate_csv(&path).map_err(|e| PKError::StorageError(format!("{path}: {e}")))?;
+    Ok(Generated {
+        path,
+        seconds: now.elapsed().as_secs_f64(),
+    })
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Generated {
+    pub path: String,
+    pub seconds: f64,
+}
+
+impl Display for Generated {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} written in {:.1}s", self.path, self.seconds)
+    }
+}
+
+impl Report for Generated {
+    fn header(&self) -> Vec<&'static str> {
+        vec!["path", "seconds"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![self.path.clone(), format!("{:.1}", self.seconds)]]
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod pk__bcm_tests {
+    use super::*;
+
+    #[test]
+    fn generate__unwritable() {
+        let args = GenerateArgs {
+            output: Some("no/such/dir/bcm.csv".to_string()),
+        };
+
+        assert!(matches!(
+            generate(&args, &Config::default()),
+            Err(PKError::StorageError(_))
+        ));
+    }
+}
@@ -0,0 +1,157 @@
+use pkcore::analysis::store::bcm::binary_card_map::SevenFiveBCM;
+use pkcore::web::service::EquityService;
+use pkcore::PKError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Where `pk` finds its data. Every one of the examples used to have its own idea of where the
+/// heads up results live, so they're all in one place now. Each path comes from, in order of
+/// who wins:
+///
+/// 1. The command line, like `--hups data/hups.db`.
+/// 2. An environment variable, like `PKCORE_HUPS_DB`.
+/// 3. A YAML file, either passed in with `--config` or `pk.yaml` in the current directory.
+/// 4. The same defaults the examples have always used.
+///
+/// ```txt
+/// hups: data/hups.db
+/// pluribus: data/pluribus/raw
+/// ```
+///
+/// The BCM path is handed on to the rest of the crate through `PKCORE_75BCM_CSV_PATH`, which is
+/// where `SevenFiveBCM` has always looked for it.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub hups: String,
+    pub bcm: String,
+    pub pluribus: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            hups: Config::DEFAULT_HUPS.to_string(),
+            bcm: SevenFiveBCM::DEFAULT_PKCORE_75BCM_CSV_PATH.to_string(),
+            pluribus: Config::DEFAULT_PLURIBUS.to_string(),
+        }
+    }
+}
+
+impl Config {
+    pub const DEFAULT_FILE: &'static str = "pk.yaml";
+    pub const DEFAULT_HUPS: &'static str = "generated/hups.db";
+    pub const DEFAULT_PLURIBUS: &'static str = "data/pluribus/raw";
+
+    pub const ENV_HUPS: &'static str = "PKCORE_HUPS_DB";
+    pub const ENV_BCM: &'static str = "PKCORE_75BCM_CSV_PATH";
+    pub const ENV_PLURIBUS: &'static str = "PKCORE_PLURIBUS_LOGS";
+
+    /// The file passed in, or `pk.yaml` if there is one, with the environment on top.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if a file that was asked for can't be read or parsed.
+    pub fn load(path: Option<&str>) -> Result<Config, PKError> {
+        let config = match path {
+            Some(path) => Config::from_file(path)?,
+            None if Path::new(Config::DEFAULT_FILE).exists() => Config::from_file(Config::DEFAULT_FILE)?,
+            None => Config::default(),
+        };
+        Ok(config.with_env(|key| std::env::var(key).ok()))
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the file can't be read or parsed.
+    pub fn from_file(path: &str) -> Result<Config, PKError> {
+        let yaml = std::fs::read_to_string(path).map_err(|e| PKError::StorageError(format!("{path}: {e}")))?;
+        Config::from_yaml(&yaml).map_err(|e| PKError::StorageError(format!("{path}: {e}")))
+    }
+
+    /// # Errors
+    ///
+    /// Passes on the error if the YAML doesn't parse.
+    pub fn from_yaml(yaml: &str) -> Result<Config, serde_yaml::Error> {
+        if yaml.trim().is_empty() {
+            return Ok(Config::default());
+        }
+        serde_yaml::from_str(yaml)
+    }
+
+    /// Overrides any of the paths that are set in the environment. The lookup is passed in so
+    /// that the tests don't have to go messing with the real one.
+    #[must_use]
+    pub fn with_env<F: Fn(&str) -> Option<String>>(mut self, lookup: F) -> Config {
+        if let Some(hups) = lookup(Config::ENV_HUPS) {
+            self.hups = hups;
+        }
+        if let Some(bcm) = lookup(Config::ENV_BCM) {
+            self.bcm = bcm;
+        }
+        if let Some(pluribus) = lookup(Config::ENV_PLURIBUS) {
+            self.pluribus = pluribus;
+        }
+        self
+    }
+
+    /// Overrides whatever was passed in on the command line.
+    #[must_use]
+    pub fn with_flags(mut self, hups: Option<String>, bcm: Option<String>, pluribus: Option<String>) -> Config {
+        self.hups = hups.unwrap_or(self.hups);
+        self.bcm = bcm.unwrap_or(self.bcm);
+        self.pluribus = pluribus.unwrap_or(self.pluribus);
+        self
+    }
+
+    /// The heads up results, if the file's there. If it isn't, anything that gets calculated is
+    /// forgotten when `pk` exits, rather than leaving a new file lying around.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the file can't be opened.
+    pub fn service(&self) -> Result<EquityService, PKError> {
+        if Path::new(&self.hups).exists() {
+            EquityService::open(&self.hups)
+        } else {
+            EquityService::in_memory()
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod pk__config_tests {
+    use super::*;
+
+    #[test]
+    fn from_yaml() {
+        let config = Config::from_yaml("hups: data/hups.db\n").unwrap();
+
+        assert_eq!("data/hups.db", config.hups);
+        assert_eq!(Config::DEFAULT_PLURIBUS, config.pluribus);
+        assert_eq!(Config::default(), Config::from_yaml("").unwrap());
+        assert!(Config::from_yaml("hups: [").is_err());
+    }
+
+    #[test]
+    fn from_file__missing() {
+        assert!(matches!(
+            Config::from_file("no/such/pk.yaml"),
+            Err(PKError::StorageError(_))
+        ));
+    }
+
+    /// The command line beats the environment, which beats the file.
+    #[test]
+    fn with_env__with_flags() {
+        let config = Config::from_yaml("hups: file.db\nbcm: file.csv\npluribus: file/logs\n")
+            .unwrap()
+            .with_env(|key| (key == Config::ENV_HUPS || key == Config::ENV_BCM).then(|| format!("{key}.env")))
+            .with_flags(Some("flag.db".to_string()), None, None);
+
+        assert_eq!("flag.db", config.hups);
+        assert_eq!("PKCORE_75BCM_CSV_PATH.env", config.bcm);
+        assert_eq!("file/logs", config.pluribus);
+    }
+}
@@ -0,0 +1,487 @@
+use crate::config::Config;
+use crate::output::{percent, render, Format, Report};
+use clap::{Args, Subcommand};
+use pkcore::analysis::store::db::headsup_preflop_result::HUPResult;
+use pkcore::analysis::store::db::hup_audit::{HupAudit, HupAuditReport, HupRepair, Recompute};
+use pkcore::analysis::store::db::hup_job::{HupJob, HupJobProgress};
+use pkcore::analysis::store::db::sqlite::Sqlable;
+use pkcore::arrays::two::Two;
+use pkcore::web::api::{HupRequest, HupResponse};
+use pkcore::PKError;
+use rusqlite::{Connection, OpenFlags};
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Subcommand, Debug)]
+pub enum HupCommand {
+    /// One matchup, in whatever order and suits.
+    Lookup(LookupArgs),
+    /// Works through a shard of the distinct matchups, picking up where it left off.
+    Generate(GenerateArgs),
+    /// Checks the stored results, and optionally fixes them.
+    Audit(AuditArgs),
+    /// Loads results from a CSV file, like the one `export --format csv` writes.
+    Import(ImportArgs),
+    /// Every stored result.
+    Export,
+}
+
+#[derive(Args, Debug)]
+pub struct LookupArgs {
+    hero: String,
+    villain: String,
+
+    /// Calculate and save it if it isn't stored. Takes a while.
+    #[clap(short = 'c', long)]
+    calculate: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct GenerateArgs {
+    /// Which shard this process works on, starting at 0.
+    #[clap(short = 's', long, default_value_t = 0)]
+    shard: usize,
+
+    /// How many shards the work is split into.
+    #[clap(short = 'n', long, default_value_t = 1)]
+    shards: usize,
+
+    #[clap(short = 'b', long, default_value_t = HupJob::DEFAULT_BATCH_SIZE)]
+    batch: usize,
+
+    /// Stop after calculating this many results.
+    #[clap(short = 'l', long)]
+    limit: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    /// Calculate this many random matchups again and compare them with what's stored.
+    #[clap(short = 's', long)]
+    sample: Option<usize>,
+
+    /// Calculate every matchup again.
+    #[clap(short = 'a', long)]
+    all: bool,
+
+    /// Don't look for gaps.
+    #[clap(short = 'g', long)]
+    skip_gaps: bool,
+
+    /// Fix what was found.
+    #[clap(short = 'r', long)]
+    repair: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    csv: String,
+}
+
+/// # Errors
+///
+/// Passes on whatever went wrong with the cards or the database.
+pub fn run(command: HupCommand, config: &Config, format: Format) -> Result<String, PKError> {
+    match command {
+        HupCommand::Lookup(args) => render(&lookup(&args, config)?, format),
+        HupCommand::Generate(args) => render(&generate(&args, config)?, format),
+        HupCommand::Audit(args) => render(&audit(&args, config)?, format),
+        HupCommand::Import(args) => render(&import(&args, config)?, format),
+        HupCommand::Export => render(&export(config)?, format),
+    }
+}
+
+fn open(config: &Config) -> Result<Connection, PKError> {
+    Connection::open(&config.hups).map_err(|_| PKError::SqlError)
+}
+
+/// For the commands that only make sense on results that are already there. `Connection::open()`
+/// would happily create an empty file for a typo.
+fn open_existing(config: &Config) -> Result<Connection, PKError> {
+    let flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+    Connection::open_with_flags(&config.hups, flags)
+        .map_err(|e| PKError::StorageError(format!("can't open {}: {e}", config.hups)))
+}
+
+fn export(config: &Config) -> Result<HupExport, PKError> {
+    let conn = open_existing(config)?;
+    let hups =
+        HUPResult::select_all(&conn).map_err(|e| PKError::StorageError(format!("can't read {}: {e}", config.hups)))?;
+    Ok(HupExport(hups))
+}
+
+fn lookup(args: &LookupArgs, config: &Config) -> Result<HupResponse, PKError> {
+    let request = HupRequest {
+        hero: Two::from_str(&args.hero)?,
+        villain: Two::from_str(&args.villain)?,
+        calculate: args.calculate,
+    };
+    config.service()?.hup(&request)?.ok_or_else(|| {
+        PKError::StorageError(format!(
+            "{} vs {} isn't in {}. Pass --calculate to work it out.",
+            request.hero, request.villain, config.hups
+        ))
+    })
+}
+
+fn generate(args: &GenerateArgs, config: &Config) -> Result<Generated, PKError> {
+    let conn = open(config)?;
+    HupJob::prepare(&conn).map_err(|_| PKError::SqlError)?;
+
+    let mut job = HupJob::distinct(args.shard, args.shards)?.batch_size(args.batch);
+    if let Some(limit) = args.limit {
+        job = job.limit(limit);
+    }
+    let progress = job.run(&conn, |progress| eprintln!("{progress}"))?;
+    Ok(Generated::from(progress))
+}
+
+fn audit(args: &AuditArgs, config: &Config) -> Result<Audited, PKError> {
+    let recompute = if args.all {
+        Recompute::All
+    } else {
+        args.sample.map_or(Recompute::None, Recompute::Sample)
+    };
+    let mut audit = HupAudit::new().recompute(recompute);
+    if args.skip_gaps {
+        audit = audit.skip_gaps();
+    }
+
+    let conn = open_existing(config)?;
+    let report = audit.run(&conn)?;
+    let repair = if args.repair && !report.is_clean() {
+        Some(report.repair(&conn)?)
+    } else {
+        None
+    };
+    Ok(Audited { report, repair })
+}
+
+fn import(args: &ImportArgs, config: &Config) -> Result<Imported, PKError> {
+    let results = HUPResult::read_csv(&args.csv)?;
+    let conn = open(config)?;
+    HUPResult::create_table(&conn).map_err(|_| PKError::SqlError)?;
+
+    let mut imported = Imported {
+        read: results.len(),
+        ..Default::default()
+    };
+    for result in &results {
+        if HUPResult::insert(&conn, result).map_err(|_| PKError::SqlError)? {
+            imported.inserted += 1;
+        } else {
+            imported.skipped += 1;
+        }
+    }
+    Ok(imported)
+}
+
+impl Report for HupResponse {
+    fn header(&self) -> Vec<&'static str> {
+        vec![
+            "hero",
+            "villain",
+            "wins",
+            "losses",
+            "ties",
+            "win_percentage",
+            "loss_percentage",
+            "tie_percentage",
+            "calculated",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.odds.hero.to_string(),
+            self.odds.villain.to_string(),
+            self.odds.wins.to_string(),
+            self.odds.losses.to_string(),
+            self.odds.ties.to_string(),
+            percent(self.win_percentage),
+            percent(self.loss_percentage),
+            percent(self.tie_percentage),
+            self.calculated.to_string(),
+        ]]
+    }
+}
+
+/// `HupJobProgress` has a `Duration` in it, which doesn't say much as JSON.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Generated {
+    pub shard: usize,
+    pub shards: usize,
+    pub total: usize,
+    pub done: usize,
+    pub calculated: usize,
+    pub seconds: f64,
+    pub rate: f64,
+}
+
+impl From<HupJobProgress> for Generated {
+    fn from(progress: HupJobProgress) -> Self {
+        Generated {
+            shard: progress.shard,
+            shards: progress.shards,
+            total: progress.total,
+            done: progress.done,
+            calculated: progress.calculated,
+            seconds: progress.elapsed.as_secs_f64(),
+            rate: progress.rate(),
+        }
+    }
+}
+
+impl Display for Generated {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "shard {}/{}: {}/{} done, {} calculated in {:.1}s ({:.2}/s)",
+            self.shard, self.shards, self.done, self.total, self.calculated, self.seconds, self.rate
+        )
+    }
+}
+
+impl Report for Generated {
+    fn header(&self) -> Vec<&'static str> {
+        vec!["shard", "shards", "total", "done", "calculated", "seconds", "rate"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.shard.to_string(),
+            self.shards.to_string(),
+            self.total.to_string(),
+            self.done.to_string(),
+            self.calculated.to_string(),
+            format!("{:.1}", self.seconds),
+            format!("{:.2}", self.rate),
+        ]]
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Audited {
+    #[serde(flatten)]
+    pub report: HupAuditReport,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repair: Option<HupRepair>,
+}
+
+impl Display for Audited {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for issue in &self.report.issues {
+            writeln!(f, "{issue}")?;
+        }
+        write!(f, "{}", self.report)?;
+        if let Some(repair) = self.repair {
+            write!(f, "\nRepaired: {repair}")?;
+        }
+        Ok(())
+    }
+}
+
+/// One row for each issue.
+impl Report for Audited {
+    fn header(&self) -> Vec<&'static str> {
+        vec!["kind", "issue"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.report
+            .issues
+            .iter()
+            .map(|issue| vec![issue.kind().to_string(), issue.to_string()])
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Imported {
+    pub read: usize,
+    pub inserted: usize,
+    pub skipped: usize,
+}
+
+impl Display for Imported {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} read, {} inserted, {} already there",
+            self.read, self.inserted, self.skipped
+        )
+    }
+}
+
+impl Report for Imported {
+    fn header(&self) -> Vec<&'static str> {
+        vec!["read", "inserted", "skipped"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![vec![
+            self.read.to_string(),
+            self.inserted.to_string(),
+            self.skipped.to_string(),
+        ]]
+    }
+}
+
+/// The CSV is the same as `HUPResult::generate_csv_from_vector()` writes, so it can be read back
+/// in with `import`.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct HupExport(pub Vec<HUPResult>);
+
+impl Display for HupExport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, result) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{result}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Report for HupExport {
+    fn header(&self) -> Vec<&'static str> {
+        vec!["Higher", "Lower", "HigherWins", "LowerWins", "Ties"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.0
+            .iter()
+            .map(|result| {
+                vec![
+                    result.higher.as_u64().to_string(),
+                    result.lower.as_u64().to_string(),
+                    result.higher_wins.to_string(),
+                    result.lower_wins.to_string(),
+                    result.ties.to_string(),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod pk__hup_tests {
+    use super::*;
+    use pkcore::util::data::TestData;
+
+    /// A scratch database with the hand in it, that cleans up after itself.
+    struct Scratch(String);
+
+    impl Scratch {
+        fn new(name: &str) -> Scratch {
+            let scratch = Scratch::empty(name);
+            let conn = Connection::open(&scratch.0).unwrap();
+            HUPResult::create_table(&conn).unwrap();
+            HUPResult::insert(&conn, &TestData::the_hand_as_hup_result()).unwrap();
+            scratch
+        }
+
+        fn empty(name: &str) -> Scratch {
+            let path = std::env::temp_dir()
+                .join(format!("pk_{name}_{}.db", std::process::id()))
+                .to_string_lossy()
+                .to_string();
+            let _ = std::fs::remove_file(&path);
+            Scratch(path)
+        }
+
+        fn config(&self) -> Config {
+            Config::default().with_flags(Some(self.0.clone()), None, None)
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn lookup() {
+        let scratch = Scratch::new("lookup");
+        let command = |hero: &str| {
+            HupCommand::Lookup(LookupArgs {
+                hero: hero.to_string(),
+                villain: "6♦ 6♣".to_string(),
+                calculate: false,
+            })
+        };
+
+        assert_eq!(
+            "5♠ 5♥ vs 6♦ 6♣: 18.39% wins 79.73% losses 1.88% ties",
+            run(command("5♠ 5♥"), &scratch.config(), Format::Text).unwrap()
+        );
+        assert!(matches!(
+            run(command("A♠ A♥"), &scratch.config(), Format::Text),
+            Err(PKError::StorageError(_))
+        ));
+    }
+
+    #[test]
+    fn export__import() {
+        let from = Scratch::new("export");
+        let to = Scratch::empty("import");
+        let csv = std::env::temp_dir().join(format!("pk_export_{}.csv", std::process::id()));
+
+        let exported = run(HupCommand::Export, &from.config(), Format::Csv).unwrap();
+        std::fs::write(&csv, &exported).unwrap();
+        let import = || {
+            let args = ImportArgs {
+                csv: csv.to_string_lossy().to_string(),
+            };
+            run(HupCommand::Import(args), &to.config(), Format::Text).unwrap()
+        };
+        let first = import();
+        let second = import();
+        std::fs::remove_file(&csv).unwrap();
+
+        assert_eq!(
+            "Higher,Lower,HigherWins,LowerWins,Ties\n8797166764032,65544,1365284,314904,32116\n",
+            exported
+        );
+        assert_eq!("1 read, 1 inserted, 0 already there", first);
+        assert_eq!("1 read, 0 inserted, 1 already there", second);
+    }
+
+    #[test]
+    fn export__missing() {
+        let missing = Scratch::empty("missing");
+        let no_table = Scratch::empty("no_table");
+        Connection::open(&no_table.0).unwrap();
+
+        assert!(matches!(
+            run(HupCommand::Export, &missing.config(), Format::Csv),
+            Err(PKError::StorageError(_))
+        ));
+        assert!(!std::path::Path::new(&missing.0).exists());
+        assert!(matches!(
+            run(HupCommand::Export, &no_table.config(), Format::Csv),
+            Err(PKError::StorageError(_))
+        ));
+    }
+
+    #[test]
+    fn audit() {
+        let scratch = Scratch::new("audit");
+        let args = AuditArgs {
+            sample: None,
+            all: false,
+            skip_gaps: false,
+            repair: false,
+        };
+
+        assert_eq!(
+            "1 rows, 1 matchups, 0 recomputed: clean",
+            run(HupCommand::Audit(args), &scratch.config(), Format::Text).unwrap()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use crate::config::Config;
+use crate::output::Format;
+use clap::{Parser, Subcommand};
+use pkcore::PKError;
+
+mod analysis;
+mod bcm;
+mod config;
+mod hup;
+mod output;
+mod pluribus;
+mod repl;
+
+#[derive(Parser, Debug)]
+#[clap(name = "pk", author, version, about, long_about = None)]
+struct Cli {
+    /// How to write out the results.
+    #[clap(long, value_enum, global = true, default_value_t = Format::Text)]
+    format: Format,
+
+    /// A YAML file with the data paths. Defaults to `pk.yaml` if there is one.
+    #[clap(long, global = true)]
+    config: Option<String>,
+
+    /// The `SQLite` file with the heads up preflop results.
+    #[clap(long, global = true)]
+    hups: Option<String>,
+
+    /// The binary card map CSV.
+    #[clap(long, global = true)]
+    bcm: Option<String>,
+
+    /// The directory with the Pluribus logs.
+    #[clap(long, global = true)]
+    pluribus: Option<String>,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// The best hand out of five to seven cards.
+    Eval(analysis::EvalArgs),
+    /// Everybody's odds at every street of a hand.
+    Calc(analysis::CalcArgs),
+    /// A hand's equity against a range, like `QQ+,AK`.
+    Range(analysis::RangeArgs),
+    /// The heads up preflop results.
+    #[clap(subcommand)]
+    Hup(hup::HupCommand),
+    /// The Pluribus experiment logs.
+    #[clap(subcommand)]
+    Pluribus(pluribus::PluribusCommand),
+    /// The binary card map.
+    #[clap(subcommand)]
+    Bcm(bcm::BcmCommand),
+    /// Builds up a hand a piece at a time and asks questions about it as it goes.
+    Repl(repl::ReplArgs),
+}
+
+/// # pk
+///
+/// All of the examples that people actually use, as one binary, with the data paths in one
+/// place and output that can be piped into something else.
+///
+/// ```txt
+/// pk eval "A♠ K♠ Q♠ J♠ T♠ 9♠ 8♠"
+/// pk calc -g "HE: 6♠ 6♥ 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ 8♠"
+/// pk range "J♠ T♠" "QQ+,AK" -b "9♠ 8♦ 2♣" --format json
+/// pk hup lookup "A♠ A♥" "K♦ K♣" --hups data/hups.db
+/// pk hup export --format csv > hups.csv
+/// pk pluribus stats -p BTN
+/// pk repl saved_hand.yaml
+/// ```
+fn main() {
+    env_logger::init();
+    let cli = Cli::parse();
+
+    match run(cli) {
+        Ok(output) => println!("{output}"),
+        Err(e) => {
+            eprintln!("pk: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<String, PKError> {
+    let config = Config::load(cli.config.as_deref())?.with_flags(cli.hups, cli.bcm, cli.pluribus);
+    std::env::set_var(Config::ENV_BCM, &config.bcm);
+
+    match cli.command {
+        Command::Eval(args) => analysis::eval(&args, cli.format),
+        Command::Calc(args) => analysis::calc(&args, &config, cli.format),
+        Command::Range(args) => analysis::range(&args, &config, cli.format),
+        Command::Hup(command) => hup::run(command, &config, cli.format),
+        Command::Pluribus(command) => pluribus::run(command, &config, cli.format),
+        Command::Bcm(command) => bcm::run(command, &config, cli.format),
+        Command::Repl(args) => repl::run(&args, &config),
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod pk__main_tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn cli() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn cli__parse() {
+        let cli = Cli::parse_from(["pk", "range", "J♠ T♠", "QQ+,AK", "--format", "csv", "--hups", "x.db"]);
+
+        assert_eq!(Format::Csv, cli.format);
+        assert_eq!(Some("x.db".to_string()), cli.hups);
+        assert!(matches!(cli.command, Command::Range(_)));
+    }
+}
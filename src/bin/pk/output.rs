@@ -0,0 +1,94 @@
+use clap::ValueEnum;
+use csv::WriterBuilder;
+use pkcore::PKError;
+use serde::Serialize;
+use std::fmt::Display;
+
+/// How `pk` writes out what it finds. Text is for people, and JSON and CSV are for piping into
+/// something else.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    Csv,
+}
+
+/// Anything a subcommand can print. The text is the `Display`, the JSON is the serde
+/// serialization, and the CSV is the header and rows.
+pub trait Report: Display + Serialize {
+    fn header(&self) -> Vec<&'static str>;
+
+    fn rows(&self) -> Vec<Vec<String>>;
+}
+
+/// # Errors
+///
+/// Returns `PKError::Fubar` if the report can't be serialized.
+pub fn render<R: Report>(report: &R, format: Format) -> Result<String, PKError> {
+    match format {
+        Format::Text => Ok(report.to_string()),
+        Format::Json => serde_json::to_string_pretty(report).map_err(|_| PKError::Fubar),
+        Format::Csv => {
+            let mut writer = WriterBuilder::new().from_writer(Vec::new());
+            writer.write_record(report.header()).map_err(|_| PKError::Fubar)?;
+            for row in report.rows() {
+                writer.write_record(row).map_err(|_| PKError::Fubar)?;
+            }
+            let bytes = writer.into_inner().map_err(|_| PKError::Fubar)?;
+            String::from_utf8(bytes).map_err(|_| PKError::Fubar)
+        }
+    }
+}
+
+/// Percentages to two places, the same as everywhere else in the crate.
+pub fn percent(value: f32) -> String {
+    format!("{value:.2}")
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod pk__output_tests {
+    use super::*;
+    use std::fmt::Formatter;
+
+    #[derive(Serialize)]
+    struct Widgets {
+        name: String,
+        count: usize,
+    }
+
+    impl Display for Widgets {
+        fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{} {}", self.count, self.name)
+        }
+    }
+
+    impl Report for Widgets {
+        fn header(&self) -> Vec<&'static str> {
+            vec!["name", "count"]
+        }
+
+        fn rows(&self) -> Vec<Vec<String>> {
+            vec![vec![self.name.clone(), self.count.to_string()]]
+        }
+    }
+
+    #[test]
+    fn render() {
+        let widgets = Widgets {
+            name: "sprockets, large".to_string(),
+            count: 3,
+        };
+
+        assert_eq!("3 sprockets, large", super::render(&widgets, Format::Text).unwrap());
+        assert_eq!(
+            "{\n  \"name\": \"sprockets, large\",\n  \"count\": 3\n}",
+            super::render(&widgets, Format::Json).unwrap()
+        );
+        assert_eq!(
+            "name,count\n\"sprockets, large\",3\n",
+            super::render(&widgets, Format::Csv).unwrap()
+        );
+    }
+}
@@ -0,0 +1,377 @@
+use crate::config::Config;
+use crate::output::{render, Format, Report};
+use clap::{Args, Subcommand};
+use itertools::Itertools;
+use pkcore::analysis::hud::{Hud, HudFilter, PlayerStats};
+use pkcore::analysis::store::nubibus::pluribus::Pluribus;
+use pkcore::analysis::store::nubibus::Nubibus;
+use pkcore::play::Position6Max;
+use pkcore::tui::app;
+use pkcore::tui::replayer::Replayer;
+use pkcore::PKError;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use strum::IntoEnumIterator;
+
+#[derive(Subcommand, Debug)]
+pub enum PluribusCommand {
+    /// Plays the hands back and checks that everybody wins what the log says they did.
+    Replay(ReplayArgs),
+    /// The HUD stats for every player.
+    Stats(StatsArgs),
+    /// Steps through the hands in a log in the terminal, with the equities and outs as it goes.
+    Watch(WatchArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct ReplayArgs {
+    /// One log, like `data/pluribus/raw/sample_game_102.log`. Defaults to every log there is.
+    log: Option<String>,
+
+    /// Only this hand, with the table at the end of it.
+    #[clap(long)]
+    hand: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+pub struct WatchArgs {
+    /// One log, like `data/pluribus/raw/sample_game_102.log`. Defaults to the first one there is.
+    log: Option<String>,
+
+    /// Start at this hand.
+    #[clap(long)]
+    hand: Option<usize>,
+}
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    /// Only count hands played from these positions, like `BTN`.
+    #[clap(short = 'p', long)]
+    position: Vec<String>,
+
+    /// Only count hands from these sessions, like `102`.
+    #[clap(short = 's', long)]
+    session: Vec<usize>,
+}
+
+/// # Errors
+///
+/// Passes on anything wrong with the logs.
+pub fn run(command: PluribusCommand, config: &Config, format: Format) -> Result<String, PKError> {
+    match command {
+        PluribusCommand::Replay(args) => render(&replay(&args, config)?, format),
+        PluribusCommand::Stats(args) => render(&stats(&args, config)?, format),
+        PluribusCommand::Watch(args) => watch(&args, config),
+    }
+}
+
+fn replay(args: &ReplayArgs, config: &Config) -> Result<Replayed, PKError> {
+    let logs: Vec<PathBuf> = match &args.log {
+        Some(log) => vec![PathBuf::from(log)],
+        None => Pluribus::logs(&config.pluribus)?
+            .into_iter()
+            .map(|(_, path)| path)
+            .collect(),
+    };
+
+    let mut replayed = Replayed::default();
+    for path in logs {
+        let log = path
+            .file_name()
+            .map_or_else(String::new, |name| name.to_string_lossy().to_string());
+        for pluribus in Pluribus::from_log(&path.to_string_lossy())? {
+            if args.hand.is_some_and(|hand| hand != pluribus.index) {
+                continue;
+            }
+            let verified = Nubibus::verify(&pluribus);
+            if args.hand.is_some() {
+                replayed.table = Some(match &verified {
+                    Ok(nubibus) => nubibus.to_string(),
+                    Err(mismatch) => mismatch.to_string(),
+                });
+            }
+            replayed.hands.push(ReplayedHand::new(
+                &log,
+                &pluribus,
+                verified.err().map(|m| m.to_string()),
+            ));
+        }
+    }
+    Ok(replayed)
+}
+
+/// There's nothing to format once the terminal's been handed back, so `--format` is ignored.
+fn watch(args: &WatchArgs, config: &Config) -> Result<String, PKError> {
+    let log = match &args.log {
+        Some(log) => log.clone(),
+        None => Pluribus::logs(&config.pluribus)?
+            .first()
+            .map(|(_, path)| path.to_string_lossy().to_string())
+            .ok_or_else(|| PKError::StorageError(format!("No logs in {}", config.pluribus)))?,
+    };
+    let mut replayer = Replayer::from_pluribus_log(&log, config.service()?)?;
+    if let Some(hand) = args.hand {
+        let suffix = format!(" #{hand}");
+        let index = replayer
+            .hands()
+            .iter()
+            .position(|replay| replay.title.ends_with(&suffix))
+            .ok_or(PKError::InvalidPluribusIndex)?;
+        replayer.select(index);
+    }
+    app::run(&mut replayer).map_err(|e| PKError::StorageError(format!("terminal: {e}")))?;
+    Ok(String::new())
+}
+
+fn stats(args: &StatsArgs, config: &Config) -> Result<Stats, PKError> {
+    let mut filter = HudFilter::default();
+    for position in &args.position {
+        let position = Position6Max::iter()
+            .find(|p| p.to_string().eq_ignore_ascii_case(position))
+            .ok_or_else(|| PKError::StorageError(format!("Unknown position {position}")))?;
+        filter = filter.position(position);
+    }
+    for session in &args.session {
+        filter = filter.session(*session);
+    }
+
+    if !Path::new(&config.pluribus).is_dir() {
+        return Err(PKError::StorageError(format!("{} isn't a directory", config.pluribus)));
+    }
+    Ok(Stats::from(Hud::from_pluribus_logs(&config.pluribus, filter)?))
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct ReplayedHand {
+    pub log: String,
+    pub index: usize,
+    pub players: Vec<String>,
+    pub hole_cards: String,
+    pub board: String,
+    pub winnings: Vec<isize>,
+    /// Why the replay didn't match the log, if it didn't.
+    pub mismatch: Option<String>,
+}
+
+impl ReplayedHand {
+    fn new(log: &str, pluribus: &Pluribus, mismatch: Option<String>) -> ReplayedHand {
+        ReplayedHand {
+            log: log.to_string(),
+            index: pluribus.index,
+            players: pluribus.players.clone(),
+            hole_cards: pluribus.hole_cards.to_string(),
+            board: pluribus.board.to_string(),
+            winnings: pluribus.winnings.clone(),
+            mismatch,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
+pub struct Replayed {
+    pub hands: Vec<ReplayedHand>,
+    #[serde(skip)]
+    pub table: Option<String>,
+}
+
+impl Replayed {
+    #[must_use]
+    pub fn mismatches(&self) -> usize {
+        self.hands.iter().filter(|hand| hand.mismatch.is_some()).count()
+    }
+}
+
+/// Just the hands that didn't add up, or the table if there was only the one hand asked for.
+impl Display for Replayed {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(table) = &self.table {
+            writeln!(f, "{}", table.trim_start())?;
+        } else {
+            for hand in &self.hands {
+                if let Some(mismatch) = &hand.mismatch {
+                    writeln!(f, "{}: {mismatch}", hand.log)?;
+                }
+            }
+        }
+        write!(
+            f,
+            "{} hands replayed, {} mismatches",
+            self.hands.len(),
+            self.mismatches()
+        )
+    }
+}
+
+/// One row for each hand.
+impl Report for Replayed {
+    fn header(&self) -> Vec<&'static str> {
+        vec!["log", "index", "players", "hole_cards", "board", "winnings", "mismatch"]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.hands
+            .iter()
+            .map(|hand| {
+                vec![
+                    hand.log.clone(),
+                    hand.index.to_string(),
+                    hand.players.join("|"),
+                    hand.hole_cards.clone(),
+                    hand.board.clone(),
+                    hand.winnings.iter().join("|"),
+                    hand.mismatch.clone().unwrap_or_default(),
+                ]
+            })
+            .collect()
+    }
+}
+
+/// `PlayerStats` keeps raw counts, which aren't what anybody piping the stats somewhere wants.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct PlayerRow {
+    pub player: String,
+    pub hands: usize,
+    pub vpip: f64,
+    pub pfr: f64,
+    pub three_bet: f64,
+    pub fold_to_three_bet: f64,
+    pub cbet: f64,
+    pub wtsd: f64,
+    pub wsd: f64,
+    pub aggression_factor: f64,
+    pub bb_per_100: f64,
+}
+
+impl PlayerRow {
+    fn new(player: &str, stats: &PlayerStats) -> PlayerRow {
+        PlayerRow {
+            player: player.to_string(),
+            hands: stats.hands,
+            vpip: stats.vpip(),
+            pfr: stats.pfr(),
+            three_bet: stats.three_bet(),
+            fold_to_three_bet: stats.fold_to_three_bet(),
+            cbet: stats.cbet(),
+            wtsd: stats.wtsd(),
+            wsd: stats.wsd(),
+            aggression_factor: stats.aggression_factor(),
+            bb_per_100: stats.bb_per_100(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+#[serde(transparent)]
+pub struct Stats {
+    pub players: Vec<PlayerRow>,
+    #[serde(skip)]
+    pub hud: Hud,
+}
+
+impl From<Hud> for Stats {
+    fn from(hud: Hud) -> Self {
+        Stats {
+            players: hud
+                .players
+                .iter()
+                .map(|(player, stats)| PlayerRow::new(player, stats))
+                .collect(),
+            hud,
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.hud.to_string().trim_end())
+    }
+}
+
+impl Report for Stats {
+    fn header(&self) -> Vec<&'static str> {
+        vec![
+            "player",
+            "hands",
+            "vpip",
+            "pfr",
+            "three_bet",
+            "fold_to_three_bet",
+            "cbet",
+            "wtsd",
+            "wsd",
+            "aggression_factor",
+            "bb_per_100",
+        ]
+    }
+
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.players
+            .iter()
+            .map(|row| {
+                vec![
+                    row.player.clone(),
+                    row.hands.to_string(),
+                    format!("{:.1}", row.vpip),
+                    format!("{:.1}", row.pfr),
+                    format!("{:.1}", row.three_bet),
+                    format!("{:.1}", row.fold_to_three_bet),
+                    format!("{:.1}", row.cbet),
+                    format!("{:.1}", row.wtsd),
+                    format!("{:.1}", row.wsd),
+                    format!("{:.2}", row.aggression_factor),
+                    format!("{:.2}", row.bb_per_100),
+                ]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod pk__pluribus_tests {
+    use super::*;
+
+    const LOG: &str = "data/pluribus/raw/sample_game_100.log";
+
+    #[test]
+    fn replay__hand() {
+        let args = ReplayArgs {
+            log: Some(LOG.to_string()),
+            hand: Some(3),
+        };
+
+        let replayed = replay(&args, &Config::default()).unwrap();
+
+        assert_eq!(1, replayed.hands.len());
+        assert_eq!(0, replayed.mismatches());
+        assert!(replayed.to_string().starts_with("SEAT #"));
+        assert!(replayed.to_string().ends_with("1 hands replayed, 0 mismatches"));
+    }
+
+    #[test]
+    fn stats() {
+        let args = StatsArgs {
+            position: vec!["btn".to_string()],
+            session: vec![100],
+        };
+
+        let csv = run(PluribusCommand::Stats(args), &Config::default(), Format::Csv).unwrap();
+
+        assert_eq!(8, csv.lines().count());
+        assert!(csv.starts_with("player,hands,vpip"));
+    }
+
+    #[test]
+    fn stats__unknown_position() {
+        let args = StatsArgs {
+            position: vec!["DEALER".to_string()],
+            session: vec![],
+        };
+
+        assert!(matches!(
+            super::stats(&args, &Config::default()),
+            Err(PKError::StorageError(_))
+        ));
+    }
+}
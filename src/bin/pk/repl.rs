@@ -0,0 +1,80 @@
+use crate::config::Config;
+use clap::Args;
+use pkcore::repl::session::Session;
+use pkcore::repl::shell::Repl;
+use pkcore::PKError;
+use std::path::PathBuf;
+
+#[derive(Args, Debug)]
+pub struct ReplArgs {
+    /// A session saved with `save`, to pick up where it left off.
+    session: Option<String>,
+
+    /// Where to keep the command history. Defaults to `~/.pk_history`.
+    #[clap(long)]
+    history: Option<String>,
+
+    /// Don't read or write a history file.
+    #[clap(long)]
+    no_history: bool,
+}
+
+/// Like `pluribus watch`, there's nothing to format once it's done, so `--format` is ignored.
+///
+/// # Errors
+///
+/// Returns `PKError::StorageError` if the session can't be loaded or the terminal goes wrong.
+pub fn run(args: &ReplArgs, config: &Config) -> Result<String, PKError> {
+    let mut repl = Repl::new(config.service()?);
+    if let Some(path) = &args.session {
+        repl.session = Session::load(path)?;
+    }
+    repl.run(history(args))
+        .map_err(|e| PKError::StorageError(format!("terminal: {e}")))?;
+    Ok(String::new())
+}
+
+fn history(args: &ReplArgs) -> Option<PathBuf> {
+    if args.no_history {
+        return None;
+    }
+    match &args.history {
+        Some(path) => Some(PathBuf::from(path)),
+        None => std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".pk_history")),
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod pk__repl_tests {
+    use super::*;
+
+    #[test]
+    fn run__missing_session() {
+        let args = ReplArgs {
+            session: Some("data/nope.yaml".to_string()),
+            history: None,
+            no_history: true,
+        };
+
+        assert!(matches!(run(&args, &Config::default()), Err(PKError::StorageError(_))));
+    }
+
+    #[test]
+    fn history() {
+        let args = ReplArgs {
+            session: None,
+            history: Some("h.txt".to_string()),
+            no_history: false,
+        };
+
+        assert_eq!(Some(PathBuf::from("h.txt")), super::history(&args));
+        assert_eq!(
+            None,
+            super::history(&ReplArgs {
+                no_history: true,
+                ..args
+            })
+        );
+    }
+}
@@ -4,12 +4,17 @@ use crate::card::Card;
 use crate::card_number::CardNumber;
 use crate::rank::Rank;
 use crate::suit::Suit;
-use crate::util::random_ordering::RandomOrdering;
 use crate::{card, PKError, Pile, SuitShift, TheNuts};
 use indexmap::set::{IntoIter, Iter};
 use indexmap::IndexSet;
 use itertools::{Combinations, Itertools};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
 use rayon::iter::{IterBridge, ParallelBridge};
+use serde::de::Deserializer;
+use serde::ser::{Serialize, Serializer};
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::fmt;
 use std::fmt::Formatter;
@@ -22,12 +27,13 @@ use strum::IntoEnumIterator;
 /// 1. Cards should be saved in order.
 /// 2. Cards should be unique.
 /// 3. Cards should be legitimate cards. (No blanks)
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct Cards(IndexSet<Card>);
+///
+/// Like `Card` and `Two`, `Cards` serializes as its display string, so a shuffled deck can be
+/// saved and reloaded in exactly the same order.
+#[derive(Deserialize, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Cards(#[serde(deserialize_with = "deserialize_cards")] IndexSet<Card>);
 
 impl Cards {
-    const NUMBER_OF_SHUFFLES: u8 = 5;
-
     /// TODO: macro!
     #[must_use]
     pub fn deck() -> Cards {
@@ -315,10 +321,34 @@ impl Cards {
         shuffled
     }
 
+    /// Returns a copy of the `Cards` shuffled from a specific seed. The same seed will always
+    /// return the same order, which is what you want when you need to replay a hand, or write
+    /// a test against a shuffled deck.
+    #[must_use]
+    pub fn shuffle_seeded(&self, seed: u64) -> Cards {
+        self.shuffle_with(&mut StdRng::seed_from_u64(seed))
+    }
+
+    #[must_use]
+    pub fn shuffle_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Cards {
+        let mut shuffled = self.clone();
+        shuffled.shuffle_in_place_with(rng);
+        shuffled
+    }
+
     pub fn shuffle_in_place(&mut self) {
-        for _ in 0..Cards::NUMBER_OF_SHUFFLES {
-            self.0.sort_by(|_, _| rand::random::<RandomOrdering>().into());
-        }
+        self.shuffle_in_place_with(&mut rand::thread_rng());
+    }
+
+    /// The original version of the shuffle sorted the cards five times with a comparator that
+    /// returned a random `Ordering`. It sort of worked, but it wasn't a fair shuffle, and it
+    /// couldn't be repeated. Now it's a straight up
+    /// [Fisher–Yates](https://en.wikipedia.org/wiki/Fisher%E2%80%93Yates_shuffle) driven by
+    /// whatever random number generator you pass in.
+    pub fn shuffle_in_place_with<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        let mut v = self.0.iter().copied().collect::<Vec<Card>>();
+        v.shuffle(rng);
+        self.0 = v.into_iter().collect();
     }
 
     /// We have uncovered a defect with out sort function. Ideally, it should sort with a higher
@@ -372,6 +402,19 @@ impl Cards {
         self.0 = sorted.0;
     }
 
+    /// The cards the way the hand history formats write them: `PokerStars`, the ACPC logs, OHH
+    /// and the `hh` tables all go rank and then a lower case suit, like `Tc`.
+    #[must_use]
+    pub fn to_letters(&self) -> Vec<String> {
+        self.iter()
+            .map(|card| {
+                let index = card.get_letter_index();
+                let (rank, suit) = index.split_at(index.len() - 1);
+                format!("{rank}{}", suit.to_ascii_lowercase())
+            })
+            .collect()
+    }
+
     //region private functions
 
     fn map_by_rank(&self) -> HashMap<Rank, Cards> {
@@ -449,6 +492,27 @@ impl fmt::Display for Cards {
     }
 }
 
+impl Serialize for Cards {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct("Cards", &self.to_string())
+    }
+}
+
+fn deserialize_cards<'de, D>(deserializer: D) -> Result<IndexSet<Card>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let buf = String::deserialize(deserializer)?;
+
+    match Cards::from_str(buf.as_str()) {
+        Ok(cards) => Ok(cards.0),
+        Err(_) => Ok(IndexSet::default()),
+    }
+}
+
 impl From<&Card> for Cards {
     /// Turns out we already have a `TryFrom<Card>` implemented, but I want something similar.
     /// This will give us the contract that if it's blank it won't be inserted, which is fine.
@@ -669,6 +733,7 @@ impl TryFrom<Card> for Cards {
 #[allow(non_snake_case)]
 mod card_tests {
     use super::*;
+    use serde_test::{assert_tokens, Token};
 
     #[test]
     fn deck() {
@@ -906,6 +971,12 @@ mod card_tests {
         assert_eq!("7♠ 8♦ 7♦ 6♣", cards.to_string());
     }
 
+    #[test]
+    fn to_letters() {
+        assert_eq!(vec!["5c", "4c", "3c", "2c", "Ac"], wheel().to_letters());
+        assert!(Cards::default().to_letters().is_empty());
+    }
+
     #[test]
     fn sort_in_place() {
         let mut wheel = wheel();
@@ -915,6 +986,35 @@ mod card_tests {
         assert_eq!("A♣ 5♣ 4♣ 3♣ 2♣", wheel.to_string());
     }
 
+    #[test]
+    fn shuffle() {
+        let shuffled = Cards::deck().shuffle();
+
+        assert_eq!(52, shuffled.len());
+        assert_eq!(Cards::deck().sort(), shuffled.sort());
+    }
+
+    #[test]
+    fn shuffle_seeded() {
+        let first = Cards::deck().shuffle_seeded(1_066);
+        let second = Cards::deck().shuffle_seeded(1_066);
+
+        assert_eq!(first.to_string(), second.to_string());
+        assert_ne!(first.to_string(), Cards::deck().shuffle_seeded(1_067).to_string());
+        assert_ne!(first.to_string(), Cards::deck().to_string());
+        assert_eq!(Cards::deck().sort(), first.sort());
+    }
+
+    #[test]
+    fn shuffle_in_place_with() {
+        let mut rng = StdRng::seed_from_u64(1_066);
+        let mut cards = Cards::deck();
+
+        cards.shuffle_in_place_with(&mut rng);
+
+        assert_eq!(Cards::deck().shuffle_seeded(1_066).to_string(), cards.to_string());
+    }
+
     //region private function tests
 
     #[test]
@@ -961,6 +1061,24 @@ mod card_tests {
         assert!(Cards::from_str("5♣ 4♣ 3A 2♣ A♣").is_err());
     }
 
+    #[test]
+    fn serialize() {
+        assert_tokens(
+            &wheel(),
+            &[Token::NewtypeStruct { name: "Cards" }, Token::Str("5♣ 4♣ 3♣ 2♣ A♣")],
+        );
+    }
+
+    #[test]
+    fn serialize__keeps_order() {
+        let shuffled = Cards::deck().shuffle_seeded(42);
+
+        let json = serde_json::to_string(&shuffled).unwrap();
+        let back: Cards = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(shuffled.to_string(), back.to_string());
+    }
+
     #[test]
     fn into_iterator() {
         let kings = Cards::deck()
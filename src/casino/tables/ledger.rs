@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::slice::Iter;
+
+/// Every movement of chips on or off a `Table`, and between the players during a hand.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Transaction {
+    BuyIn {
+        seat: usize,
+        handle: String,
+        amount: usize,
+    },
+    TopUp {
+        seat: usize,
+        amount: usize,
+    },
+    CashOut {
+        seat: usize,
+        handle: String,
+        amount: usize,
+    },
+    Hand {
+        number: usize,
+        seat: usize,
+        put_in: usize,
+        won: usize,
+    },
+}
+
+impl Display for Transaction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transaction::BuyIn { seat, handle, amount } => write!(f, "Seat {seat}: {handle} buys in for {amount}"),
+            Transaction::TopUp { seat, amount } => write!(f, "Seat {seat}: tops up {amount}"),
+            Transaction::CashOut { seat, handle, amount } => {
+                write!(f, "Seat {seat}: {handle} cashes out {amount}")
+            }
+            Transaction::Hand {
+                number,
+                seat,
+                put_in,
+                won,
+            } => write!(f, "Hand #{number} Seat {seat}: put in {put_in} won {won}"),
+        }
+    }
+}
+
+/// The cashier's book for a `Table`.
+///
+/// Chips are never created or destroyed at a poker table. They come on with a buy in or a top up,
+/// move between the players during a hand, and leave with a cash out. If you add all that up you
+/// know exactly how many chips should be in front of every seat, and if that isn't what's actually
+/// there, somebody has some explaining to do.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Ledger(Vec<Transaction>);
+
+impl Ledger {
+    /// The number of chips that the ledger says should be in front of a seat.
+    #[must_use]
+    pub fn balance(&self, seat: usize) -> usize {
+        self.0.iter().fold(0, |balance, transaction| match transaction {
+            Transaction::BuyIn { seat: s, amount, .. } | Transaction::TopUp { seat: s, amount } if *s == seat => {
+                balance + amount
+            }
+            Transaction::CashOut { seat: s, amount, .. } if *s == seat => balance.saturating_sub(*amount),
+            Transaction::Hand {
+                seat: s, put_in, won, ..
+            } if *s == seat => (balance + won).saturating_sub(*put_in),
+            _ => balance,
+        })
+    }
+
+    /// All the chips brought to the table, with buy ins and top ups.
+    #[must_use]
+    pub fn bought_in(&self) -> usize {
+        self.0
+            .iter()
+            .map(|transaction| match transaction {
+                Transaction::BuyIn { amount, .. } | Transaction::TopUp { amount, .. } => *amount,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    #[must_use]
+    pub fn cashed_out(&self) -> usize {
+        self.0
+            .iter()
+            .map(|transaction| match transaction {
+                Transaction::CashOut { amount, .. } => *amount,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    /// Returns a description of every hand where the chips won don't add up to the chips put in.
+    #[must_use]
+    pub fn unbalanced_hands(&self) -> Vec<String> {
+        let mut totals: Vec<(usize, usize, usize)> = Vec::new();
+        for transaction in &self.0 {
+            if let Transaction::Hand {
+                number, put_in, won, ..
+            } = transaction
+            {
+                match totals.iter_mut().find(|(n, _, _)| n == number) {
+                    Some(total) => {
+                        total.1 += put_in;
+                        total.2 += won;
+                    }
+                    None => totals.push((*number, *put_in, *won)),
+                }
+            }
+        }
+        totals
+            .iter()
+            .filter(|(_, put_in, won)| put_in != won)
+            .map(|(number, put_in, won)| format!("Hand #{number}: {put_in} put in but {won} won"))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, Transaction> {
+        self.0.iter()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn push(&mut self, transaction: Transaction) {
+        self.0.push(transaction);
+    }
+}
+
+impl Display for Ledger {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for transaction in &self.0 {
+            writeln!(f, "{transaction}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod casino__tables__ledger_tests {
+    use super::*;
+
+    fn ledger() -> Ledger {
+        let mut ledger = Ledger::default();
+        ledger.push(Transaction::BuyIn {
+            seat: 0,
+            handle: "Elmer".to_string(),
+            amount: 1_000,
+        });
+        ledger.push(Transaction::BuyIn {
+            seat: 3,
+            handle: "Bugsy".to_string(),
+            amount: 800,
+        });
+        ledger.push(Transaction::Hand {
+            number: 1,
+            seat: 0,
+            put_in: 300,
+            won: 0,
+        });
+        ledger.push(Transaction::Hand {
+            number: 1,
+            seat: 3,
+            put_in: 300,
+            won: 600,
+        });
+        ledger.push(Transaction::TopUp { seat: 0, amount: 300 });
+        ledger
+    }
+
+    #[test]
+    fn balance() {
+        let ledger = ledger();
+
+        assert_eq!(1_000, ledger.balance(0));
+        assert_eq!(1_100, ledger.balance(3));
+        assert_eq!(0, ledger.balance(1));
+        assert_eq!(2_100, ledger.bought_in());
+    }
+
+    #[test]
+    fn balance__cash_out() {
+        let mut ledger = ledger();
+        ledger.push(Transaction::CashOut {
+            seat: 3,
+            handle: "Bugsy".to_string(),
+            amount: 1_100,
+        });
+
+        assert_eq!(0, ledger.balance(3));
+        assert_eq!(1_100, ledger.cashed_out());
+    }
+
+    #[test]
+    fn unbalanced_hands() {
+        let mut ledger = ledger();
+        assert!(ledger.unbalanced_hands().is_empty());
+
+        ledger.push(Transaction::Hand {
+            number: 2,
+            seat: 3,
+            put_in: 0,
+            won: 50,
+        });
+
+        assert_eq!(vec!["Hand #2: 0 put in but 50 won"], ledger.unbalanced_hands());
+    }
+}
@@ -1 +1,739 @@
+use crate::casino::cashier::chips::Chips;
+use crate::casino::players::player::Player;
+use crate::casino::tables::ledger::{Ledger, Transaction};
+use crate::play::agents::{Agent, Decision, Situation};
+use crate::play::dealer::Dealer;
+use crate::play::events::{EventStream, SeatState, TableEvent, TableState};
+use crate::play::run_it::RunIt;
+use crate::play::Position6Max;
+use crate::{Betting, PKError};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::{Display, Formatter};
 
+/// A `Player` sitting at a `Table`. The chips in `stack` are the ones in play. The chips still
+/// in the player's own `chips` are their bankroll, back at the cage.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Seat {
+    pub player: Player,
+    pub stack: Chips,
+    pub sitting_out: bool,
+}
+
+impl Display for Seat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let status = if self.sitting_out { " (sitting out)" } else { "" };
+        write!(f, "{} {}{status}", self.player.handle, self.stack)
+    }
+}
+
+/// # A cash game table
+///
+/// Players sit down in numbered seats, buy in for somewhere between the table's minimum and
+/// maximum, and play hands until they feel like leaving. Between hands they can sit out, top up
+/// their stack back towards the maximum, or cash out and go home. Every chip that moves, moves
+/// through the `Betting` trait, and every move gets written down in the `Ledger`, so at any point
+/// the table can be audited.
+///
+/// The hands themselves are played by `Agent`s, dealt from a `Dealer`, and recorded as an
+/// `EventStream`, so any hand of a session can be replayed later.
+///
+/// When everybody left in a hand is all in before the river, the rest of the board is run
+/// `run_it` times. Each run is recorded as a `TableEvent::RunOut`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Table {
+    pub small_blind: usize,
+    pub big_blind: usize,
+    pub min_buy_in: usize,
+    pub max_buy_in: usize,
+    /// The seat with the dealer button. It moves to the next seat dealt in before every hand.
+    pub button: usize,
+    pub hands_played: usize,
+    /// How many times the board is run when everybody is all in. Once, unless the table says
+    /// otherwise.
+    pub run_it: usize,
+    pub ledger: Ledger,
+    seats: Vec<Option<Seat>>,
+}
+
+impl Table {
+    pub const MIN_BUY_IN_BBS: usize = 40;
+    pub const MAX_BUY_IN_BBS: usize = 100;
+
+    /// A table with the standard buy in range of 40 to 100 big blinds.
+    #[must_use]
+    pub fn new(seats: usize, small_blind: usize, big_blind: usize) -> Self {
+        Table {
+            small_blind,
+            big_blind,
+            min_buy_in: big_blind * Table::MIN_BUY_IN_BBS,
+            max_buy_in: big_blind * Table::MAX_BUY_IN_BBS,
+            button: seats.saturating_sub(1),
+            hands_played: 0,
+            run_it: 1,
+            ledger: Ledger::default(),
+            seats: vec![None; seats],
+        }
+    }
+
+    #[must_use]
+    pub fn with_buy_ins(mut self, min: usize, max: usize) -> Self {
+        self.min_buy_in = min;
+        self.max_buy_in = max;
+        self
+    }
+
+    #[must_use]
+    pub fn with_run_it(mut self, times: usize) -> Self {
+        self.run_it = times;
+        self
+    }
+
+    #[must_use]
+    pub fn seat(&self, seat: usize) -> Option<&Seat> {
+        self.seats.get(seat).and_then(Option::as_ref)
+    }
+
+    #[must_use]
+    pub fn seat_count(&self) -> usize {
+        self.seats.len()
+    }
+
+    /// The numbers of every seat with a player in it.
+    #[must_use]
+    pub fn occupied(&self) -> Vec<usize> {
+        (0..self.seats.len()).filter(|i| self.seat(*i).is_some()).collect()
+    }
+
+    /// The seats that will be dealt into the next hand: occupied, not sitting out, and with
+    /// chips.
+    #[must_use]
+    pub fn dealt_in(&self) -> Vec<usize> {
+        (0..self.seats.len())
+            .filter(|i| {
+                self.seat(*i)
+                    .is_some_and(|seat| !seat.sitting_out && !seat.stack.is_empty())
+            })
+            .collect()
+    }
+
+    /// Every chip in play at the table.
+    #[must_use]
+    pub fn chips_on_table(&self) -> usize {
+        self.seats.iter().flatten().map(|seat| seat.stack.size()).sum()
+    }
+
+    /// Sits a player down, moving `buy_in` chips from their bankroll to the table.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::InvalidIndex` if the seat doesn't exist.
+    /// * `PKError::SeatTaken` if somebody is already sitting there.
+    /// * `PKError::InvalidBuyIn` if the buy in is outside of the table's limits.
+    /// * `PKError::InsufficientChips` if the player can't cover the buy in.
+    pub fn sit(&mut self, seat: usize, mut player: Player, buy_in: usize) -> Result<(), PKError> {
+        match self.seats.get(seat) {
+            None => return Err(PKError::InvalidIndex),
+            Some(Some(_)) => return Err(PKError::SeatTaken),
+            Some(None) => {}
+        }
+        if buy_in < self.min_buy_in || buy_in > self.max_buy_in {
+            return Err(PKError::InvalidBuyIn);
+        }
+        let stack = player.chips.bet(buy_in)?;
+        self.ledger.push(Transaction::BuyIn {
+            seat,
+            handle: player.handle.clone(),
+            amount: buy_in,
+        });
+        self.seats[seat] = Some(Seat {
+            player,
+            stack,
+            sitting_out: false,
+        });
+        Ok(())
+    }
+
+    /// Adds chips from the player's bankroll to their stack. Since hands are played one at a
+    /// time, this can only ever happen between hands. A stack can't be topped up past the
+    /// maximum buy in.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::InvalidIndex` if nobody is sitting in the seat.
+    /// * `PKError::InvalidBuyIn` if the top up would take the stack over the maximum buy in.
+    /// * `PKError::InsufficientChips` if the player can't cover it.
+    pub fn top_up(&mut self, seat: usize, amount: usize) -> Result<(), PKError> {
+        let max = self.max_buy_in;
+        let s = self.seat_mut(seat)?;
+        if s.stack.size() + amount > max {
+            return Err(PKError::InvalidBuyIn);
+        }
+        let chips = s.player.chips.bet(amount)?;
+        s.stack.wins(chips);
+        self.ledger.push(Transaction::TopUp { seat, amount });
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidIndex` if nobody is sitting in the seat.
+    pub fn sit_out(&mut self, seat: usize) -> Result<(), PKError> {
+        self.seat_mut(seat)?.sitting_out = true;
+        Ok(())
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidIndex` if nobody is sitting in the seat.
+    pub fn sit_in(&mut self, seat: usize) -> Result<(), PKError> {
+        self.seat_mut(seat)?.sitting_out = false;
+        Ok(())
+    }
+
+    /// The player cashes out, taking their stack back to their bankroll, and leaves the table.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidIndex` if nobody is sitting in the seat.
+    pub fn leave(&mut self, seat: usize) -> Result<Player, PKError> {
+        let Some(Seat {
+            mut player, mut stack, ..
+        }) = self.seats.get_mut(seat).and_then(Option::take)
+        else {
+            return Err(PKError::InvalidIndex);
+        };
+        let amount = stack.size();
+        if let Ok(chips) = stack.all_in() {
+            player.chips.wins(chips);
+        }
+        self.ledger.push(Transaction::CashOut {
+            seat,
+            handle: player.handle.clone(),
+            amount,
+        });
+        Ok(player)
+    }
+
+    /// Checks the chips at the table against the ledger. Returns a description of every
+    /// problem found, so an empty `Vec` means the books balance.
+    #[must_use]
+    pub fn audit(&self) -> Vec<String> {
+        let mut problems = self.ledger.unbalanced_hands();
+        for (i, seat) in self.seats.iter().enumerate() {
+            let expected = self.ledger.balance(i);
+            let actual = seat.as_ref().map(|seat| seat.stack.size()).unwrap_or_default();
+            if expected != actual {
+                problems.push(format!("Seat {i}: ledger says {expected} but there are {actual}"));
+            }
+        }
+        let expected = self.ledger.bought_in().saturating_sub(self.ledger.cashed_out());
+        if expected != self.chips_on_table() {
+            problems.push(format!(
+                "Table: ledger says {expected} but there are {}",
+                self.chips_on_table()
+            ));
+        }
+        problems
+    }
+
+    /// Plays `hands` hands, each one dealt from a `Dealer` seeded from `seed`, so the same seed
+    /// and the same agents will always play out the same session. Stops early if there aren't
+    /// enough players left with chips.
+    ///
+    /// `agents` is indexed by seat number.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `play_hand()` other than `PKError::NotEnoughHands`.
+    pub fn session(
+        &mut self,
+        hands: usize,
+        seed: u64,
+        agents: &mut [Box<dyn Agent>],
+    ) -> Result<Vec<EventStream>, PKError> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut streams = Vec::with_capacity(hands);
+        for _ in 0..hands {
+            match self.play_hand(&Dealer::seeded(rng.gen()), agents) {
+                Ok(stream) => streams.push(stream),
+                Err(PKError::NotEnoughHands) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(streams)
+    }
+
+    /// Plays a single hand with everyone who is dealt in, and returns the record of it.
+    ///
+    /// Seats in the returned `EventStream` are in the order the hand is played, starting with the
+    /// small blind, the same way that the Pluribus logs list their players. Heads up, the button
+    /// posts the small blind.
+    ///
+    /// If an agent makes a decision that isn't legal, they check if they can and fold if they
+    /// can't.
+    ///
+    /// If the table runs it more than once, none of the runs come from the dealer's deck, the
+    /// first one included. Every run is dealt by `RunIt::deal()` with a random number generator
+    /// seeded from the dealer's seed, so playing the hand again with the same `Dealer` and
+    /// `run_it` gives the same runs, but the dealer's `board()` on its own won't match them.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::NotEnoughHands` if fewer than two players are dealt in.
+    /// * `PKError::InvalidIndex` if there's no agent for a seat that's dealt in.
+    /// * `PKError::TooManyHands` if the dealer's deck can't cover everybody.
+    pub fn play_hand(&mut self, dealer: &Dealer, agents: &mut [Box<dyn Agent>]) -> Result<EventStream, PKError> {
+        let dealt_in = self.dealt_in();
+        if dealt_in.len() < 2 {
+            return Err(PKError::NotEnoughHands);
+        }
+        if dealt_in.iter().any(|seat| *seat >= agents.len()) {
+            return Err(PKError::InvalidIndex);
+        }
+
+        self.button = dealt_in
+            .iter()
+            .copied()
+            .find(|seat| *seat > self.button)
+            .unwrap_or(dealt_in[0]);
+        let button = dealt_in
+            .iter()
+            .position(|seat| *seat == self.button)
+            .unwrap_or_default();
+        let start = if dealt_in.len() == 2 { button } else { button + 1 };
+        let players = (0..dealt_in.len())
+            .map(|i| dealt_in[(start + i) % dealt_in.len()])
+            .collect::<Vec<usize>>();
+
+        let stream = EventStream::new(
+            players
+                .iter()
+                .filter_map(|seat| self.seat(*seat))
+                .map(|seat| SeatState::new(&seat.player.handle, seat.stack.size()))
+                .collect(),
+        );
+        let mut hand = Hand {
+            state: TableState::new(stream.seats.clone()),
+            stream,
+            pot: Chips::default(),
+            players,
+        };
+
+        for (i, cards) in dealer.hole_cards(hand.players.len())?.iter().enumerate() {
+            self.push(&mut hand, TableEvent::Deal { seat: i, cards: *cards })?;
+        }
+        for (i, blind) in [self.small_blind, self.big_blind].iter().enumerate() {
+            let amount = (*blind).min(hand.state.seats[i].stack);
+            if amount > 0 {
+                self.push(&mut hand, TableEvent::Post { seat: i, amount })?;
+            }
+        }
+
+        let count = hand.players.len();
+        self.betting_round(&mut hand, agents, if count == 2 { 0 } else { 2 % count })?;
+
+        let board = dealer.board(count)?;
+        let streets = [
+            vec![board.flop.first(), board.flop.second(), board.flop.third()],
+            vec![board.turn],
+            vec![board.river],
+        ];
+        for street in streets {
+            if hand.state.in_hand_count() < 2 {
+                break;
+            }
+            if self.run_it > 1 && Table::all_in(&hand.state) {
+                let mut rng = StdRng::seed_from_u64(dealer.seed.unwrap_or_default());
+                for run in RunIt::deal(&hand.state, self.run_it, &mut rng)?.runs {
+                    self.push(&mut hand, TableEvent::RunOut { board: run.board })?;
+                }
+                break;
+            }
+            for card in street {
+                self.push(&mut hand, TableEvent::BoardCard { card })?;
+            }
+            self.betting_round(&mut hand, agents, usize::from(count == 2))?;
+        }
+
+        for (i, amount) in RunIt::showdown(&hand.state)?.iter().enumerate() {
+            if *amount > 0 {
+                self.push(
+                    &mut hand,
+                    TableEvent::Award {
+                        seat: i,
+                        amount: *amount,
+                    },
+                )?;
+            }
+        }
+
+        self.hands_played += 1;
+        for (i, seat) in hand.players.iter().enumerate() {
+            self.ledger.push(Transaction::Hand {
+                number: self.hands_played,
+                seat: *seat,
+                put_in: hand.state.seats[i].in_pot,
+                won: hand.state.seats[i].won,
+            });
+        }
+
+        Ok(hand.stream)
+    }
+
+    /// Goes around the table, starting with the hand seat `first`, until everyone still in the
+    /// hand has acted and matched the biggest bet, or is all in.
+    fn betting_round(&mut self, hand: &mut Hand, agents: &mut [Box<dyn Agent>], first: usize) -> Result<(), PKError> {
+        let count = hand.players.len();
+        let mut acted = vec![false; count];
+        let mut min_raise = self.big_blind;
+        let mut i = first;
+
+        loop {
+            if hand.state.in_hand_count() < 2 {
+                return Ok(());
+            }
+            let to_match = hand.state.to_match();
+            let pending = |state: &TableState, acted: &[bool], i: usize| {
+                let seat = &state.seats[i];
+                seat.is_active() && seat.stack > 0 && (!acted[i] || seat.in_round < to_match)
+            };
+            let Some(next) = (0..count)
+                .map(|offset| (i + offset) % count)
+                .find(|i| pending(&hand.state, &acted, *i))
+            else {
+                return Ok(());
+            };
+            // Nobody to bet against.
+            let others = (0..count)
+                .filter(|j| *j != next && hand.state.seats[*j].is_active() && hand.state.seats[*j].stack > 0)
+                .count();
+            if others == 0 && hand.state.seats[next].in_round >= to_match {
+                return Ok(());
+            }
+
+            let seat = &hand.state.seats[next];
+            let situation = Situation {
+                phase: hand.state.phase,
+                position: Table::position(next, count),
+                hand: seat.cards,
+                board: hand.state.board.clone(),
+                pot: hand.state.pot,
+                to_call: to_match - seat.in_round,
+                min_raise,
+                stack: seat.stack,
+                big_blind: self.big_blind,
+                opponents: hand.state.in_hand_count() - 1,
+            };
+
+            let mut decision = agents[hand.players[next]].act(&situation);
+            if !situation.is_legal(&decision) {
+                decision = situation.check_or_fold();
+            }
+            let event = match decision {
+                Decision::Fold => TableEvent::Fold { seat: next },
+                Decision::Check => TableEvent::Bet { seat: next, amount: 0 },
+                Decision::Call => TableEvent::Bet {
+                    seat: next,
+                    amount: situation.to_call,
+                },
+                Decision::Raise(amount) => TableEvent::Bet { seat: next, amount },
+                Decision::AllIn => TableEvent::Bet {
+                    seat: next,
+                    amount: situation.stack,
+                },
+            };
+            if let TableEvent::Bet { amount, .. } = event {
+                let raise = amount.saturating_sub(situation.to_call);
+                if raise >= min_raise {
+                    min_raise = raise;
+                    acted = vec![false; count];
+                }
+            }
+            self.push(hand, event)?;
+            acted[next] = true;
+            i = (next + 1) % count;
+        }
+    }
+
+    /// Records the event, and moves the chips it involves.
+    fn push(&mut self, hand: &mut Hand, event: TableEvent) -> Result<(), PKError> {
+        hand.state.apply(&event)?;
+        match event {
+            TableEvent::Post { seat, amount } | TableEvent::Bet { seat, amount } => {
+                let chips = self.seat_mut(hand.players[seat])?.stack.bet(amount)?;
+                hand.pot.wins(chips);
+            }
+            TableEvent::Award { seat, amount } => {
+                let chips = hand.pot.bet(amount)?;
+                self.seat_mut(hand.players[seat])?.stack.wins(chips);
+            }
+            _ => {}
+        }
+        hand.stream.push(event)
+    }
+
+    /// Nobody left in the hand can bet, because at most one of them has any chips left.
+    fn all_in(state: &TableState) -> bool {
+        state
+            .seats
+            .iter()
+            .filter(|seat| seat.is_active() && seat.stack > 0)
+            .count()
+            < 2
+    }
+
+    /// The position of a seat in a hand, counting from the small blind.
+    fn position(i: usize, count: usize) -> Position6Max {
+        match (i, count - i) {
+            (0, _) => Position6Max::SB,
+            (1, _) => Position6Max::BB,
+            (_, 1) => Position6Max::BTN,
+            (_, 2) => Position6Max::CO,
+            (_, 3) => Position6Max::MP,
+            _ => Position6Max::UTG,
+        }
+    }
+
+    fn seat_mut(&mut self, seat: usize) -> Result<&mut Seat, PKError> {
+        self.seats
+            .get_mut(seat)
+            .and_then(Option::as_mut)
+            .ok_or(PKError::InvalidIndex)
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}/{} NL, buy in {}-{}, {} hands played",
+            self.small_blind, self.big_blind, self.min_buy_in, self.max_buy_in, self.hands_played
+        )?;
+        for (i, seat) in self.seats.iter().enumerate() {
+            let button = if i == self.button { " (button)" } else { "" };
+            match seat {
+                Some(seat) => writeln!(f, "  Seat {i}: {seat}{button}")?,
+                None => writeln!(f, "  Seat {i}: empty")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The working state of a hand while it's being played.
+struct Hand {
+    stream: EventStream,
+    state: TableState,
+    /// The chips in the middle of the table.
+    pot: Chips,
+    /// The table seat for each seat in the hand.
+    players: Vec<usize>,
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod casino__tables__table_tests {
+    use super::*;
+    use crate::play::agents::calling_station::CallingStation;
+    use crate::play::agents::push_fold::PushFold;
+    use crate::play::agents::random::RandomBot;
+    use crate::play::agents::tight_aggressive::TightAggressive;
+
+    fn table() -> Table {
+        let mut table = Table::new(6, 50, 100);
+        table
+            .sit(0, Player::new_with_chips("Elmer".to_string(), 50_000), 10_000)
+            .unwrap();
+        table
+            .sit(2, Player::new_with_chips("Bugsy".to_string(), 50_000), 10_000)
+            .unwrap();
+        table
+            .sit(3, Player::new_with_chips("Daffy".to_string(), 50_000), 4_000)
+            .unwrap();
+        table
+    }
+
+    fn agents() -> Vec<Box<dyn Agent>> {
+        vec![
+            Box::new(TightAggressive::new(1)),
+            Box::new(RandomBot::new(2)),
+            Box::new(CallingStation::new(3)),
+            Box::new(PushFold::new(4)),
+            Box::new(RandomBot::new(5)),
+            Box::new(TightAggressive::new(6)),
+        ]
+    }
+
+    #[test]
+    fn sit() {
+        let table = table();
+
+        assert_eq!(vec![0, 2, 3], table.occupied());
+        assert_eq!(40_000, table.seat(0).unwrap().player.chips.size());
+        assert_eq!(10_000, table.seat(0).unwrap().stack.size());
+        assert_eq!(24_000, table.chips_on_table());
+        assert!(table.audit().is_empty());
+    }
+
+    #[test]
+    fn sit__invalid() {
+        let mut table = table();
+        let player = || Player::new_with_chips("Porky".to_string(), 5_000);
+
+        assert_eq!(PKError::SeatTaken, table.sit(0, player(), 5_000).unwrap_err());
+        assert_eq!(PKError::InvalidIndex, table.sit(6, player(), 5_000).unwrap_err());
+        assert_eq!(PKError::InvalidBuyIn, table.sit(1, player(), 3_999).unwrap_err());
+        assert_eq!(PKError::InvalidBuyIn, table.sit(1, player(), 10_001).unwrap_err());
+        assert_eq!(
+            PKError::InsufficientChips,
+            table
+                .sit(1, Player::new_with_chips("Porky".to_string(), 4_000), 5_000)
+                .unwrap_err()
+        );
+        assert_eq!(vec![0, 2, 3], table.occupied());
+    }
+
+    #[test]
+    fn top_up() {
+        let mut table = table();
+
+        table.top_up(3, 6_000).unwrap();
+
+        assert_eq!(10_000, table.seat(3).unwrap().stack.size());
+        assert_eq!(PKError::InvalidBuyIn, table.top_up(3, 1).unwrap_err());
+        assert_eq!(PKError::InvalidIndex, table.top_up(1, 1).unwrap_err());
+        assert!(table.audit().is_empty());
+    }
+
+    #[test]
+    fn sit_out() {
+        let mut table = table();
+
+        table.sit_out(2).unwrap();
+        assert_eq!(vec![0, 3], table.dealt_in());
+
+        table.sit_in(2).unwrap();
+        assert_eq!(vec![0, 2, 3], table.dealt_in());
+    }
+
+    #[test]
+    fn leave() {
+        let mut table = table();
+
+        let player = table.leave(2).unwrap();
+
+        assert_eq!(50_000, player.chips.size());
+        assert_eq!(vec![0, 3], table.occupied());
+        assert_eq!(PKError::InvalidIndex, table.leave(2).unwrap_err());
+        assert!(table.audit().is_empty());
+    }
+
+    #[test]
+    fn play_hand() {
+        let mut table = table();
+
+        let stream = table.play_hand(&Dealer::seeded(42), &mut agents()).unwrap();
+        let state = stream.final_state().unwrap();
+
+        assert_eq!(3, stream.seats.len());
+        assert_eq!(0, table.button);
+        assert_eq!(24_000, state.total_chips());
+        assert_eq!(0, state.pot);
+        assert_eq!(24_000, table.chips_on_table());
+        assert!(table.audit().is_empty());
+    }
+
+    struct Shover;
+
+    impl Agent for Shover {
+        fn name(&self) -> String {
+            "Shover".to_string()
+        }
+
+        fn act(&mut self, _situation: &Situation) -> Decision {
+            Decision::AllIn
+        }
+    }
+
+    #[test]
+    fn play_hand__run_it_twice() {
+        let mut table = table().with_run_it(2);
+        let mut agents: Vec<Box<dyn Agent>> = (0..4).map(|_| Box::new(Shover) as Box<dyn Agent>).collect();
+
+        let stream = table.play_hand(&Dealer::seeded(42), &mut agents).unwrap();
+        let state = stream.final_state().unwrap();
+        let runouts = stream
+            .events
+            .iter()
+            .filter(|event| matches!(event, TableEvent::RunOut { .. }))
+            .count();
+
+        assert_eq!(2, runouts);
+        assert_eq!(2, state.runouts.len());
+        assert!(state.board.is_empty());
+        assert_eq!(0, state.pot);
+        assert_eq!(24_000, table.chips_on_table());
+        assert!(table.audit().is_empty());
+    }
+
+    #[test]
+    fn play_hand__not_enough_players() {
+        let mut table = table();
+        table.sit_out(0).unwrap();
+        table.sit_out(2).unwrap();
+
+        assert_eq!(
+            PKError::NotEnoughHands,
+            table.play_hand(&Dealer::seeded(42), &mut agents()).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn session() {
+        let mut table = table();
+        table
+            .sit(5, Player::new_with_chips("Porky".to_string(), 50_000), 7_500)
+            .unwrap();
+        let mut agents = agents();
+
+        let streams = table.session(50, 1_066, &mut agents).unwrap();
+        assert!(table.audit().is_empty());
+
+        let elmer = table.leave(0).unwrap();
+        table
+            .sit(1, Player::new_with_chips("Tweety".to_string(), 10_000), 10_000)
+            .unwrap();
+        let more = table.session(50, 1_067, &mut agents).unwrap();
+
+        assert!(!streams.is_empty());
+        assert_eq!(streams.len() + more.len(), table.hands_played);
+        assert_eq!(
+            table.ledger.bought_in(),
+            table.chips_on_table() + table.ledger.cashed_out()
+        );
+        assert_eq!(40_000 + table.ledger.cashed_out(), elmer.chips.size());
+        assert!(table.audit().is_empty(), "{:?}", table.audit());
+    }
+
+    #[test]
+    fn session__deterministic() {
+        let mut first = table();
+        let mut second = table();
+
+        first.session(20, 7, &mut agents()).unwrap();
+        second.session(20, 7, &mut agents()).unwrap();
+
+        assert_eq!(first.ledger, second.ledger);
+        assert_eq!(first.to_string(), second.to_string());
+    }
+
+    #[test]
+    fn position() {
+        assert_eq!(Position6Max::SB, Table::position(0, 2));
+        assert_eq!(Position6Max::BB, Table::position(1, 2));
+        assert_eq!(Position6Max::BTN, Table::position(2, 3));
+        assert_eq!(Position6Max::UTG, Table::position(2, 6));
+        assert_eq!(Position6Max::CO, Table::position(4, 6));
+    }
+}
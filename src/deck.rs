@@ -1,6 +1,7 @@
 use crate::card::Card;
 use crate::cards::Cards;
 use itertools::{Combinations, Itertools};
+use rand::Rng;
 use rayon::prelude::*;
 use rayon::slice::Iter;
 use std::array::IntoIter;
@@ -112,6 +113,17 @@ impl Deck {
         cards.shuffle_in_place();
         cards
     }
+
+    /// A freshly shuffled deck that will be in the same order every time for the same seed.
+    #[must_use]
+    pub fn poker_cards_shuffled_seeded(seed: u64) -> Cards {
+        Deck::poker_cards().shuffle_seeded(seed)
+    }
+
+    #[must_use]
+    pub fn poker_cards_shuffled_with<R: Rng + ?Sized>(rng: &mut R) -> Cards {
+        Deck::poker_cards().shuffle_with(rng)
+    }
 }
 
 #[cfg(test)]
@@ -136,4 +148,13 @@ mod poker_deck_tests {
         }
         assert_eq!(cards.len(), Deck::len());
     }
+
+    #[test]
+    fn poker_cards_shuffled_seeded() {
+        let cards = Deck::poker_cards_shuffled_seeded(7);
+
+        assert_eq!(cards.len(), Deck::len());
+        assert_eq!(cards.to_string(), Deck::poker_cards_shuffled_seeded(7).to_string());
+        assert_ne!(cards.to_string(), Deck::poker_cards().to_string());
+    }
 }
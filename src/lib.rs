@@ -38,8 +38,11 @@ pub mod deck;
 mod lookups;
 pub mod play;
 pub mod rank;
+pub mod repl;
 pub mod suit;
+pub mod tui;
 pub mod util;
+pub mod web;
 
 // region CONSTANTS
 
@@ -96,19 +99,28 @@ pub enum PKError {
     Fubar,
     Incomplete,
     InsufficientChips,
+    InvalidAcpc(String),
     InvalidBinaryFormat,
+    InvalidBuyIn,
     InvalidCard,
     InvalidCardNumber,
     InvalidCardCount,
+    InvalidCommand(String),
     InvalidHand,
+    InvalidHandHistory(usize, String),
     InvalidIndex,
+    InvalidNotation(String),
+    InvalidOhh(String),
     InvalidPluribusIndex,
     InvalidPosition,
+    InvalidUci(usize, String),
     NotDealt,
     NotEnoughCards,
     NotEnoughHands,
     PlayerOutOfHand,
+    SeatTaken,
     SqlError,
+    StorageError(String),
     TooManyCards,
     TooManyHands,
     InvalidTwo(String),
@@ -126,19 +138,30 @@ impl Display for PKError {
             PKError::Fubar => "Unexpected Error",
             PKError::Incomplete => "Incomplete Error",
             PKError::InsufficientChips => "Insufficient chips Error",
+            PKError::InvalidAcpc(reason) => return write!(f, "Invalid ACPC message: {reason}"),
             PKError::InvalidBinaryFormat => "Invalid binary format Error",
+            PKError::InvalidBuyIn => "Invalid buy in Error",
             PKError::InvalidCard => "Invalid Card Error",
             PKError::InvalidCardNumber => "Invalid Card Number Error",
             PKError::InvalidCardCount => "Invalid Card Count Error",
+            PKError::InvalidCommand(reason) => return write!(f, "Invalid command: {reason}"),
             PKError::InvalidHand => "Invalid Hand Error",
+            PKError::InvalidHandHistory(line, reason) => {
+                return write!(f, "Invalid hand history at line {line}: {reason}")
+            }
             PKError::InvalidIndex => "Invalid Index Error",
+            PKError::InvalidNotation(reason) => return write!(f, "Invalid game notation: {reason}"),
+            PKError::InvalidOhh(reason) => return write!(f, "Invalid open hand history: {reason}"),
             PKError::InvalidPluribusIndex => "Invalid Pluribus Index Error",
             PKError::InvalidPosition => "Invalid Position Error",
+            PKError::InvalidUci(line, reason) => return write!(f, "Invalid UCI poker hand at line {line}: {reason}"),
             PKError::NotDealt => "Not Dealt Error",
             PKError::NotEnoughCards => "Not Enough Cards Error",
             PKError::NotEnoughHands => "Not Enough Hands Error",
             PKError::PlayerOutOfHand => "Player is out of hand Error",
+            PKError::SeatTaken => "Seat is taken Error",
             PKError::SqlError => "SQL Error",
+            PKError::StorageError(reason) => return write!(f, "Storage error: {reason}"),
             PKError::TooManyCards => "Too Many Cards Error",
             PKError::TooManyHands => "Too Many Hands Error",
             PKError::InvalidTwo(_) => "Invalid Two Error",
@@ -0,0 +1,65 @@
+use crate::play::acpc::game::GameDef;
+use crate::play::acpc::state::{BettingState, MatchState};
+use crate::play::acpc::VERSION;
+use crate::play::agents::Agent;
+use crate::PKError;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::str::FromStr;
+
+/// # ACPC Client
+///
+/// Sits an `Agent` down at an ACPC dealer, whether it's our `AcpcDealer` or the ACPC's own
+/// `dealer` program running on the same machine. The client keeps track of the betting from the
+/// match states it's sent, and whenever it's the agent's turn it asks for a `Decision` and sends
+/// back the matching ACPC action.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AcpcClient {
+    pub def: GameDef,
+}
+
+impl AcpcClient {
+    #[must_use]
+    pub fn new(def: GameDef) -> AcpcClient {
+        AcpcClient { def }
+    }
+
+    /// Connects to a dealer on localhost and plays until the dealer hangs up. Returns how many
+    /// hands were played.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::Fubar` if the dealer can't be reached or the connection drops.
+    /// * `PKError::InvalidAcpc` if the dealer sends something that isn't a valid match state.
+    pub fn play<A: Agent + ?Sized>(&self, port: u16, agent: &mut A) -> Result<usize, PKError> {
+        let stream = TcpStream::connect(("127.0.0.1", port)).map_err(|_| PKError::Fubar)?;
+        let mut writer = stream.try_clone().map_err(|_| PKError::Fubar)?;
+        writer
+            .write_all(format!("{VERSION}\r\n").as_bytes())
+            .map_err(|_| PKError::Fubar)?;
+
+        let mut hands = 0;
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            let match_state = MatchState::from_str(&line)?;
+            let state = BettingState::from_betting(&self.def, &match_state.betting)?;
+            if state.finished {
+                hands = hands.max(match_state.hand_number + 1);
+                continue;
+            }
+            if state.actor == Some(match_state.position) {
+                let situation = state.situation(match_state.hand()?, match_state.board()?);
+                let action = state.action(agent.act(&situation));
+                writer
+                    .write_all(format!("{match_state}:{action}\r\n").as_bytes())
+                    .map_err(|_| PKError::Fubar)?;
+            }
+        }
+        Ok(hands)
+    }
+}
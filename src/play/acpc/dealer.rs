@@ -0,0 +1,302 @@
+use crate::arrays::two::Two;
+use crate::cards::Cards;
+use crate::play::acpc::game::GameDef;
+use crate::play::acpc::state::{AcpcAction, BettingState, MatchState};
+use crate::play::acpc::{acpc_cards, VERSION};
+use crate::{PKError, Pile};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A player's connection to the dealer.
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn send(&mut self, line: &str) -> Result<(), PKError> {
+        self.writer
+            .write_all(format!("{line}\r\n").as_bytes())
+            .map_err(|_| PKError::Fubar)
+    }
+
+    /// The next line from the player that isn't a comment.
+    fn receive(&mut self) -> Result<String, PKError> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) | Err(_) => return Err(PKError::Fubar),
+                Ok(_) => {}
+            }
+            let line = line.trim_end();
+            if !line.is_empty() && !line.starts_with('#') && !line.starts_with(';') {
+                return Ok(line.to_string());
+            }
+        }
+    }
+}
+
+/// # ACPC Dealer
+///
+/// Deals a match of no-limit hold'em to players connected over TCP, speaking the ACPC match state
+/// protocol. Each player connects, says `VERSION:2.0.0`, and from then on gets a `MATCHSTATE`
+/// line every time anything happens. When it's their turn they answer with the same line and
+/// their action tacked on the end, like `MATCHSTATE:2:0::||2cAc|||:r225`.
+///
+/// The dealer only listens on localhost. Players move one position to the left every hand, so
+/// that the first player to connect is the small blind on the first hand, the button on the
+/// second, and so on, the same as the Pluribus logs.
+///
+/// Like the ACPC's own dealer, an action that isn't legal is treated as a call.
+///
+/// The results come back as ACPC `STATE` lines, which can be fed right back into `Pluribus`.
+#[derive(Debug)]
+pub struct AcpcDealer {
+    pub def: GameDef,
+    listener: TcpListener,
+}
+
+impl AcpcDealer {
+    /// How long the dealer waits on a player before giving up on the match.
+    pub const TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Starts listening on a localhost port. Pass in `0` to let the operating system pick one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the port can't be bound.
+    pub fn bind(def: GameDef, port: u16) -> Result<AcpcDealer, PKError> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|_| PKError::Fubar)?;
+        Ok(AcpcDealer { def, listener })
+    }
+
+    /// The port the dealer is listening on.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the socket's address can't be read.
+    pub fn port(&self) -> Result<u16, PKError> {
+        self.listener
+            .local_addr()
+            .map(|address| address.port())
+            .map_err(|_| PKError::Fubar)
+    }
+
+    /// Waits for every player to connect, and then deals them a match. The same seed always deals
+    /// the same cards.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::InvalidIndex` if there isn't a name for every player in the game.
+    /// * `PKError::InvalidAcpc` if a player doesn't speak the protocol.
+    /// * `PKError::Fubar` if a player disconnects or times out.
+    pub fn play(&self, names: &[&str], hands: usize, seed: u64) -> Result<Vec<String>, PKError> {
+        let players = self.def.players();
+        if names.len() != players {
+            return Err(PKError::InvalidIndex);
+        }
+
+        let mut connections = Vec::with_capacity(players);
+        for _ in 0..players {
+            let (stream, _) = self.listener.accept().map_err(|_| PKError::Fubar)?;
+            stream
+                .set_read_timeout(Some(AcpcDealer::TIMEOUT))
+                .map_err(|_| PKError::Fubar)?;
+            let writer = stream.try_clone().map_err(|_| PKError::Fubar)?;
+            let mut connection = Connection {
+                reader: BufReader::new(stream),
+                writer,
+            };
+            let version = connection.receive()?;
+            if version != VERSION {
+                return Err(PKError::InvalidAcpc(format!("expected {VERSION}, not {version}")));
+            }
+            connections.push(connection);
+        }
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut states = Vec::with_capacity(hands);
+        for hand in 0..hands {
+            states.push(self.deal(hand, names, &mut connections, &mut rng)?);
+        }
+        Ok(states)
+    }
+
+    /// Deals a single hand, returning its `STATE` line.
+    fn deal(
+        &self,
+        hand: usize,
+        names: &[&str],
+        connections: &mut [Connection],
+        rng: &mut StdRng,
+    ) -> Result<String, PKError> {
+        let players = self.def.players();
+        let seat = |position: usize| (position + hand) % players;
+
+        let mut deck = Cards::deck().shuffle_with(rng);
+        let mut holes = Vec::with_capacity(players);
+        for _ in 0..players {
+            holes.push(deck.draw(2)?);
+        }
+        let board = deck.draw(5)?;
+        let mut state = BettingState::new(&self.def);
+
+        loop {
+            for position in 0..players {
+                let cards = AcpcDealer::cards(&state, &holes, &board, Some(position));
+                let match_state = MatchState {
+                    position,
+                    hand_number: hand,
+                    betting: state.betting.clone(),
+                    cards,
+                };
+                connections[seat(position)].send(&match_state.to_string())?;
+            }
+            let Some(actor) = state.actor else {
+                break;
+            };
+
+            let expected = MatchState {
+                position: actor,
+                hand_number: hand,
+                betting: state.betting.clone(),
+                cards: AcpcDealer::cards(&state, &holes, &board, Some(actor)),
+            }
+            .to_string();
+            let response = connections[seat(actor)].receive()?;
+            let action = response
+                .strip_prefix(&format!("{expected}:"))
+                .and_then(|action| AcpcAction::from_str(action).ok())
+                .filter(|action| state.is_legal(*action))
+                .unwrap_or(AcpcAction::Call);
+            state.apply(action)?;
+        }
+
+        let hands = holes
+            .iter()
+            .map(|cards| Two::try_from(cards.clone()))
+            .collect::<Result<Vec<Two>, PKError>>()?;
+        let payouts = state
+            .payouts(&hands, &board)?
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+        let names = (0..players)
+            .map(|position| names[seat(position)])
+            .collect::<Vec<&str>>();
+        Ok(format!(
+            "STATE:{hand}:{}:{}:{}:{}",
+            state.betting,
+            AcpcDealer::cards(&state, &holes, &board, None),
+            payouts.join("|"),
+            names.join("|")
+        ))
+    }
+
+    /// The cards a position gets to see. Everyone sees the cards of the players left at a
+    /// showdown, and passing in `None` shows every card that was dealt.
+    fn cards(state: &BettingState, holes: &[Cards], board: &Cards, viewer: Option<usize>) -> String {
+        let hole = holes
+            .iter()
+            .enumerate()
+            .map(|(position, cards)| {
+                let visible = viewer.map_or(true, |viewer| viewer == position)
+                    || (state.is_showdown() && !state.folded[position]);
+                if visible {
+                    acpc_cards(cards)
+                } else {
+                    String::new()
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("|");
+        let board = board.to_vec();
+        let mut dealt = 0;
+        let mut streets = String::new();
+        for round in 1..=state.round {
+            let count = GameDef::BOARD_CARDS[round];
+            streets.push('/');
+            streets.push_str(&acpc_cards(&Cards::from(board[dealt..dealt + count].to_vec())));
+            dealt += count;
+        }
+        format!("{hole}{streets}")
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__acpc__dealer_tests {
+    use super::*;
+    use crate::analysis::store::nubibus::pluribus::Pluribus;
+    use crate::play::acpc::client::AcpcClient;
+    use crate::play::agents::calling_station::CallingStation;
+    use crate::play::agents::random::RandomBot;
+    use crate::play::agents::tight_aggressive::TightAggressive;
+    use std::thread;
+
+    fn play(def: &GameDef, names: &[&str], hands: usize, seed: u64) -> Vec<String> {
+        let dealer = AcpcDealer::bind(def.clone(), 0).unwrap();
+        let port = dealer.port().unwrap();
+        let clients = (0..def.players())
+            .map(|i| {
+                let client = AcpcClient::new(def.clone());
+                let seed = seed + i as u64;
+                thread::spawn(move || match i % 3 {
+                    0 => client.play(port, &mut RandomBot::new(seed)),
+                    1 => client.play(port, &mut CallingStation::new(seed)),
+                    _ => client.play(port, &mut TightAggressive::new(seed)),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let states = dealer.play(names, hands, seed).unwrap();
+
+        for client in clients {
+            assert_eq!(hands, client.join().unwrap().unwrap());
+        }
+        states
+    }
+
+    #[test]
+    fn play__six_handed() {
+        let names = ["Elmer", "Bugsy", "Daffy", "Porky", "Tweety", "Taz"];
+
+        let states = play(&GameDef::pluribus(), &names, 25, 42);
+
+        assert_eq!(25, states.len());
+        for (i, state) in states.iter().enumerate() {
+            let pluribus = Pluribus::from_str(state).unwrap();
+            let payouts = state.split(':').nth(4).unwrap().split('|');
+
+            assert_eq!(i, pluribus.index);
+            assert_eq!(names[i % 6], pluribus.players[0]);
+            assert!(payouts.map(|n| n.parse::<f64>().unwrap()).sum::<f64>().abs() < f64::EPSILON);
+            assert!(
+                BettingState::from_betting(&GameDef::pluribus(), &pluribus.rounds.join("/"))
+                    .unwrap()
+                    .finished
+            );
+        }
+    }
+
+    #[test]
+    fn play__deterministic() {
+        let names = ["Elmer", "Bugsy"];
+
+        assert_eq!(
+            play(&GameDef::heads_up(), &names, 10, 7),
+            play(&GameDef::heads_up(), &names, 10, 7)
+        );
+    }
+
+    #[test]
+    fn play__wrong_number_of_names() {
+        let dealer = AcpcDealer::bind(GameDef::heads_up(), 0).unwrap();
+
+        assert_eq!(PKError::InvalidIndex, dealer.play(&["Elmer"], 1, 1).unwrap_err());
+    }
+}
@@ -0,0 +1,213 @@
+use crate::PKError;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// An ACPC game definition for no-limit hold'em.
+///
+/// The ACPC dealer reads these from files like `holdem.nolimit.6p.game`, which is the one that the
+/// Pluribus sessions were played with. All of the hold'em specific lines, like the number of
+/// suits and ranks, and how many cards go out on each street, are fixed. What's left is how many
+/// players there are, how deep they are, what they post, and who acts first on each street.
+///
+/// Positions are counted from zero, which is the small blind, in code, and from one in the file,
+/// the same as the ACPC.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameDef {
+    pub stacks: Vec<usize>,
+    pub blinds: Vec<usize>,
+    /// The position that acts first on each of the four streets.
+    pub first_player: [usize; 4],
+}
+
+impl GameDef {
+    pub const ROUNDS: usize = 4;
+    pub const BOARD_CARDS: [usize; 4] = [0, 3, 1, 1];
+
+    /// The six handed, 10,000 chip deep, 50/100 game that Pluribus played.
+    #[must_use]
+    pub fn pluribus() -> GameDef {
+        GameDef {
+            stacks: vec![10_000; 6],
+            blinds: vec![50, 100, 0, 0, 0, 0],
+            first_player: [2, 0, 0, 0],
+        }
+    }
+
+    /// The ACPC competition heads up game. The button posts the small blind, so the big blind is
+    /// position zero, and the button acts first before the flop and last after it.
+    #[must_use]
+    pub fn heads_up() -> GameDef {
+        GameDef {
+            stacks: vec![20_000; 2],
+            blinds: vec![100, 50],
+            first_player: [1, 0, 0, 0],
+        }
+    }
+
+    #[must_use]
+    pub fn big_blind(&self) -> usize {
+        self.blinds.iter().max().copied().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn players(&self) -> usize {
+        self.stacks.len()
+    }
+
+    fn numbers(key: &str, value: &str) -> Result<Vec<usize>, PKError> {
+        value
+            .split_whitespace()
+            .map(|n| {
+                n.parse::<usize>()
+                    .map_err(|_| PKError::InvalidAcpc(format!("{key} = {value}")))
+            })
+            .collect()
+    }
+
+    fn expect(key: &str, value: &str, expected: &str) -> Result<(), PKError> {
+        if value.split_whitespace().collect::<Vec<&str>>().join(" ") == expected {
+            Ok(())
+        } else {
+            Err(PKError::InvalidAcpc(format!("{key} must be {expected}, not {value}")))
+        }
+    }
+}
+
+impl Default for GameDef {
+    fn default() -> Self {
+        GameDef::pluribus()
+    }
+}
+
+impl Display for GameDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let join = |v: &[usize]| v.iter().map(ToString::to_string).collect::<Vec<String>>().join(" ");
+        writeln!(f, "GAMEDEF")?;
+        writeln!(f, "nolimit")?;
+        writeln!(f, "numPlayers = {}", self.players())?;
+        writeln!(f, "numRounds = {}", GameDef::ROUNDS)?;
+        writeln!(f, "stack = {}", join(&self.stacks))?;
+        writeln!(f, "blind = {}", join(&self.blinds))?;
+        writeln!(
+            f,
+            "firstPlayer = {}",
+            join(&self.first_player.iter().map(|p| p + 1).collect::<Vec<usize>>())
+        )?;
+        writeln!(f, "numSuits = 4")?;
+        writeln!(f, "numRanks = 13")?;
+        writeln!(f, "numHoleCards = 2")?;
+        writeln!(f, "numBoardCards = {}", join(&GameDef::BOARD_CARDS))?;
+        write!(f, "END GAMEDEF")
+    }
+}
+
+impl FromStr for GameDef {
+    type Err = PKError;
+
+    /// Parses an ACPC game definition. Lines starting with `#` are comments.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut players = None;
+        let mut stacks = Vec::new();
+        let mut blinds = Vec::new();
+        let mut first_player = Vec::new();
+        let mut nolimit = false;
+
+        for line in s.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.eq_ignore_ascii_case("GAMEDEF") {
+                continue;
+            }
+            if line.eq_ignore_ascii_case("END GAMEDEF") {
+                break;
+            }
+            if line.eq_ignore_ascii_case("nolimit") {
+                nolimit = true;
+                continue;
+            }
+            if line.eq_ignore_ascii_case("limit") {
+                break;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| PKError::InvalidAcpc(format!("unrecognized line {line}")))?;
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "numPlayers" => players = GameDef::numbers(key, value)?.first().copied(),
+                "numRounds" | "numSuits" => GameDef::expect(key, value, "4")?,
+                "stack" => stacks = GameDef::numbers(key, value)?,
+                "blind" => blinds = GameDef::numbers(key, value)?,
+                "firstPlayer" => first_player = GameDef::numbers(key, value)?,
+                "numRanks" => GameDef::expect(key, value, "13")?,
+                "numHoleCards" => GameDef::expect(key, value, "2")?,
+                "numBoardCards" => GameDef::expect(key, value, "0 3 1 1")?,
+                _ => return Err(PKError::InvalidAcpc(format!("unsupported setting {key}"))),
+            }
+        }
+
+        if !nolimit {
+            return Err(PKError::InvalidAcpc("only nolimit games are supported".to_string()));
+        }
+        let players = players.ok_or_else(|| PKError::InvalidAcpc("missing numPlayers".to_string()))?;
+        if players < 2 || stacks.len() != players || blinds.len() != players {
+            return Err(PKError::InvalidAcpc(
+                "stack and blind need a value for every player".to_string(),
+            ));
+        }
+        let first_player = match first_player.as_slice() {
+            [preflop, flop, turn, river] if first_player.iter().all(|position| (1..=players).contains(position)) => {
+                [preflop - 1, flop - 1, turn - 1, river - 1]
+            }
+            _ => {
+                return Err(PKError::InvalidAcpc(
+                    "firstPlayer needs a position for every round".to_string(),
+                ))
+            }
+        };
+
+        Ok(GameDef {
+            stacks,
+            blinds,
+            first_player,
+        })
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__acpc__game_tests {
+    use super::*;
+
+    const HOLDEM_NOLIMIT_6P: &str = "GAMEDEF
+nolimit
+numPlayers = 6
+numRounds = 4
+stack = 10000 10000 10000 10000 10000 10000
+blind = 50 100 0 0 0 0
+firstPlayer = 3 1 1 1
+numSuits = 4
+numRanks = 13
+numHoleCards = 2
+numBoardCards = 0 3 1 1
+END GAMEDEF";
+
+    #[test]
+    fn from_str() {
+        assert_eq!(GameDef::pluribus(), GameDef::from_str(HOLDEM_NOLIMIT_6P).unwrap());
+    }
+
+    #[test]
+    fn from_str__limit() {
+        assert_eq!(
+            PKError::InvalidAcpc("only nolimit games are supported".to_string()),
+            GameDef::from_str(&HOLDEM_NOLIMIT_6P.replace("nolimit", "limit")).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(HOLDEM_NOLIMIT_6P, GameDef::pluribus().to_string());
+        assert_eq!(
+            GameDef::heads_up(),
+            GameDef::from_str(&GameDef::heads_up().to_string()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use crate::cards::Cards;
+
+pub mod client;
+pub mod dealer;
+pub mod game;
+pub mod state;
+
+/// The only version of the ACPC protocol there is.
+pub const VERSION: &str = "VERSION:2.0.0";
+
+/// Cards the way the ACPC writes them, with no spaces, like `2cAc`.
+pub(crate) fn acpc_cards(cards: &Cards) -> String {
+    cards.to_letters().concat()
+}
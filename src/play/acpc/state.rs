@@ -0,0 +1,532 @@
+use crate::arrays::two::Two;
+use crate::cards::Cards;
+use crate::play::acpc::game::GameDef;
+use crate::play::agents::{Decision, Situation};
+use crate::play::board::Board;
+use crate::play::phases::PhaseHoldem;
+use crate::play::pots::Pots;
+use crate::play::Position6Max;
+use crate::util::Util;
+use crate::PKError;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// A single ACPC betting action. A raise is to the total number of chips the player will have
+/// put in over the whole hand, not just the street, so `r10000` is always all in at a 10,000
+/// chip table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AcpcAction {
+    Fold,
+    Call,
+    Raise(usize),
+}
+
+impl Display for AcpcAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AcpcAction::Fold => write!(f, "f"),
+            AcpcAction::Call => write!(f, "c"),
+            AcpcAction::Raise(to) => write!(f, "r{to}"),
+        }
+    }
+}
+
+impl FromStr for AcpcAction {
+    type Err = PKError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "f" => Ok(AcpcAction::Fold),
+            "c" | "k" => Ok(AcpcAction::Call),
+            s => s
+                .strip_prefix('r')
+                .and_then(|to| to.parse::<usize>().ok())
+                .map(AcpcAction::Raise)
+                .ok_or_else(|| PKError::InvalidAcpc(format!("invalid action {s}"))),
+        }
+    }
+}
+
+/// What the dealer tells a player: `MATCHSTATE:<position>:<hand number>:<betting>:<cards>`.
+///
+/// The cards are everybody's hole cards in position order, separated by `|`, followed by the
+/// board for each street, separated by `/`. A player only gets to see their own hole cards,
+/// until there's a showdown.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MatchState {
+    pub position: usize,
+    pub hand_number: usize,
+    pub betting: String,
+    pub cards: String,
+}
+
+impl MatchState {
+    /// The player's hole cards.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidAcpc` if the player's cards aren't there.
+    pub fn hand(&self) -> Result<Two, PKError> {
+        let hole = self
+            .cards
+            .split('/')
+            .next()
+            .and_then(|hands| hands.split('|').nth(self.position))
+            .unwrap_or_default();
+        Cards::from_str(&Util::str_len_splitter(hole, 2))
+            .and_then(Two::try_from)
+            .map_err(|_| PKError::InvalidAcpc(format!("no hole cards for position {}", self.position)))
+    }
+
+    /// Every card dealt to the board so far.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidAcpc` if the board can't be parsed.
+    pub fn board(&self) -> Result<Cards, PKError> {
+        let board = self.cards.split('/').skip(1).collect::<String>();
+        if board.is_empty() {
+            return Ok(Cards::default());
+        }
+        Cards::from_str(&Util::str_len_splitter(&board, 2))
+            .map_err(|_| PKError::InvalidAcpc(format!("invalid board {}", self.cards)))
+    }
+}
+
+impl Display for MatchState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "MATCHSTATE:{}:{}:{}:{}",
+            self.position, self.hand_number, self.betting, self.cards
+        )
+    }
+}
+
+impl FromStr for MatchState {
+    type Err = PKError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s.trim().split(':').collect::<Vec<&str>>();
+        let number = |i: usize| {
+            fields
+                .get(i)
+                .and_then(|n| n.parse::<usize>().ok())
+                .ok_or_else(|| PKError::InvalidAcpc(s.to_string()))
+        };
+        match fields.as_slice() {
+            ["MATCHSTATE", _, _, betting, cards] => Ok(MatchState {
+                position: number(1)?,
+                hand_number: number(2)?,
+                betting: (*betting).to_string(),
+                cards: (*cards).to_string(),
+            }),
+            _ => Err(PKError::InvalidAcpc(s.to_string())),
+        }
+    }
+}
+
+/// # ACPC Betting
+///
+/// Where the hand is, worked out from the betting in a `MatchState`. The dealer uses it to decide
+/// whose turn it is and whether an action is legal, and the client uses it to tell its `Agent`
+/// what it's facing.
+///
+/// The betting string has a `/` every time a street is done, even if nobody is left who can
+/// bet, so a hand where everyone is all in before the flop ends with `r10000c///`.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BettingState {
+    pub round: usize,
+    pub spent: Vec<usize>,
+    pub folded: Vec<bool>,
+    pub acted: Vec<bool>,
+    /// The size of the smallest raise allowed, which is the biggest raise so far on the street,
+    /// and never less than the big blind.
+    pub min_raise: usize,
+    pub actor: Option<usize>,
+    pub finished: bool,
+    pub betting: String,
+    stacks: Vec<usize>,
+    first_player: [usize; 4],
+    big_blind: usize,
+}
+
+impl BettingState {
+    /// A new hand with the blinds posted.
+    #[must_use]
+    pub fn new(def: &GameDef) -> BettingState {
+        let players = def.players();
+        let mut state = BettingState {
+            round: 0,
+            spent: def
+                .blinds
+                .iter()
+                .zip(def.stacks.iter())
+                .map(|(blind, stack)| *blind.min(stack))
+                .collect(),
+            folded: vec![false; players],
+            acted: vec![false; players],
+            min_raise: def.big_blind(),
+            actor: None,
+            finished: false,
+            betting: String::new(),
+            stacks: def.stacks.clone(),
+            first_player: def.first_player,
+            big_blind: def.big_blind(),
+        };
+        state.advance(None);
+        state
+    }
+
+    /// Plays out a betting string, like `ffr225fcc/ccc/ccc/ccr562cf`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidAcpc` if an action is illegal, or the streets don't line up.
+    pub fn from_betting(def: &GameDef, betting: &str) -> Result<BettingState, PKError> {
+        let mut state = BettingState::new(def);
+        let mut chars = betting.chars().peekable();
+        while let Some(c) = chars.next() {
+            let action = match c {
+                '/' => continue,
+                'r' => {
+                    let mut digits = String::new();
+                    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                        digits.push(digit);
+                    }
+                    AcpcAction::from_str(&format!("r{digits}"))?
+                }
+                _ => AcpcAction::from_str(&c.to_string())?,
+            };
+            state.apply(action)?;
+        }
+        if state.betting == betting {
+            Ok(state)
+        } else {
+            Err(PKError::InvalidAcpc(format!(
+                "betting {betting} should be {}",
+                state.betting
+            )))
+        }
+    }
+
+    /// Applies an action for the player whose turn it is.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidAcpc` if the hand is over or the action isn't legal.
+    pub fn apply(&mut self, action: AcpcAction) -> Result<(), PKError> {
+        let actor = self
+            .actor
+            .ok_or_else(|| PKError::InvalidAcpc(format!("{action} after the hand is over")))?;
+        if !self.is_legal(action) {
+            return Err(PKError::InvalidAcpc(format!(
+                "illegal action {action} after {}",
+                self.betting
+            )));
+        }
+        let max = self.max_spent();
+        match action {
+            AcpcAction::Fold => self.folded[actor] = true,
+            AcpcAction::Call => self.spent[actor] = max.min(self.stacks[actor]),
+            AcpcAction::Raise(to) => {
+                self.min_raise = self.min_raise.max(to - max);
+                self.spent[actor] = to;
+                self.acted.iter_mut().for_each(|acted| *acted = false);
+            }
+        }
+        self.acted[actor] = true;
+        self.betting.push_str(&action.to_string());
+        self.advance(Some(actor));
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn is_legal(&self, action: AcpcAction) -> bool {
+        let Some(actor) = self.actor else {
+            return false;
+        };
+        let max = self.max_spent();
+        let stack = self.stacks[actor];
+        match action {
+            AcpcAction::Fold => self.spent[actor] < max,
+            AcpcAction::Call => true,
+            AcpcAction::Raise(to) => to > max && to <= stack && (to >= max + self.min_raise || to == stack),
+        }
+    }
+
+    /// Turns an `Agent`'s decision into a legal action, calling if there's no way to make it
+    /// work.
+    #[must_use]
+    pub fn action(&self, decision: Decision) -> AcpcAction {
+        let Some(actor) = self.actor else {
+            return AcpcAction::Call;
+        };
+        let stack = self.stacks[actor];
+        let max = self.max_spent();
+        let action = match decision {
+            Decision::Fold => AcpcAction::Fold,
+            Decision::Check | Decision::Call => AcpcAction::Call,
+            Decision::Raise(amount) => {
+                AcpcAction::Raise((self.spent[actor] + amount).max(max + self.min_raise).min(stack))
+            }
+            Decision::AllIn => AcpcAction::Raise(stack),
+        };
+        if self.is_legal(action) {
+            action
+        } else {
+            AcpcAction::Call
+        }
+    }
+
+    #[must_use]
+    pub fn is_all_in(&self, position: usize) -> bool {
+        self.spent[position] >= self.stacks[position]
+    }
+
+    #[must_use]
+    pub fn max_spent(&self) -> usize {
+        self.spent.iter().max().copied().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn phase(&self) -> PhaseHoldem {
+        match self.round {
+            0 => PhaseHoldem::Preflop,
+            1 => PhaseHoldem::Flop,
+            2 => PhaseHoldem::Turn,
+            _ => PhaseHoldem::River,
+        }
+    }
+
+    #[must_use]
+    pub fn pot(&self) -> usize {
+        self.spent.iter().sum()
+    }
+
+    /// Returns true if the hand is over and more than one player is left to show their cards.
+    #[must_use]
+    pub fn is_showdown(&self) -> bool {
+        self.finished && self.folded.iter().filter(|folded| !**folded).count() > 1
+    }
+
+    /// How much each position won or lost in the hand. Split pots can leave half a chip.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::NotEnoughCards` if there's a showdown without a complete board.
+    pub fn payouts(&self, hands: &[Two], board: &Cards) -> Result<Vec<f64>, PKError> {
+        #[allow(clippy::cast_precision_loss)]
+        let mut payouts = self.spent.iter().map(|spent| 0.0 - *spent as f64).collect::<Vec<f64>>();
+        for pot in Pots::from_contributions(&self.spent, &self.folded).iter() {
+            let winners = if pot.eligible.len() > 1 {
+                pot.winners(hands, &Board::try_from(board.clone())?)
+            } else {
+                pot.eligible.clone()
+            };
+            for winner in &winners {
+                #[allow(clippy::cast_precision_loss)]
+                let share = pot.amount as f64 / winners.len() as f64;
+                payouts[*winner] += share;
+            }
+        }
+        Ok(payouts)
+    }
+
+    /// What the player whose turn it is is facing, in the terms an `Agent` understands.
+    /// `Position6Max` only makes sense six handed; at other tables the position is the closest
+    /// match counting from the small blind.
+    #[must_use]
+    pub fn situation(&self, hand: Two, board: Cards) -> Situation {
+        let actor = self.actor.unwrap_or_default();
+        Situation {
+            phase: self.phase(),
+            position: Position6Max::try_from(u8::try_from(actor + 1).unwrap_or_default()).unwrap_or_default(),
+            hand,
+            board,
+            pot: self.pot(),
+            to_call: self.max_spent() - self.spent[actor],
+            min_raise: self.min_raise,
+            stack: self.stacks[actor] - self.spent[actor],
+            big_blind: self.big_blind,
+            opponents: self.folded.iter().filter(|folded| !**folded).count().saturating_sub(1),
+        }
+    }
+
+    /// Moves on to the next player to act, starting new streets as they're finished, until either
+    /// somebody has a decision to make or the hand is over.
+    fn advance(&mut self, last: Option<usize>) {
+        let players = self.spent.len();
+        let mut from = last.map_or(self.first_player[0], |last| last + 1);
+        loop {
+            if self.folded.iter().filter(|folded| !**folded).count() < 2 {
+                break;
+            }
+            let next = (0..players)
+                .map(|i| (from + i) % players)
+                .find(|p| self.needs_to_act(*p));
+            if next.is_some() {
+                self.actor = next;
+                return;
+            }
+            if self.round + 1 >= GameDef::ROUNDS {
+                break;
+            }
+            self.round += 1;
+            self.betting.push('/');
+            self.min_raise = self.big_blind;
+            self.acted.iter_mut().for_each(|acted| *acted = false);
+            from = self.first_player[self.round];
+        }
+        self.actor = None;
+        self.finished = true;
+    }
+
+    fn needs_to_act(&self, position: usize) -> bool {
+        if self.folded[position] || self.is_all_in(position) {
+            return false;
+        }
+        let max = self.max_spent();
+        let can_bet = (0..self.spent.len())
+            .filter(|p| !self.folded[*p] && !self.is_all_in(*p))
+            .count();
+        if can_bet < 2 && self.spent[position] >= max {
+            return false;
+        }
+        !self.acted[position] || self.spent[position] < max
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__acpc__state_tests {
+    use super::*;
+    use crate::analysis::store::nubibus::pluribus::Pluribus;
+    use rstest::rstest;
+    use std::fs;
+
+    #[test]
+    fn action__from_str() {
+        assert_eq!(AcpcAction::Raise(225), AcpcAction::from_str("r225").unwrap());
+        assert_eq!(AcpcAction::Fold, AcpcAction::from_str("f").unwrap());
+        assert_eq!(
+            PKError::InvalidAcpc("invalid action x".to_string()),
+            AcpcAction::from_str("x").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn match_state() {
+        let s = "MATCHSTATE:4:0:ffr225fcc/ccc:||||6dAd|/Qs9c4s";
+
+        let state = MatchState::from_str(s).unwrap();
+
+        assert_eq!(4, state.position);
+        assert_eq!("ffr225fcc/ccc", state.betting);
+        assert_eq!(Two::from_str("A♦ 6♦").unwrap(), state.hand().unwrap());
+        assert_eq!(Cards::from_str("Q♠ 9♣ 4♠").unwrap(), state.board().unwrap());
+        assert_eq!(s, state.to_string());
+        assert!(MatchState::from_str("STATE:0:ff").is_err());
+    }
+
+    #[test]
+    fn new() {
+        let state = BettingState::new(&GameDef::pluribus());
+
+        assert_eq!(Some(2), state.actor);
+        assert_eq!(150, state.pot());
+        assert_eq!(100, state.min_raise);
+    }
+
+    #[test]
+    fn from_betting() {
+        let state = BettingState::from_betting(&GameDef::pluribus(), "ffr225fcc/ccc/ccc/ccr562cf").unwrap();
+
+        assert!(state.finished);
+        assert!(state.is_showdown());
+        assert_eq!(vec![562, 225, 0, 0, 562, 0], state.spent);
+    }
+
+    #[test]
+    fn from_betting__in_progress() {
+        let state = BettingState::from_betting(&GameDef::pluribus(), "ffr225fcc/c").unwrap();
+
+        assert_eq!(Some(1), state.actor);
+        assert_eq!(PhaseHoldem::Flop, state.phase());
+        assert!(state.is_legal(AcpcAction::Raise(325)));
+        assert!(!state.is_legal(AcpcAction::Raise(300)));
+        assert!(!state.is_legal(AcpcAction::Fold));
+    }
+
+    #[test]
+    fn from_betting__all_in() {
+        let state = BettingState::from_betting(&GameDef::pluribus(), "r10000fffff").unwrap();
+        assert!(state.finished);
+        assert!(!state.is_showdown());
+
+        let state = BettingState::from_betting(&GameDef::pluribus(), "r10000ffffc///").unwrap();
+        assert!(state.is_showdown());
+    }
+
+    #[rstest]
+    #[case("ffr225fcc/ccc/ccc/ccr562cf/")]
+    #[case("ffr225fcc/ccc")]
+    #[case("ffr225fcc/ccc/r50")]
+    #[case("r10000ffffc")]
+    #[case("ffffff")]
+    fn from_betting__invalid(#[case] betting: &str) {
+        assert!(BettingState::from_betting(&GameDef::pluribus(), betting).is_err());
+    }
+
+    #[test]
+    fn action() {
+        let state = BettingState::from_betting(&GameDef::pluribus(), "ffr225").unwrap();
+
+        assert_eq!(AcpcAction::Raise(350), state.action(Decision::Raise(300)));
+        assert_eq!(AcpcAction::Raise(500), state.action(Decision::Raise(500)));
+        assert_eq!(AcpcAction::Raise(10_000), state.action(Decision::AllIn));
+        assert_eq!(AcpcAction::Fold, state.action(Decision::Fold));
+        assert_eq!(AcpcAction::Call, state.action(Decision::Check));
+    }
+
+    #[test]
+    fn situation() {
+        let state = BettingState::from_betting(&GameDef::pluribus(), "ffr225f").unwrap();
+
+        let situation = state.situation(Two::from_str("A♣ 2♣").unwrap(), Cards::default());
+
+        assert_eq!(Position6Max::SB, situation.position);
+        assert_eq!(175, situation.to_call);
+        assert_eq!(125, situation.min_raise);
+        assert_eq!(9_950, situation.stack);
+        assert_eq!(2, situation.opponents);
+    }
+
+    /// Every hand in a Pluribus session replays, and pays out what the ACPC dealer paid.
+    #[test]
+    fn payouts__pluribus() {
+        let def = GameDef::pluribus();
+        let log = fs::read_to_string("data/pluribus/raw/sample_game_102.log").unwrap();
+        for row in log.lines().filter(|line| line.starts_with("STATE")) {
+            let pluribus = Pluribus::from_str(row).unwrap();
+            let fields = row.split(':').collect::<Vec<&str>>();
+            let mut cards = fields[3].split('/');
+            let hands = cards
+                .next()
+                .unwrap()
+                .split('|')
+                .map(|hand| Two::try_from(Cards::from_str(&Util::str_len_splitter(hand, 2)).unwrap()).unwrap())
+                .collect::<Vec<Two>>();
+            let board = Cards::from_str(&Util::str_len_splitter(&cards.collect::<String>(), 2)).unwrap_or_default();
+            let expected = fields[4]
+                .split('|')
+                .map(|n| n.parse::<f64>().unwrap())
+                .collect::<Vec<f64>>();
+
+            let state = BettingState::from_betting(&def, &pluribus.rounds.join("/")).unwrap();
+
+            assert!(state.finished, "{row}");
+            assert_eq!(expected, state.payouts(&hands, &board).unwrap(), "{row}");
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::play::agents::{Agent, Decision, Situation};
+
+/// Never folds, never raises. Checks when it can and calls everything else, all in if it has to.
+///
+/// Every value bet you make against this player gets paid, and every bluff gets snapped off,
+/// which is exactly why it's such a good baseline. A strategy that can't beat a calling station
+/// isn't a strategy.
+#[derive(Clone, Debug, Default)]
+pub struct CallingStation {
+    pub seed: u64,
+}
+
+impl CallingStation {
+    /// The calling station doesn't need any randomness, but it takes a seed so that it can be
+    /// created the same way as all the other agents.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        CallingStation { seed }
+    }
+}
+
+impl Agent for CallingStation {
+    fn name(&self) -> String {
+        format!("Calling Station #{}", self.seed)
+    }
+
+    fn act(&mut self, situation: &Situation) -> Decision {
+        situation.call()
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__agents__calling_station_tests {
+    use super::*;
+    use crate::play::agents::play__agents_tests::{flop, preflop};
+    use crate::play::Position6Max;
+
+    #[test]
+    fn act() {
+        let mut bot = CallingStation::new(1);
+
+        assert_eq!(Decision::Call, bot.act(&preflop("7♠ 2♥", Position6Max::UTG)));
+        assert_eq!(Decision::Check, bot.act(&flop("7♠ 2♥", "K♦ 8♣ 3♠", 0)));
+        assert_eq!(Decision::AllIn, bot.act(&flop("7♠ 2♥", "K♦ 8♣ 3♠", 9_000)));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::analysis::case_evals::CaseEvals;
+use crate::arrays::combos::twos::Twos;
+use crate::arrays::three::Three;
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::play::agents::{Agent, Decision, Situation};
+use crate::play::board::Board;
+use crate::play::game::Game;
+use crate::play::hole_cards::HoleCards;
+use crate::play::phases::PhaseHoldem;
+use crate::util::wincounter::results::Results;
+use crate::Pile;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::str::FromStr;
+
+/// A bot that does the math. After the flop it deals its opponents random hands, runs out every
+/// possible board with `CaseEvals`, and compares its average equity against a couple of
+/// thresholds:
+///
+/// * At or above `raise_threshold` it bets for value.
+/// * At or above `call_threshold`, and getting the right price, it calls.
+/// * Otherwise, it checks or gives up.
+///
+/// Before the flop it sticks to a simple range, since running out all 1.7 million boards for every
+/// decision would have it timing out at every table.
+#[derive(Clone, Debug)]
+pub struct EquityThreshold {
+    pub seed: u64,
+    pub call_threshold: f32,
+    pub raise_threshold: f32,
+    /// The number of random opponent hands that are sampled for each decision.
+    pub samples: usize,
+    pub preflop_raise: Twos,
+    pub preflop_call: Twos,
+    rng: StdRng,
+}
+
+impl EquityThreshold {
+    pub const PREFLOP_RAISE: &'static str = "TT+,AK,AQs";
+    pub const PREFLOP_CALL: &'static str =
+        "22+,AQ,AJ,AT,A9s,A8s,A7s,A6s,A5s,A4s,A3s,A2s,KQ,KJs,KTs,QJs,QTs,JTs,T9s,98s";
+
+    /// # Panics
+    ///
+    /// Only if the default range constants can't be parsed, which the tests guard against.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        EquityThreshold {
+            seed,
+            call_threshold: 0.35,
+            raise_threshold: 0.65,
+            samples: 10,
+            preflop_raise: Twos::from_str(EquityThreshold::PREFLOP_RAISE).unwrap(),
+            preflop_call: Twos::from_str(EquityThreshold::PREFLOP_CALL).unwrap(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    #[must_use]
+    pub fn with_thresholds(mut self, call_threshold: f32, raise_threshold: f32) -> Self {
+        self.call_threshold = call_threshold;
+        self.raise_threshold = raise_threshold;
+        self
+    }
+
+    #[must_use]
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
+    /// Estimates the bot's chances of winning the hand, from `0.0` to `1.0`, against random hands
+    /// for each of the opponents still in the hand. Ties are counted as wins.
+    ///
+    /// Returns `0.0` if there isn't a flop.
+    pub fn equity(&mut self, situation: &Situation) -> f32 {
+        let Ok(flop) = Three::try_from(Cards::from(
+            situation.board.iter().take(3).copied().collect::<Vec<Card>>(),
+        )) else {
+            return 0.0;
+        };
+        let turn = situation.board.get_index(3).copied().unwrap_or_default();
+        let river = situation.board.get_index(4).copied().unwrap_or_default();
+
+        let mut known = situation.hand.cards();
+        known.insert_all(&situation.board);
+        let remaining = Cards::deck_minus(&known).to_vec();
+
+        let samples = self.samples.max(1);
+        let mut total = 0.0_f32;
+        for _ in 0..samples {
+            let mut hands = HoleCards::from(vec![situation.hand]);
+            let dealt: Vec<Card> = remaining
+                .choose_multiple(&mut self.rng, situation.opponents.max(1) * 2)
+                .copied()
+                .collect();
+            for pair in dealt.chunks(2) {
+                hands.push(Two::from(pair.to_vec()));
+            }
+
+            let case_evals = match situation.phase {
+                PhaseHoldem::Flop => CaseEvals::from_holdem_at_flop(flop, &hands),
+                PhaseHoldem::Turn => Game::new(hands.clone(), Board::new(flop, turn, Card::BLANK)).turn_case_evals(),
+                _ => {
+                    let mut case_evals = CaseEvals::default();
+                    if let Ok(case_eval) = Game::new(hands.clone(), Board::new(flop, turn, river)).river_case_eval() {
+                        case_evals.push(case_eval);
+                    }
+                    case_evals
+                }
+            };
+            let results = Results::from_wins(&case_evals.wins(), hands.len());
+            total += results.wins_total_percentage(0) / 100.0;
+        }
+
+        #[allow(clippy::cast_precision_loss)]
+        let equity = total / samples as f32;
+        equity
+    }
+
+    /// The share of the pot, after calling, that the player has to put in.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn pot_odds(situation: &Situation) -> f32 {
+        match situation.pot + situation.to_call {
+            0 => 0.0,
+            total => situation.to_call as f32 / total as f32,
+        }
+    }
+
+    fn preflop(&self, situation: &Situation) -> Decision {
+        if self.preflop_raise.contains(&situation.hand) {
+            situation.raise(situation.to_call * 3)
+        } else if self.preflop_call.contains(&situation.hand) {
+            situation.call()
+        } else {
+            situation.check_or_fold()
+        }
+    }
+
+    fn postflop(&mut self, situation: &Situation) -> Decision {
+        let equity = self.equity(situation);
+        if equity >= self.raise_threshold {
+            situation.raise(situation.to_call + situation.pot * 2 / 3)
+        } else if equity >= self.call_threshold.max(EquityThreshold::pot_odds(situation)) {
+            situation.call()
+        } else {
+            situation.check_or_fold()
+        }
+    }
+}
+
+impl Agent for EquityThreshold {
+    fn name(&self) -> String {
+        format!("Equity Threshold #{}", self.seed)
+    }
+
+    fn act(&mut self, situation: &Situation) -> Decision {
+        match situation.phase {
+            PhaseHoldem::Preflop => self.preflop(situation),
+            _ => self.postflop(situation),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__agents__equity_threshold_tests {
+    use super::*;
+    use crate::play::agents::play__agents_tests::{flop, preflop};
+    use crate::play::Position6Max;
+
+    #[test]
+    fn equity__flop() {
+        let mut bot = EquityThreshold::new(7).with_samples(3);
+
+        let sets = bot.equity(&flop("8♠ 8♥", "K♦ 8♣ 2♠", 0));
+        let air = bot.equity(&flop("4♠ 3♥", "K♦ 8♣ Q♠", 0));
+
+        assert!(sets > 0.8);
+        assert!(air < sets);
+    }
+
+    #[test]
+    fn equity__river() {
+        let mut bot = EquityThreshold::new(7);
+        let mut situation = flop("A♠ K♠", "Q♠ J♠ T♠ 2♦ 3♣", 0);
+        situation.phase = PhaseHoldem::River;
+
+        assert!((bot.equity(&situation) - 1.0).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn equity__preflop() {
+        let mut bot = EquityThreshold::new(7);
+
+        assert!(bot.equity(&preflop("A♠ A♥", Position6Max::BTN)).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn equity__deterministic() {
+        let situation = flop("A♠ 9♥", "K♦ 9♣ 2♠", 0);
+
+        let first = EquityThreshold::new(3).with_samples(3).equity(&situation);
+        let second = EquityThreshold::new(3).with_samples(3).equity(&situation);
+
+        assert!((first - second).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn pot_odds() {
+        let odds = EquityThreshold::pot_odds(&flop("A♠ 9♥", "K♦ 9♣ 2♠", 500));
+
+        assert!((odds - 1.0 / 3.0).abs() < f32::EPSILON);
+        assert!(EquityThreshold::pot_odds(&flop("A♠ 9♥", "K♦ 9♣ 2♠", 0)).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn act() {
+        let mut bot = EquityThreshold::new(7).with_samples(2);
+
+        assert_eq!(Decision::Raise(300), bot.act(&preflop("A♠ A♥", Position6Max::BTN)));
+        assert_eq!(Decision::Raise(666), bot.act(&flop("8♠ 8♥", "K♦ 8♣ 2♠", 0)));
+        assert_eq!(Decision::Fold, bot.act(&flop("4♠ 3♥", "K♦ 8♣ Q♠", 900)));
+    }
+}
@@ -0,0 +1,298 @@
+use crate::analysis::hand_rank::HandRank;
+use crate::arrays::five::Five;
+use crate::arrays::seven::Seven;
+use crate::arrays::six::Six;
+use crate::arrays::two::Two;
+use crate::arrays::HandRanker;
+use crate::cards::Cards;
+use crate::play::phases::PhaseHoldem;
+use crate::play::Position6Max;
+use crate::Pile;
+use std::fmt::{Display, Formatter};
+
+pub mod calling_station;
+pub mod equity_threshold;
+pub mod push_fold;
+pub mod random;
+pub mod tight_aggressive;
+
+/// An `Agent` is anything that can sit in a seat and make a decision when the action gets to it.
+///
+/// The reason for these is simple: before I can say that a strategy is any good, I need something
+/// to measure it against. Every poker player knows the archetypes: the maniac who clicks buttons,
+/// the calling station who never lets go, the nit who only plays premiums, and the short stack
+/// who shoves or folds. These are my baselines.
+///
+/// Every built-in agent takes a seed, so that the same seed facing the same situations will
+/// always make the same decisions. A test that passes once will pass every time.
+pub trait Agent {
+    fn name(&self) -> String;
+
+    fn act(&mut self, situation: &Situation) -> Decision;
+}
+
+/// The possible choices for a player when the action is on them.
+///
+/// `Raise` holds the total number of chips the player is putting in with this action, including
+/// whatever it takes to call.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Decision {
+    Fold,
+    Check,
+    Call,
+    Raise(usize),
+    AllIn,
+}
+
+impl Display for Decision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Decision::Fold => write!(f, "folds"),
+            Decision::Check => write!(f, "checks"),
+            Decision::Call => write!(f, "calls"),
+            Decision::Raise(amount) => write!(f, "raises {amount}"),
+            Decision::AllIn => write!(f, "all in"),
+        }
+    }
+}
+
+/// Everything an `Agent` gets to know about the hand when it's their turn to act.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Situation {
+    pub phase: PhaseHoldem,
+    pub position: Position6Max,
+    pub hand: Two,
+    pub board: Cards,
+    pub pot: usize,
+    pub to_call: usize,
+    pub min_raise: usize,
+    pub stack: usize,
+    pub big_blind: usize,
+    pub opponents: usize,
+}
+
+impl Situation {
+    /// Returns true if the player can check.
+    #[must_use]
+    pub fn can_check(&self) -> bool {
+        self.to_call == 0
+    }
+
+    /// Returns true if the player has enough chips to call without going all in.
+    #[must_use]
+    pub fn can_call(&self) -> bool {
+        self.to_call > 0 && self.stack > self.to_call
+    }
+
+    /// Returns true if the player has enough chips to make at least a minimum raise without
+    /// going all in.
+    #[must_use]
+    pub fn can_raise(&self) -> bool {
+        self.stack > self.min_raise_total()
+    }
+
+    /// The smallest number of chips that a legal `Decision::Raise` can hold.
+    #[must_use]
+    pub fn min_raise_total(&self) -> usize {
+        self.to_call + self.min_raise.max(self.big_blind)
+    }
+
+    /// The players stack measured in big blinds.
+    #[must_use]
+    pub fn stack_in_bbs(&self) -> usize {
+        match self.big_blind {
+            0 => 0,
+            bb => self.stack / bb,
+        }
+    }
+
+    /// Returns true if the pot hasn't been raised yet preflop. At most the player has to
+    /// call the big blind.
+    #[must_use]
+    pub fn is_unopened(&self) -> bool {
+        self.phase == PhaseHoldem::Preflop && self.to_call <= self.big_blind
+    }
+
+    #[must_use]
+    pub fn is_legal(&self, decision: &Decision) -> bool {
+        match *decision {
+            Decision::Fold => self.to_call > 0,
+            Decision::Check => self.can_check(),
+            Decision::Call => self.can_call(),
+            Decision::Raise(amount) => amount >= self.min_raise_total() && amount < self.stack,
+            Decision::AllIn => self.stack > 0,
+        }
+    }
+
+    /// Returns every type of legal decision for the `Situation`. Raises are represented by the
+    /// minimum legal raise.
+    #[must_use]
+    pub fn legal_decisions(&self) -> Vec<Decision> {
+        let mut decisions = Vec::new();
+        if self.can_check() {
+            decisions.push(Decision::Check);
+        } else {
+            decisions.push(Decision::Fold);
+        }
+        if self.can_call() {
+            decisions.push(Decision::Call);
+        }
+        if self.can_raise() {
+            decisions.push(Decision::Raise(self.min_raise_total()));
+        }
+        if self.stack > 0 {
+            decisions.push(Decision::AllIn);
+        }
+        decisions
+    }
+
+    /// Turns a raise of any size into a legal decision, bumping it up to the minimum raise, and
+    /// going all in if the player doesn't have enough chips to cover it.
+    #[must_use]
+    pub fn raise(&self, amount: usize) -> Decision {
+        let amount = amount.max(self.min_raise_total());
+        if amount >= self.stack {
+            Decision::AllIn
+        } else {
+            Decision::Raise(amount)
+        }
+    }
+
+    /// Calls if the player can, otherwise goes all in. Used by agents who want to stay in the
+    /// hand no matter what.
+    #[must_use]
+    pub fn call(&self) -> Decision {
+        if self.can_check() {
+            Decision::Check
+        } else if self.can_call() {
+            Decision::Call
+        } else {
+            Decision::AllIn
+        }
+    }
+
+    /// Checks if it's free, otherwise folds.
+    #[must_use]
+    pub fn check_or_fold(&self) -> Decision {
+        if self.can_check() {
+            Decision::Check
+        } else {
+            Decision::Fold
+        }
+    }
+
+    /// The best hand the player can make with their hole cards and the board. Returns the
+    /// default, invalid, `HandRank` before the flop.
+    #[must_use]
+    pub fn hand_rank(&self) -> HandRank {
+        let mut cards = self.hand.cards();
+        cards.insert_all(&self.board);
+        match cards.len() {
+            5 => Five::try_from(cards).map(|five| five.hand_rank()).unwrap_or_default(),
+            6 => Six::try_from(cards).map(|six| six.hand_rank()).unwrap_or_default(),
+            7 => Seven::try_from(cards)
+                .map(|seven| seven.hand_rank())
+                .unwrap_or_default(),
+            _ => HandRank::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+pub(crate) mod play__agents_tests {
+    use super::*;
+    use crate::analysis::name::Name;
+    use std::str::FromStr;
+
+    pub fn preflop(hand: &str, position: Position6Max) -> Situation {
+        Situation {
+            phase: PhaseHoldem::Preflop,
+            position,
+            hand: Two::from_str(hand).unwrap(),
+            board: Cards::default(),
+            pot: 150,
+            to_call: 100,
+            min_raise: 100,
+            stack: 10_000,
+            big_blind: 100,
+            opponents: 5,
+        }
+    }
+
+    pub fn flop(hand: &str, board: &str, to_call: usize) -> Situation {
+        Situation {
+            phase: PhaseHoldem::Flop,
+            position: Position6Max::BTN,
+            hand: Two::from_str(hand).unwrap(),
+            board: Cards::from_str(board).unwrap(),
+            pot: 1_000,
+            to_call,
+            min_raise: 100,
+            stack: 9_000,
+            big_blind: 100,
+            opponents: 1,
+        }
+    }
+
+    #[test]
+    fn legal_decisions__facing_bet() {
+        let situation = preflop("A♠ A♥", Position6Max::UTG);
+
+        assert_eq!(
+            vec![Decision::Fold, Decision::Call, Decision::Raise(200), Decision::AllIn],
+            situation.legal_decisions()
+        );
+    }
+
+    #[test]
+    fn legal_decisions__short_stack() {
+        let mut situation = preflop("A♠ A♥", Position6Max::UTG);
+        situation.stack = 100;
+
+        assert_eq!(vec![Decision::Fold, Decision::AllIn], situation.legal_decisions());
+    }
+
+    #[test]
+    fn legal_decisions__free() {
+        let situation = flop("A♠ A♥", "K♦ 8♣ 2♠", 0);
+
+        assert_eq!(
+            vec![Decision::Check, Decision::Raise(100), Decision::AllIn],
+            situation.legal_decisions()
+        );
+    }
+
+    #[test]
+    fn is_legal() {
+        let situation = preflop("A♠ A♥", Position6Max::UTG);
+
+        assert!(situation.is_legal(&Decision::Fold));
+        assert!(situation.is_legal(&Decision::Call));
+        assert!(situation.is_legal(&Decision::Raise(300)));
+        assert!(!situation.is_legal(&Decision::Check));
+        assert!(!situation.is_legal(&Decision::Raise(150)));
+        assert!(!situation.is_legal(&Decision::Raise(10_000)));
+    }
+
+    #[test]
+    fn raise() {
+        let situation = preflop("A♠ A♥", Position6Max::UTG);
+
+        assert_eq!(Decision::Raise(200), situation.raise(150));
+        assert_eq!(Decision::Raise(300), situation.raise(300));
+        assert_eq!(Decision::AllIn, situation.raise(20_000));
+    }
+
+    #[test]
+    fn hand_rank() {
+        assert_eq!(Name::Pair, flop("A♠ K♥", "K♦ 8♣ 2♠", 0).hand_rank().name);
+        assert_eq!(Name::Invalid, preflop("A♠ K♥", Position6Max::BTN).hand_rank().name);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("raises 300", Decision::Raise(300).to_string());
+        assert_eq!("folds", Decision::Fold.to_string());
+    }
+}
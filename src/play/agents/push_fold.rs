@@ -0,0 +1,121 @@
+use crate::analysis::name::Name;
+use crate::arrays::combos::twos::Twos;
+use crate::play::agents::{Agent, Decision, Situation};
+use crate::play::phases::PhaseHoldem;
+use std::str::FromStr;
+
+/// The short stack special. Every decision preflop is either all in or fold. The shorter the
+/// stack, measured in big blinds, the wider the shoving range.
+///
+/// Think of the end of a tournament, when the blinds are big and the stacks are small, and you
+/// see the same handful of players doing nothing but jamming.
+#[derive(Clone, Debug)]
+pub struct PushFold {
+    pub seed: u64,
+    /// Pushing ranges ordered from shortest stack to deepest. Each entry holds the maximum stack
+    /// size in big blinds for the range.
+    pub push: Vec<(usize, Twos)>,
+    /// The range the bot calls off its stack with when someone else has already raised.
+    pub call: Twos,
+}
+
+impl PushFold {
+    pub const PUSH_6BB: &'static str = "22+,AK,AQ,AJ,AT,A9,A8,A7,A6,A5,A4,A3,A2,KQ,KJ,KT,K9,QJ,QT,JT,T9s,98s,87s,76s";
+    pub const PUSH_10BB: &'static str = "22+,AK,AQ,AJ,AT,A9,A8,A7s,A6s,A5s,A4s,A3s,A2s,KQ,KJ,KTs,QJs,JTs";
+    pub const PUSH_15BB: &'static str = "55+,AK,AQ,AJ,ATs,A9s,KQs";
+    pub const PUSH_DEEP: &'static str = "TT+,AK";
+    pub const CALL: &'static str = "88+,AK,AQs";
+
+    /// # Panics
+    ///
+    /// Only if the default chart constants can't be parsed, which the tests guard against.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        PushFold {
+            seed,
+            push: vec![
+                (6, Twos::from_str(PushFold::PUSH_6BB).unwrap()),
+                (10, Twos::from_str(PushFold::PUSH_10BB).unwrap()),
+                (15, Twos::from_str(PushFold::PUSH_15BB).unwrap()),
+                (usize::MAX, Twos::from_str(PushFold::PUSH_DEEP).unwrap()),
+            ],
+            call: Twos::from_str(PushFold::CALL).unwrap(),
+        }
+    }
+
+    /// Returns the range the bot shoves with for a stack of a given number of big blinds.
+    #[must_use]
+    pub fn pushing_range(&self, bbs: usize) -> Option<&Twos> {
+        self.push.iter().find(|(max, _)| bbs <= *max).map(|(_, twos)| twos)
+    }
+}
+
+impl Agent for PushFold {
+    fn name(&self) -> String {
+        format!("Push Fold #{}", self.seed)
+    }
+
+    fn act(&mut self, situation: &Situation) -> Decision {
+        let hand = &situation.hand;
+        match situation.phase {
+            PhaseHoldem::Preflop => {
+                let range = if situation.is_unopened() {
+                    self.pushing_range(situation.stack_in_bbs())
+                } else {
+                    Some(&self.call)
+                };
+                match range {
+                    Some(twos) if twos.contains(hand) => Decision::AllIn,
+                    _ => situation.check_or_fold(),
+                }
+            }
+            _ => {
+                if situation.hand_rank().name <= Name::Pair {
+                    Decision::AllIn
+                } else {
+                    situation.check_or_fold()
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__agents__push_fold_tests {
+    use super::*;
+    use crate::play::agents::play__agents_tests::{flop, preflop};
+    use crate::play::Position6Max;
+
+    #[test]
+    fn pushing_range() {
+        let bot = PushFold::new(1);
+
+        assert!(bot.pushing_range(5).unwrap().len() > bot.pushing_range(8).unwrap().len());
+        assert!(bot.pushing_range(8).unwrap().len() > bot.pushing_range(12).unwrap().len());
+        assert!(bot.pushing_range(12).unwrap().len() > bot.pushing_range(100).unwrap().len());
+    }
+
+    #[test]
+    fn act__preflop() {
+        let mut bot = PushFold::new(1);
+        let mut situation = preflop("K♠ 9♥", Position6Max::BTN);
+
+        assert_eq!(Decision::Fold, bot.act(&situation));
+
+        situation.stack = 500;
+        assert_eq!(Decision::AllIn, bot.act(&situation));
+
+        situation.to_call = 300;
+        assert_eq!(Decision::Fold, bot.act(&situation));
+    }
+
+    #[test]
+    fn act__postflop() {
+        let mut bot = PushFold::new(1);
+
+        assert_eq!(Decision::AllIn, bot.act(&flop("A♠ K♥", "K♦ 8♣ 2♠", 0)));
+        assert_eq!(Decision::Check, bot.act(&flop("7♠ 6♥", "K♦ 8♣ 2♠", 0)));
+        assert_eq!(Decision::Fold, bot.act(&flop("7♠ 6♥", "K♦ 8♣ 2♠", 100)));
+    }
+}
@@ -0,0 +1,72 @@
+use crate::play::agents::{Agent, Decision, Situation};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+/// The maniac. Picks any legal action, and when it raises, picks any legal size.
+///
+/// Useful as a fuzzer as much as an opponent. If something in a table engine can break, this bot
+/// will find it.
+#[derive(Clone, Debug)]
+pub struct RandomBot {
+    pub seed: u64,
+    rng: StdRng,
+}
+
+impl RandomBot {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        RandomBot {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Agent for RandomBot {
+    fn name(&self) -> String {
+        format!("Random Bot #{}", self.seed)
+    }
+
+    fn act(&mut self, situation: &Situation) -> Decision {
+        let decisions = situation.legal_decisions();
+        match decisions.choose(&mut self.rng) {
+            Some(Decision::Raise(min)) => {
+                let amount = self.rng.gen_range(*min..situation.stack);
+                Decision::Raise(amount)
+            }
+            Some(decision) => *decision,
+            None => Decision::Fold,
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__agents__random_tests {
+    use super::*;
+    use crate::play::agents::play__agents_tests::{flop, preflop};
+    use crate::play::Position6Max;
+
+    #[test]
+    fn act__is_legal() {
+        let mut bot = RandomBot::new(42);
+        let situation = preflop("7♠ 2♥", Position6Max::UTG);
+
+        for _ in 0..100 {
+            assert!(situation.is_legal(&bot.act(&situation)));
+        }
+    }
+
+    #[test]
+    fn act__deterministic() {
+        let mut first = RandomBot::new(1_234);
+        let mut second = RandomBot::new(1_234);
+        let situation = flop("7♠ 2♥", "K♦ 8♣ 2♠", 300);
+
+        let a: Vec<Decision> = (0..50).map(|_| first.act(&situation)).collect();
+        let b: Vec<Decision> = (0..50).map(|_| second.act(&situation)).collect();
+
+        assert_eq!(a, b);
+    }
+}
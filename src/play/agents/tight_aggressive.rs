@@ -0,0 +1,201 @@
+use crate::analysis::name::Name;
+use crate::arrays::combos::twos::Twos;
+use crate::play::agents::{Agent, Decision, Situation};
+use crate::play::phases::PhaseHoldem;
+use crate::play::Position6Max;
+use crate::PKError;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::str::FromStr;
+
+/// The classic TAG. Plays a tight preflop chart that opens up in late position, three bets
+/// premiums, and bets when it's got something after the flop.
+///
+/// The charts are plain `Twos` ranges, so they can be swapped out for any range that you can
+/// write down as a string:
+///
+/// ```
+/// use pkcore::play::agents::tight_aggressive::TightAggressive;
+///
+/// let tag = TightAggressive::new(42)
+///     .with_ranges("QQ+,AKs", "99+,AKs,AQs,AKo", "KK+", "QQ,JJ,AKs")
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct TightAggressive {
+    pub seed: u64,
+    pub early: Twos,
+    pub late: Twos,
+    pub three_bet: Twos,
+    pub call: Twos,
+    /// How often the bot fires a continuation bet with air on the flop.
+    pub cbet_frequency: f64,
+    rng: StdRng,
+}
+
+impl TightAggressive {
+    pub const EARLY: &'static str = "88+,AKs,AQs,AJs,ATs,KQs,KJs,QJs,JTs,AKo,AQo";
+    pub const LATE: &'static str = "22+,AKs,AQs,AJs,ATs,A9s,A8s,A7s,A6s,A5s,A4s,A3s,A2s,KQs,KJs,KTs,QJs,QTs,JTs,T9s,98s,87s,76s,AKo,AQo,AJo,ATo,KQo,KJo,QJo";
+    pub const THREE_BET: &'static str = "QQ+,AKs,AKo";
+    pub const CALL: &'static str = "JJ,TT,99,AQs,AJs,KQs,AQo";
+
+    /// # Panics
+    ///
+    /// Only if the default chart constants can't be parsed, which the tests guard against.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        TightAggressive {
+            seed,
+            early: Twos::from_str(TightAggressive::EARLY).unwrap(),
+            late: Twos::from_str(TightAggressive::LATE).unwrap(),
+            three_bet: Twos::from_str(TightAggressive::THREE_BET).unwrap(),
+            call: Twos::from_str(TightAggressive::CALL).unwrap(),
+            cbet_frequency: 0.65,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Replaces the preflop charts.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidIndex` if any of the ranges can't be parsed.
+    pub fn with_ranges(mut self, early: &str, late: &str, three_bet: &str, call: &str) -> Result<Self, PKError> {
+        self.early = Twos::from_str(early)?;
+        self.late = Twos::from_str(late)?;
+        self.three_bet = Twos::from_str(three_bet)?;
+        self.call = Twos::from_str(call)?;
+        Ok(self)
+    }
+
+    /// The range the bot opens with from a specific position.
+    #[must_use]
+    pub fn opening_range(&self, position: Position6Max) -> &Twos {
+        match position {
+            Position6Max::UTG | Position6Max::MP => &self.early,
+            Position6Max::CO | Position6Max::BTN | Position6Max::SB | Position6Max::BB => &self.late,
+        }
+    }
+
+    fn preflop(&self, situation: &Situation) -> Decision {
+        let hand = &situation.hand;
+        if situation.is_unopened() {
+            if self.opening_range(situation.position).contains(hand) {
+                situation.raise(situation.to_call + situation.big_blind * 3)
+            } else {
+                situation.check_or_fold()
+            }
+        } else if self.three_bet.contains(hand) {
+            situation.raise(situation.to_call * 3)
+        } else if self.call.contains(hand) {
+            situation.call()
+        } else {
+            situation.check_or_fold()
+        }
+    }
+
+    fn postflop(&mut self, situation: &Situation) -> Decision {
+        let name = situation.hand_rank().name;
+        if name <= Name::TwoPair {
+            situation.raise(situation.to_call + situation.pot * 2 / 3)
+        } else if name == Name::Pair {
+            if situation.can_check() {
+                situation.raise(situation.pot / 2)
+            } else if situation.to_call <= situation.pot / 2 {
+                situation.call()
+            } else {
+                Decision::Fold
+            }
+        } else if situation.can_check()
+            && situation.phase == PhaseHoldem::Flop
+            && self.rng.gen_bool(self.cbet_frequency)
+        {
+            situation.raise(situation.pot / 2)
+        } else {
+            situation.check_or_fold()
+        }
+    }
+}
+
+impl Agent for TightAggressive {
+    fn name(&self) -> String {
+        format!("Tight Aggressive #{}", self.seed)
+    }
+
+    fn act(&mut self, situation: &Situation) -> Decision {
+        match situation.phase {
+            PhaseHoldem::Preflop => self.preflop(situation),
+            _ => self.postflop(situation),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__agents__tight_aggressive_tests {
+    use super::*;
+    use crate::arrays::two::Two;
+    use crate::play::agents::play__agents_tests::{flop, preflop};
+
+    #[test]
+    fn new() {
+        let tag = TightAggressive::new(1);
+
+        assert!(!tag.early.is_empty());
+        assert!(tag.late.len() > tag.early.len());
+        assert!(!tag.three_bet.is_empty());
+        assert!(!tag.call.is_empty());
+    }
+
+    #[test]
+    fn with_ranges__invalid() {
+        assert!(TightAggressive::new(1).with_ranges("QQ+", "XX", "KK+", "QQ").is_err());
+    }
+
+    #[test]
+    fn act__preflop_open() {
+        let mut tag = TightAggressive::new(1);
+
+        assert_eq!(Decision::Raise(400), tag.act(&preflop("A♠ A♥", Position6Max::UTG)));
+        assert_eq!(Decision::Fold, tag.act(&preflop("7♠ 6♠", Position6Max::UTG)));
+        assert_eq!(Decision::Raise(400), tag.act(&preflop("7♠ 6♠", Position6Max::BTN)));
+    }
+
+    #[test]
+    fn act__preflop_facing_raise() {
+        let mut tag = TightAggressive::new(1);
+        let mut situation = preflop("A♠ A♥", Position6Max::BTN);
+        situation.to_call = 300;
+
+        assert_eq!(Decision::Raise(900), tag.act(&situation));
+
+        situation.hand = Two::from_str("J♠ J♥").unwrap();
+        assert_eq!(Decision::Call, tag.act(&situation));
+
+        situation.hand = Two::from_str("7♠ 6♠").unwrap();
+        assert_eq!(Decision::Fold, tag.act(&situation));
+    }
+
+    #[test]
+    fn act__postflop() {
+        let mut tag = TightAggressive::new(1);
+
+        assert_eq!(Decision::Raise(666), tag.act(&flop("K♠ 8♥", "K♦ 8♣ 2♠", 0)));
+        assert_eq!(Decision::Call, tag.act(&flop("A♠ K♥", "K♦ 8♣ 2♠", 300)));
+        assert_eq!(Decision::Fold, tag.act(&flop("7♠ 6♥", "K♦ 8♣ 2♠", 300)));
+    }
+
+    #[test]
+    fn act__deterministic() {
+        let situation = flop("7♠ 6♥", "K♦ 8♣ 2♠", 0);
+        let mut first = TightAggressive::new(99);
+        let mut second = TightAggressive::new(99);
+
+        let a: Vec<Decision> = (0..20).map(|_| first.act(&situation)).collect();
+        let b: Vec<Decision> = (0..20).map(|_| second.act(&situation)).collect();
+
+        assert_eq!(a, b);
+        assert!(a.contains(&Decision::Check));
+        assert!(a.contains(&Decision::Raise(500)));
+    }
+}
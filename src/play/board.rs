@@ -4,13 +4,14 @@ use crate::card::Card;
 use crate::cards::Cards;
 use crate::util::Util;
 use crate::{PKError, Pile, Plurable, TheNuts};
+use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 use std::ops::Index;
 use std::str::FromStr;
 
 /// A `Board` is a type that represents a single instance of the face up `Cards`
 /// of one `Game` of `Texas hold 'em`.
-#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Board {
     pub flop: Three,
     pub turn: Card,
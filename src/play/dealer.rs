@@ -0,0 +1,233 @@
+use crate::arrays::three::Three;
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::deck::Deck;
+use crate::play::board::Board;
+use crate::play::game::Game;
+use crate::play::hole_cards::HoleCards;
+use crate::PKError;
+use serde::{Deserialize, Serialize};
+
+/// A `Dealer` is a record of the exact order of the deck for a single hand.
+///
+/// If you know the order of the deck, and how many players were dealt in, you know everything
+/// about the hand: every player's hole cards, every burn card, and every card on the board,
+/// including the ones that never got dealt because everybody folded. That last bit is what
+/// players call [rabbit hunting](https://www.pokernews.com/pokerterms/rabbit-hunting.htm).
+///
+/// The dealer deals like a real one does: one card to each player at a time, going around the
+/// table twice, and then burning a card before the flop, the turn, and the river.
+///
+/// ```
+/// use pkcore::play::dealer::Dealer;
+///
+/// let dealer = Dealer::seeded(1_066);
+/// let json = serde_json::to_string(&dealer).unwrap();
+/// let replayed: Dealer = serde_json::from_str(&json).unwrap();
+///
+/// assert_eq!(dealer.game(3).unwrap(), replayed.game(3).unwrap());
+/// ```
+/// `Cards` equality ignores order, so `Dealer` doesn't implement `PartialEq`. Compare the
+/// `order` strings, or the dealt `Game`, instead.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Dealer {
+    /// The seed used to shuffle the deck, if there was one.
+    pub seed: Option<u64>,
+    pub order: Cards,
+}
+
+impl Dealer {
+    pub const MIN_PLAYERS: usize = 2;
+    pub const BOARD_SIZE: usize = 5;
+    pub const BURN_CARDS: usize = 3;
+
+    /// A deck shuffled from a specific seed.
+    #[must_use]
+    pub fn seeded(seed: u64) -> Self {
+        Dealer {
+            seed: Some(seed),
+            order: Deck::poker_cards_shuffled_seeded(seed),
+        }
+    }
+
+    /// A randomly shuffled deck. The seed is picked at random, but it's still recorded, so the
+    /// hand can be dealt again.
+    #[must_use]
+    pub fn shuffled() -> Self {
+        Dealer::seeded(rand::random::<u64>())
+    }
+
+    /// The most players that can be dealt in with this deck.
+    #[must_use]
+    pub fn max_players(&self) -> usize {
+        self.order.len().saturating_sub(Dealer::BOARD_SIZE + Dealer::BURN_CARDS) / 2
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::NotEnoughHands` if there are fewer than two players, and
+    /// `PKError::TooManyHands` if the deck can't cover everyone and the board.
+    pub fn hole_cards(&self, players: usize) -> Result<HoleCards, PKError> {
+        self.validate(players)?;
+        let mut hole_cards = HoleCards::with_capacity(players);
+        for seat in 0..players {
+            hole_cards.push(Two::new(self.card(seat)?, self.card(seat + players)?)?);
+        }
+        Ok(hole_cards)
+    }
+
+    /// The complete board, burn cards removed, for a hand with the passed in number of players.
+    ///
+    /// # Errors
+    ///
+    /// Same as `hole_cards()`.
+    pub fn board(&self, players: usize) -> Result<Board, PKError> {
+        self.validate(players)?;
+        let start = players * 2;
+        Ok(Board::new(
+            Three::from([self.card(start + 1)?, self.card(start + 2)?, self.card(start + 3)?]),
+            self.card(start + 5)?,
+            self.card(start + 7)?,
+        ))
+    }
+
+    /// Deals the hand again, exactly as it was dealt the first time.
+    ///
+    /// # Errors
+    ///
+    /// Same as `hole_cards()`.
+    pub fn game(&self, players: usize) -> Result<Game, PKError> {
+        Ok(Game::new(self.hole_cards(players)?, self.board(players)?))
+    }
+
+    /// Returns the board cards that would have come if the hand had kept going, after `dealt`
+    /// cards had already made it to the board. Pass in `0` if the hand ended before the flop,
+    /// `3` if it ended on the flop, and `4` if it ended on the turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCardCount` if `dealt` isn't one of the places where a hand can
+    /// end, or any of the errors from `hole_cards()`.
+    pub fn rabbit_hunt(&self, players: usize, dealt: usize) -> Result<Cards, PKError> {
+        let board = self.board(players)?;
+        let cards = [
+            board.flop.first(),
+            board.flop.second(),
+            board.flop.third(),
+            board.turn,
+            board.river,
+        ];
+        match dealt {
+            0 | 3 | 4 => Ok(Cards::from(cards[dealt..].to_vec())),
+            _ => Err(PKError::InvalidCardCount),
+        }
+    }
+
+    fn card(&self, index: usize) -> Result<Card, PKError> {
+        self.order.get_index(index).copied().ok_or(PKError::NotEnoughCards)
+    }
+
+    fn validate(&self, players: usize) -> Result<(), PKError> {
+        if players < Dealer::MIN_PLAYERS {
+            Err(PKError::NotEnoughHands)
+        } else if players > self.max_players() {
+            Err(PKError::TooManyHands)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl From<Cards> for Dealer {
+    fn from(order: Cards) -> Self {
+        Dealer { seed: None, order }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__dealer_tests {
+    use super::*;
+    use crate::Pile;
+    use std::str::FromStr;
+
+    fn unshuffled() -> Dealer {
+        Dealer::from(Deck::poker_cards())
+    }
+
+    #[test]
+    fn seeded() {
+        assert_eq!(
+            Dealer::seeded(42).order.to_string(),
+            Dealer::seeded(42).order.to_string()
+        );
+        assert_eq!(
+            Dealer::seeded(42).order.to_string(),
+            Deck::poker_cards_shuffled_seeded(42).to_string()
+        );
+    }
+
+    #[test]
+    fn shuffled() {
+        let dealer = Dealer::shuffled();
+
+        assert!(dealer.seed.is_some());
+        assert_eq!(
+            Dealer::seeded(dealer.seed.unwrap()).order.to_string(),
+            dealer.order.to_string()
+        );
+    }
+
+    #[test]
+    fn hole_cards() {
+        let hole_cards = unshuffled().hole_cards(2).unwrap();
+
+        assert_eq!("[A♠ Q♠, K♠ J♠]", hole_cards.to_string());
+    }
+
+    #[test]
+    fn hole_cards__invalid() {
+        assert_eq!(PKError::NotEnoughHands, unshuffled().hole_cards(1).unwrap_err());
+        assert_eq!(PKError::TooManyHands, unshuffled().hole_cards(23).unwrap_err());
+        assert!(unshuffled().hole_cards(22).is_ok());
+    }
+
+    #[test]
+    fn board() {
+        let board = unshuffled().board(2).unwrap();
+
+        assert_eq!(Board::from_str("9♠ 8♠ 7♠ 5♠ 3♠").unwrap(), board);
+    }
+
+    #[test]
+    fn game__redeal() {
+        let dealer = Dealer::seeded(2_112);
+        let game = dealer.game(6).unwrap();
+
+        assert_eq!(game, dealer.clone().game(6).unwrap());
+        assert!(game.hands.cards().are_unique());
+        assert_eq!(12, game.hands.cards().len());
+    }
+
+    #[test]
+    fn rabbit_hunt() {
+        let dealer = unshuffled();
+
+        assert_eq!("9♠ 8♠ 7♠ 5♠ 3♠", dealer.rabbit_hunt(2, 0).unwrap().to_string());
+        assert_eq!("5♠ 3♠", dealer.rabbit_hunt(2, 3).unwrap().to_string());
+        assert_eq!("3♠", dealer.rabbit_hunt(2, 4).unwrap().to_string());
+        assert_eq!(PKError::InvalidCardCount, dealer.rabbit_hunt(2, 1).unwrap_err());
+    }
+
+    #[test]
+    fn serde() {
+        let dealer = Dealer::seeded(1_066);
+
+        let json = serde_json::to_string(&dealer).unwrap();
+        let replayed: Dealer = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(dealer.order.to_string(), replayed.order.to_string());
+        assert_eq!(dealer.game(4).unwrap(), replayed.game(4).unwrap());
+    }
+}
@@ -0,0 +1,712 @@
+use crate::analysis::store::nubibus::pluribus::Pluribus;
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::play::board::Board;
+use crate::play::phases::PhaseHoldem;
+use crate::{PKError, Pile};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// # Event sourcing a hand of poker
+///
+/// `Nubibus` tracks a hand with a pile of queues, a ledger, and a bunch of `Cell`s, and the only
+/// way to see what things looked like at any point is a `SeatSnapshot` for a single seat. That's
+/// a lot of moving parts for something that is, at its heart, a list of things that happened.
+///
+/// So let's flip it around. A hand is a stream of `TableEvent`s. The state of the table at any
+/// point is just what you get when you fold the events up to that point over the starting seats.
+/// Want to go back a step? Fold one less event. Want to save the hand? Serialize the events.
+///
+/// Every bet, call, raise, and check is a `Bet` holding the number of chips the player put into
+/// the pot with that action. A check is a `Bet` of zero.
+///
+/// When everybody is all in and the players agree to run it more than once, each run is a
+/// `RunOut` with the complete board for that run. The cards that were already out stay on
+/// `TableState::board`, and the rest of each run is kept in `TableState::runouts`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TableEvent {
+    Deal { seat: usize, cards: Two },
+    Post { seat: usize, amount: usize },
+    Bet { seat: usize, amount: usize },
+    Fold { seat: usize },
+    BoardCard { card: Card },
+    RunOut { board: Board },
+    Award { seat: usize, amount: usize },
+}
+
+impl Display for TableEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableEvent::Deal { seat, cards } => write!(f, "Seat {seat} is dealt {cards}"),
+            TableEvent::Post { seat, amount } => write!(f, "Seat {seat} posts {amount}"),
+            TableEvent::Bet { seat, amount: 0 } => write!(f, "Seat {seat} checks"),
+            TableEvent::Bet { seat, amount } => write!(f, "Seat {seat} bets {amount}"),
+            TableEvent::Fold { seat } => write!(f, "Seat {seat} folds"),
+            TableEvent::BoardCard { card } => write!(f, "Board {card}"),
+            TableEvent::RunOut { board } => write!(f, "Run out {board}"),
+            TableEvent::Award { seat, amount } => write!(f, "Seat {seat} wins {amount}"),
+        }
+    }
+}
+
+/// The state of a single seat at a specific point in the hand.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SeatState {
+    pub name: String,
+    pub stack: usize,
+    /// Chips put into the pot during the current street.
+    pub in_round: usize,
+    /// Chips put into the pot over the whole hand.
+    pub in_pot: usize,
+    pub cards: Two,
+    pub folded: bool,
+    pub won: usize,
+}
+
+impl SeatState {
+    #[must_use]
+    pub fn new(name: &str, stack: usize) -> Self {
+        SeatState {
+            name: name.to_string(),
+            stack,
+            ..Default::default()
+        }
+    }
+
+    #[must_use]
+    pub fn is_all_in(&self) -> bool {
+        self.stack == 0 && self.in_pot > 0
+    }
+
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.folded
+    }
+
+    fn put_in(&mut self, amount: usize) -> Result<(), PKError> {
+        if self.folded {
+            return Err(PKError::PlayerOutOfHand);
+        }
+        if amount > self.stack {
+            return Err(PKError::InsufficientChips);
+        }
+        self.stack -= amount;
+        self.in_round += amount;
+        self.in_pot += amount;
+        Ok(())
+    }
+}
+
+/// The state of the whole table, derived by folding `TableEvent`s over the starting seats.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct TableState {
+    pub seats: Vec<SeatState>,
+    pub board: Cards,
+    /// Every complete board, when the board is run more than once.
+    pub runouts: Vec<Board>,
+    pub pot: usize,
+    pub phase: PhaseHoldem,
+}
+
+impl TableState {
+    #[must_use]
+    pub fn new(seats: Vec<SeatState>) -> Self {
+        TableState {
+            seats,
+            ..Default::default()
+        }
+    }
+
+    /// Applies a single event to the state of the table.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::InvalidIndex` if the event refers to a seat that doesn't exist.
+    /// * `PKError::AlreadyDealt` if a seat is dealt cards twice.
+    /// * `PKError::PlayerOutOfHand` if a player who has folded tries to act.
+    /// * `PKError::InsufficientChips` if a player bets more than they have, or more is awarded
+    ///   than is in the pot.
+    /// * `PKError::Duplicate` if a card shows up twice.
+    /// * `PKError::TooManyCards` if more than five cards are dealt to the board, a card is dealt
+    ///   to the board after it's been run out, or a run out doesn't add any cards.
+    /// * `PKError::InvalidCard` if a run out doesn't start with the cards already on the board.
+    pub fn apply(&mut self, event: &TableEvent) -> Result<(), PKError> {
+        match event {
+            TableEvent::Deal { seat, cards } => {
+                let dealt = self.cards();
+                if dealt.get(&cards.first()).is_some() || dealt.get(&cards.second()).is_some() {
+                    return Err(PKError::Duplicate);
+                }
+                let seat = self.seat_mut(*seat)?;
+                if seat.cards != Two::default() {
+                    return Err(PKError::AlreadyDealt);
+                }
+                seat.cards = *cards;
+                if self.phase == PhaseHoldem::Init {
+                    self.phase = PhaseHoldem::Preflop;
+                }
+            }
+            TableEvent::Post { seat, amount } | TableEvent::Bet { seat, amount } => {
+                self.seat_mut(*seat)?.put_in(*amount)?;
+                self.pot += amount;
+            }
+            TableEvent::Fold { seat } => {
+                let seat = self.seat_mut(*seat)?;
+                if seat.folded {
+                    return Err(PKError::PlayerOutOfHand);
+                }
+                seat.folded = true;
+            }
+            TableEvent::BoardCard { card } => {
+                if self.cards().get(card).is_some() {
+                    return Err(PKError::Duplicate);
+                }
+                if self.board.len() >= 5 || !self.runouts.is_empty() {
+                    return Err(PKError::TooManyCards);
+                }
+                self.board.insert(*card);
+                let phase = match self.board.len() {
+                    0..=2 => self.phase,
+                    3 => PhaseHoldem::Flop,
+                    4 => PhaseHoldem::Turn,
+                    _ => PhaseHoldem::River,
+                };
+                if phase != self.phase {
+                    self.phase = phase;
+                    self.end_round();
+                }
+            }
+            TableEvent::RunOut { board } => {
+                let cards = board.cards();
+                if cards.len() != 5 || cards.contains_blank() {
+                    return Err(PKError::NotEnoughCards);
+                }
+                if self.board.iter().any(|card| cards.get(card).is_none()) {
+                    return Err(PKError::InvalidCard);
+                }
+                let dealt = self.cards();
+                let new = cards
+                    .iter()
+                    .filter(|card| self.board.get(card).is_none())
+                    .collect::<Vec<&Card>>();
+                if new.is_empty() {
+                    return Err(PKError::TooManyCards);
+                }
+                if new.iter().any(|card| dealt.get(card).is_some()) {
+                    return Err(PKError::Duplicate);
+                }
+                self.runouts.push(*board);
+                if self.phase != PhaseHoldem::River {
+                    self.phase = PhaseHoldem::River;
+                    self.end_round();
+                }
+            }
+            TableEvent::Award { seat, amount } => {
+                if *amount > self.pot {
+                    return Err(PKError::InsufficientChips);
+                }
+                let seat = self.seat_mut(*seat)?;
+                seat.stack += amount;
+                seat.won += amount;
+                self.pot -= amount;
+                self.phase = PhaseHoldem::Over;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every card that's been dealt, to the players, to the board, and to any run outs.
+    #[must_use]
+    pub fn cards(&self) -> Cards {
+        let mut cards = self.board.clone();
+        for runout in &self.runouts {
+            cards.insert_all(&runout.cards());
+        }
+        for seat in &self.seats {
+            if seat.cards != Two::default() {
+                cards.insert(seat.cards.first());
+                cards.insert(seat.cards.second());
+            }
+        }
+        cards
+    }
+
+    /// The number of players who haven't folded.
+    #[must_use]
+    pub fn in_hand_count(&self) -> usize {
+        self.seats.iter().filter(|seat| seat.is_active()).count()
+    }
+
+    /// The largest amount anyone has put in during the current street.
+    #[must_use]
+    pub fn to_match(&self) -> usize {
+        self.seats.iter().map(|seat| seat.in_round).max().unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn stacks(&self) -> Vec<usize> {
+        self.seats.iter().map(|seat| seat.stack).collect()
+    }
+
+    /// The total number of chips on the table, in stacks and in the pot. Since chips are never
+    /// created or destroyed, this should never change over the course of a hand.
+    #[must_use]
+    pub fn total_chips(&self) -> usize {
+        self.seats.iter().map(|seat| seat.stack).sum::<usize>() + self.pot
+    }
+
+    fn end_round(&mut self) {
+        for seat in &mut self.seats {
+            seat.in_round = 0;
+        }
+    }
+
+    fn seat_mut(&mut self, seat: usize) -> Result<&mut SeatState, PKError> {
+        self.seats.get_mut(seat).ok_or(PKError::InvalidIndex)
+    }
+}
+
+impl Display for TableState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} POT: {} BOARD: {}", self.phase, self.pot, self.board)?;
+        for (i, seat) in self.seats.iter().enumerate() {
+            let status = if seat.folded { " (folded)" } else { "" };
+            writeln!(
+                f,
+                "  Seat {i}: {} {} [{}] in pot: {}{status}",
+                seat.name, seat.stack, seat.cards, seat.in_pot
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A hand of poker as a list of events, along with a cursor that can step forward and backward
+/// through the hand.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EventStream {
+    pub seats: Vec<SeatState>,
+    pub events: Vec<TableEvent>,
+    #[serde(skip)]
+    cursor: usize,
+}
+
+impl EventStream {
+    #[must_use]
+    pub fn new(seats: Vec<SeatState>) -> Self {
+        EventStream {
+            seats,
+            events: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    /// Adds an event to the end of the stream, as long as it's legal given everything that's
+    /// come before it. The cursor moves to the end of the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `TableState::apply()`.
+    pub fn push(&mut self, event: TableEvent) -> Result<(), PKError> {
+        let mut state = self.state_at(self.events.len())?;
+        state.apply(&event)?;
+        self.events.push(event);
+        self.cursor = self.events.len();
+        Ok(())
+    }
+
+    #[must_use]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// The event that the cursor just stepped past.
+    #[must_use]
+    pub fn current(&self) -> Option<&TableEvent> {
+        match self.cursor {
+            0 => None,
+            i => self.events.get(i - 1),
+        }
+    }
+
+    /// Moves the cursor forward one event. Returns false if it's already at the end.
+    pub fn forward(&mut self) -> bool {
+        if self.cursor < self.events.len() {
+            self.cursor += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Moves the cursor back one event. Returns false if it's already at the start.
+    pub fn backward(&mut self) -> bool {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn rewind(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn fast_forward(&mut self) {
+        self.cursor = self.events.len();
+    }
+
+    /// The state of the table at the cursor.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from `TableState::apply()`, which can only happen if the events have
+    /// been tampered with, say in a hand-edited JSON file.
+    pub fn state(&self) -> Result<TableState, PKError> {
+        self.state_at(self.cursor)
+    }
+
+    /// The state of the table after the first `n` events.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidIndex` if there aren't `n` events, otherwise same as `state()`.
+    pub fn state_at(&self, n: usize) -> Result<TableState, PKError> {
+        if n > self.events.len() {
+            return Err(PKError::InvalidIndex);
+        }
+        self.events[..n]
+            .iter()
+            .try_fold(TableState::new(self.seats.clone()), |mut state, event| {
+                state.apply(event)?;
+                Ok(state)
+            })
+    }
+
+    /// The state of the table once every event has been applied.
+    ///
+    /// # Errors
+    ///
+    /// Same as `state()`.
+    pub fn final_state(&self) -> Result<TableState, PKError> {
+        self.state_at(self.events.len())
+    }
+}
+
+/// Turns a Pluribus log line into an `EventStream`.
+///
+/// In the Pluribus logs, every raise amount is the total number of chips that the player has put
+/// into the pot over the course of the hand, not just for the current round, so we need to keep
+/// a running total for each player to work out how many chips each action actually puts in.
+///
+/// The winnings are the net result for each player, so the amount awarded to a player is their
+/// winnings plus what they put in.
+impl TryFrom<&Pluribus> for EventStream {
+    type Error = PKError;
+
+    fn try_from(pluribus: &Pluribus) -> Result<Self, Self::Error> {
+        let count = pluribus.players.len();
+        if count < 2 {
+            return Err(PKError::NotEnoughHands);
+        }
+        let seats = pluribus
+            .players
+            .iter()
+            .map(|name| SeatState::new(name, Pluribus::STARTING_STACK))
+            .collect::<Vec<SeatState>>();
+        let mut stream = EventStream::new(seats);
+
+        for (seat, cards) in pluribus.hole_cards.iter().enumerate() {
+            stream.push(TableEvent::Deal { seat, cards: *cards })?;
+        }
+        stream.push(TableEvent::Post {
+            seat: 0,
+            amount: Pluribus::SMALL_BLIND,
+        })?;
+        stream.push(TableEvent::Post {
+            seat: 1,
+            amount: Pluribus::BIG_BLIND,
+        })?;
+
+        let board = [
+            pluribus.board.flop.first(),
+            pluribus.board.flop.second(),
+            pluribus.board.flop.third(),
+            pluribus.board.turn,
+            pluribus.board.river,
+        ];
+        let mut board_dealt = 0;
+        let mut bet_to = Pluribus::BIG_BLIND;
+
+        for (round, actions) in pluribus.rounds.iter().enumerate() {
+            if round > 0 {
+                let street_size = if round == 1 { 3 } else { 1 };
+                for card in board.iter().skip(board_dealt).take(street_size) {
+                    if card.is_dealt() {
+                        stream.push(TableEvent::BoardCard { card: *card })?;
+                        board_dealt += 1;
+                    }
+                }
+            }
+
+            let state = stream.final_state()?;
+            let mut seat = if round == 0 { 2 % count } else { count - 1 };
+            if round > 0 {
+                seat = EventStream::next_to_act(&state, seat);
+            }
+
+            let mut chars = actions.chars().peekable();
+            while let Some(c) = chars.next() {
+                let state = stream.final_state()?;
+                let in_pot = state.seats[seat].in_pot;
+                match c {
+                    'f' => stream.push(TableEvent::Fold { seat })?,
+                    'c' => {
+                        let amount = bet_to.saturating_sub(in_pot).min(state.seats[seat].stack);
+                        stream.push(TableEvent::Bet { seat, amount })?;
+                    }
+                    'r' => {
+                        let mut digits = String::new();
+                        while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+                            digits.push(*d);
+                            chars.next();
+                        }
+                        bet_to = digits.parse::<usize>().map_err(|_| PKError::InvalidPluribusIndex)?;
+                        let amount = bet_to.saturating_sub(in_pot);
+                        stream.push(TableEvent::Bet { seat, amount })?;
+                    }
+                    _ => return Err(PKError::InvalidPluribusIndex),
+                }
+                seat = EventStream::next_to_act(&stream.final_state()?, seat);
+            }
+        }
+
+        for card in board.iter().skip(board_dealt) {
+            if card.is_dealt() {
+                stream.push(TableEvent::BoardCard { card: *card })?;
+            }
+        }
+
+        let state = stream.final_state()?;
+        for (seat, winnings) in pluribus.winnings.iter().enumerate() {
+            let in_pot = state.seats.get(seat).ok_or(PKError::InvalidIndex)?.in_pot;
+            let amount = in_pot.checked_add_signed(*winnings).unwrap_or_default();
+            if amount > 0 {
+                stream.push(TableEvent::Award { seat, amount })?;
+            }
+        }
+
+        stream.rewind();
+        Ok(stream)
+    }
+}
+
+impl EventStream {
+    /// The next seat after `seat` that still has a decision to make.
+    fn next_to_act(state: &TableState, seat: usize) -> usize {
+        let count = state.seats.len();
+        for i in 1..=count {
+            let next = (seat + i) % count;
+            let s = &state.seats[next];
+            if s.is_active() && !s.is_all_in() {
+                return next;
+            }
+        }
+        seat
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__events_tests {
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    fn heads_up() -> EventStream {
+        let mut stream = EventStream::new(vec![SeatState::new("Daniel", 1_000), SeatState::new("Gus", 1_000)]);
+        stream
+            .push(TableEvent::Deal {
+                seat: 0,
+                cards: Two::from_str("6♠ 6♥").unwrap(),
+            })
+            .unwrap();
+        stream
+            .push(TableEvent::Deal {
+                seat: 1,
+                cards: Two::from_str("5♦ 5♣").unwrap(),
+            })
+            .unwrap();
+        stream.push(TableEvent::Post { seat: 0, amount: 50 }).unwrap();
+        stream.push(TableEvent::Post { seat: 1, amount: 100 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 0, amount: 250 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 1, amount: 200 }).unwrap();
+        for card in Cards::from_str("9♣ 6♦ 5♥").unwrap() {
+            stream.push(TableEvent::BoardCard { card }).unwrap();
+        }
+        stream.push(TableEvent::Bet { seat: 0, amount: 0 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 1, amount: 300 }).unwrap();
+        stream.push(TableEvent::Fold { seat: 0 }).unwrap();
+        stream.push(TableEvent::Award { seat: 1, amount: 900 }).unwrap();
+        stream
+    }
+
+    #[test]
+    fn push__invalid() {
+        let mut stream = heads_up();
+
+        assert_eq!(
+            PKError::PlayerOutOfHand,
+            stream.push(TableEvent::Bet { seat: 0, amount: 10 }).unwrap_err()
+        );
+        assert_eq!(
+            PKError::InsufficientChips,
+            stream
+                .push(TableEvent::Bet {
+                    seat: 1,
+                    amount: 10_000
+                })
+                .unwrap_err()
+        );
+        assert_eq!(
+            PKError::InvalidIndex,
+            stream.push(TableEvent::Fold { seat: 7 }).unwrap_err()
+        );
+        assert_eq!(
+            PKError::Duplicate,
+            stream
+                .push(TableEvent::BoardCard { card: Card::SIX_SPADES })
+                .unwrap_err()
+        );
+        assert_eq!(13, stream.len());
+    }
+
+    #[test]
+    fn state() {
+        let state = heads_up().final_state().unwrap();
+
+        assert_eq!(vec![700, 1_300], state.stacks());
+        assert_eq!(0, state.pot);
+        assert_eq!(2_000, state.total_chips());
+        assert_eq!(PhaseHoldem::Over, state.phase);
+        assert_eq!(1, state.in_hand_count());
+        assert_eq!(900, state.seats[1].won);
+    }
+
+    #[test]
+    fn forward_and_backward() {
+        let mut stream = heads_up();
+        stream.rewind();
+
+        assert!(!stream.backward());
+        assert!(stream.current().is_none());
+        assert_eq!(PhaseHoldem::Init, stream.state().unwrap().phase);
+
+        for _ in 0..6 {
+            stream.forward();
+        }
+        let preflop = stream.state().unwrap();
+        assert_eq!(600, preflop.pot);
+        assert_eq!(PhaseHoldem::Preflop, preflop.phase);
+        assert_eq!(300, preflop.to_match());
+
+        stream.forward();
+        stream.forward();
+        stream.forward();
+        assert_eq!(PhaseHoldem::Flop, stream.state().unwrap().phase);
+        assert_eq!(0, stream.state().unwrap().to_match());
+
+        assert!(stream.backward());
+        assert_eq!(PhaseHoldem::Preflop, stream.state().unwrap().phase);
+
+        stream.fast_forward();
+        assert!(!stream.forward());
+        assert_eq!(Some(&TableEvent::Award { seat: 1, amount: 900 }), stream.current());
+    }
+
+    #[test]
+    fn state_at__invalid() {
+        assert_eq!(PKError::InvalidIndex, heads_up().state_at(100).unwrap_err());
+    }
+
+    #[test]
+    fn serde() {
+        let stream = heads_up();
+
+        let json = serde_json::to_string(&stream).unwrap();
+        let back: EventStream = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(stream.events, back.events);
+        assert_eq!(stream.final_state().unwrap(), back.final_state().unwrap());
+    }
+
+    #[test]
+    fn apply__run_out() {
+        let mut state = heads_up().state_at(9).unwrap();
+        let board = |s: &str| TableEvent::RunOut {
+            board: Board::from_str(s).unwrap(),
+        };
+
+        state.apply(&board("9♣ 6♦ 5♥ 5♠ 8♠")).unwrap();
+        state.apply(&board("9♣ 6♦ 5♥ 2♠ 3♠")).unwrap();
+
+        assert_eq!(2, state.runouts.len());
+        assert_eq!(3, state.board.len());
+        assert_eq!(PhaseHoldem::River, state.phase);
+        assert_eq!(Err(PKError::Duplicate), state.apply(&board("9♣ 6♦ 5♥ 5♠ 4♠")));
+        assert_eq!(Err(PKError::InvalidCard), state.apply(&board("9♣ 6♦ 4♥ 4♠ 4♣")));
+        assert_eq!(
+            Err(PKError::TooManyCards),
+            state.apply(&TableEvent::BoardCard { card: Card::ACE_CLUBS })
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!("Seat 1 checks", TableEvent::Bet { seat: 1, amount: 0 }.to_string());
+        assert_eq!("Seat 2 bets 300", TableEvent::Bet { seat: 2, amount: 300 }.to_string());
+    }
+
+    #[rstest]
+    #[case("STATE:27:r200ffcfc/cr850cf/cr1825r3775c/r10000c:Qc4h|Tc9c|8sAs|Qh7c|JcQd|5h5d/3h7s5c/Qs/6c:-50|-200|-10000|0|0|10250:Eddie|Bill|Pluribus|MrWhite|Gogo|Budd")]
+    #[case("STATE:0:fr210ffcf/cc/cc/r440f:TcQc|8s4c|9c3d|Ah4h|Th5s|6c7s/7d5h9d/7c/Qh:310|-100|0|-210|0|0:MrBlue|MrBlonde|MrWhite|MrPink|MrBrown|Pluribus")]
+    #[case("STATE:0:ffr225fff:3c9s|6d5s|9dTs|2sQs|AdKd|7cTc:-50|-100|0|0|150|0:MrWhite|Gogo|Budd|Eddie|Bill|Pluribus")]
+    fn try_from__pluribus(#[case] log: &str) {
+        let pluribus = Pluribus::from_str(log).unwrap();
+
+        let state = EventStream::try_from(&pluribus).unwrap().final_state().unwrap();
+
+        assert_eq!(0, state.pot);
+        for (i, winnings) in pluribus.winnings.iter().enumerate() {
+            assert_eq!(
+                Pluribus::STARTING_STACK.checked_add_signed(*winnings).unwrap(),
+                state.seats[i].stack
+            );
+        }
+    }
+
+    #[test]
+    fn try_from__pluribus_corpus() {
+        let log = std::fs::read_to_string("data/pluribus/raw/sample_game_100.log").unwrap();
+
+        for line in log.lines().filter(|line| line.starts_with("STATE:")) {
+            let pluribus = Pluribus::from_str(line).unwrap();
+            let state = EventStream::try_from(&pluribus).unwrap().final_state().unwrap();
+
+            assert_eq!(0, state.pot, "{line}");
+            assert_eq!(Pluribus::STARTING_STACK * 6, state.total_chips(), "{line}");
+        }
+    }
+}
@@ -5,14 +5,17 @@ use crate::analysis::outs::Outs;
 use crate::arrays::four::Four;
 use crate::arrays::seven::Seven;
 use crate::arrays::six::Six;
+use crate::arrays::two::Two;
 use crate::arrays::HandRanker;
 use crate::play::board::Board;
 use crate::play::hole_cards::HoleCards;
+use crate::play::notation::{GameType, NotatedPlayer, Notation};
 use crate::util::wincounter::results::Results;
 use crate::util::wincounter::wins::Wins;
 use crate::{Card, Cards, PKError, Pile, TheNuts};
 use log::debug;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use std::sync::mpsc;
 use std::thread;
 
@@ -215,16 +218,43 @@ use std::thread;
 /// I'm feeling like this struct is getting too bloated with analysis, and it's getting
 /// hard to refactor things. The idea here is to move each phase of the game over to
 /// their own struct where we can optimize the code through things like concurrency.
+///
+/// ## Notation
+///
+/// A `Game` can be written down, and read back in, with the compact notation from the
+/// serialization epic, `HE: 6♠ 6♥ 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ 8♠`. See `Notation` for all the
+/// bells and whistles. `names` and `dead` are only there so that the notation can make the round
+/// trip. `names` is either empty or has one entry for each of the hands. The dead cards are taken
+/// out of the deck for the calculations at every street.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct Game {
     pub hands: HoleCards,
     pub board: Board,
+    pub names: Vec<Option<String>>,
+    pub dead: Cards,
 }
 
 impl Game {
     #[must_use]
     pub fn new(hands: HoleCards, board: Board) -> Self {
-        Game { hands, board }
+        Game {
+            hands,
+            board,
+            ..Default::default()
+        }
+    }
+
+    /// The name of the player holding the hand at the index, if there is one.
+    #[must_use]
+    pub fn name(&self, index: usize) -> Option<&str> {
+        self.names.get(index).and_then(Option::as_deref)
+    }
+
+    /// Everybody's odds before the flop, from every possible board without the dead cards. There
+    /// are about a million and a half of them, so it takes a while.
+    #[must_use]
+    pub fn preflop_results(&self) -> Results {
+        Results::from_wins(&CaseEvals::wins_at_deal(&self.hands, &self.dead), self.hands.len())
     }
 
     // region The Turn
@@ -297,6 +327,7 @@ impl Game {
     fn turn_remaining(&self) -> Cards {
         let mut cards = self.turn_cards();
         cards.insert_all(&self.hands.cards());
+        cards.insert_all(&self.dead);
         Cards::deck_minus(&cards)
     }
 
@@ -304,7 +335,9 @@ impl Game {
     /// `possible_evals_at_turn()`.
     #[must_use]
     pub fn turn_remaining_board(&self) -> Cards {
-        Cards::deck_minus(&self.turn_cards())
+        let mut cards = self.turn_cards();
+        cards.insert_all(&self.dead);
+        Cards::deck_minus(&cards)
     }
 
     /// This function is insanely slow.
@@ -716,7 +749,75 @@ impl Game {
 
 impl Display for Game {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "DEALT: {} {}", self.hands, self.board)
+        write!(f, "{}", Notation::from(self))
+    }
+}
+
+impl From<&Game> for Notation {
+    fn from(game: &Game) -> Self {
+        let players = game
+            .hands
+            .iter()
+            .enumerate()
+            .map(|(i, two)| NotatedPlayer::new(game.name(i).map(str::to_string), Cards::from(two.to_arr().to_vec())))
+            .collect();
+        Notation {
+            game_type: GameType::Holdem,
+            players,
+            board: Cards::from(
+                game.board
+                    .to_vec()
+                    .into_iter()
+                    .filter(Card::is_dealt)
+                    .collect::<Vec<Card>>(),
+            ),
+            dead: game.dead.clone(),
+        }
+    }
+}
+
+impl TryFrom<Notation> for Game {
+    type Error = PKError;
+
+    /// `Game` only knows how to play hold'em, so Omaha and stud are turned away.
+    fn try_from(notation: Notation) -> Result<Self, Self::Error> {
+        if notation.game_type != GameType::Holdem {
+            return Err(PKError::InvalidNotation(format!(
+                "a Game can only be hold'em, not {}",
+                notation.game_type
+            )));
+        }
+        notation.validate()?;
+
+        let mut twos = Vec::with_capacity(notation.players.len());
+        for player in &notation.players {
+            twos.push(Two::try_from(player.cards.clone())?);
+        }
+        let board = if notation.board.is_empty() {
+            Board::default()
+        } else {
+            Board::try_from(notation.board)?
+        };
+        let names = if notation.players.iter().any(|player| player.name.is_some()) {
+            notation.players.into_iter().map(|player| player.name).collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(Game {
+            hands: HoleCards::from(twos),
+            board,
+            names,
+            dead: notation.dead,
+        })
+    }
+}
+
+impl FromStr for Game {
+    type Err = PKError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Game::try_from(Notation::from_str(s)?)
     }
 }
 
@@ -740,10 +841,10 @@ mod play__game_tests {
 
     #[test]
     fn case_eval_at_turn() {
-        let game = Game {
-            hands: TestData::hole_cards_the_hand(),
-            board: Board::from_str("9♣ 6♦ 5♥ 5♠ 8♠").unwrap(),
-        };
+        let game = Game::new(
+            TestData::hole_cards_the_hand(),
+            Board::from_str("9♣ 6♦ 5♥ 5♠ 8♠").unwrap(),
+        );
 
         let actual = game.turn_case_eval(&Card::SIX_CLUBS);
 
@@ -1048,12 +1149,48 @@ mod play__game_tests {
 
     #[test]
     fn display() {
+        assert_eq!("HE: 6♠ 6♥ 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ 8♠", TestData::the_hand().to_string());
+    }
+
+    #[test]
+    fn from_str() {
         assert_eq!(
-            "DEALT: [6♠ 6♥, 5♦ 5♣] FLOP: 9♣ 6♦ 5♥, TURN: 5♠, RIVER: 8♠",
-            TestData::the_hand().to_string()
+            TestData::the_hand(),
+            Game::from_str("HE: 6♠ 6♥ 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ 8♠").unwrap()
         );
     }
 
+    #[test]
+    fn from_str__names_partial_board_and_dead() {
+        let s = "HE: Negreanu: 6♠ 6♥ Hansen: 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ DEAD: 8♠";
+
+        let game = Game::from_str(s).unwrap();
+
+        assert_eq!(Some("Hansen"), game.name(1));
+        assert_eq!(Card::FIVE_SPADES, game.board.turn);
+        assert!(!game.board.river.is_dealt());
+        assert!(!game.turn_remaining().contains(&Card::EIGHT_SPADES));
+        assert_eq!(43, game.turn_remaining().len());
+        assert_eq!(s, game.to_string());
+    }
+
+    #[test]
+    fn from_str__no_board() {
+        let game = Game::from_str("HE: 5♠ 5♦ 9♠ 9♥ K♣ T♦").unwrap();
+
+        assert_eq!(3, game.hands.len());
+        assert_eq!(Board::default(), game.board);
+        assert_eq!("HE: 5♠ 5♦ 9♠ 9♥ K♣ T♦", game.to_string());
+    }
+
+    #[test]
+    fn from_str__not_holdem() {
+        assert!(matches!(
+            Game::from_str("PLO: A♠ A♥ K♠ K♥ 9♣ 8♣ 7♦ 6♦ - 2♠ 3♠ 4♥"),
+            Err(PKError::InvalidNotation(_))
+        ));
+    }
+
     /// This test comes out of an issue discovered by running the cards from this
     /// [tweet](https://twitter.com/ElieNYC/status/1555121459386728448) by Elie Mystal.
     ///
@@ -2,13 +2,20 @@ use crate::PKError;
 use std::fmt::{Display, Formatter};
 use strum_macros::{EnumCount, EnumIter};
 
+pub mod acpc;
 pub mod actions;
+pub mod agents;
 pub mod board;
+pub mod dealer;
+pub mod events;
 pub mod game;
 pub mod hole_cards;
+pub mod notation;
 pub mod phases;
 pub mod position;
 pub mod positions;
+pub mod pots;
+pub mod run_it;
 pub mod stages;
 pub mod table;
 
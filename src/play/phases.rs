@@ -1,10 +1,13 @@
 use log::warn;
+use serde::{Deserialize, Serialize};
 use std::cell::Cell;
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
 use strum_macros::{EnumCount, EnumIter};
 
-#[derive(Clone, Copy, Debug, Default, EnumCount, EnumIter, Eq, Hash, PartialEq, strum_macros::Display)]
+#[derive(
+    Clone, Copy, Debug, Default, Deserialize, EnumCount, EnumIter, Eq, Hash, PartialEq, Serialize, strum_macros::Display,
+)]
 pub enum PhaseHoldem {
     #[default]
     Init = 0,
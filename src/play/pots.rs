@@ -0,0 +1,253 @@
+use crate::analysis::hand_rank::HandRank;
+use crate::arrays::seven::Seven;
+use crate::arrays::two::Two;
+use crate::arrays::HandRanker;
+use crate::play::board::Board;
+use crate::play::events::TableState;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::slice::Iter;
+
+/// A single pot, and the seats that are eligible to win it.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Pot {
+    pub amount: usize,
+    pub eligible: Vec<usize>,
+}
+
+impl Pot {
+    /// Splits the pot evenly between the winners. Any odd chips go to the winners in seat order,
+    /// one at a time.
+    #[must_use]
+    pub fn split(amount: usize, winners: &[usize], seats: usize) -> Vec<usize> {
+        let mut awards = vec![0; seats];
+        if winners.is_empty() {
+            return awards;
+        }
+        let share = amount / winners.len();
+        let odd = amount % winners.len();
+        for (i, winner) in winners.iter().enumerate() {
+            if let Some(award) = awards.get_mut(*winner) {
+                *award += share + usize::from(i < odd);
+            }
+        }
+        awards
+    }
+
+    /// The eligible seats with the best hand on the board, out of everyone's hole cards, indexed
+    /// by seat. If only one seat is eligible, they win it without a showdown.
+    #[must_use]
+    pub fn winners(&self, hands: &[Two], board: &Board) -> Vec<usize> {
+        if self.eligible.len() < 2 {
+            return self.eligible.clone();
+        }
+        let mut ranks = vec![HandRank::default(); hands.len()];
+        for seat in &self.eligible {
+            if let Some(hand) = hands.get(*seat) {
+                ranks[*seat] = Seven::from_case_and_board(hand, board).eval().hand_rank;
+            }
+        }
+        self.best(&ranks)
+    }
+
+    /// The eligible seats with the best `HandRank`, out of everyone's ranks indexed by seat. When
+    /// the same board decides more than one pot, this saves working out the hands for each of
+    /// them.
+    #[must_use]
+    pub fn best(&self, ranks: &[HandRank]) -> Vec<usize> {
+        if self.eligible.len() < 2 {
+            return self.eligible.clone();
+        }
+        let rank = |seat: &usize| ranks.get(*seat).copied().unwrap_or_default();
+        let best = self.eligible.iter().map(rank).max().unwrap_or_default();
+        self.eligible
+            .iter()
+            .filter(|seat| rank(seat) == best)
+            .copied()
+            .collect()
+    }
+}
+
+/// The main pot and any side pots for a hand.
+///
+/// When a player is all in for less than everybody else, they can only win from each of the other
+/// players what they put in themselves. Everything over that goes into a side pot that only the
+/// players who covered it can win. With enough short stacks, you can end up with a stack of side
+/// pots.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Pots(Vec<Pot>);
+
+impl Pots {
+    /// Builds the pots from how much each seat put in over the hand, and whether they folded.
+    /// Chips from folded players stay in the pots, but they aren't eligible to win any of them.
+    #[must_use]
+    pub fn from_contributions(contributions: &[usize], folded: &[bool]) -> Pots {
+        let is_folded = |i: usize| folded.get(i).copied().unwrap_or_default();
+        let mut levels = contributions
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| !is_folded(*i) && **c > 0)
+            .map(|(_, c)| *c)
+            .collect::<Vec<usize>>();
+        levels.sort_unstable();
+        levels.dedup();
+
+        let mut pots = Vec::new();
+        let mut previous = 0;
+        for level in &levels {
+            let amount = contributions
+                .iter()
+                .map(|c| (*c).min(*level) - (*c).min(previous))
+                .sum();
+            let eligible = contributions
+                .iter()
+                .enumerate()
+                .filter(|(i, c)| !is_folded(*i) && **c >= *level)
+                .map(|(i, _)| i)
+                .collect();
+            pots.push(Pot { amount, eligible });
+            previous = *level;
+        }
+
+        // Dead money from folded players who put in more than anyone left in the hand.
+        let dead: usize = contributions.iter().map(|c| c.saturating_sub(previous)).sum();
+        if dead > 0 {
+            match pots.last_mut() {
+                Some(pot) => pot.amount += dead,
+                None => pots.push(Pot {
+                    amount: dead,
+                    eligible: Vec::new(),
+                }),
+            }
+        }
+
+        Pots(pots)
+    }
+
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&Pot> {
+        self.0.get(index)
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> Iter<'_, Pot> {
+        self.0.iter()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn total(&self) -> usize {
+        self.0.iter().map(|pot| pot.amount).sum()
+    }
+}
+
+impl Display for Pots {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let s = self
+            .0
+            .iter()
+            .map(|pot| format!("{} {:?}", pot.amount, pot.eligible))
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{s}")
+    }
+}
+
+impl From<&TableState> for Pots {
+    fn from(state: &TableState) -> Self {
+        let contributions = state.seats.iter().map(|seat| seat.in_pot).collect::<Vec<usize>>();
+        let folded = state.seats.iter().map(|seat| seat.folded).collect::<Vec<bool>>();
+        Pots::from_contributions(&contributions, &folded)
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__pots_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn from_contributions__single_pot() {
+        let pots = Pots::from_contributions(&[500, 500, 100], &[false, false, true]);
+
+        assert_eq!(1, pots.len());
+        assert_eq!(1_100, pots.total());
+        assert_eq!(vec![0, 1], pots.get(0).unwrap().eligible);
+    }
+
+    #[test]
+    fn from_contributions__side_pots() {
+        let pots = Pots::from_contributions(&[100, 500, 1_000, 1_000], &[false, false, false, false]);
+
+        assert_eq!("400 [0, 1, 2, 3], 1200 [1, 2, 3], 1000 [2, 3]", pots.to_string());
+        assert_eq!(2_600, pots.total());
+    }
+
+    #[test]
+    fn from_contributions__dead_money() {
+        let pots = Pots::from_contributions(&[1_000, 300, 300], &[true, false, false]);
+
+        assert_eq!(1, pots.len());
+        assert_eq!(1_600, pots.total());
+        assert_eq!(vec![1, 2], pots.get(0).unwrap().eligible);
+    }
+
+    #[test]
+    fn winners() {
+        let hands = [
+            Two::from_str("A♠ A♥").unwrap(),
+            Two::from_str("K♠ K♥").unwrap(),
+            Two::from_str("A♦ A♣").unwrap(),
+        ];
+        let board = Board::from_str("2♠ 7♦ 9♣ J♥ 4♦").unwrap();
+        let pot = Pot {
+            amount: 300,
+            eligible: vec![0, 1, 2],
+        };
+
+        assert_eq!(vec![0, 2], pot.winners(&hands, &board));
+        assert_eq!(
+            vec![1],
+            Pot {
+                amount: 300,
+                eligible: vec![1]
+            }
+            .winners(&hands, &board)
+        );
+    }
+
+    #[test]
+    fn best() {
+        let ranks = [HandRank::from(1_609), HandRank::from(22), HandRank::from(22)];
+        let pot = Pot {
+            amount: 300,
+            eligible: vec![0, 2],
+        };
+
+        assert_eq!(vec![2], pot.best(&ranks));
+        assert_eq!(
+            vec![1, 2],
+            Pot {
+                amount: 300,
+                eligible: vec![0, 1, 2]
+            }
+            .best(&ranks)
+        );
+        assert!(Pot::default().best(&ranks).is_empty());
+    }
+
+    #[test]
+    fn split() {
+        assert_eq!(vec![51, 0, 50], Pot::split(101, &[0, 2], 3));
+        assert_eq!(vec![0, 0, 0], Pot::split(101, &[], 3));
+    }
+}
@@ -0,0 +1,434 @@
+use crate::analysis::case_eval::CaseEval;
+use crate::analysis::case_evals::CaseEvals;
+use crate::arrays::two::Two;
+use crate::cards::Cards;
+use crate::play::board::Board;
+use crate::play::events::TableState;
+use crate::play::hole_cards::HoleCards;
+use crate::play::pots::{Pot, Pots};
+use crate::util::wincounter::win::Win;
+use crate::PKError;
+use rand::Rng;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// One complete board, and how many chips each seat won from it.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RunOut {
+    pub board: Board,
+    pub awards: Vec<usize>,
+}
+
+impl Display for RunOut {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} AWARDS: {:?}", self.board, self.awards)
+    }
+}
+
+/// # Running it more than once
+///
+/// When everybody is all in before the river, the players can agree to deal the rest of the
+/// board more than once. Each pot, main and side, is split evenly between the runs, and each run
+/// awards its share to whoever wins on that board. The cards used up by one run aren't put back
+/// for the next, so the only limit on how many times it can be run is the deck.
+///
+/// Running it twice doesn't change anyone's expected value, it just cuts down on the variance.
+/// `RunIt::ev()` works the EV out exactly with `CaseEvals`, so you can see for yourself that the
+/// average result of running it lots of times lines up with it.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RunIt {
+    pub runs: Vec<RunOut>,
+    pub totals: Vec<usize>,
+}
+
+impl RunIt {
+    /// Deals out the rest of the board `times` times, using cards shuffled with the passed in
+    /// random number generator. Every card that's been dealt, including the folded players'
+    /// cards, stays out of the deck.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::InvalidIndex` if `times` is zero.
+    /// * `PKError::NotDealt` if a player still in the hand doesn't have cards.
+    /// * `PKError::NotEnoughCards` if the deck runs out of cards.
+    pub fn deal<R: Rng + ?Sized>(state: &TableState, times: usize, rng: &mut R) -> Result<RunIt, PKError> {
+        if times == 0 {
+            return Err(PKError::InvalidIndex);
+        }
+        RunIt::validate(state)?;
+
+        let mut deck = Cards::deck_minus(&state.cards()).shuffle_with(rng);
+        let needed = 5 - state.board.len();
+        let mut boards = Vec::with_capacity(times);
+        for _ in 0..times {
+            let mut cards = state.board.clone();
+            cards.insert_all(&deck.draw(needed)?);
+            boards.push(Board::try_from(cards)?);
+        }
+
+        Ok(RunIt::award(state, &boards))
+    }
+
+    /// The exact expected number of chips each seat gets back from the pots, no matter how many
+    /// times the board is run.
+    ///
+    /// Every possible board is run out, and each pot gets its own `CaseEvals`, over just the
+    /// seats that are eligible for it, so a side pot is only ever fought over by the players in
+    /// it. The cards of players who have folded are out of the deck, same as with
+    /// `RunIt::deal()`. Before the flop, that's over a million boards, so it takes a while.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::NotDealt` if a player still in the hand doesn't have cards.
+    /// * `PKError::TooManyCards` if the board has one or two cards.
+    pub fn ev(state: &TableState) -> Result<Vec<f32>, PKError> {
+        RunIt::validate(state)?;
+        if matches!(state.board.len(), 1 | 2) {
+            return Err(PKError::TooManyCards);
+        }
+
+        let hands = RunIt::hands(state);
+        let boards = Cards::deck_minus(&state.cards())
+            .combinations(5 - state.board.len())
+            .map(|cards| {
+                let mut board = state.board.clone();
+                board.insert_all(&Cards::from(cards));
+                Board::try_from(board)
+            })
+            .collect::<Result<Vec<Board>, PKError>>()?;
+
+        let mut totals = vec![0.0_f64; state.seats.len()];
+        for pot in Pots::from(state).iter() {
+            #[allow(clippy::cast_precision_loss)]
+            let amount = pot.amount as f64;
+            if let [seat] = pot.eligible.as_slice() {
+                totals[*seat] += amount;
+                continue;
+            }
+
+            let contenders = HoleCards::from(pot.eligible.iter().map(|seat| hands[*seat]).collect::<Vec<Two>>());
+            let case_evals = CaseEvals::from(
+                boards
+                    .par_iter()
+                    .map(|board| contenders.river_case_eval(board))
+                    .collect::<Vec<CaseEval>>(),
+            );
+
+            #[allow(clippy::cast_precision_loss)]
+            let per_case = amount / case_evals.len().max(1) as f64;
+            for case_eval in case_evals.iter() {
+                let flag = case_eval.win_count();
+                let winners = (0..pot.eligible.len())
+                    .filter(|i| flag & Win::from_index(*i) != 0)
+                    .map(|i| pot.eligible[i])
+                    .collect::<Vec<usize>>();
+                #[allow(clippy::cast_precision_loss)]
+                let share = per_case / winners.len().max(1) as f64;
+                for seat in winners {
+                    totals[seat] += share;
+                }
+            }
+        }
+
+        #[allow(clippy::cast_possible_truncation)]
+        Ok(totals.iter().map(|total| *total as f32).collect())
+    }
+
+    /// Awards every pot to the best hand among the players eligible for it. A pot that only one
+    /// player is eligible for, because everyone else folded, goes to them without a showdown.
+    ///
+    /// If the board has been run out more than once, each pot is split between the runs, the
+    /// same way as `RunIt::deal()` does it.
+    ///
+    /// # Errors
+    ///
+    /// * `PKError::NotDealt` if a player still in the hand doesn't have cards.
+    /// * `PKError::NotEnoughCards` if a pot needs a showdown and the board isn't complete.
+    pub fn showdown(state: &TableState) -> Result<Vec<usize>, PKError> {
+        RunIt::validate(state)?;
+        if !state.runouts.is_empty() {
+            return Ok(RunIt::award(state, &state.runouts).totals);
+        }
+
+        let seats = state.seats.len();
+        let hands = RunIt::hands(state);
+        let mut awards = vec![0; seats];
+        for pot in Pots::from(state).iter() {
+            let winners = if pot.eligible.len() > 1 {
+                if state.board.len() < 5 {
+                    return Err(PKError::NotEnoughCards);
+                }
+                pot.winners(&hands, &Board::try_from(state.board.clone())?)
+            } else {
+                pot.eligible.clone()
+            };
+            for (seat, award) in Pot::split(pot.amount, &winners, seats).iter().enumerate() {
+                awards[seat] += award;
+            }
+        }
+        Ok(awards)
+    }
+
+    /// Splits every pot evenly between the boards, with any odd chips going to the earlier runs,
+    /// and awards each run's share to the winners on that board.
+    fn award(state: &TableState, boards: &[Board]) -> RunIt {
+        let seats = state.seats.len();
+        let hands = RunIt::hands(state);
+        let times = boards.len();
+        let mut runs = boards
+            .iter()
+            .map(|board| RunOut {
+                board: *board,
+                awards: vec![0; seats],
+            })
+            .collect::<Vec<RunOut>>();
+
+        for pot in Pots::from(state).iter() {
+            for (i, run) in runs.iter_mut().enumerate() {
+                let share = pot.amount / times + usize::from(i < pot.amount % times);
+                let winners = pot.winners(&hands, &run.board);
+                for (seat, award) in Pot::split(share, &winners, seats).iter().enumerate() {
+                    run.awards[seat] += award;
+                }
+            }
+        }
+
+        let totals = (0..seats)
+            .map(|seat| runs.iter().map(|run| run.awards[seat]).sum())
+            .collect();
+
+        RunIt { runs, totals }
+    }
+
+    /// Everybody's hole cards, indexed by seat.
+    fn hands(state: &TableState) -> Vec<Two> {
+        state.seats.iter().map(|seat| seat.cards).collect()
+    }
+
+    fn validate(state: &TableState) -> Result<(), PKError> {
+        if state
+            .seats
+            .iter()
+            .any(|seat| !seat.folded && seat.cards == Two::default())
+        {
+            Err(PKError::NotDealt)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl Display for RunIt {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, run) in self.runs.iter().enumerate() {
+            writeln!(f, "Run #{}: {run}", i + 1)?;
+        }
+        write!(f, "TOTALS: {:?}", self.totals)
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod play__run_it_tests {
+    use super::*;
+    use crate::arrays::three::Three;
+    use crate::card::Card;
+    use crate::play::events::{EventStream, SeatState, TableEvent};
+    use crate::play::phases::PhaseHoldem;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::str::FromStr;
+
+    /// Daniel Negreanu's 6♠ 6♥ against Gus Hansen's 5♦ 5♣ on a 9♣ 6♦ 5♥ flop, with both all in.
+    fn the_hand_all_in(board: &str) -> TableState {
+        let mut stream = EventStream::new(vec![
+            SeatState::new("Daniel", 1_000),
+            SeatState::new("Gus", 1_000),
+            SeatState::new("Folder", 1_000),
+        ]);
+        stream
+            .push(TableEvent::Deal {
+                seat: 0,
+                cards: Two::from_str("6♠ 6♥").unwrap(),
+            })
+            .unwrap();
+        stream
+            .push(TableEvent::Deal {
+                seat: 1,
+                cards: Two::from_str("5♦ 5♣").unwrap(),
+            })
+            .unwrap();
+        stream
+            .push(TableEvent::Deal {
+                seat: 2,
+                cards: Two::from_str("A♦ K♣").unwrap(),
+            })
+            .unwrap();
+        stream.push(TableEvent::Post { seat: 0, amount: 50 }).unwrap();
+        stream.push(TableEvent::Post { seat: 1, amount: 100 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 2, amount: 300 }).unwrap();
+        stream.push(TableEvent::Fold { seat: 2 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 0, amount: 950 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 1, amount: 900 }).unwrap();
+        for card in Cards::from_str(board).unwrap() {
+            stream.push(TableEvent::BoardCard { card }).unwrap();
+        }
+        stream.final_state().unwrap()
+    }
+
+    #[test]
+    fn deal() {
+        let state = the_hand_all_in("9♣ 6♦ 5♥");
+        let mut rng = StdRng::seed_from_u64(1);
+
+        let run_it = RunIt::deal(&state, 2, &mut rng).unwrap();
+
+        assert_eq!(2, run_it.runs.len());
+        assert_eq!(2_300, run_it.totals.iter().sum::<usize>());
+        assert_eq!(0, run_it.totals[2]);
+        for run in &run_it.runs {
+            assert_eq!(1_150, run.awards.iter().sum::<usize>());
+            assert_eq!(Three::from_str("9♣ 6♦ 5♥").unwrap(), run.board.flop);
+        }
+        assert_ne!(run_it.runs[0].board.turn, run_it.runs[1].board.turn);
+    }
+
+    #[test]
+    fn deal__deterministic() {
+        let state = the_hand_all_in("9♣ 6♦ 5♥");
+
+        assert_eq!(
+            RunIt::deal(&state, 3, &mut StdRng::seed_from_u64(7)).unwrap(),
+            RunIt::deal(&state, 3, &mut StdRng::seed_from_u64(7)).unwrap()
+        );
+    }
+
+    #[test]
+    fn deal__invalid() {
+        let state = the_hand_all_in("9♣ 6♦ 5♥");
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(PKError::InvalidIndex, RunIt::deal(&state, 0, &mut rng).unwrap_err());
+        assert_eq!(PKError::NotEnoughCards, RunIt::deal(&state, 22, &mut rng).unwrap_err());
+    }
+
+    #[test]
+    fn deal__river() {
+        let state = the_hand_all_in("9♣ 6♦ 5♥ 5♠ 8♠");
+
+        let run_it = RunIt::deal(&state, 2, &mut StdRng::seed_from_u64(1)).unwrap();
+
+        assert_eq!(vec![0, 2_300, 0], run_it.totals);
+    }
+
+    #[test]
+    fn deal__as_many_as_the_deck_allows() {
+        let state = the_hand_all_in("9♣ 6♦ 5♥");
+
+        let run_it = RunIt::deal(&state, 21, &mut StdRng::seed_from_u64(1)).unwrap();
+
+        assert_eq!(21, run_it.runs.len());
+        assert_eq!(2_300, run_it.totals.iter().sum::<usize>());
+    }
+
+    #[test]
+    fn showdown__run_outs() {
+        let mut state = the_hand_all_in("9♣ 6♦ 5♥");
+        let run_it = RunIt::deal(&state, 3, &mut StdRng::seed_from_u64(7)).unwrap();
+
+        for run in &run_it.runs {
+            state.apply(&TableEvent::RunOut { board: run.board }).unwrap();
+        }
+
+        assert_eq!(run_it.totals, RunIt::showdown(&state).unwrap());
+        assert_eq!(PhaseHoldem::River, state.phase);
+    }
+
+    #[test]
+    fn showdown() {
+        assert_eq!(
+            vec![0, 2_300, 0],
+            RunIt::showdown(&the_hand_all_in("9♣ 6♦ 5♥ 5♠ 8♠")).unwrap()
+        );
+        assert_eq!(
+            PKError::NotEnoughCards,
+            RunIt::showdown(&the_hand_all_in("9♣ 6♦ 5♥")).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn ev__turn() {
+        let state = the_hand_all_in("9♣ 6♦ 5♥ 5♠");
+
+        let ev = RunIt::ev(&state).unwrap();
+
+        assert!((ev.iter().sum::<f32>() - 2_300.0).abs() < 0.01);
+        assert!(ev[0] < 100.0);
+        assert!(ev[2].abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn ev__flop() {
+        let state = the_hand_all_in("9♣ 6♦ 5♥");
+
+        let ev = RunIt::ev(&state).unwrap();
+
+        assert!((ev.iter().sum::<f32>() - 2_300.0).abs() < 0.01);
+        assert!(ev[0] > ev[1]);
+    }
+
+    /// A short stack with Q♦ Q♣ all in against K♠ K♥ and Q♠ Q♥, who are both all in for more.
+    /// With both of the other queens in the short stack's hand, Q♠ Q♥ is drawing dead to the
+    /// side pot as well as the main pot. If the side pot were run out with only the two hands
+    /// that can win it, the short stack's queens would end up on the river.
+    fn three_way(board: &str) -> TableState {
+        let mut stream = EventStream::new(vec![
+            SeatState::new("Short", 300),
+            SeatState::new("Kings", 1_000),
+            SeatState::new("Queens", 1_000),
+        ]);
+        for (seat, cards) in ["Q♦ Q♣", "K♠ K♥", "Q♠ Q♥"].iter().enumerate() {
+            stream
+                .push(TableEvent::Deal {
+                    seat,
+                    cards: Two::from_str(cards).unwrap(),
+                })
+                .unwrap();
+        }
+        stream.push(TableEvent::Post { seat: 0, amount: 50 }).unwrap();
+        stream.push(TableEvent::Post { seat: 1, amount: 100 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 2, amount: 1_000 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 0, amount: 250 }).unwrap();
+        stream.push(TableEvent::Bet { seat: 1, amount: 900 }).unwrap();
+        for card in Cards::from_str(board).unwrap() {
+            stream.push(TableEvent::BoardCard { card }).unwrap();
+        }
+        stream.final_state().unwrap()
+    }
+
+    #[test]
+    fn ev__side_pot() {
+        let state = three_way("2♣ 7♦ 9♠ J♥");
+
+        let ev = RunIt::ev(&state).unwrap();
+
+        assert_eq!("900 [0, 1, 2], 1400 [1, 2]", Pots::from(&state).to_string());
+        assert!(ev[0].abs() < f32::EPSILON);
+        assert!((ev[1] - 2_300.0).abs() < 0.01);
+        assert!(ev[2].abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn deal__side_pot() {
+        let state = three_way("2♣ 7♦ 9♠ J♥");
+
+        let run_it = RunIt::deal(&state, 4, &mut StdRng::seed_from_u64(3)).unwrap();
+
+        assert_eq!(vec![0, 2_300, 0], run_it.totals);
+        assert!(run_it
+            .runs
+            .iter()
+            .all(|run| run.board.river != Card::QUEEN_DIAMONDS && run.board.river != Card::QUEEN_CLUBS));
+    }
+}
@@ -7,7 +7,7 @@ use crate::play::game::Game;
 use crate::play::hole_cards::HoleCards;
 use crate::util::wincounter::results::Results;
 use crate::util::wincounter::wins::Wins;
-use crate::{PKError, Pile};
+use crate::{Cards, PKError, Pile};
 
 /// I'm feeling the need to refactor our `Game` struct. As we get deeper into
 /// the analysis phase of our library, each stage of a hand will need to have
@@ -210,7 +210,14 @@ pub struct FlopEval {
 impl FlopEval {
     #[must_use]
     pub fn new(board: Three, hands: HoleCards) -> FlopEval {
-        let case_evals = CaseEvals::from_holdem_at_flop_mpsc(board, &hands);
+        FlopEval::with_dead(board, hands, &Cards::default())
+    }
+
+    /// The same as `new()`, but with some cards that we know are out of the deck, like the ones
+    /// somebody showed when they folded.
+    #[must_use]
+    pub fn with_dead(board: Three, hands: HoleCards, dead: &Cards) -> FlopEval {
+        let case_evals = CaseEvals::from_holdem_at_flop_mpsc(board, &hands, dead);
         let wins = case_evals.wins();
         let results = Results::from_wins(&wins, hands.len());
 
@@ -286,7 +293,7 @@ impl TryFrom<Game> for FlopEval {
         if !game.board.flop.is_dealt() || game.hands.is_empty() {
             Err(PKError::NotDealt)
         } else {
-            Ok(FlopEval::new(game.board.flop, game.hands))
+            Ok(FlopEval::with_dead(game.board.flop, game.hands, &game.dead))
         }
     }
 }
@@ -295,7 +302,9 @@ impl TryFrom<Game> for FlopEval {
 #[allow(non_snake_case)]
 mod play__stages__flop_eval_tests {
     use super::*;
+    use crate::play::board::Board;
     use crate::util::data::TestData;
+    use std::str::FromStr;
 
     #[test]
     fn new() {
@@ -329,10 +338,7 @@ mod play__stages__flop_eval_tests {
     #[test]
     fn try_from__game__board_not_dealt() {
         let game = TestData::the_hand();
-        let game = Game {
-            hands: game.hands,
-            board: Default::default(),
-        };
+        let game = Game::new(game.hands, Board::default());
 
         let sut = FlopEval::try_from(game);
 
@@ -343,10 +349,7 @@ mod play__stages__flop_eval_tests {
     #[test]
     fn try_from__game__hands_not_dealt() {
         let game = TestData::the_hand();
-        let game = Game {
-            hands: HoleCards::default(),
-            board: game.board,
-        };
+        let game = Game::new(HoleCards::default(), game.board);
 
         let sut = FlopEval::try_from(game);
 
@@ -354,6 +357,17 @@ mod play__stages__flop_eval_tests {
         assert_eq!(PKError::NotDealt, sut.unwrap_err());
     }
 
+    #[test]
+    fn try_from__game__dead() {
+        let live = FlopEval::try_from(Game::from_str("HE: A♠ K♥ 8♦ 6♣ - A♣ 8♥ 7♥").unwrap()).unwrap();
+
+        let sut = FlopEval::try_from(Game::from_str("HE: A♠ K♥ 8♦ 6♣ - A♣ 8♥ 7♥ DEAD: 9♠").unwrap()).unwrap();
+
+        assert_eq!(946, sut.case_evals.len());
+        assert_ne!(live.results, sut.results);
+        assert!(sut.results.wins_total_percentage(0) > live.results.wins_total_percentage(0));
+    }
+
     #[test]
     fn iterations_heads_up() {
         let game = TestData::the_hand();
@@ -0,0 +1,278 @@
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::PKError;
+use std::str::FromStr;
+
+/// The parts of a `Session` that can be cleared on their own.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Clear {
+    #[default]
+    All,
+    Board,
+    Dead,
+}
+
+/// # Command
+///
+/// One line typed into the REPL. Cards can be typed with the suit letters or symbols, so
+/// `board 9c 6d 5h` is the same as `board 9♣ 6♦ 5♥`. Players are picked by name or by seat,
+/// counting from one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// `add [name] [cards]`
+    Add {
+        name: Option<String>,
+        hand: Option<Two>,
+    },
+    /// `remove <player>`
+    Remove(String),
+    /// `hand <player> <cards>`
+    Hand {
+        player: String,
+        hand: Two,
+    },
+    /// `board <cards>`, with no cards for an empty board.
+    Board(Vec<Card>),
+    /// `deal <cards>`
+    Deal(Vec<Card>),
+    /// `dead <cards>`
+    Dead(Vec<Card>),
+    /// `clear [board|dead]`
+    Clear(Clear),
+    Equity,
+    Outs,
+    Nuts,
+    /// `range <player> <range>`, like `range Hansen QQ+, AK`.
+    Range {
+        player: String,
+        range: String,
+    },
+    Undo,
+    /// `save <file>`
+    Save(String),
+    /// `load <file>`
+    Load(String),
+    Show,
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Everything that can go at the start of a line, for the tab completion.
+    pub const NAMES: [&'static str; 17] = [
+        "add", "board", "clear", "deal", "dead", "equity", "hand", "help", "load", "nuts", "outs", "quit", "range",
+        "remove", "save", "show", "undo",
+    ];
+
+    /// The commands that take a player as their first argument.
+    pub const TAKES_PLAYER: [&'static str; 3] = ["hand", "range", "remove"];
+
+    pub const HELP: &'static str = "\
+add [name] [cards]     sit somebody down, with or without their cards
+remove <player>        get rid of a player, by name or seat
+hand <player> <cards>  give a player their cards
+board [cards]          set the whole board, or empty it
+deal <cards>           add cards to the board
+dead <cards>           take cards out of the deck
+clear [board|dead]     clear everything, or just the board or the dead cards
+equity                 everybody's odds at the current street
+outs                   the cards that win it on the river, at the turn
+nuts                   the best possible hands on the board
+range <player> <range> a player's equity against a range, like QQ+, AK
+undo                   take back the last change
+save <file>            write the session out as YAML
+load <file>            read a session back in
+show                   the table as it is now
+help                   this
+quit                   bye
+
+Cards can be typed as A♠ or As. Tab completes commands, players and cards.";
+
+    fn cards(args: &[&str]) -> Result<Vec<Card>, PKError> {
+        let mut cards: Vec<Card> = Vec::new();
+        for arg in args {
+            let card = Command::card(arg)?;
+            if cards.contains(&card) {
+                return Err(PKError::Duplicate);
+            }
+            cards.push(card);
+        }
+        Ok(cards)
+    }
+
+    fn card(token: &str) -> Result<Card, PKError> {
+        if token.chars().count() != 2 {
+            return Err(PKError::InvalidCommand(format!("{token} is not a card")));
+        }
+        Card::from_str(token).map_err(|_| PKError::InvalidCommand(format!("{token} is not a card")))
+    }
+
+    fn two(args: &[&str]) -> Result<Two, PKError> {
+        match Command::cards(args)?.as_slice() {
+            [first, second] => Two::new(*first, *second),
+            _ => Err(PKError::InvalidCommand("a hand is two cards".to_string())),
+        }
+    }
+
+    fn one(command: &str, args: &[&str]) -> Result<String, PKError> {
+        match args {
+            [arg] => Ok((*arg).to_string()),
+            _ => Err(PKError::InvalidCommand(format!("{command} needs one argument"))),
+        }
+    }
+
+    /// `add` takes a name, cards, both or neither, so anything that isn't a card is the name.
+    fn add(args: &[&str]) -> Result<Command, PKError> {
+        let (name, cards) = match args.first() {
+            Some(first) if Command::card(first).is_err() => (Some((*first).to_string()), &args[1..]),
+            _ => (None, args),
+        };
+        let hand = if cards.is_empty() {
+            None
+        } else {
+            Some(Command::two(cards)?)
+        };
+        Ok(Command::Add { name, hand })
+    }
+}
+
+impl FromStr for Command {
+    type Err = PKError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let Some((command, args)) = words.split_first() else {
+            return Err(PKError::InvalidCommand("nothing there".to_string()));
+        };
+        let command = command.to_lowercase();
+        let no_args = |parsed: Command| {
+            if args.is_empty() {
+                Ok(parsed)
+            } else {
+                Err(PKError::InvalidCommand(format!("{command} doesn't take anything")))
+            }
+        };
+
+        match command.as_str() {
+            "add" => Command::add(args),
+            "remove" | "rm" => Ok(Command::Remove(Command::one(&command, args)?)),
+            "hand" => match args.split_first() {
+                Some((player, cards)) => Ok(Command::Hand {
+                    player: (*player).to_string(),
+                    hand: Command::two(cards)?,
+                }),
+                None => Err(PKError::InvalidCommand("hand needs a player and two cards".to_string())),
+            },
+            "board" => Ok(Command::Board(Command::cards(args)?)),
+            "deal" => Ok(Command::Deal(Command::cards(args)?)),
+            "dead" => Ok(Command::Dead(Command::cards(args)?)),
+            "clear" => match args {
+                [] => Ok(Command::Clear(Clear::All)),
+                [what] if what.eq_ignore_ascii_case("board") => Ok(Command::Clear(Clear::Board)),
+                [what] if what.eq_ignore_ascii_case("dead") => Ok(Command::Clear(Clear::Dead)),
+                _ => Err(PKError::InvalidCommand("clear takes board or dead".to_string())),
+            },
+            "equity" | "odds" => no_args(Command::Equity),
+            "outs" => no_args(Command::Outs),
+            "nuts" => no_args(Command::Nuts),
+            "range" => match args.split_first() {
+                Some((player, range)) if !range.is_empty() => Ok(Command::Range {
+                    player: (*player).to_string(),
+                    range: range.join(" "),
+                }),
+                _ => Err(PKError::InvalidCommand("range needs a player and a range".to_string())),
+            },
+            "undo" => no_args(Command::Undo),
+            "save" => Ok(Command::Save(Command::one(&command, args)?)),
+            "load" => Ok(Command::Load(Command::one(&command, args)?)),
+            "show" => no_args(Command::Show),
+            "help" | "?" => no_args(Command::Help),
+            "quit" | "exit" | "q" => no_args(Command::Quit),
+            _ => Err(PKError::InvalidCommand(format!("don't know {command}, try help"))),
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod repl__command_tests {
+    use super::*;
+
+    #[test]
+    fn from_str__add() {
+        assert_eq!(
+            Command::Add {
+                name: Some("Gus".to_string()),
+                hand: Some(Two::HAND_AS_KH)
+            },
+            Command::from_str("add Gus As K♥").unwrap()
+        );
+        assert_eq!(
+            Command::Add {
+                name: None,
+                hand: Some(Two::HAND_AS_KH)
+            },
+            Command::from_str("ADD kh as").unwrap()
+        );
+        assert_eq!(
+            Command::Add { name: None, hand: None },
+            Command::from_str("add").unwrap()
+        );
+    }
+
+    #[test]
+    fn from_str__cards() {
+        assert_eq!(
+            Command::Board(vec![Card::NINE_CLUBS, Card::SIX_DIAMONDS, Card::FIVE_HEARTS]),
+            Command::from_str("board 9c 6♦ 5h").unwrap()
+        );
+        assert_eq!(Command::Board(vec![]), Command::from_str("board").unwrap());
+        assert_eq!(
+            Command::Dead(vec![Card::DEUCE_CLUBS]),
+            Command::from_str(" dead  2c ").unwrap()
+        );
+        assert_eq!(Err(PKError::Duplicate), Command::from_str("deal 5s 5♠"));
+        assert!(matches!(Command::from_str("deal 5sx"), Err(PKError::InvalidCommand(_))));
+    }
+
+    #[test]
+    fn from_str__players() {
+        assert_eq!(
+            Command::Hand {
+                player: "2".to_string(),
+                hand: Two::HAND_AS_KH
+            },
+            Command::from_str("hand 2 As Kh").unwrap()
+        );
+        assert_eq!(Command::Remove("Gus".to_string()), Command::from_str("rm Gus").unwrap());
+        assert_eq!(
+            Command::Range {
+                player: "Gus".to_string(),
+                range: "QQ+, AK".to_string()
+            },
+            Command::from_str("range Gus QQ+, AK").unwrap()
+        );
+        assert!(matches!(
+            Command::from_str("hand Gus As"),
+            Err(PKError::InvalidCommand(_))
+        ));
+    }
+
+    #[test]
+    fn from_str__the_rest() {
+        assert_eq!(Command::Clear(Clear::All), Command::from_str("clear").unwrap());
+        assert_eq!(Command::Clear(Clear::Dead), Command::from_str("clear DEAD").unwrap());
+        assert_eq!(
+            Command::Save("hand.yaml".to_string()),
+            Command::from_str("save hand.yaml").unwrap()
+        );
+        assert_eq!(Command::Equity, Command::from_str("odds").unwrap());
+        assert_eq!(Command::Quit, Command::from_str("q").unwrap());
+        assert!(matches!(
+            Command::from_str("nuts please"),
+            Err(PKError::InvalidCommand(_))
+        ));
+        assert!(matches!(Command::from_str("fold"), Err(PKError::InvalidCommand(_))));
+        assert!(matches!(Command::from_str("   "), Err(PKError::InvalidCommand(_))));
+    }
+}
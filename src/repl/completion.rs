@@ -0,0 +1,125 @@
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::rank::Rank;
+use crate::repl::command::Command;
+use crate::repl::session::Session;
+use crate::suit::Suit;
+use std::str::FromStr;
+
+/// What the last word on the line could be finished as, where the line is everything up to the
+/// cursor. The first word is a command, the word after `hand`, `range` or `remove` is a player,
+/// and everything else is a card.
+///
+/// Cards are what this is really for, since the suit symbols are a pain to type. A rank on its
+/// own gives every suit of it that's still in the deck, and a rank with a suit letter turns into
+/// the symbol:
+///
+/// ```txt
+/// A   → A♠ A♥ A♦ A♣
+/// as  → A♠
+/// ```
+///
+/// Cards that are already in the session, or further back on the line, aren't offered.
+#[must_use]
+pub fn complete(line: &str, session: &Session) -> Vec<String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (before, word) = if line.is_empty() || line.ends_with(char::is_whitespace) {
+        (words.as_slice(), "")
+    } else {
+        words
+            .split_last()
+            .map_or((words.as_slice(), ""), |(last, before)| (before, *last))
+    };
+
+    match before {
+        [] => starting_with(Command::NAMES.iter().copied(), word),
+        [command] if Command::TAKES_PLAYER.contains(&command.to_lowercase().as_str()) => {
+            starting_with(session.players.iter().map(|player| player.name.as_str()), word)
+        }
+        [command] if command.eq_ignore_ascii_case("clear") => starting_with(["board", "dead"].into_iter(), word),
+        _ => {
+            let mut used = session.used();
+            for card in before.iter().filter_map(|token| card(token)) {
+                used.insert(card);
+            }
+            cards(word, &used)
+        }
+    }
+}
+
+fn starting_with<'a>(candidates: impl Iterator<Item = &'a str>, word: &str) -> Vec<String> {
+    let word = word.to_lowercase();
+    candidates
+        .filter(|candidate| candidate.to_lowercase().starts_with(&word))
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn card(token: &str) -> Option<Card> {
+    if token.chars().count() == 2 {
+        Card::from_str(token).ok()
+    } else {
+        None
+    }
+}
+
+fn cards(word: &str, used: &Cards) -> Vec<String> {
+    let mut chars = word.chars();
+    let candidates = match (chars.next(), chars.next(), chars.next()) {
+        (Some(rank), None, None) => {
+            let rank = Rank::from(rank);
+            if rank == Rank::BLANK {
+                return Vec::new();
+            }
+            [Suit::SPADES, Suit::HEARTS, Suit::DIAMONDS, Suit::CLUBS]
+                .into_iter()
+                .map(|suit| Card::new(rank, suit))
+                .collect()
+        }
+        (Some(_), Some(_), None) => card(word).into_iter().collect(),
+        _ => Vec::new(),
+    };
+    candidates
+        .into_iter()
+        .filter(|card| used.get(card).is_none())
+        .map(|card| card.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod repl__completion_tests {
+    use super::*;
+    use crate::arrays::two::Two;
+
+    fn session() -> Session {
+        let mut session = Session::default();
+        session.add_player(Some("Gus"), Some(Two::HAND_AS_KH)).unwrap();
+        session.add_player(Some("Phil"), None).unwrap();
+        session
+    }
+
+    #[test]
+    fn complete__commands() {
+        assert_eq!(vec!["deal", "dead"], complete("de", &session()));
+        assert_eq!(vec!["equity"], complete("E", &session()));
+        assert_eq!(Command::NAMES.len(), complete("", &session()).len());
+    }
+
+    #[test]
+    fn complete__players() {
+        assert_eq!(vec!["Phil"], complete("hand p", &session()));
+        assert_eq!(vec!["Gus", "Phil"], complete("remove ", &session()));
+        assert_eq!(vec!["dead"], complete("clear d", &session()));
+    }
+
+    #[test]
+    fn complete__cards() {
+        assert_eq!(vec!["A♥", "A♦", "A♣"], complete("board a", &session()));
+        assert_eq!(vec!["Q♦"], complete("board 2c Qd", &session()));
+        assert_eq!(vec!["A♥", "A♣"], complete("hand Phil A♦ a", &session()));
+        assert!(complete("deal kh", &session()).is_empty());
+        assert!(complete("deal x", &session()).is_empty());
+        assert!(complete("deal ", &session()).is_empty());
+    }
+}
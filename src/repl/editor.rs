@@ -0,0 +1,503 @@
+use crossterm::cursor::MoveToColumn;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, Clear, ClearType};
+use crossterm::{execute, queue};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
+
+/// The lines typed so far, oldest first, and where the up and down arrows have got to in them.
+/// Whatever was being typed before going up is kept, so that coming back down gets it back.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct History {
+    entries: Vec<String>,
+    position: Option<usize>,
+    draft: String,
+}
+
+impl History {
+    /// How many lines are kept in the file.
+    pub const MAX: usize = 1_000;
+
+    /// A missing or unreadable file is just an empty history, since it's nothing to stop over.
+    #[must_use]
+    pub fn load(path: &PathBuf) -> History {
+        let entries = std::fs::read_to_string(path)
+            .map(|history| history.lines().map(ToString::to_string).collect())
+            .unwrap_or_default();
+        History {
+            entries,
+            ..History::default()
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Passes on anything that goes wrong writing the file.
+    pub fn save(&self, path: &PathBuf) -> io::Result<()> {
+        let skip = self.entries.len().saturating_sub(History::MAX);
+        let mut history = self.entries[skip..].join("\n");
+        history.push('\n');
+        std::fs::write(path, history)
+    }
+
+    #[must_use]
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Blank lines and the same line twice in a row aren't worth remembering.
+    pub fn push(&mut self, line: &str) {
+        self.reset();
+        let line = line.trim();
+        if !line.is_empty() && self.entries.last().map(String::as_str) != Some(line) {
+            self.entries.push(line.to_string());
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+
+    /// One line further back, or `None` if there's nothing further back.
+    pub fn previous(&mut self, current: &str) -> Option<String> {
+        let position = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => return None,
+            Some(position) => position - 1,
+        };
+        self.position = Some(position);
+        self.entries.get(position).cloned()
+    }
+
+    /// One line forward, ending up back at whatever was being typed.
+    pub fn next(&mut self) -> Option<String> {
+        let position = self.position?;
+        if position + 1 < self.entries.len() {
+            self.position = Some(position + 1);
+            self.entries.get(position + 1).cloned()
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+}
+
+/// The line being edited and where the cursor is in it, counted in `char`s so that the suit
+/// symbols don't throw it off.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LineBuffer {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl LineBuffer {
+    #[must_use]
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    #[must_use]
+    pub fn before_cursor(&self) -> String {
+        self.chars[..self.cursor].iter().collect()
+    }
+
+    #[must_use]
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    pub fn set(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.insert(c);
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+
+    /// Everything before the cursor, like `Ctrl-U` in a shell.
+    pub fn kill_to_start(&mut self) {
+        self.chars.drain(..self.cursor);
+        self.cursor = 0;
+    }
+
+    /// Finishes off the word before the cursor. One candidate replaces it outright, with a space
+    /// after it ready for the next one. More than one only get as far as they all agree. Returns
+    /// false if that didn't change anything, which is when the candidates should be listed.
+    pub fn complete(&mut self, candidates: &[String]) -> bool {
+        let start = self.chars[..self.cursor]
+            .iter()
+            .rposition(|c| c.is_whitespace())
+            .map_or(0, |i| i + 1);
+        let word: String = self.chars[start..self.cursor].iter().collect();
+        let replacement = match candidates {
+            [] => return false,
+            [only] => format!("{only} "),
+            _ => LineBuffer::common_prefix(candidates),
+        };
+        if replacement.chars().count() <= word.chars().count() {
+            return false;
+        }
+        self.chars.drain(start..self.cursor);
+        self.cursor = start;
+        self.insert_str(&replacement);
+        true
+    }
+
+    fn common_prefix(candidates: &[String]) -> String {
+        let mut prefix: Vec<char> = candidates.first().map(|c| c.chars().collect()).unwrap_or_default();
+        for candidate in candidates {
+            let agreed = prefix
+                .iter()
+                .zip(candidate.chars())
+                .take_while(|(a, b)| a.eq_ignore_ascii_case(b))
+                .count();
+            prefix.truncate(agreed);
+        }
+        prefix.into_iter().collect()
+    }
+}
+
+/// What a key press did to the line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Edit {
+    Redraw,
+    Submit,
+    /// `Ctrl-C`, which throws the line away.
+    Cancel,
+    /// `Ctrl-D` on an empty line.
+    Eof,
+    /// Tab couldn't finish the word on its own, so these are what it could be.
+    Candidates(Vec<String>),
+}
+
+/// # `LineEditor`
+///
+/// Just enough of readline for the REPL: moving around the line, the up and down arrows for the
+/// history, a few of the emacs keys, and tab completion. None of the line editing crates are
+/// something I want to pull in for that, and crossterm is already here for the TUI.
+///
+/// When stdin isn't a terminal, like when a file of commands is piped in, it reads plain lines
+/// and doesn't print a prompt, so that the output is only the answers.
+#[derive(Clone, Debug, Default)]
+pub struct LineEditor {
+    pub history: History,
+    path: Option<PathBuf>,
+}
+
+impl LineEditor {
+    /// With a path, the history is loaded from it, and saved back after every line.
+    #[must_use]
+    pub fn new(path: Option<PathBuf>) -> LineEditor {
+        LineEditor {
+            history: path.as_ref().map(History::load).unwrap_or_default(),
+            path,
+        }
+    }
+
+    /// The next line, or `None` once the input is done.
+    ///
+    /// # Errors
+    ///
+    /// Passes on anything that goes wrong with the terminal or stdin.
+    pub fn read_line<F>(&mut self, prompt: &str, complete: F) -> io::Result<Option<String>>
+    where
+        F: Fn(&str) -> Vec<String>,
+    {
+        if !io::stdin().is_terminal() {
+            let mut line = String::new();
+            return match io::stdin().lock().read_line(&mut line)? {
+                0 => Ok(None),
+                _ => Ok(Some(line.trim_end().to_string())),
+            };
+        }
+
+        enable_raw_mode()?;
+        let result = self.edit(prompt, complete);
+        disable_raw_mode()?;
+
+        if let Ok(Some(line)) = &result {
+            self.history.push(line);
+            if let Some(path) = &self.path {
+                self.history.save(path)?;
+            }
+        }
+        result
+    }
+
+    fn edit<F>(&mut self, prompt: &str, complete: F) -> io::Result<Option<String>>
+    where
+        F: Fn(&str) -> Vec<String>,
+    {
+        let mut out = io::stdout();
+        let mut buffer = LineBuffer::default();
+        loop {
+            LineEditor::render(&mut out, prompt, &buffer)?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match self.on_key(&mut buffer, key, &complete) {
+                Edit::Redraw => {}
+                Edit::Submit => {
+                    execute!(out, Print("\r\n"))?;
+                    return Ok(Some(buffer.text()));
+                }
+                Edit::Cancel => {
+                    execute!(out, Print("^C\r\n"))?;
+                    return Ok(Some(String::new()));
+                }
+                Edit::Eof => {
+                    execute!(out, Print("\r\n"))?;
+                    return Ok(None);
+                }
+                Edit::Candidates(candidates) => {
+                    execute!(out, Print("\r\n"), Print(candidates.join("  ")), Print("\r\n"))?;
+                }
+            }
+        }
+    }
+
+    fn render(out: &mut impl Write, prompt: &str, buffer: &LineBuffer) -> io::Result<()> {
+        let column = u16::try_from(prompt.chars().count() + buffer.cursor()).unwrap_or(u16::MAX);
+        queue!(
+            out,
+            MoveToColumn(0),
+            Clear(ClearType::CurrentLine),
+            Print(prompt),
+            Print(buffer.text()),
+            MoveToColumn(column)
+        )?;
+        out.flush()
+    }
+
+    /// Everything a key does, away from the terminal so that it can be tested.
+    pub fn on_key<F>(&mut self, buffer: &mut LineBuffer, key: KeyEvent, complete: F) -> Edit
+    where
+        F: Fn(&str) -> Vec<String>,
+    {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('c') => return Edit::Cancel,
+                KeyCode::Char('d') if buffer.is_empty() => return Edit::Eof,
+                KeyCode::Char('d') => buffer.delete(),
+                KeyCode::Char('a') => buffer.home(),
+                KeyCode::Char('e') => buffer.end(),
+                KeyCode::Char('b') => buffer.left(),
+                KeyCode::Char('f') => buffer.right(),
+                KeyCode::Char('u') => buffer.kill_to_start(),
+                KeyCode::Char('p') => self.previous(buffer),
+                KeyCode::Char('n') => self.next(buffer),
+                _ => {}
+            }
+            return Edit::Redraw;
+        }
+        match key.code {
+            KeyCode::Enter => return Edit::Submit,
+            KeyCode::Char(c) => buffer.insert(c),
+            KeyCode::Backspace => buffer.backspace(),
+            KeyCode::Delete => buffer.delete(),
+            KeyCode::Left => buffer.left(),
+            KeyCode::Right => buffer.right(),
+            KeyCode::Home => buffer.home(),
+            KeyCode::End => buffer.end(),
+            KeyCode::Up => self.previous(buffer),
+            KeyCode::Down => self.next(buffer),
+            KeyCode::Tab => {
+                let candidates = complete(&buffer.before_cursor());
+                if !buffer.complete(&candidates) && candidates.len() > 1 {
+                    return Edit::Candidates(candidates);
+                }
+            }
+            _ => {}
+        }
+        Edit::Redraw
+    }
+
+    fn previous(&mut self, buffer: &mut LineBuffer) {
+        if let Some(line) = self.history.previous(&buffer.text()) {
+            buffer.set(&line);
+        }
+    }
+
+    fn next(&mut self, buffer: &mut LineBuffer) {
+        if let Some(line) = self.history.next() {
+            buffer.set(&line);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod repl__editor_tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn none(_: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn aces(_: &str) -> Vec<String> {
+        vec!["A♠".to_string(), "A♥".to_string()]
+    }
+
+    fn typed(editor: &mut LineEditor, text: &str) -> LineBuffer {
+        let mut buffer = LineBuffer::default();
+        for c in text.chars() {
+            editor.on_key(&mut buffer, key(KeyCode::Char(c)), none);
+        }
+        buffer
+    }
+
+    #[test]
+    fn history() {
+        let mut history = History::default();
+        history.push("add Gus");
+        history.push("add Gus");
+        history.push("  ");
+        history.push("equity");
+
+        assert_eq!(Some("equity".to_string()), history.previous("dea"));
+        assert_eq!(Some("add Gus".to_string()), history.previous("equity"));
+        assert_eq!(None, history.previous("add Gus"));
+        assert_eq!(Some("equity".to_string()), history.next());
+        assert_eq!(Some("dea".to_string()), history.next());
+        assert_eq!(None, history.next());
+    }
+
+    #[test]
+    fn history__save_and_load() {
+        let path = std::env::temp_dir().join(format!("pk_repl_history_{}", std::process::id()));
+        let mut history = History::default();
+        history.push("board 9c 6d 5h");
+        history.push("nuts");
+
+        history.save(&path).unwrap();
+        let loaded = History::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(history.entries(), loaded.entries());
+        assert!(History::load(&path).entries().is_empty());
+    }
+
+    #[test]
+    fn line_buffer() {
+        let mut buffer = LineBuffer::default();
+        buffer.insert_str("deal 5♠");
+        buffer.left();
+        buffer.backspace();
+        buffer.insert('8');
+        buffer.end();
+        buffer.insert(' ');
+
+        assert_eq!("deal 8♠ ", buffer.text());
+        assert_eq!("deal 8♠ ", buffer.before_cursor());
+
+        buffer.home();
+        buffer.delete();
+        buffer.right();
+        buffer.kill_to_start();
+
+        assert_eq!("al 8♠ ", buffer.text());
+        assert_eq!(0, buffer.cursor());
+    }
+
+    #[test]
+    fn line_buffer__complete() {
+        let mut buffer = LineBuffer::default();
+        buffer.set("board 9c a");
+
+        assert!(!buffer.complete(&aces("")));
+        assert!(buffer.complete(&["A♥".to_string()]));
+        assert_eq!("board 9c A♥ ", buffer.text());
+
+        buffer.set("de");
+        assert!(buffer.complete(&["deal".to_string(), "dead".to_string()]));
+        assert_eq!("dea", buffer.text());
+        assert!(!buffer.complete(&[]));
+    }
+
+    #[test]
+    fn on_key() {
+        let mut editor = LineEditor::default();
+        let mut buffer = typed(&mut editor, "add a");
+
+        assert_eq!(
+            Edit::Candidates(aces("")),
+            editor.on_key(&mut buffer, key(KeyCode::Tab), aces)
+        );
+        assert_eq!(Edit::Submit, editor.on_key(&mut buffer, key(KeyCode::Enter), none));
+        assert_eq!(Edit::Cancel, editor.on_key(&mut buffer, ctrl('c'), none));
+        assert_eq!(Edit::Redraw, editor.on_key(&mut buffer, ctrl('d'), none));
+        assert_eq!(Edit::Eof, editor.on_key(&mut LineBuffer::default(), ctrl('d'), none));
+    }
+
+    #[test]
+    fn on_key__history() {
+        let mut editor = LineEditor::default();
+        editor.history.push("equity");
+        let mut buffer = typed(&mut editor, "ou");
+
+        editor.on_key(&mut buffer, key(KeyCode::Up), none);
+        assert_eq!("equity", buffer.text());
+
+        editor.on_key(&mut buffer, key(KeyCode::Down), none);
+        assert_eq!("ou", buffer.text());
+    }
+}
@@ -0,0 +1,5 @@
+pub mod command;
+pub mod completion;
+pub mod editor;
+pub mod session;
+pub mod shell;
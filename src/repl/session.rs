@@ -0,0 +1,577 @@
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::play::notation::{GameType, NotatedPlayer, Notation};
+use crate::{PKError, Pile};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// Somebody at the table. They can sit down before anybody knows what they're holding, which is
+/// how it goes when I'm following along with a TV show and the hole cams are a street behind.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SessionPlayer {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hand: Option<Two>,
+}
+
+impl SessionPlayer {
+    #[must_use]
+    pub fn new(name: &str, hand: Option<Two>) -> SessionPlayer {
+        SessionPlayer {
+            name: name.to_string(),
+            hand,
+        }
+    }
+}
+
+impl Display for SessionPlayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.hand {
+            Some(hand) => write!(f, "{}: {hand}", self.name),
+            None => write!(f, "{}: __ __", self.name),
+        }
+    }
+}
+
+/// Everything that gets put back by an undo.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct Snapshot {
+    players: Vec<SessionPlayer>,
+    board: Cards,
+    dead: Cards,
+}
+
+/// # Session
+///
+/// The hand that the REPL is looking at, built up a piece at a time. Every change is checked
+/// before it's made, so there's never a card in two places, and the way things were before is
+/// pushed onto the undo stack. The undo stack doesn't get saved with the session.
+///
+/// Sessions are saved as YAML, so that they can be fixed up by hand:
+///
+/// ```yaml
+/// players:
+/// - name: Negreanu
+///   hand: 6♠ 6♥
+/// - name: Hansen
+///   hand: 5♦ 5♣
+/// board: 9♣ 6♦ 5♥
+/// dead: 2♣
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Session {
+    #[serde(default)]
+    pub players: Vec<SessionPlayer>,
+    #[serde(default, skip_serializing_if = "Cards::is_empty")]
+    pub board: Cards,
+    #[serde(default, skip_serializing_if = "Cards::is_empty")]
+    pub dead: Cards,
+    #[serde(skip)]
+    undo: Vec<Snapshot>,
+}
+
+impl Session {
+    pub const MAX_BOARD: usize = 5;
+    pub const MAX_PLAYERS: usize = 10;
+
+    /// Every card that's somewhere, in the players' hands, on the board or dead.
+    #[must_use]
+    pub fn used(&self) -> Cards {
+        let mut used = Cards::default();
+        for hand in self.players.iter().filter_map(|player| player.hand) {
+            used.insert(hand.first());
+            used.insert(hand.second());
+        }
+        used.insert_all(&self.board);
+        used.insert_all(&self.dead);
+        used
+    }
+
+    /// The players that have their cards, since they're the only ones that anything can be
+    /// worked out for.
+    #[must_use]
+    pub fn dealt(&self) -> Vec<&SessionPlayer> {
+        self.players.iter().filter(|player| player.hand.is_some()).collect()
+    }
+
+    /// Finds a player by name, ignoring case, or by where they're sitting, counting from one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCommand` if there's nobody like that.
+    pub fn find(&self, who: &str) -> Result<usize, PKError> {
+        if let Ok(seat) = who.parse::<usize>() {
+            if (1..=self.players.len()).contains(&seat) {
+                return Ok(seat - 1);
+            }
+        }
+        self.players
+            .iter()
+            .position(|player| player.name.eq_ignore_ascii_case(who))
+            .ok_or_else(|| PKError::InvalidCommand(format!("nobody called {who}")))
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCommand` if the name is taken or could be mistaken for a card or a
+    /// seat, `PKError::TooManyHands` if the table is full, and `PKError::Duplicate` if one of the
+    /// cards is already somewhere else.
+    pub fn add_player(&mut self, name: Option<&str>, hand: Option<Two>) -> Result<String, PKError> {
+        if self.players.len() >= Session::MAX_PLAYERS {
+            return Err(PKError::TooManyHands);
+        }
+        let name = match name {
+            Some(name) => {
+                self.check_name(name)?;
+                name.to_string()
+            }
+            None => self.next_name(),
+        };
+        if let Some(hand) = hand {
+            self.check_free(&hand.to_vec(), &Cards::default())?;
+        }
+        self.save_undo();
+        self.players.push(SessionPlayer::new(&name, hand));
+        Ok(name)
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCommand` if there's nobody like that.
+    pub fn remove_player(&mut self, who: &str) -> Result<SessionPlayer, PKError> {
+        let index = self.find(who)?;
+        self.save_undo();
+        Ok(self.players.remove(index))
+    }
+
+    /// Gives a player their cards, or swaps them for new ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCommand` if there's nobody like that, and `PKError::Duplicate` if
+    /// one of the cards is already somewhere other than their old hand.
+    pub fn set_hand(&mut self, who: &str, hand: Two) -> Result<(), PKError> {
+        let index = self.find(who)?;
+        let old = self.players[index]
+            .hand
+            .map_or_else(Cards::default, |old| Cards::from(old.to_vec()));
+        self.check_free(&hand.to_vec(), &old)?;
+        self.save_undo();
+        self.players[index].hand = Some(hand);
+        Ok(())
+    }
+
+    /// Swaps the whole board for a new one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::TooManyCards` for more than five cards, and `PKError::Duplicate` if one
+    /// of them is already somewhere other than the old board.
+    pub fn set_board(&mut self, board: &[Card]) -> Result<(), PKError> {
+        if board.len() > Session::MAX_BOARD {
+            return Err(PKError::TooManyCards);
+        }
+        self.check_free(board, &self.board.clone())?;
+        self.save_undo();
+        self.board = Cards::from(board.to_vec());
+        Ok(())
+    }
+
+    /// Adds cards to the end of the board, like the dealer would.
+    ///
+    /// # Errors
+    ///
+    /// Same as `Session::set_board()`.
+    pub fn deal(&mut self, cards: &[Card]) -> Result<(), PKError> {
+        if self.board.len() + cards.len() > Session::MAX_BOARD {
+            return Err(PKError::TooManyCards);
+        }
+        self.check_free(cards, &Cards::default())?;
+        self.save_undo();
+        for card in cards {
+            self.board.insert(*card);
+        }
+        Ok(())
+    }
+
+    /// Takes cards out of the deck, like a burn card that flashed or somebody's mucked hand.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Duplicate` if one of them is already somewhere.
+    pub fn kill(&mut self, cards: &[Card]) -> Result<(), PKError> {
+        self.check_free(cards, &Cards::default())?;
+        self.save_undo();
+        for card in cards {
+            self.dead.insert(*card);
+        }
+        Ok(())
+    }
+
+    pub fn clear_board(&mut self) {
+        self.save_undo();
+        self.board = Cards::default();
+    }
+
+    pub fn clear_dead(&mut self) {
+        self.save_undo();
+        self.dead = Cards::default();
+    }
+
+    /// Starts over, but it can still be undone.
+    pub fn clear(&mut self) {
+        self.save_undo();
+        self.players.clear();
+        self.board = Cards::default();
+        self.dead = Cards::default();
+    }
+
+    /// Swaps everything for a session loaded from somewhere else, keeping the undo stack so that
+    /// loading over the top of something can be undone too.
+    pub fn replace(&mut self, other: Session) {
+        self.save_undo();
+        self.players = other.players;
+        self.board = other.board;
+        self.dead = other.dead;
+    }
+
+    /// Puts back the way things were before the last change. Returns false if there's nothing
+    /// left to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo.pop() {
+            Some(snapshot) => {
+                self.players = snapshot.players;
+                self.board = snapshot.board;
+                self.dead = snapshot.dead;
+                true
+            }
+            None => false,
+        }
+    }
+
+    #[must_use]
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// The session as the compact notation, with only the players that have cards, since that's
+    /// what `GameRequest` and the rest of the library know how to work with.
+    #[must_use]
+    pub fn notation(&self) -> Notation {
+        Notation {
+            game_type: GameType::Holdem,
+            players: self
+                .dealt()
+                .into_iter()
+                .filter_map(|player| {
+                    player
+                        .hand
+                        .map(|hand| NotatedPlayer::new(Some(player.name.clone()), Cards::from(hand.to_vec())))
+                })
+                .collect(),
+            board: self.board.clone(),
+            dead: self.dead.clone(),
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the file can't be written.
+    pub fn save(&self, path: &str) -> Result<(), PKError> {
+        let yaml = serde_yaml::to_string(self).map_err(|e| PKError::StorageError(format!("{path}: {e}")))?;
+        std::fs::write(path, yaml).map_err(|e| PKError::StorageError(format!("{path}: {e}")))
+    }
+
+    /// # Errors
+    ///
+    /// Returns `PKError::StorageError` if the file can't be read or parsed, and whatever
+    /// `Session::from_str()` doesn't like about what's in it.
+    pub fn load(path: &str) -> Result<Session, PKError> {
+        let yaml = std::fs::read_to_string(path).map_err(|e| PKError::StorageError(format!("{path}: {e}")))?;
+        Session::from_str(&yaml)
+    }
+
+    // region private
+
+    fn save_undo(&mut self) {
+        self.undo.push(Snapshot {
+            players: self.players.clone(),
+            board: self.board.clone(),
+            dead: self.dead.clone(),
+        });
+    }
+
+    /// Names are one word, so that the commands can tell them apart from everything else.
+    fn check_name(&self, name: &str) -> Result<(), PKError> {
+        if name.is_empty() || name.contains(char::is_whitespace) || name.ends_with(':') {
+            return Err(PKError::InvalidCommand(format!("{name} can't be a name")));
+        }
+        if name.parse::<usize>().is_ok() || (name.chars().count() == 2 && Card::from_str(name).is_ok()) {
+            return Err(PKError::InvalidCommand(format!("{name} looks like a seat or a card")));
+        }
+        if self.players.iter().any(|player| player.name.eq_ignore_ascii_case(name)) {
+            return Err(PKError::InvalidCommand(format!("{name} is already playing")));
+        }
+        Ok(())
+    }
+
+    fn next_name(&self) -> String {
+        (1..=Session::MAX_PLAYERS + 1)
+            .map(|i| format!("Player{i}"))
+            .find(|name| !self.players.iter().any(|player| player.name.eq_ignore_ascii_case(name)))
+            .unwrap_or_default()
+    }
+
+    /// The cards can't already be in use, unless they're in `replacing`, which is about to be
+    /// swapped out. They can't be in there twice either.
+    fn check_free(&self, cards: &[Card], replacing: &Cards) -> Result<(), PKError> {
+        let mut used = Cards::from(
+            self.used()
+                .to_vec()
+                .into_iter()
+                .filter(|card| !replacing.contains(card))
+                .collect::<Vec<Card>>(),
+        );
+        for card in cards {
+            if *card == Card::BLANK {
+                return Err(PKError::InvalidCard);
+            }
+            if !used.insert(*card) {
+                return Err(PKError::Duplicate);
+            }
+        }
+        Ok(())
+    }
+
+    // endregion
+}
+
+/// What's in a saved session file. The board and dead cards are read in as plain strings rather
+/// than `Cards`, since the `Cards` deserializer turns a typo into no cards at all, and quietly
+/// drops the second of a repeated card.
+#[derive(Debug, Default, Deserialize)]
+struct SavedSession {
+    #[serde(default)]
+    players: Vec<SessionPlayer>,
+    #[serde(default)]
+    board: String,
+    #[serde(default)]
+    dead: String,
+}
+
+impl SavedSession {
+    /// Every card in the string, repeats and all, so that `Session` can complain about them.
+    fn cards(s: &str) -> Result<Vec<Card>, PKError> {
+        s.split_whitespace()
+            .map(|index| match Card::from_str(index) {
+                Ok(card) if card != Card::BLANK => Ok(card),
+                _ => Err(PKError::InvalidCard),
+            })
+            .collect()
+    }
+}
+
+/// A saved session, which gets the same checks as if it had been typed in.
+impl FromStr for Session {
+    type Err = PKError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let saved: SavedSession = serde_yaml::from_str(s).map_err(|e| PKError::StorageError(e.to_string()))?;
+        let mut session = Session::default();
+        for player in &saved.players {
+            session.add_player(Some(&player.name), player.hand)?;
+        }
+        session.set_board(&SavedSession::cards(&saved.board)?)?;
+        session.kill(&SavedSession::cards(&saved.dead)?)?;
+        session.undo.clear();
+        Ok(session)
+    }
+}
+
+impl Display for Session {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.players.is_empty() {
+            writeln!(f, "Nobody's sitting down yet.")?;
+        }
+        for (i, player) in self.players.iter().enumerate() {
+            writeln!(f, "{}. {player}", i + 1)?;
+        }
+        if self.board.is_empty() {
+            write!(f, "Board: -")?;
+        } else {
+            write!(f, "Board: {}", self.board)?;
+        }
+        if !self.dead.is_empty() {
+            write!(f, "\nDead:  {}", self.dead)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod repl__session_tests {
+    use super::*;
+
+    fn cards(s: &str) -> Vec<Card> {
+        Cards::from_str(s).unwrap().to_vec()
+    }
+
+    fn the_hand() -> Session {
+        let mut session = Session::default();
+        session.add_player(Some("Negreanu"), Some(Two::HAND_6S_6H)).unwrap();
+        session.add_player(Some("Hansen"), Some(Two::HAND_5D_5C)).unwrap();
+        session.set_board(&cards("9♣ 6♦ 5♥")).unwrap();
+        session
+    }
+
+    #[test]
+    fn add_player() {
+        let mut session = Session::default();
+
+        assert_eq!("Player1", session.add_player(None, None).unwrap());
+        assert_eq!("Gus", session.add_player(Some("Gus"), Some(Two::HAND_AS_AH)).unwrap());
+        assert_eq!("Player2", session.add_player(None, None).unwrap());
+        assert_eq!(1, session.find("gus").unwrap());
+        assert_eq!(1, session.dealt().len());
+    }
+
+    #[test]
+    fn add_player__bad_names() {
+        let mut session = the_hand();
+
+        assert!(matches!(
+            session.add_player(Some("hansen"), None),
+            Err(PKError::InvalidCommand(_))
+        ));
+        assert!(matches!(
+            session.add_player(Some("As"), None),
+            Err(PKError::InvalidCommand(_))
+        ));
+        assert!(matches!(
+            session.add_player(Some("3"), None),
+            Err(PKError::InvalidCommand(_))
+        ));
+        assert_eq!(
+            Err(PKError::Duplicate),
+            session.add_player(Some("Gus"), Some(Two::HAND_9C_2D))
+        );
+        assert_eq!(2, session.players.len());
+    }
+
+    #[test]
+    fn set_hand() {
+        let mut session = the_hand();
+
+        session.set_hand("Negreanu", Two::HAND_6S_6C).unwrap();
+
+        assert_eq!(Err(PKError::Duplicate), session.set_hand("2", Two::HAND_6C_2D));
+        assert_eq!(
+            "HE: Negreanu: 6♠ 6♣ Hansen: 5♦ 5♣ - 9♣ 6♦ 5♥",
+            session.notation().to_string()
+        );
+    }
+
+    #[test]
+    fn deal() {
+        let mut session = the_hand();
+
+        session.deal(&cards("5♠")).unwrap();
+
+        assert_eq!(Err(PKError::Duplicate), session.deal(&cards("6♠")));
+        assert_eq!(Err(PKError::TooManyCards), session.deal(&cards("8♠ 7♠")));
+        assert_eq!("9♣ 6♦ 5♥ 5♠", session.board.to_string());
+    }
+
+    #[test]
+    fn kill() {
+        let mut session = the_hand();
+
+        session.kill(&cards("2♣ 3♣")).unwrap();
+
+        assert_eq!(Err(PKError::Duplicate), session.kill(&cards("2♣")));
+        assert_eq!(
+            "HE: Negreanu: 6♠ 6♥ Hansen: 5♦ 5♣ - 9♣ 6♦ 5♥ DEAD: 2♣ 3♣",
+            session.notation().to_string()
+        );
+    }
+
+    #[test]
+    fn undo() {
+        let mut session = the_hand();
+        let before = session.clone();
+
+        session.remove_player("Negreanu").unwrap();
+        session.clear();
+
+        assert!(session.undo());
+        assert_eq!(1, session.players.len());
+        assert!(session.undo());
+        assert_eq!(before, session);
+    }
+
+    #[test]
+    fn undo__nothing() {
+        assert!(!Session::default().undo());
+    }
+
+    #[test]
+    fn from_str() {
+        let mut session = the_hand();
+        session.add_player(Some("Ivey"), None).unwrap();
+        session.kill(&cards("2♣")).unwrap();
+
+        let yaml = serde_yaml::to_string(&session).unwrap();
+        let loaded = Session::from_str(&yaml).unwrap();
+
+        assert_eq!(session.notation(), loaded.notation());
+        assert_eq!(3, loaded.players.len());
+        assert!(!loaded.can_undo());
+    }
+
+    #[test]
+    fn from_str__duplicate() {
+        let yaml = "players:\n- name: Gus\n  hand: A♠ A♥\nboard: A♠ K♦ 2♣\n";
+
+        assert_eq!(Err(PKError::Duplicate), Session::from_str(yaml));
+    }
+
+    /// Hand edited files get the same checks as everything else, rather than losing cards.
+    #[test]
+    fn from_str__hand_edited() {
+        let players = "players:\n- name: Gus\n  hand: A♠ A♥\n";
+
+        assert_eq!(
+            Err(PKError::InvalidCard),
+            Session::from_str(&format!("{players}board: 9x 6♦ 5♥\n"))
+        );
+        assert_eq!(
+            Err(PKError::Duplicate),
+            Session::from_str(&format!("{players}board: 9♣ 6♦ 5♥\ndead: 2♣ 2♣\n"))
+        );
+        assert_eq!(
+            Err(PKError::Duplicate),
+            Session::from_str(&format!("{players}board: 9♣ 9♣ 5♥\n"))
+        );
+        assert_eq!(
+            3,
+            Session::from_str(&format!("{players}board: 9c 6d 5h\n"))
+                .unwrap()
+                .board
+                .len()
+        );
+    }
+
+    #[test]
+    fn display() {
+        let mut session = the_hand();
+        session.add_player(Some("Ivey"), None).unwrap();
+
+        assert_eq!(
+            "1. Negreanu: 6♠ 6♥\n2. Hansen: 5♦ 5♣\n3. Ivey: __ __\nBoard: 9♣ 6♦ 5♥",
+            session.to_string()
+        );
+    }
+}
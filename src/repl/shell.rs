@@ -0,0 +1,320 @@
+use crate::arrays::five::Five;
+use crate::arrays::four::Four;
+use crate::arrays::three::Three;
+use crate::repl::command::{Clear, Command};
+use crate::repl::completion;
+use crate::repl::editor::LineEditor;
+use crate::repl::session::Session;
+use crate::web::api::{GameRequest, RangeRequest};
+use crate::web::service::EquityService;
+use crate::{PKError, Pile};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// # Repl
+///
+/// A `Session` that can be talked to a line at a time, with the equities coming from the same
+/// `EquityService` as `pk calc` and the web service:
+///
+/// ```txt
+/// pk> add Negreanu 6s 6h
+/// pk> add Hansen 5d 5c
+/// pk> board 9c 6d 5h
+/// pk> equity
+/// pk> deal 5s
+/// pk> outs
+/// pk> undo
+/// ```
+///
+/// The dead cards come out of the deck at every street, preflop included, although preflop with
+/// dead cards is always worked out the long way since the stored tables don't know about them.
+/// The nuts are for the board on its own, without anybody's hole cards or the dead cards taken
+/// out.
+pub struct Repl {
+    pub session: Session,
+    service: EquityService,
+}
+
+impl Repl {
+    pub const PROMPT: &'static str = "pk> ";
+
+    /// How many of the nuts to show. Nobody cares about the 26th best hand.
+    pub const NUTS: usize = 10;
+
+    #[must_use]
+    pub fn new(service: EquityService) -> Repl {
+        Repl {
+            session: Session::default(),
+            service,
+        }
+    }
+
+    /// Reads commands until `quit` or the end of the input. Anything that goes wrong with a
+    /// command is printed and then it's on to the next one.
+    ///
+    /// # Errors
+    ///
+    /// Passes on anything that goes wrong with the terminal.
+    pub fn run(&mut self, history: Option<PathBuf>) -> std::io::Result<()> {
+        let mut editor = LineEditor::new(history);
+        while let Some(line) = editor.read_line(Repl::PROMPT, |line| completion::complete(line, &self.session))? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match Command::from_str(&line) {
+                Ok(Command::Quit) => break,
+                Ok(command) => match self.execute(command) {
+                    Ok(output) => println!("{output}"),
+                    Err(e) => println!("{e}"),
+                },
+                Err(e) => println!("{e}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs one command and says how it went.
+    ///
+    /// # Errors
+    ///
+    /// Passes on whatever the session or the `EquityService` don't like about it.
+    pub fn execute(&mut self, command: Command) -> Result<String, PKError> {
+        match command {
+            Command::Add { name, hand } => {
+                self.session.add_player(name.as_deref(), hand)?;
+                Ok(self.session.to_string())
+            }
+            Command::Remove(who) => {
+                let player = self.session.remove_player(&who)?;
+                Ok(format!("{} is gone\n{}", player.name, self.session))
+            }
+            Command::Hand { player, hand } => {
+                self.session.set_hand(&player, hand)?;
+                Ok(self.session.to_string())
+            }
+            Command::Board(cards) => {
+                self.session.set_board(&cards)?;
+                Ok(self.session.to_string())
+            }
+            Command::Deal(cards) => {
+                self.session.deal(&cards)?;
+                Ok(self.session.to_string())
+            }
+            Command::Dead(cards) => {
+                self.session.kill(&cards)?;
+                Ok(self.session.to_string())
+            }
+            Command::Clear(what) => {
+                match what {
+                    Clear::All => self.session.clear(),
+                    Clear::Board => self.session.clear_board(),
+                    Clear::Dead => self.session.clear_dead(),
+                }
+                Ok(self.session.to_string())
+            }
+            Command::Equity => self.equity(),
+            Command::Outs => Ok(EquityService::outs(&self.request())?.to_string()),
+            Command::Nuts => self.nuts(),
+            Command::Range { player, range } => self.range(&player, &range),
+            Command::Undo => {
+                if self.session.undo() {
+                    Ok(self.session.to_string())
+                } else {
+                    Ok("Nothing to undo".to_string())
+                }
+            }
+            Command::Save(path) => {
+                self.session.save(&path)?;
+                Ok(format!("Saved to {path}"))
+            }
+            Command::Load(path) => {
+                let loaded = Session::load(&path)?;
+                self.session.replace(loaded);
+                Ok(self.session.to_string())
+            }
+            Command::Show => Ok(self.session.to_string()),
+            Command::Help => Ok(Command::HELP.to_string()),
+            Command::Quit => Ok(String::new()),
+        }
+    }
+
+    /// The session as a `GameRequest`. The notation carries the names and the dead cards, which
+    /// the hands and board fields don't.
+    fn request(&self) -> GameRequest {
+        GameRequest {
+            game: Some(self.session.notation().to_string()),
+            preflop: self.session.board.is_empty(),
+            ..GameRequest::default()
+        }
+    }
+
+    /// Only the street that the board is at. Preflop heads up is worked out if it isn't stored,
+    /// which takes a while.
+    fn equity(&self) -> Result<String, PKError> {
+        if matches!(self.session.board.len(), 1 | 2) {
+            return Err(PKError::NotEnoughCards);
+        }
+        let mut odds = self.service.odds(&self.request())?;
+        let street = odds.streets.pop().ok_or(PKError::Incomplete)?;
+        Ok(street.to_string())
+    }
+
+    fn nuts(&self) -> Result<String, PKError> {
+        let board = self.session.board.clone();
+        let the_nuts = match board.len() {
+            3 => Three::try_from(board.clone())?.the_nuts(),
+            4 => Four::from(board.to_vec()).the_nuts(),
+            5 => Five::try_from(board.clone())?.the_nuts(),
+            _ => return Err(PKError::NotEnoughCards),
+        };
+        let mut out = format!("The nuts on {board}:");
+        for (i, eval) in the_nuts.to_vec().iter().take(Repl::NUTS).enumerate() {
+            let _ = write!(out, "\n{:>3}. {eval}", i + 1);
+        }
+        Ok(out)
+    }
+
+    fn range(&self, player: &str, range: &str) -> Result<String, PKError> {
+        let player = &self.session.players[self.session.find(player)?];
+        let hand = player
+            .hand
+            .ok_or_else(|| PKError::InvalidCommand(format!("{} doesn't have any cards yet", player.name)))?;
+        // Nobody's at the other end of the repl to ask for `partial`, so it's on, and the
+        // response says so when it matters.
+        let response = self.service.range(&RangeRequest {
+            hand,
+            range: range.to_string(),
+            board: (!self.session.board.is_empty()).then(|| self.session.board.clone()),
+            partial: true,
+        })?;
+        Ok(format!("{}: {response}", player.name))
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod repl__shell_tests {
+    use super::*;
+
+    fn repl(lines: &[&str]) -> Repl {
+        let mut repl = Repl::new(EquityService::in_memory().unwrap());
+        for line in lines {
+            repl.execute(Command::from_str(line).unwrap()).unwrap();
+        }
+        repl
+    }
+
+    fn run(repl: &mut Repl, line: &str) -> Result<String, PKError> {
+        repl.execute(Command::from_str(line).unwrap())
+    }
+
+    const THE_HAND: [&str; 3] = ["add Negreanu 6s 6h", "add Hansen 5d 5c", "board 9c 6d 5h"];
+
+    #[test]
+    fn execute__equity() {
+        let mut repl = repl(&THE_HAND);
+
+        let flop = run(&mut repl, "equity").unwrap();
+        run(&mut repl, "deal 5s").unwrap();
+        let turn = run(&mut repl, "equity").unwrap();
+
+        assert!(flop.starts_with("Flop: 9♣ 6♦ 5♥\n  Negreanu [6♠ 6♥]"));
+        assert!(turn.starts_with("Turn: 9♣ 6♦ 5♥ 5♠\n  Negreanu [6♠ 6♥]"));
+        assert!(turn.contains("Hansen [5♦ 5♣] 97.7%"));
+    }
+
+    #[test]
+    fn execute__dead() {
+        let mut repl = repl(&THE_HAND);
+        run(&mut repl, "deal 2s").unwrap();
+        let live = run(&mut repl, "equity").unwrap();
+
+        run(&mut repl, "dead 5s").unwrap();
+
+        assert_ne!(live, run(&mut repl, "equity").unwrap());
+        assert!(run(&mut repl, "equity").unwrap().contains("Negreanu [6♠ 6♥] 100.0%"));
+    }
+
+    #[test]
+    fn execute__dead__flop() {
+        let mut repl = repl(&["add A♠ K♥", "add 8♦ 6♣", "board A♣ 8♥ 7♥"]);
+        let live = run(&mut repl, "equity").unwrap();
+
+        run(&mut repl, "dead 9s").unwrap();
+        let dead = run(&mut repl, "equity").unwrap();
+
+        assert!(dead.starts_with("Flop: A♣ 8♥ 7♥\n"));
+        assert_ne!(live, dead);
+    }
+
+    #[test]
+    fn execute__outs() {
+        let mut repl = repl(&THE_HAND);
+
+        assert_eq!(Err(PKError::Incomplete), run(&mut repl, "outs"));
+
+        run(&mut repl, "deal 5s").unwrap();
+
+        assert!(run(&mut repl, "outs").unwrap().contains("[6♠ 6♥] 1 OUTS: 6♣"));
+    }
+
+    #[test]
+    fn execute__nuts() {
+        let mut repl = repl(&THE_HAND);
+
+        let nuts = run(&mut repl, "nuts").unwrap();
+
+        assert!(nuts.starts_with("The nuts on 9♣ 6♦ 5♥:\n  1. 9♣ 8♠ 7♠ 6♦ 5♥ - 1605-NineHighStraight"));
+        assert_eq!(Repl::NUTS + 1, nuts.lines().count());
+        assert_eq!(
+            Err(PKError::NotEnoughCards),
+            run(&mut repl, "clear board").and_then(|_| run(&mut repl, "nuts"))
+        );
+    }
+
+    #[test]
+    fn execute__range() {
+        let mut repl = repl(&THE_HAND);
+        run(&mut repl, "add Ivey").unwrap();
+        run(&mut repl, "deal 5s").unwrap();
+
+        let range = run(&mut repl, "range 2 QQ+, 99").unwrap();
+
+        assert!(range.starts_with("Hansen: 5♦ 5♣ vs QQ+, 99 on 9♣ 6♦ 5♥ 5♠: 99.68% equity"));
+        assert!(matches!(
+            run(&mut repl, "range Ivey AA"),
+            Err(PKError::InvalidCommand(_))
+        ));
+    }
+
+    #[test]
+    fn execute__undo() {
+        let mut repl = repl(&THE_HAND);
+
+        run(&mut repl, "remove Hansen").unwrap();
+        run(&mut repl, "undo").unwrap();
+
+        assert_eq!(2, repl.session.players.len());
+        assert_eq!(Err(PKError::Duplicate), run(&mut repl, "hand Negreanu 5d 7d"));
+    }
+
+    #[test]
+    fn execute__save_and_load() {
+        let path = std::env::temp_dir().join(format!("pk_repl_session_{}.yaml", std::process::id()));
+        let path = path.to_string_lossy().to_string();
+        let mut repl = repl(&THE_HAND);
+        let before = repl.session.to_string();
+
+        run(&mut repl, &format!("save {path}")).unwrap();
+        run(&mut repl, "clear").unwrap();
+        run(&mut repl, &format!("load {path}")).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(before, repl.session.to_string());
+        assert!(matches!(
+            run(&mut repl, &format!("load {path}")),
+            Err(PKError::StorageError(_))
+        ));
+    }
+}
@@ -0,0 +1,67 @@
+use crate::tui::replayer::Replayer;
+use crate::tui::view;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::{Backend, CrosstermBackend};
+use ratatui::Terminal;
+use std::io;
+
+/// Takes over the terminal until `q` or `Esc` is pressed, and then puts it back the way it was,
+/// even if drawing went wrong along the way.
+///
+/// # Errors
+///
+/// Passes on anything that goes wrong with the terminal.
+pub fn run(replayer: &mut Replayer) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    let result = event_loop(&mut terminal, replayer);
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+fn event_loop<B: Backend>(terminal: &mut Terminal<B>, replayer: &mut Replayer) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| view::draw(frame, replayer))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && !on_key(replayer, key.code) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Returns false when it's time to quit. The vi keys work too, since I can't help myself.
+pub fn on_key(replayer: &mut Replayer, key: KeyCode) -> bool {
+    match key {
+        KeyCode::Char('q') | KeyCode::Esc => return false,
+        KeyCode::Right | KeyCode::Char('l' | ' ') => {
+            replayer.forward();
+        }
+        KeyCode::Left | KeyCode::Char('h') => {
+            replayer.backward();
+        }
+        KeyCode::Down | KeyCode::Char('j') => {
+            replayer.next_street();
+        }
+        KeyCode::Up | KeyCode::Char('k') => {
+            replayer.previous_street();
+        }
+        KeyCode::Home => replayer.rewind(),
+        KeyCode::End => replayer.fast_forward(),
+        KeyCode::Char('n') | KeyCode::PageDown => {
+            replayer.next_hand();
+        }
+        KeyCode::Char('p') | KeyCode::PageUp => {
+            replayer.previous_hand();
+        }
+        _ => {}
+    }
+    true
+}
@@ -0,0 +1,536 @@
+use crate::analysis::store::nubibus::pluribus::Pluribus;
+use crate::analysis::store::nubibus::Nubibus;
+use crate::arrays::two::Two;
+use crate::card::Card;
+use crate::cards::Cards;
+use crate::play::events::{EventStream, TableEvent, TableState};
+use crate::play::phases::PhaseHoldem;
+use crate::play::Position6Max;
+use crate::web::api::{GameRequest, HupRequest, HupResponse, PlayerOdds, StreetOdds};
+use crate::web::service::EquityService;
+use crate::{PKError, Pile};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// One hand that can be stepped through, along with what to call it in the title bar.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ReplayHand {
+    pub title: String,
+    pub stream: EventStream,
+}
+
+impl ReplayHand {
+    #[must_use]
+    pub fn new(title: &str, stream: EventStream) -> ReplayHand {
+        ReplayHand {
+            title: title.to_string(),
+            stream,
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns whatever `EventStream::try_from()` doesn't like about the hand.
+    pub fn from_pluribus(title: &str, pluribus: &Pluribus) -> Result<ReplayHand, PKError> {
+        Ok(ReplayHand::new(title, EventStream::try_from(pluribus)?))
+    }
+
+    /// A `Nubibus` is still holding on to the `Pluribus` hand that it was made from, which is
+    /// what the events come from. Replaying the hand doesn't change that, so it doesn't matter
+    /// if it's been played yet or not.
+    ///
+    /// # Errors
+    ///
+    /// Same as `ReplayHand::from_pluribus()`.
+    pub fn from_nubibus(title: &str, nubibus: &Nubibus) -> Result<ReplayHand, PKError> {
+        ReplayHand::from_pluribus(title, &nubibus.pluribus)
+    }
+}
+
+/// # Replayer
+///
+/// Everything the TUI knows about the hands it's showing, without any of the terminal stuff, so
+/// that it can be tested without one. The table comes from folding the `EventStream` up to the
+/// cursor, and the equities come from the `EquityService`, the same as the `pk calc` command and
+/// the web service.
+///
+/// Working out the equities on the flop with six players in the hand takes a moment, so they're
+/// remembered for each set of hands and board. Stepping back and forth through the betting on a
+/// street doesn't need to calculate anything again unless somebody folds.
+///
+/// Before the flop the only equities are heads up ones that are already stored. Running the
+/// 1.7 million or so boards for every preflop step would make for a pretty sluggish replay.
+pub struct Replayer {
+    hands: Vec<ReplayHand>,
+    current: usize,
+    service: EquityService,
+    equity: Option<StreetOdds>,
+    cache: HashMap<(Vec<Two>, Vec<Card>), Option<StreetOdds>>,
+}
+
+impl Replayer {
+    #[must_use]
+    pub fn new(hands: Vec<ReplayHand>, service: EquityService) -> Replayer {
+        let mut replayer = Replayer {
+            hands,
+            current: 0,
+            service,
+            equity: None,
+            cache: HashMap::new(),
+        };
+        replayer.refresh();
+        replayer
+    }
+
+    /// Every hand in a Pluribus ACPC log, titled with the file name and hand number. Hands that
+    /// can't be turned into events are skipped, with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the log can't be read, and `PKError::NotDealt` if there
+    /// aren't any hands in it.
+    pub fn from_pluribus_log(path: &str, service: EquityService) -> Result<Replayer, PKError> {
+        let name = Path::new(path)
+            .file_name()
+            .map_or_else(|| path.to_string(), |name| name.to_string_lossy().to_string());
+        let hands: Vec<ReplayHand> = Pluribus::from_log(path)?
+            .iter()
+            .filter_map(|pluribus| {
+                let title = format!("{name} #{}", pluribus.index);
+                ReplayHand::from_pluribus(&title, pluribus)
+                    .map_err(|e| log::warn!("Skipping {title}: {e}"))
+                    .ok()
+            })
+            .collect();
+        if hands.is_empty() {
+            return Err(PKError::NotDealt);
+        }
+        Ok(Replayer::new(hands, service))
+    }
+
+    // region navigation
+
+    #[must_use]
+    pub fn hands(&self) -> &[ReplayHand] {
+        &self.hands
+    }
+
+    #[must_use]
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    #[must_use]
+    pub fn hand(&self) -> Option<&ReplayHand> {
+        self.hands.get(self.current)
+    }
+
+    /// Jumps to a hand, back at its start. Returns false if there isn't one at that index.
+    pub fn select(&mut self, index: usize) -> bool {
+        if index >= self.hands.len() {
+            return false;
+        }
+        self.current = index;
+        if let Some(hand) = self.hands.get_mut(index) {
+            hand.stream.rewind();
+        }
+        self.refresh();
+        true
+    }
+
+    pub fn next_hand(&mut self) -> bool {
+        self.select(self.current + 1)
+    }
+
+    pub fn previous_hand(&mut self) -> bool {
+        self.current > 0 && self.select(self.current - 1)
+    }
+
+    /// One event forward. Returns false if it's already at the end of the hand.
+    pub fn forward(&mut self) -> bool {
+        self.step(EventStream::forward)
+    }
+
+    /// One event back. Returns false if it's already at the start of the hand.
+    pub fn backward(&mut self) -> bool {
+        self.step(EventStream::backward)
+    }
+
+    pub fn rewind(&mut self) {
+        self.step(|stream| {
+            stream.rewind();
+            true
+        });
+    }
+
+    pub fn fast_forward(&mut self) {
+        self.step(|stream| {
+            stream.fast_forward();
+            true
+        });
+    }
+
+    /// Forward to the start of the next street, which is the point where its board cards have
+    /// all been dealt, or to the end of the hand if there aren't any more.
+    pub fn next_street(&mut self) -> bool {
+        let cursor = self.cursor();
+        let target = self
+            .street_starts()
+            .into_iter()
+            .find(|start| *start > cursor)
+            .unwrap_or_else(|| self.len());
+        self.move_to(target)
+    }
+
+    /// Back to the start of the current street, or of the one before it if it's already there.
+    pub fn previous_street(&mut self) -> bool {
+        let cursor = self.cursor();
+        let target = self
+            .street_starts()
+            .into_iter()
+            .rev()
+            .find(|start| *start < cursor)
+            .unwrap_or_default();
+        self.move_to(target)
+    }
+
+    #[must_use]
+    pub fn cursor(&self) -> usize {
+        self.hand().map_or(0, |hand| hand.stream.cursor())
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.hand().map_or(0, |hand| hand.stream.len())
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn move_to(&mut self, target: usize) -> bool {
+        let cursor = self.cursor();
+        if target == cursor {
+            return false;
+        }
+        self.step(|stream| {
+            while stream.cursor() < target && stream.forward() {}
+            while stream.cursor() > target && stream.backward() {}
+            true
+        })
+    }
+
+    fn step<F: FnOnce(&mut EventStream) -> bool>(&mut self, f: F) -> bool {
+        let Some(hand) = self.hands.get_mut(self.current) else {
+            return false;
+        };
+        let moved = f(&mut hand.stream);
+        if moved {
+            self.refresh();
+        }
+        moved
+    }
+
+    /// The cursors where the phase of the hand changes.
+    fn street_starts(&self) -> Vec<usize> {
+        let Some(hand) = self.hand() else {
+            return Vec::new();
+        };
+        let mut state = TableState::new(hand.stream.seats.clone());
+        let mut starts = Vec::new();
+        for (i, event) in hand.stream.events.iter().enumerate() {
+            let phase = state.phase;
+            if state.apply(event).is_err() {
+                break;
+            }
+            if i > 0 && state.phase != phase && state.phase != PhaseHoldem::Preflop {
+                starts.push(i + 1);
+            }
+        }
+        starts
+    }
+
+    // endregion
+
+    /// The table at the cursor.
+    #[must_use]
+    pub fn state(&self) -> TableState {
+        self.hand()
+            .and_then(|hand| hand.stream.state().ok())
+            .unwrap_or_default()
+    }
+
+    /// The position of each seat. Pluribus always lists the small blind first.
+    #[must_use]
+    pub fn position(seat: usize) -> Option<Position6Max> {
+        Position6Max::try_from(u8::try_from(seat + 1).ok()?).ok()
+    }
+
+    /// The seat that made the last move, if the last event was somebody's.
+    #[must_use]
+    pub fn last_seat(&self) -> Option<usize> {
+        match self.hand()?.stream.current()? {
+            TableEvent::Deal { seat, .. }
+            | TableEvent::Post { seat, .. }
+            | TableEvent::Bet { seat, .. }
+            | TableEvent::Fold { seat }
+            | TableEvent::Award { seat, .. } => Some(*seat),
+            TableEvent::BoardCard { .. } | TableEvent::RunOut { .. } => None,
+        }
+    }
+
+    /// What's happened so far, with the players' names, and calls and raises told apart from
+    /// each other. A `TableEvent::Bet` is just the chips that went in.
+    #[must_use]
+    pub fn log(&self) -> Vec<String> {
+        let Some(hand) = self.hand() else {
+            return Vec::new();
+        };
+        let mut state = TableState::new(hand.stream.seats.clone());
+        let mut log = Vec::new();
+        for event in hand.stream.events.iter().take(hand.stream.cursor()) {
+            log.push(Replayer::describe(&state, event));
+            if state.apply(event).is_err() {
+                break;
+            }
+        }
+        log
+    }
+
+    fn describe(before: &TableState, event: &TableEvent) -> String {
+        let name = |seat: &usize| {
+            before
+                .seats
+                .get(*seat)
+                .map_or_else(|| format!("Seat {seat}"), |seat| seat.name.clone())
+        };
+        match event {
+            TableEvent::Deal { seat, cards } => format!("{} is dealt {cards}", name(seat)),
+            TableEvent::Post { seat, amount } => format!("{} posts {amount}", name(seat)),
+            TableEvent::Bet { seat, amount: 0 } => format!("{} checks", name(seat)),
+            TableEvent::Bet { seat, amount } => {
+                let Some(state) = before.seats.get(*seat) else {
+                    return event.to_string();
+                };
+                let total = state.in_round + amount;
+                let all_in = if *amount == state.stack { " all in" } else { "" };
+                if total <= before.to_match() {
+                    format!("{} calls {amount}{all_in}", name(seat))
+                } else if before.to_match() == 0 {
+                    format!("{} bets {amount}{all_in}", name(seat))
+                } else {
+                    format!("{} raises to {total}{all_in}", name(seat))
+                }
+            }
+            TableEvent::Fold { seat } => format!("{} folds", name(seat)),
+            TableEvent::BoardCard { card } => format!("Dealt {card}"),
+            TableEvent::RunOut { board } => format!("Run out {}", board.cards()),
+            TableEvent::Award { seat, amount } => format!("{} wins {amount}", name(seat)),
+        }
+    }
+
+    // region equity
+
+    /// The equities of the players still in the hand, at the cursor.
+    #[must_use]
+    pub fn equity(&self) -> Option<&StreetOdds> {
+        self.equity.as_ref()
+    }
+
+    fn refresh(&mut self) {
+        let state = self.state();
+        let seats: Vec<(String, Two)> = state
+            .seats
+            .iter()
+            .filter(|seat| seat.is_active() && seat.cards.is_dealt())
+            .map(|seat| (seat.name.clone(), seat.cards))
+            .collect();
+        let hands: Vec<Two> = seats.iter().map(|(_, two)| *two).collect();
+        let key = (hands, state.board.iter().copied().collect::<Vec<Card>>());
+
+        let equity = if let Some(equity) = self.cache.get(&key) {
+            equity.clone()
+        } else {
+            let equity = self.calculate(&key.0, &state.board);
+            self.cache.insert(key, equity.clone());
+            equity
+        };
+        self.equity = equity.map(|mut equity| {
+            for (player, (name, _)) in equity.players.iter_mut().zip(seats.iter()) {
+                player.name = Some(name.clone());
+            }
+            equity
+        });
+    }
+
+    fn calculate(&self, hands: &[Two], board: &Cards) -> Option<StreetOdds> {
+        if hands.len() < 2 {
+            return None;
+        }
+        match board.len() {
+            0 => self.preflop(hands),
+            3..=5 => {
+                let request = GameRequest {
+                    hands: hands.to_vec(),
+                    board: Some(board.clone()),
+                    ..Default::default()
+                };
+                self.service.odds(&request).ok()?.streets.pop()
+            }
+            _ => None,
+        }
+    }
+
+    fn preflop(&self, hands: &[Two]) -> Option<StreetOdds> {
+        let [hero, villain] = hands else {
+            return None;
+        };
+        let request = HupRequest {
+            hero: *hero,
+            villain: *villain,
+            calculate: false,
+        };
+        let response = self.service.hup(&request).ok()??;
+        let player = |hand: Two, wins: u64, win_percentage: f32| PlayerOdds {
+            hand,
+            name: None,
+            wins: usize::try_from(wins).unwrap_or_default(),
+            ties: usize::try_from(response.odds.ties).unwrap_or_default(),
+            win_percentage,
+            tie_percentage: response.tie_percentage,
+            outs: None,
+        };
+        let HupResponse {
+            odds,
+            win_percentage,
+            loss_percentage,
+            ..
+        } = &response;
+        Some(StreetOdds {
+            street: PhaseHoldem::Preflop,
+            board: Cards::default(),
+            boards: usize::try_from(odds.wins + odds.losses + odds.ties).unwrap_or_default(),
+            players: vec![
+                player(*hero, odds.wins, *win_percentage),
+                player(*villain, odds.losses, *loss_percentage),
+            ],
+        })
+    }
+
+    // endregion
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tui__replayer_tests {
+    use super::*;
+    use std::str::FromStr;
+
+    /// Budd calls Bill down with top pair, and it holds up against Bill's flush draw.
+    const HAND: &str = "STATE:7:fr225fffc/cr475c/cr1225c/cc:5hJc|Jd9h|6s5c|Ah7h|2s2d|3hTs/3sJh2h/Tc/Ks:-50|1275|0|-1225|0|0:Gogo|Budd|Eddie|Bill|Pluribus|MrWhite";
+
+    fn replayer() -> Replayer {
+        let hand = ReplayHand::from_pluribus("test #7", &Pluribus::from_str(HAND).unwrap()).unwrap();
+        Replayer::new(vec![hand], EquityService::in_memory().unwrap())
+    }
+
+    #[test]
+    fn forward__backward() {
+        let mut replayer = replayer();
+
+        assert_eq!(0, replayer.cursor());
+        assert!(!replayer.backward());
+        assert!(replayer.forward());
+        assert_eq!(vec!["Gogo is dealt J♣ 5♥"], replayer.log());
+        assert_eq!(Some(0), replayer.last_seat());
+
+        replayer.fast_forward();
+
+        assert!(!replayer.forward());
+        assert_eq!(PhaseHoldem::Over, replayer.state().phase);
+        assert_eq!(Some("Budd wins 2500".to_string()), replayer.log().last().cloned());
+    }
+
+    #[test]
+    fn next_street__previous_street() {
+        let mut replayer = replayer();
+
+        assert!(replayer.next_street());
+        assert_eq!(PhaseHoldem::Flop, replayer.state().phase);
+        assert_eq!("3♠ J♥ 2♥", replayer.state().board.to_string());
+        assert!(replayer.next_street());
+        assert_eq!(PhaseHoldem::Turn, replayer.state().phase);
+
+        replayer.forward();
+        assert!(replayer.previous_street());
+        assert_eq!(PhaseHoldem::Turn, replayer.state().phase);
+        assert!(replayer.previous_street());
+        assert_eq!(PhaseHoldem::Flop, replayer.state().phase);
+        assert!(replayer.previous_street());
+        assert_eq!(0, replayer.cursor());
+        assert!(!replayer.previous_street());
+    }
+
+    #[test]
+    fn log() {
+        let mut replayer = replayer();
+        replayer.next_street();
+
+        assert_eq!(
+            vec![
+                "Gogo posts 50",
+                "Budd posts 100",
+                "Eddie folds",
+                "Bill raises to 225",
+                "Pluribus folds",
+                "MrWhite folds",
+                "Gogo folds",
+                "Budd calls 125",
+                "Dealt 3♠",
+                "Dealt J♥",
+                "Dealt 2♥",
+            ],
+            replayer.log()[6..]
+        );
+    }
+
+    /// Heads up on the turn, with no stored preflop results to go on.
+    #[test]
+    fn equity() {
+        let mut replayer = replayer();
+
+        replayer.forward();
+        assert!(replayer.equity().is_none());
+
+        replayer.next_street();
+        replayer.next_street();
+        let equity = replayer.equity().unwrap();
+
+        assert_eq!(PhaseHoldem::Turn, equity.street);
+        assert_eq!(2, equity.players.len());
+        assert_eq!(Some("Budd".to_string()), equity.players[0].name);
+        assert_eq!("J♦ 9♥", equity.players[0].hand.to_string());
+        assert!(equity.players[0].win_percentage > 50.0);
+        assert!(equity.players[1].outs.is_some());
+    }
+
+    #[test]
+    fn select() {
+        let mut replayer = replayer();
+        replayer.fast_forward();
+
+        assert!(!replayer.next_hand());
+        assert!(!replayer.previous_hand());
+        assert!(replayer.select(0));
+        assert_eq!(0, replayer.cursor());
+        assert!(!replayer.select(1));
+    }
+
+    #[test]
+    fn position() {
+        assert_eq!(Some(Position6Max::SB), Replayer::position(0));
+        assert_eq!(Some(Position6Max::BTN), Replayer::position(5));
+        assert_eq!(None, Replayer::position(6));
+    }
+}
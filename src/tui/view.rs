@@ -0,0 +1,283 @@
+use crate::play::events::TableState;
+use crate::play::phases::PhaseHoldem;
+use crate::tui::replayer::Replayer;
+use crate::web::api::StreetOdds;
+use crate::Pile;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, List, ListItem, Paragraph, Row, Table, Wrap};
+use ratatui::Frame;
+use thousands::Separable;
+
+/// The keys, for the bottom line and for anybody reading the code to find them.
+pub const HELP: &str = "←/→ step  ↑/↓ street  Home/End start/end  n/p next/previous hand  q quit";
+
+/// How wide the equity bars are, in characters.
+const BAR_WIDTH: usize = 16;
+
+/// Draws the whole screen:
+///
+/// ```txt
+/// ┌ title ─────────────────────────────────────────────────────┐
+/// ├ seats ───────────────────────────┬ equity ─────────────────┤
+/// │                                  │                         │
+/// ├ board ───────────────────────────┼ outs ───────────────────┤
+/// ├ log ─────────────────────────────┴─────────────────────────┤
+/// └ help ──────────────────────────────────────────────────────┘
+/// ```
+pub fn draw(frame: &mut Frame, replayer: &Replayer) {
+    let state = replayer.state();
+    let [title, main, log, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(12),
+        Constraint::Length(10),
+        Constraint::Length(1),
+    ])
+    .areas(frame.size());
+    let [table, side] = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(main);
+    let [seats, board] = Layout::vertical([Constraint::Min(8), Constraint::Length(4)]).areas(table);
+    let [equity, outs] = Layout::vertical([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(side);
+
+    frame.render_widget(title_bar(replayer, &state), title);
+    frame.render_widget(seats_table(replayer, &state), seats);
+    frame.render_widget(board_panel(&state), board);
+    frame.render_widget(equity_panel(replayer.equity(), &state), equity);
+    frame.render_widget(outs_panel(replayer.equity()), outs);
+    frame.render_widget(log_panel(replayer, log), log);
+    frame.render_widget(Paragraph::new(HELP).style(Style::default().fg(Color::DarkGray)), help);
+}
+
+fn title_bar<'a>(replayer: &Replayer, state: &TableState) -> Paragraph<'a> {
+    let title = replayer.hand().map(|hand| hand.title.clone()).unwrap_or_default();
+    Paragraph::new(Line::from(vec![
+        Span::styled(title, Style::default().add_modifier(Modifier::BOLD)),
+        Span::raw(format!(
+            "   {}   event {}/{}   hand {}/{}",
+            state.phase,
+            replayer.cursor(),
+            replayer.len(),
+            replayer.current() + 1,
+            replayer.hands().len()
+        )),
+    ]))
+    .block(Block::bordered().title("pk replay"))
+}
+
+/// Folded players are greyed out, whoever just acted is yellow, and the winners are green.
+fn seats_table<'a>(replayer: &Replayer, state: &TableState) -> Table<'a> {
+    let last = replayer.last_seat();
+    let rows: Vec<Row> = state
+        .seats
+        .iter()
+        .enumerate()
+        .map(|(i, seat)| {
+            let style = if seat.won > 0 {
+                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+            } else if seat.folded {
+                Style::default().fg(Color::DarkGray)
+            } else if last == Some(i) {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            };
+            let status = if seat.won > 0 {
+                format!("wins {}", seat.won.separate_with_commas())
+            } else if seat.folded {
+                "folded".to_string()
+            } else if seat.is_all_in() {
+                "all in".to_string()
+            } else {
+                String::new()
+            };
+            let cards = if seat.cards.is_dealt() {
+                seat.cards.to_string()
+            } else {
+                String::new()
+            };
+            Row::new(vec![
+                Cell::from(Replayer::position(i).map(|p| p.to_string()).unwrap_or_default()),
+                Cell::from(seat.name.clone()),
+                Cell::from(seat.stack.separate_with_commas()),
+                Cell::from(seat.in_round.separate_with_commas()),
+                Cell::from(seat.in_pot.separate_with_commas()),
+                Cell::from(cards),
+                Cell::from(status),
+            ])
+            .style(style)
+        })
+        .collect();
+
+    Table::new(
+        rows,
+        [
+            Constraint::Length(4),
+            Constraint::Min(10),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Length(6),
+            Constraint::Min(6),
+        ],
+    )
+    .header(
+        Row::new(vec!["", "Player", "Stack", "Bet", "In pot", "Cards", ""])
+            .style(Style::default().add_modifier(Modifier::UNDERLINED)),
+    )
+    .block(Block::bordered().title("Table"))
+}
+
+fn board_panel<'a>(state: &TableState) -> Paragraph<'a> {
+    let board = if state.board.is_empty() {
+        "-".to_string()
+    } else {
+        state.board.to_string()
+    };
+    Paragraph::new(vec![
+        Line::from(vec![
+            Span::raw("Board: "),
+            Span::styled(board, Style::default().add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(format!("Pot:   {}", state.pot.separate_with_commas())),
+    ])
+    .block(Block::bordered())
+}
+
+fn equity_panel<'a>(equity: Option<&StreetOdds>, state: &TableState) -> Paragraph<'a> {
+    let lines = match equity {
+        Some(equity) => equity
+            .players
+            .iter()
+            .flat_map(|player| {
+                let share = player.win_percentage + player.tie_percentage;
+                vec![
+                    Line::from(format!("{} [{}]", player.name.clone().unwrap_or_default(), player.hand)),
+                    Line::from(vec![
+                        Span::styled(bar(share), Style::default().fg(Color::Cyan)),
+                        Span::raw(format!(
+                            " {share:.1}% ({:.2}%/{:.2}%)",
+                            player.win_percentage, player.tie_percentage
+                        )),
+                    ]),
+                ]
+            })
+            .collect(),
+        None => vec![Line::from(no_equity(state))],
+    };
+    let title = equity.map_or_else(|| "Equity".to_string(), |equity| format!("Equity {}", equity.street));
+    Paragraph::new(lines).block(Block::bordered().title(title))
+}
+
+fn no_equity(state: &TableState) -> &'static str {
+    if state.in_hand_count() < 2 {
+        "Nobody left to play against."
+    } else if state.phase == PhaseHoldem::Preflop || state.phase == PhaseHoldem::Init {
+        "Only stored heads up odds before the flop."
+    } else {
+        ""
+    }
+}
+
+#[allow(
+    clippy::cast_possible_truncation,
+    clippy::cast_precision_loss,
+    clippy::cast_sign_loss
+)]
+fn bar(percentage: f32) -> String {
+    let filled = ((percentage.clamp(0.0, 100.0) * BAR_WIDTH as f32 / 100.0).round() as usize).min(BAR_WIDTH);
+    format!("{}{}", "█".repeat(filled), "░".repeat(BAR_WIDTH - filled))
+}
+
+fn outs_panel<'a>(equity: Option<&StreetOdds>) -> Paragraph<'a> {
+    let lines: Vec<Line> = match equity.filter(|equity| equity.street == PhaseHoldem::Turn) {
+        Some(equity) => equity
+            .players
+            .iter()
+            .filter_map(|player| {
+                let outs = player.outs.as_ref().filter(|outs| !outs.is_empty())?;
+                Some(Line::from(format!(
+                    "{} ({}): {outs}",
+                    player.name.clone().unwrap_or_default(),
+                    outs.len()
+                )))
+            })
+            .collect(),
+        None => vec![Line::from("Outs show up on the turn.")],
+    };
+    Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .block(Block::bordered().title("Outs"))
+}
+
+/// The most recent events that fit, with the latest one highlighted.
+fn log_panel<'a>(replayer: &Replayer, area: Rect) -> List<'a> {
+    let log = replayer.log();
+    let height = usize::from(area.height.saturating_sub(2));
+    let skip = log.len().saturating_sub(height);
+    let last = log.len().saturating_sub(1);
+    let items: Vec<ListItem> = log
+        .into_iter()
+        .enumerate()
+        .skip(skip)
+        .map(|(i, line)| {
+            let style = if i == last {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            ListItem::new(line).style(style)
+        })
+        .collect();
+    List::new(items).block(Block::bordered().title("Action"))
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod tui__view_tests {
+    use super::*;
+    use crate::analysis::store::nubibus::pluribus::Pluribus;
+    use crate::tui::replayer::ReplayHand;
+    use crate::web::service::EquityService;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+    use std::str::FromStr;
+
+    fn screen(replayer: &Replayer) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        terminal.draw(|frame| super::draw(frame, replayer)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content
+            .chunks(usize::from(buffer.area.width))
+            .map(|row| row.iter().map(ratatui::buffer::Cell::symbol).collect::<String>())
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn draw() {
+        let pluribus = Pluribus::from_str("STATE:7:fr225fffc/cr475c/cr1225c/cc:5hJc|Jd9h|6s5c|Ah7h|2s2d|3hTs/3sJh2h/Tc/Ks:-50|1275|0|-1225|0|0:Gogo|Budd|Eddie|Bill|Pluribus|MrWhite").unwrap();
+        let hand = ReplayHand::from_pluribus("sample_game_7.log #7", &pluribus).unwrap();
+        let mut replayer = Replayer::new(vec![hand], EquityService::in_memory().unwrap());
+        replayer.next_street();
+        replayer.next_street();
+
+        let screen = screen(&replayer);
+
+        assert!(screen.contains("sample_game_7.log #7   Turn   event 21/28   hand 1/1"));
+        assert!(screen.contains("Board: 3♠ J♥ 2♥ T♣"));
+        assert!(screen.contains("Budd [J♦ 9♥]"));
+        assert!(screen.contains("████████████░░░░ 75.0% (75.00%/0.00%)"));
+        assert!(screen.contains("Equity Turn"));
+        assert!(screen.contains("Bill ("));
+        assert!(screen.contains("Dealt T♣"));
+        assert!(screen.contains(HELP));
+    }
+
+    #[test]
+    fn bar() {
+        assert_eq!("░".repeat(16), super::bar(0.0));
+        assert_eq!(format!("{}{}", "█".repeat(12), "░".repeat(4)), super::bar(75.0));
+        assert_eq!("█".repeat(16), super::bar(100.0));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::analysis::eval::Eval;
 use crate::analysis::store::bcm::binary_card_map::SevenFiveBCM;
 use crate::analysis::store::db::headsup_preflop_result::HUPResult;
+use crate::analysis::store::db::multiway_preflop_result::{MultiwayResult, Share, SortedMultiway};
+use crate::analysis::store::db::sqlite::Sqlable;
 use crate::arrays::five::Five;
 use crate::arrays::matchups::sorted_heads_up::SortedHeadsUp;
 use crate::arrays::seven::Seven;
@@ -13,6 +15,7 @@ use crate::play::hole_cards::HoleCards;
 use crate::util::wincounter::win::Win;
 use crate::util::wincounter::wins::Wins;
 use crate::{Card, Pile};
+use rusqlite::Connection;
 use std::str::FromStr;
 
 /// I am a classicist when it comes to testing. Martin Fowler, in his essay
@@ -45,10 +48,7 @@ impl TestData {
     pub fn the_hand() -> Game {
         let board = Board::from_str("9♣ 6♦ 5♥ 5♠ 8♠").unwrap();
 
-        Game {
-            hands: TestData::hole_cards_the_hand(),
-            board,
-        }
+        Game::new(TestData::hole_cards_the_hand(), board)
     }
 
     /// Based on HSP S04E08 Harman/Safai but with the river bringing quads
@@ -58,7 +58,7 @@ impl TestData {
     pub fn the_board() -> Game {
         let hands = HoleCards::from(vec![Two::HAND_AC_QS, Two::HAND_TD_TC, Two::HAND_6D_4D, Two::HAND_2H_2D]);
         let board = Board::from_str("J♦ J♠ J♥ A♥ J♣").unwrap();
-        Game { hands, board }
+        Game::new(hands, board)
     }
 
     /// The 985th case at the flop when running `The Hand`:
@@ -165,6 +165,47 @@ impl TestData {
         }
     }
 
+    /// Made up, but it adds up. `A♦ A♣`, `K♦ K♣` and `Q♦ Q♣` over ten boards, with one of them
+    /// a three way split.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn aces_kings_queens() -> MultiwayResult {
+        MultiwayResult {
+            hands: SortedMultiway::canonical(&[Two::HAND_AS_AH, Two::HAND_KS_KH, Two::HAND_QS_QH])
+                .unwrap()
+                .0,
+            boards: 10,
+            shares: vec![
+                Share {
+                    wins: 6,
+                    ties: 1,
+                    split: 1.0 / 3.0,
+                },
+                Share {
+                    wins: 2,
+                    ties: 1,
+                    split: 1.0 / 3.0,
+                },
+                Share {
+                    wins: 1,
+                    ties: 1,
+                    split: 1.0 / 3.0,
+                },
+            ],
+        }
+    }
+
+    /// An in memory database with the table for `T` and the records in it, so that each of the
+    /// storage tests doesn't need its own.
+    #[must_use]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn connection<T: Sqlable<T, S>, S>(records: &[T]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        T::create_table(&conn).unwrap();
+        T::insert_many(&conn, records.iter().collect()).unwrap();
+        conn
+    }
+
     #[must_use]
     pub fn the_hand_sorted_headsup() -> SortedHeadsUp {
         SortedHeadsUp::new(Two::HAND_6S_6H, Two::HAND_5D_5C)
@@ -0,0 +1,479 @@
+use crate::analysis::store::db::hup_lookup::HupOdds;
+use crate::arrays::two::Two;
+use crate::cards::Cards;
+use crate::play::game::Game;
+use crate::play::notation::{GameType, NotatedPlayer, Notation};
+use crate::play::phases::PhaseHoldem;
+use crate::util::wincounter::results::Results;
+use crate::{PKError, Pile};
+use serde::de::{Deserializer, Error as _};
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The JSON that goes back and forth with the `EquityService`.
+///
+/// Cards go over the wire as the same strings that `Two` and `Cards` serialize to, `"A♠ K♠"`, and
+/// either the symbols or the letters work coming in. The one gotcha is that the card
+/// deserializers don't fail on garbage, they hand back blank cards, so everything that comes in
+/// is checked before it gets anywhere near a calculation.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EvalRequest {
+    #[serde(deserialize_with = "deserialize_unique_cards")]
+    pub cards: Cards,
+}
+
+impl EvalRequest {
+    /// # Errors
+    ///
+    /// Returns `PKError::NotEnoughCards` or `PKError::TooManyCards` if there aren't five to seven
+    /// cards.
+    pub fn validate(&self) -> Result<(), PKError> {
+        match self.cards.len() {
+            0..=4 => Err(PKError::NotEnoughCards),
+            5..=7 => Ok(()),
+            _ => Err(PKError::TooManyCards),
+        }
+    }
+}
+
+/// The same as the `Cards` deserializer, except that it doesn't let anything slide. A card that
+/// won't parse is `PKError::InvalidCard` rather than no cards at all, and the same card twice is
+/// `PKError::Duplicate` instead of being quietly dropped, since `A♠ A♠ K♠ Q♠ J♠` isn't a hand
+/// anybody can have.
+fn deserialize_unique_cards<'de, D>(deserializer: D) -> Result<Cards, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    unique_cards(&String::deserialize(deserializer)?).map_err(D::Error::custom)
+}
+
+/// `deserialize_unique_cards()` for a board, where a blank string or `null` is no board at all.
+fn deserialize_unique_board<'de, D>(deserializer: D) -> Result<Option<Cards>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match Option::<String>::deserialize(deserializer)? {
+        Some(buf) if !buf.trim().is_empty() => unique_cards(&buf).map(Some).map_err(D::Error::custom),
+        _ => Ok(None),
+    }
+}
+
+fn unique_cards(buf: &str) -> Result<Cards, PKError> {
+    let cards = Cards::from_str(buf).map_err(|_| PKError::InvalidCard)?;
+    if cards.len() == buf.split_whitespace().count() {
+        Ok(cards)
+    } else {
+        Err(PKError::Duplicate)
+    }
+}
+
+/// The best five card hand out of the cards that were sent.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EvalResponse {
+    pub cards: Cards,
+    pub best: Cards,
+    pub rank: u16,
+    pub name: String,
+    pub class: String,
+}
+
+/// A `Game` either as notation, `"HE: A♠ K♠ 8♦ 7♦ - 9♠ 8♠ 2♣"`, or as hands and an optional
+/// board. The preflop odds are only worked out if they're asked for, since if nobody's stored
+/// them they're a run through every one of the 1.7 million or so boards.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GameRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hands: Vec<Two>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_unique_board",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub board: Option<Cards>,
+    #[serde(default)]
+    pub preflop: bool,
+}
+
+impl GameRequest {
+    /// Turns the request into a `Game`, with the same checks as the notation.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidNotation` if the notation or the cards don't make sense,
+    /// `PKError::InvalidCard` if one of the hands didn't parse, and `PKError::NotEnoughHands` if
+    /// there are fewer than two hands.
+    pub fn game(&self) -> Result<Game, PKError> {
+        let game = if let Some(notation) = &self.game {
+            Game::from_str(notation)?
+        } else {
+            if self.hands.iter().any(|two| !two.is_dealt()) {
+                return Err(PKError::InvalidCard);
+            }
+            let board = self.board.clone().unwrap_or_default();
+            if board.iter().any(|card| !card.is_dealt()) {
+                return Err(PKError::InvalidCard);
+            }
+            Game::try_from(Notation {
+                game_type: GameType::Holdem,
+                players: self
+                    .hands
+                    .iter()
+                    .map(|two| NotatedPlayer::new(None, Cards::from(two.to_vec())))
+                    .collect(),
+                board,
+                dead: Cards::default(),
+            })?
+        };
+        if game.hands.len() < 2 {
+            return Err(PKError::NotEnoughHands);
+        }
+        Ok(game)
+    }
+}
+
+/// How one player does at one street.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct PlayerOdds {
+    pub hand: Two,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub wins: usize,
+    pub ties: usize,
+    pub win_percentage: f32,
+    pub tie_percentage: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outs: Option<Cards>,
+}
+
+/// Everybody's odds at one street. `boards` is how many ways the rest of the board could come.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct StreetOdds {
+    pub street: PhaseHoldem,
+    pub board: Cards,
+    pub boards: usize,
+    pub players: Vec<PlayerOdds>,
+}
+
+impl StreetOdds {
+    #[must_use]
+    pub fn from_results(street: PhaseHoldem, board: Cards, game: &Game, results: &Results) -> StreetOdds {
+        StreetOdds {
+            street,
+            board,
+            boards: results.case_count,
+            players: game
+                .hands
+                .iter()
+                .enumerate()
+                .map(|(i, hand)| {
+                    let (wins, ties) = results.wins_and_ties(i);
+                    let (win_percentage, tie_percentage) = results.wins_and_ties_percentages(i);
+                    PlayerOdds {
+                        hand: *hand,
+                        name: game.name(i).map(str::to_string),
+                        wins,
+                        ties,
+                        win_percentage,
+                        tie_percentage,
+                        outs: None,
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct OddsResponse {
+    pub game: String,
+    pub streets: Vec<StreetOdds>,
+}
+
+/// The cards that put each player ahead at the river, for a `Game` that's at the turn.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PlayerOuts {
+    pub hand: Two,
+    pub outs: Cards,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OutsResponse {
+    pub game: String,
+    pub players: Vec<PlayerOuts>,
+}
+
+/// A heads up preflop matchup. If it isn't stored and `calculate` is set, it's worked out, saved
+/// and cached, which takes a while. Otherwise a miss is a `404`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HupRequest {
+    pub hero: Two,
+    pub villain: Two,
+    #[serde(default)]
+    pub calculate: bool,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct HupResponse {
+    #[serde(flatten)]
+    pub odds: HupOdds,
+    pub win_percentage: f32,
+    pub loss_percentage: f32,
+    pub tie_percentage: f32,
+    pub calculated: bool,
+}
+
+impl HupResponse {
+    #[must_use]
+    pub fn new(odds: HupOdds, calculated: bool) -> HupResponse {
+        HupResponse {
+            odds,
+            win_percentage: odds.win_percentage(),
+            loss_percentage: odds.loss_percentage(),
+            tie_percentage: odds.tie_percentage(),
+            calculated,
+        }
+    }
+}
+
+/// A hand against a range, like `"QQ+,AK"`, with an optional flop, turn or river. Before the
+/// flop, a range with matchups that aren't stored is turned away unless `partial` is set, and
+/// then the numbers are only over the ones that are.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RangeRequest {
+    pub hand: Two,
+    pub range: String,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_unique_board",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub board: Option<Cards>,
+    #[serde(default)]
+    pub partial: bool,
+}
+
+/// The hand's equity against the range, with every combination counting the same.
+///
+/// * `combos` is how many of the range's combinations went into the numbers.
+/// * `blocked` is how many couldn't be there, since they share a card with the hand or board.
+/// * `missing` is how many preflop matchups weren't stored. Those are skipped rather than
+///   calculated, since a wide range could mean hundreds of runs through the whole deck.
+/// * `partial` is set if anything's `missing`, since then the equity isn't against the whole
+///   range.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RangeResponse {
+    pub hand: Two,
+    pub range: String,
+    pub board: Cards,
+    pub combos: usize,
+    pub blocked: usize,
+    pub missing: usize,
+    pub partial: bool,
+    pub win_percentage: f32,
+    pub tie_percentage: f32,
+    pub equity: f32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HealthResponse {
+    pub status: String,
+    pub ready: bool,
+    pub uptime_secs: u64,
+    pub hups: usize,
+    pub multiway: usize,
+}
+
+// region Display
+//
+// The text versions are for the `pk` command line. They follow the `calc` example's layout as
+// closely as I could manage without the hand by hand breakdowns.
+
+impl Display for EvalResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}-{})", self.best, self.rank, self.class)
+    }
+}
+
+/// The same as `Results::player_to_string()`, with the hand in front.
+impl Display for PlayerOdds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if let Some(name) = &self.name {
+            write!(f, "{name} ")?;
+        }
+        write!(
+            f,
+            "[{}] {:.1}% ({:.2}%/{:.2}%) [{}/{}]",
+            self.hand,
+            self.win_percentage + self.tie_percentage,
+            self.win_percentage,
+            self.tie_percentage,
+            self.wins,
+            self.ties
+        )?;
+        match &self.outs {
+            Some(outs) if !outs.is_empty() => write!(f, " OUTS: {outs}"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Display for StreetOdds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.board.is_empty() {
+            write!(f, "{}:", self.street)?;
+        } else {
+            write!(f, "{}: {}", self.street, self.board)?;
+        }
+        for player in &self.players {
+            write!(f, "\n  {player}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for OddsResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.game)?;
+        for street in &self.streets {
+            write!(f, "\n\n{street}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for OutsResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.game)?;
+        for player in &self.players {
+            write!(f, "\n  [{}] {} OUTS: {}", player.hand, player.count, player.outs)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for HupResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.odds)?;
+        if self.calculated {
+            write!(f, " (calculated)")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for RangeResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} vs {}", self.hand, self.range)?;
+        if !self.board.is_empty() {
+            write!(f, " on {}", self.board)?;
+        }
+        write!(
+            f,
+            ": {:.2}% equity ({:.2}% wins, {:.2}% ties) over {} combos, {} blocked, {} missing",
+            self.equity, self.win_percentage, self.tie_percentage, self.combos, self.blocked, self.missing
+        )?;
+        if self.partial {
+            write!(f, " (PARTIAL: only the stored matchups)")?;
+        }
+        Ok(())
+    }
+}
+
+// endregion
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod web__api_tests {
+    use super::*;
+
+    #[test]
+    fn game_request__game() {
+        let from_notation: GameRequest = serde_json::from_str(r#"{"game": "HE: As Ks 8d 7d - 9s 8s 2c"}"#).unwrap();
+        let from_hands: GameRequest =
+            serde_json::from_str(r#"{"hands": ["A♠ K♠", "8♦ 7♦"], "board": "9♠ 8♠ 2♣"}"#).unwrap();
+
+        assert_eq!(from_notation.game().unwrap(), from_hands.game().unwrap());
+        assert!(!from_hands.preflop);
+    }
+
+    #[test]
+    fn game_request__game__invalid() {
+        let request = |json: &str| serde_json::from_str::<GameRequest>(json).unwrap().game();
+
+        assert_eq!(Err(PKError::InvalidCard), request(r#"{"hands": ["A♠ K♠", "nope"]}"#));
+        assert_eq!(Err(PKError::NotEnoughHands), request(r#"{"hands": ["A♠ K♠"]}"#));
+        assert!(request(r#"{"hands": ["A♠ K♠", "A♠ 7♦"]}"#).is_err());
+        assert!(request(r#"{"hands": ["A♠ K♠", "8♦ 7♦"], "board": "9♠ 8♠"}"#).is_err());
+    }
+
+    #[test]
+    fn eval_request__deserialize__duplicate() {
+        let request = |json: &str| serde_json::from_str::<EvalRequest>(json);
+
+        assert_eq!(5, request(r#"{"cards": "A♠ K♠ Q♠ J♠ T♠"}"#).unwrap().cards.len());
+        assert!(request(r#"{"cards": "A♠ K♠ Q♠ J♠ A♠ T♠"}"#).is_err());
+        assert!(request(r#"{"cards": "A♠ K♠ Q♠ J♠ AS T♠"}"#).is_err());
+    }
+
+    #[test]
+    fn eval_request__deserialize__invalid_card() {
+        let error = serde_json::from_str::<EvalRequest>(r#"{"cards": "A♠ Kx Q♠ J♠ T♠"}"#).unwrap_err();
+
+        assert!(error.to_string().starts_with(&PKError::InvalidCard.to_string()));
+    }
+
+    #[test]
+    fn game_request__deserialize__board() {
+        let request = |json: &str| serde_json::from_str::<GameRequest>(json);
+
+        let malformed = request(r#"{"hands": ["A♠ K♠", "8♦ 7♦"], "board": "9x 8♠ 2♣"}"#).unwrap_err();
+        let duplicate = request(r#"{"hands": ["A♠ K♠", "8♦ 7♦"], "board": "9♠ 9♠ 2♣ 3♦"}"#).unwrap_err();
+
+        assert!(malformed.to_string().starts_with(&PKError::InvalidCard.to_string()));
+        assert!(duplicate.to_string().starts_with(&PKError::Duplicate.to_string()));
+        assert_eq!(
+            None,
+            request(r#"{"hands": ["A♠ K♠", "8♦ 7♦"], "board": ""}"#).unwrap().board
+        );
+        assert_eq!(None, request(r#"{"hands": ["A♠ K♠", "8♦ 7♦"]}"#).unwrap().board);
+    }
+
+    #[test]
+    fn eval_request__validate() {
+        let request = |cards: &str| EvalRequest {
+            cards: Cards::from_str(cards).unwrap(),
+        };
+
+        assert!(request("A♠ K♠ Q♠ J♠ T♠").validate().is_ok());
+        assert!(request("A♠ K♠ Q♠ J♠ T♠ 9♠ 8♠").validate().is_ok());
+        assert_eq!(Err(PKError::NotEnoughCards), request("A♠ K♠ Q♠ J♠").validate());
+        assert_eq!(
+            Err(PKError::TooManyCards),
+            request("A♠ K♠ Q♠ J♠ T♠ 9♠ 8♠ 7♠").validate()
+        );
+    }
+
+    #[test]
+    fn display__player_odds() {
+        let mut odds = PlayerOdds {
+            hand: Two::from_str("6♠ 6♥").unwrap(),
+            name: None,
+            wins: 1,
+            ties: 0,
+            win_percentage: 2.27,
+            tie_percentage: 0.0,
+            outs: Some(Cards::from_str("6♣").unwrap()),
+        };
+
+        assert_eq!("[6♠ 6♥] 2.3% (2.27%/0.00%) [1/0] OUTS: 6♣", odds.to_string());
+
+        odds.name = Some("Gus".to_string());
+        odds.outs = Some(Cards::default());
+
+        assert_eq!("Gus [6♠ 6♥] 2.3% (2.27%/0.00%) [1/0]", odds.to_string());
+    }
+}
@@ -0,0 +1,219 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io::{BufRead, BufReader, Read, Take, Write};
+
+/// Just enough HTTP/1.1 to answer JSON requests on localhost. One request per connection, no
+/// chunked bodies, no keep alive. Anything fancier, and it's time for a real web framework.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl HttpRequest {
+    /// Bodies bigger than this are turned away. Nothing we take in comes anywhere close.
+    pub const MAX_BODY: usize = 64 * 1024;
+
+    /// The request line and headers together get this much, and not a byte more. Without it a
+    /// client could stream one endless header line at us and we'd keep buffering it.
+    pub const MAX_HEADER: u64 = 8 * 1024;
+
+    #[must_use]
+    pub fn new(method: &str, path: &str, body: &str) -> HttpRequest {
+        HttpRequest {
+            method: method.to_string(),
+            path: path.to_string(),
+            headers: HashMap::new(),
+            body: body.to_string(),
+        }
+    }
+
+    /// Reads a request off of the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `HttpResponse` to send back if the request can't be read: `400` if it isn't
+    /// HTTP, `413` if the body is too big, and `431` if the request line and headers run past
+    /// `MAX_HEADER`.
+    pub fn read<R: Read>(reader: R) -> Result<HttpRequest, HttpResponse> {
+        let mut reader = BufReader::new(reader);
+        let bad_request = || HttpResponse::error(400, "Unable to read the request");
+
+        let mut head = reader.by_ref().take(HttpRequest::MAX_HEADER);
+        let mut line = String::new();
+        HttpRequest::read_line(&mut head, &mut line)?;
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+            return Err(bad_request());
+        };
+        let path = target.split('?').next().unwrap_or_default().to_string();
+        let method = method.to_string();
+
+        let mut headers = HashMap::new();
+        loop {
+            let mut line = String::new();
+            if HttpRequest::read_line(&mut head, &mut line)? == 0 {
+                break;
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let length = match headers.get("content-length") {
+            Some(length) => length.parse::<usize>().map_err(|_| bad_request())?,
+            None => 0,
+        };
+        if length > HttpRequest::MAX_BODY {
+            return Err(HttpResponse::error(413, "The request body is too big"));
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).map_err(|_| bad_request())?;
+        let body = String::from_utf8(body).map_err(|_| bad_request())?;
+
+        Ok(HttpRequest {
+            method,
+            path,
+            headers,
+            body,
+        })
+    }
+
+    /// Reads one line of the request head. If the line is cut off because the head has used up
+    /// all of `MAX_HEADER`, the client gets a `431`.
+    fn read_line<R: BufRead>(head: &mut Take<R>, line: &mut String) -> Result<usize, HttpResponse> {
+        let read = head
+            .read_line(line)
+            .map_err(|_| HttpResponse::error(400, "Unable to read the request"))?;
+        if head.limit() == 0 && !line.ends_with('\n') {
+            return Err(HttpResponse::error(431, "The request headers are too big"));
+        }
+        Ok(read)
+    }
+}
+
+/// A JSON response. Everything the service sends back is JSON, errors included.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    error: &'a str,
+}
+
+impl HttpResponse {
+    /// Serializes the body, falling back to a `500` in the unlikely event that serde can't.
+    pub fn json<T: Serialize>(status: u16, body: &T) -> HttpResponse {
+        match serde_json::to_string(body) {
+            Ok(body) => HttpResponse { status, body },
+            Err(e) => HttpResponse::error(500, &e.to_string()),
+        }
+    }
+
+    #[must_use]
+    pub fn error(status: u16, message: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            body: serde_json::to_string(&ErrorBody { error: message }).unwrap_or_default(),
+        }
+    }
+
+    #[must_use]
+    pub fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Internal Server Error",
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Passes on any error writing to the stream.
+    pub fn write<W: Write>(&self, mut writer: W) -> std::io::Result<()> {
+        writer.write_all(self.to_string().as_bytes())?;
+        writer.flush()
+    }
+}
+
+impl Display for HttpResponse {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.reason(),
+            self.body.len(),
+            self.body
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod web__http_tests {
+    use super::*;
+
+    #[test]
+    fn read() {
+        let raw = "POST /eval?pretty HTTP/1.1\r\nHost: localhost\r\nContent-Length: 11\r\n\r\n{\"a\": \"b\"}\n";
+
+        let request = HttpRequest::read(raw.as_bytes()).unwrap();
+
+        assert_eq!("POST", request.method);
+        assert_eq!("/eval", request.path);
+        assert_eq!("localhost", request.headers["host"]);
+        assert_eq!("{\"a\": \"b\"}\n", request.body);
+    }
+
+    #[test]
+    fn read__no_body() {
+        let request = HttpRequest::read("GET /health HTTP/1.1\r\n\r\n".as_bytes()).unwrap();
+
+        assert_eq!(HttpRequest::new("GET", "/health", ""), request);
+    }
+
+    #[test]
+    fn read__errors() {
+        assert_eq!(400, HttpRequest::read("".as_bytes()).unwrap_err().status);
+        assert_eq!(
+            413,
+            HttpRequest::read("POST / HTTP/1.1\r\nContent-Length: 9999999\r\n\r\n".as_bytes())
+                .unwrap_err()
+                .status
+        );
+    }
+
+    #[test]
+    fn read__header_too_big() {
+        let padding = "x".repeat(usize::try_from(HttpRequest::MAX_HEADER).unwrap());
+        let long_line = format!("GET /{padding} HTTP/1.1\r\n\r\n");
+        let long_header = format!("GET / HTTP/1.1\r\nX-Padding: {padding}\r\n\r\n");
+
+        assert_eq!(431, HttpRequest::read(long_line.as_bytes()).unwrap_err().status);
+        assert_eq!(431, HttpRequest::read(long_header.as_bytes()).unwrap_err().status);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            "HTTP/1.1 404 Not Found\r\nContent-Type: application/json\r\nContent-Length: 16\r\nConnection: close\r\n\r\n{\"error\":\"Nope\"}",
+            HttpResponse::error(404, "Nope").to_string()
+        );
+    }
+}
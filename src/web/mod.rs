@@ -0,0 +1,4 @@
+pub mod api;
+pub mod http;
+pub mod server;
+pub mod service;
@@ -0,0 +1,160 @@
+use crate::web::http::{HttpRequest, HttpResponse};
+use crate::web::service::EquityService;
+use crate::PKError;
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// # Equity Server
+///
+/// Puts an `EquityService` on a localhost port. Every connection gets its own thread, one
+/// request and one response, and then it's closed. Preflop calculations can take a while, so
+/// there's no write timeout, but a client that connects and then doesn't say anything is dropped
+/// after `TIMEOUT`. Once `MAX_CONNECTIONS` are open, anyone else gets a `503` until one of them
+/// finishes, so a pile of slow clients can't run us out of threads.
+pub struct EquityServer {
+    service: Arc<EquityService>,
+    listener: TcpListener,
+    connections: Arc<AtomicUsize>,
+}
+
+/// Holds one of the server's connection slots, and gives it back when the thread is done.
+struct Connection(Arc<AtomicUsize>);
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl EquityServer {
+    /// How long the server waits on a client to send its request.
+    pub const TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// How many connections are answered at once.
+    pub const MAX_CONNECTIONS: usize = 64;
+
+    /// Starts listening on a localhost port. Pass in `0` to let the operating system pick one.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the port can't be bound.
+    pub fn bind(service: Arc<EquityService>, port: u16) -> Result<EquityServer, PKError> {
+        let listener = TcpListener::bind(("127.0.0.1", port)).map_err(|_| PKError::Fubar)?;
+        Ok(EquityServer {
+            service,
+            listener,
+            connections: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// The port the server is listening on.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::Fubar` if the socket has gone away.
+    pub fn port(&self) -> Result<u16, PKError> {
+        self.listener
+            .local_addr()
+            .map(|address| address.port())
+            .map_err(|_| PKError::Fubar)
+    }
+
+    /// Answers requests until the process is stopped.
+    pub fn serve(&self) {
+        for stream in self.listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if self.connections.fetch_add(1, Ordering::SeqCst) >= EquityServer::MAX_CONNECTIONS {
+                        self.connections.fetch_sub(1, Ordering::SeqCst);
+                        log::warn!("EquityServer::serve() turning away a connection, too many open");
+                        let _ = HttpResponse::error(503, "The server is busy").write(&stream);
+                        continue;
+                    }
+                    let connection = Connection(Arc::clone(&self.connections));
+                    let service = Arc::clone(&self.service);
+                    thread::spawn(move || {
+                        EquityServer::answer(&service, &stream);
+                        drop(connection);
+                    });
+                }
+                Err(e) => log::warn!("EquityServer::serve() {e}"),
+            }
+        }
+    }
+
+    fn answer(service: &EquityService, stream: &TcpStream) {
+        if stream.set_read_timeout(Some(EquityServer::TIMEOUT)).is_err() {
+            return;
+        }
+        let response = match HttpRequest::read(stream) {
+            Ok(request) => {
+                let response = service.handle(&request);
+                log::info!("{} {} {}", request.method, request.path, response.status);
+                response
+            }
+            Err(response) => response,
+        };
+        if let Err(e) = response.write(stream) {
+            log::warn!("EquityServer::answer() {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod web__server_tests {
+    use super::*;
+    use std::io::{Read, Write};
+
+    fn request(port: u16, raw: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        stream.write_all(raw.as_bytes()).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    #[test]
+    fn serve() {
+        let service = Arc::new(EquityService::in_memory().unwrap());
+        service.preload();
+        let server = EquityServer::bind(service, 0).unwrap();
+        let port = server.port().unwrap();
+        thread::spawn(move || server.serve());
+
+        let health = request(port, "GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        let body = r#"{"cards": "A♠ K♠ Q♠ J♠ T♠"}"#;
+        let eval = request(
+            port,
+            &format!("POST /eval HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}", body.len()),
+        );
+
+        assert!(health.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(health.contains(r#""ready":true"#));
+        assert!(eval.contains(r#""class":"RoyalFlush""#));
+        assert!(request(port, "nonsense\r\n\r\n").starts_with("HTTP/1.1 400 Bad Request"));
+    }
+
+    #[test]
+    fn serve__too_many_connections() {
+        let service = Arc::new(EquityService::in_memory().unwrap());
+        let server = EquityServer::bind(service, 0).unwrap();
+        let port = server.port().unwrap();
+        thread::spawn(move || server.serve());
+
+        let idle: Vec<TcpStream> = (0..EquityServer::MAX_CONNECTIONS)
+            .map(|_| TcpStream::connect(("127.0.0.1", port)).unwrap())
+            .collect();
+        let mut busy = String::new();
+        TcpStream::connect(("127.0.0.1", port))
+            .unwrap()
+            .read_to_string(&mut busy)
+            .unwrap();
+        drop(idle);
+
+        assert!(busy.starts_with("HTTP/1.1 503 Service Unavailable"));
+    }
+}
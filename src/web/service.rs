@@ -0,0 +1,777 @@
+use crate::analysis::case_evals::CaseEvals;
+use crate::analysis::store::db::headsup_preflop_result::HUPResult;
+use crate::analysis::store::db::hup_lookup::{HupLookup, HupOdds};
+use crate::analysis::store::db::multiway_lookup::{MultiwayLookup, MultiwayOdds};
+use crate::analysis::store::db::multiway_preflop_result::{MultiwayResult, SortedMultiway};
+use crate::analysis::store::db::schema::{Schema, Versioned};
+use crate::analysis::store::db::sqlite::Sqlable;
+use crate::arrays::combos::twos::Twos;
+use crate::arrays::five::Five;
+use crate::arrays::seven::Seven;
+use crate::arrays::six::Six;
+use crate::arrays::two::Two;
+use crate::arrays::HandRanker;
+use crate::cards::Cards;
+use crate::play::board::Board;
+use crate::play::game::Game;
+use crate::play::hole_cards::HoleCards;
+use crate::play::phases::PhaseHoldem;
+use crate::play::stages::flop_eval::FlopEval;
+use crate::util::wincounter::results::Results;
+use crate::web::api::{
+    EvalRequest, EvalResponse, GameRequest, HealthResponse, HupRequest, HupResponse, OddsResponse, OutsResponse,
+    PlayerOuts, RangeRequest, RangeResponse, StreetOdds,
+};
+use crate::web::http::{HttpRequest, HttpResponse};
+use crate::{PKError, Pile};
+use rusqlite::Connection;
+use serde::de::DeserializeOwned;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock};
+use std::thread;
+use std::time::Instant;
+
+/// # Equity Service
+///
+/// Everything behind the HTTP endpoints, minus the HTTP, so that it can be tested without
+/// opening a socket. `EquityServer` hands each request to `handle()`, and sends back whatever it
+/// returns.
+///
+/// | Endpoint       | Body             | Returns          |
+/// |----------------|------------------|------------------|
+/// | `GET /health`  |                  | `HealthResponse` |
+/// | `GET /ready`   |                  | `HealthResponse`, `503` until the tables are loaded |
+/// | `POST /eval`   | `EvalRequest`    | `EvalResponse`   |
+/// | `POST /odds`   | `GameRequest`    | `OddsResponse`   |
+/// | `POST /outs`   | `GameRequest`    | `OutsResponse`   |
+/// | `POST /hup`    | `HupRequest`     | `HupResponse`    |
+/// | `POST /range`  | `RangeRequest`   | `RangeResponse`  |
+///
+/// The heads up and multiway tables are loaded into memory by `preload()`, which the `serve`
+/// example kicks off in the background so that the socket is up straight away. Until it's done,
+/// anything that needs the preflop tables gets a `503`, rather than going off and calculating
+/// something that's about to be sitting in memory. Preflop matchups that aren't stored at all
+/// are calculated, saved and cached, so the service gets faster the more it's used.
+///
+/// Bad requests are a `400` with the `PKError` as the message, and a panic anywhere in a
+/// calculation is caught and turned into a `500`, so one strange hand doesn't take the server
+/// down with it.
+pub struct EquityService {
+    conn: Mutex<Connection>,
+    hups: RwLock<HupLookup>,
+    multiway: RwLock<MultiwayLookup>,
+    ready: AtomicBool,
+    started: Instant,
+}
+
+impl EquityService {
+    /// Opens the `SQLite` file with the stored results, creating the tables if they aren't there.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the file can't be opened or the tables can't be created,
+    /// and `PKError::StorageError` if they need upgrading first.
+    pub fn open(path: &str) -> Result<EquityService, PKError> {
+        EquityService::new(Connection::open(path).map_err(|_| PKError::SqlError)?)
+    }
+
+    /// A service that starts out knowing nothing, and forgets everything it calculates when it
+    /// stops.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the tables can't be created.
+    pub fn in_memory() -> Result<EquityService, PKError> {
+        EquityService::new(Connection::open_in_memory().map_err(|_| PKError::SqlError)?)
+    }
+
+    /// Creates the tables if they aren't there, but won't upgrade ones that are. Upgrades are
+    /// for `cargo run --example schema -- -u`, where somebody's watching.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::SqlError` if the tables can't be read or created, and
+    /// `PKError::StorageError` if they're from an older version of the schema.
+    pub fn new(conn: Connection) -> Result<EquityService, PKError> {
+        EquityService::prepare::<HUPResult, _>(&conn)?;
+        EquityService::prepare::<MultiwayResult, _>(&conn)?;
+        Ok(EquityService {
+            conn: Mutex::new(conn),
+            hups: RwLock::new(HupLookup::new()),
+            multiway: RwLock::new(MultiwayLookup::new()),
+            ready: AtomicBool::new(false),
+            started: Instant::now(),
+        })
+    }
+
+    fn prepare<T: Versioned + Sqlable<T, S>, S>(conn: &Connection) -> Result<(), PKError> {
+        let latest = Schema::latest::<T>();
+        match Schema::version::<T>(conn).map_err(|_| PKError::SqlError)? {
+            (0, _) => T::create_table(conn).map(|_| ()).map_err(|_| PKError::SqlError),
+            (version, _) if version < latest => Err(PKError::StorageError(format!(
+                "{} is at v{version} and needs upgrading to v{latest} with the schema example",
+                T::STORE
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Loads the stored heads up and multiway results into memory, and marks the service as
+    /// ready. If the tables can't be read, that gets logged and everything keeps going to the
+    /// database, which will say what's wrong with it when it's asked.
+    pub fn preload(&self) {
+        let loaded = {
+            let conn = self.conn();
+            HupLookup::preload(&conn).and_then(|hups| Ok((hups, MultiwayLookup::preload(&conn)?)))
+        };
+        match loaded {
+            Ok((hups, multiway)) => {
+                log::info!(
+                    "EquityService::preload() {} heads up and {} multiway matchups",
+                    hups.len(),
+                    multiway.len()
+                );
+                *self.hups.write().unwrap_or_else(PoisonError::into_inner) = hups;
+                *self.multiway.write().unwrap_or_else(PoisonError::into_inner) = multiway;
+            }
+            Err(e) => log::error!("EquityService::preload() couldn't read the stored results: {e}"),
+        }
+        self.ready.store(true, Ordering::SeqCst);
+    }
+
+    /// Runs `preload()` on its own thread.
+    pub fn preload_in_background(self: &Arc<Self>) -> thread::JoinHandle<()> {
+        let service = Arc::clone(self);
+        thread::spawn(move || service.preload())
+    }
+
+    #[must_use]
+    pub fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::SeqCst)
+    }
+
+    #[must_use]
+    pub fn health(&self) -> HealthResponse {
+        HealthResponse {
+            status: "ok".to_string(),
+            ready: self.is_ready(),
+            uptime_secs: self.started.elapsed().as_secs(),
+            hups: self.hups.read().unwrap_or_else(PoisonError::into_inner).len(),
+            multiway: self.multiway.read().unwrap_or_else(PoisonError::into_inner).len(),
+        }
+    }
+
+    /// Works out the response to a request, whatever happens.
+    #[must_use]
+    pub fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        catch_unwind(AssertUnwindSafe(|| self.route(request)))
+            .unwrap_or_else(|_| HttpResponse::error(500, "The calculation blew up"))
+    }
+
+    fn route(&self, request: &HttpRequest) -> HttpResponse {
+        let method = match request.path.as_str() {
+            "/health" | "/ready" => "GET",
+            "/eval" | "/odds" | "/outs" | "/hup" | "/range" => "POST",
+            _ => return HttpResponse::error(404, &format!("Nothing at {}", request.path)),
+        };
+        if request.method != method {
+            return HttpResponse::error(405, &format!("{} only takes {method}", request.path));
+        }
+
+        match request.path.as_str() {
+            "/health" => HttpResponse::json(200, &self.health()),
+            "/ready" => HttpResponse::json(if self.is_ready() { 200 } else { 503 }, &self.health()),
+            "/eval" => EquityService::respond(request, |r: EvalRequest| EquityService::eval(&r)),
+            "/outs" => EquityService::respond(request, |r: GameRequest| EquityService::outs(&r)),
+            "/odds" => match EquityService::parse::<GameRequest>(request) {
+                Ok(r) if r.preflop && !self.is_ready() => EquityService::loading(),
+                Ok(r) => EquityService::reply(self.odds(&r)),
+                Err(response) => response,
+            },
+            "/hup" if !self.is_ready() => EquityService::loading(),
+            "/hup" => match EquityService::parse::<HupRequest>(request) {
+                Ok(r) => match self.hup(&r) {
+                    Ok(None) => HttpResponse::error(404, &format!("{} vs {} isn't stored", r.hero, r.villain)),
+                    result => EquityService::reply(result),
+                },
+                Err(response) => response,
+            },
+            _ => self.range_response(request),
+        }
+    }
+
+    fn range_response(&self, request: &HttpRequest) -> HttpResponse {
+        match EquityService::parse::<RangeRequest>(request) {
+            Ok(r) if r.board.as_ref().map_or(true, Cards::is_empty) && !self.is_ready() => EquityService::loading(),
+            Ok(r) => match self.range(&r) {
+                Err(PKError::Incomplete) => HttpResponse::error(
+                    404,
+                    &format!(
+                        "Not every preflop matchup against {} is stored. Send \"partial\": true for the ones that are",
+                        r.range
+                    ),
+                ),
+                result => EquityService::reply(result),
+            },
+            Err(response) => response,
+        }
+    }
+
+    /// The best five card hand out of five to seven cards.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::NotEnoughCards` or `PKError::TooManyCards` if there aren't five to seven
+    /// cards, and `PKError::InvalidCard` if any of them didn't parse.
+    pub fn eval(request: &EvalRequest) -> Result<EvalResponse, PKError> {
+        request.validate()?;
+        if request.cards.iter().any(|card| !card.is_dealt()) {
+            return Err(PKError::InvalidCard);
+        }
+        let cards = request.cards.clone();
+        let (hand_rank, best) = match cards.len() {
+            5 => Five::try_from(cards.clone())?.hand_rank_and_hand(),
+            6 => Six::try_from(cards.clone())?.hand_rank_and_hand(),
+            _ => Seven::try_from(cards.clone())?.hand_rank_and_hand(),
+        };
+        Ok(EvalResponse {
+            cards,
+            best: Cards::from(best.to_vec()),
+            rank: hand_rank.value,
+            name: format!("{:?}", hand_rank.name),
+            class: format!("{:?}", hand_rank.class),
+        })
+    }
+
+    /// Everybody's odds at each street that's been dealt, and before the flop if it's asked for.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `GameRequest::game()`, and returns `PKError::SqlError` if a
+    /// newly calculated preflop result can't be saved.
+    pub fn odds(&self, request: &GameRequest) -> Result<OddsResponse, PKError> {
+        let game = request.game()?;
+        let mut streets = Vec::new();
+
+        if request.preflop {
+            let results = if game.dead.is_empty() {
+                self.preflop(&game.hands)?
+            } else {
+                game.preflop_results()
+            };
+            streets.push(StreetOdds::from_results(
+                PhaseHoldem::Preflop,
+                Cards::default(),
+                &game,
+                &results,
+            ));
+        }
+        if game.board.flop.is_dealt() {
+            let results = FlopEval::try_from(game.clone())?.results;
+            streets.push(StreetOdds::from_results(
+                PhaseHoldem::Flop,
+                game.board.flop.cards(),
+                &game,
+                &results,
+            ));
+        }
+        if game.board.turn.is_dealt() {
+            let (_, _, results, outs) = game.turn_calculations();
+            let mut turn = game.board.flop.cards();
+            turn.insert(game.board.turn);
+            let mut street = StreetOdds::from_results(PhaseHoldem::Turn, turn, &game, &results);
+            for (i, player) in street.players.iter_mut().enumerate() {
+                player.outs = Some(outs.get(i + 1).cloned().unwrap_or_default());
+            }
+            streets.push(street);
+        }
+        if game.board.river.is_dealt() {
+            let results = EquityService::river(&game)?;
+            streets.push(StreetOdds::from_results(
+                PhaseHoldem::River,
+                game.board.cards(),
+                &game,
+                &results,
+            ));
+        }
+
+        Ok(OddsResponse {
+            game: game.to_string(),
+            streets,
+        })
+    }
+
+    /// The cards that win it for each player on the river.
+    ///
+    /// # Errors
+    ///
+    /// Passes on any errors from `GameRequest::game()`, and returns `PKError::Incomplete` if the
+    /// game isn't at the turn.
+    pub fn outs(request: &GameRequest) -> Result<OutsResponse, PKError> {
+        let game = request.game()?;
+        if !game.board.turn.is_dealt() || game.board.river.is_dealt() {
+            return Err(PKError::Incomplete);
+        }
+        let (_, _, _, outs) = game.turn_calculations();
+        Ok(OutsResponse {
+            game: game.to_string(),
+            players: game
+                .hands
+                .iter()
+                .enumerate()
+                .map(|(i, hand)| {
+                    let outs = outs.get(i + 1).cloned().unwrap_or_default();
+                    PlayerOuts {
+                        hand: *hand,
+                        count: outs.len(),
+                        outs,
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    /// The stored odds for a heads up matchup, or freshly calculated ones if it isn't stored and
+    /// the request asks for them. `None` means it isn't stored and it wasn't asked to be.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidCard` if either hand didn't parse, `PKError::Duplicate` if they
+    /// share a card, and `PKError::SqlError` if a new result can't be saved.
+    pub fn hup(&self, request: &HupRequest) -> Result<Option<HupResponse>, PKError> {
+        if let Some(odds) = self.find_hup(request.hero, request.villain)? {
+            return Ok(Some(HupResponse::new(odds, false)));
+        }
+        if !request.calculate {
+            return Ok(None);
+        }
+        let odds = self.calculate_hup(request.hero, request.villain)?;
+        Ok(Some(HupResponse::new(odds, true)))
+    }
+
+    /// The hand's equity against every combination in the range, averaged out. Before the flop
+    /// it's only the stored matchups. Anything else is counted as `missing`, and unless the
+    /// request says a `partial` answer is OK, that's an error rather than a number that looks
+    /// like it's for the whole range.
+    ///
+    /// # Errors
+    ///
+    /// Returns `PKError::InvalidIndex` if the range doesn't parse, `PKError::InvalidCard` if the
+    /// hand or board don't, the usual `Board` errors if the board is a strange size, and
+    /// `PKError::Incomplete` if preflop matchups are missing and `partial` isn't set.
+    pub fn range(&self, request: &RangeRequest) -> Result<RangeResponse, PKError> {
+        let twos = Twos::from_str(&request.range)?;
+        let board_cards = request.board.clone().unwrap_or_default();
+        if !request.hand.is_dealt() || board_cards.iter().any(|card| !card.is_dealt()) {
+            return Err(PKError::InvalidCard);
+        }
+        let board = if board_cards.is_empty() {
+            Board::default()
+        } else {
+            Board::try_from(board_cards.clone())?
+        };
+        if board_cards.iter().any(|card| request.hand.contains_card(*card)) {
+            return Err(PKError::Duplicate);
+        }
+
+        let mut response = RangeResponse {
+            hand: request.hand,
+            range: request.range.clone(),
+            board: board_cards.clone(),
+            ..Default::default()
+        };
+        let (mut wins, mut ties) = (0.0, 0.0);
+        for villain in twos.to_vec() {
+            if villain
+                .to_vec()
+                .iter()
+                .any(|card| request.hand.contains_card(*card) || board_cards.contains(card))
+            {
+                response.blocked += 1;
+                continue;
+            }
+            let results = if board_cards.is_empty() {
+                let Some(odds) = self.find_hup(request.hand, villain)? else {
+                    response.missing += 1;
+                    continue;
+                };
+                Results::from(&odds)
+            } else {
+                EquityService::postflop(&Game::new(HoleCards::from(vec![request.hand, villain]), board))?
+            };
+            let (win_percentage, tie_percentage) = results.wins_and_ties_percentages(0);
+            wins += win_percentage;
+            ties += tie_percentage;
+            response.combos += 1;
+        }
+        if response.missing > 0 && !request.partial {
+            return Err(PKError::Incomplete);
+        }
+        response.partial = response.missing > 0;
+
+        if response.combos > 0 {
+            #[allow(clippy::cast_precision_loss)]
+            let combos = response.combos as f32;
+            response.win_percentage = wins / combos;
+            response.tie_percentage = ties / combos;
+            response.equity = response.win_percentage + response.tie_percentage / 2.0;
+        }
+        Ok(response)
+    }
+
+    /// Preflop odds from the tables where there's one for this many players, and calculated the
+    /// long way where there isn't.
+    fn preflop(&self, hands: &HoleCards) -> Result<Results, PKError> {
+        let twos: Vec<Two> = hands.iter().copied().collect();
+        match twos.as_slice() {
+            [hero, villain] => {
+                let odds = match self.find_hup(*hero, *villain)? {
+                    Some(odds) => odds,
+                    None => self.calculate_hup(*hero, *villain)?,
+                };
+                Ok(Results::from(&odds))
+            }
+            hands if (SortedMultiway::MIN_PLAYERS..=SortedMultiway::MAX_PLAYERS).contains(&hands.len()) => {
+                let odds = match self.find_multiway(hands)? {
+                    Some(odds) => odds,
+                    None => self.calculate_multiway(hands)?,
+                };
+                Ok(Results::from(&odds))
+            }
+            _ => Ok(Game::new(hands.clone(), Board::default()).preflop_results()),
+        }
+    }
+
+    /// The odds at the last street that's been dealt.
+    fn postflop(game: &Game) -> Result<Results, PKError> {
+        if game.board.river.is_dealt() {
+            EquityService::river(game)
+        } else if game.board.turn.is_dealt() {
+            let (_, _, results, _) = game.turn_calculations();
+            Ok(results)
+        } else {
+            let wins = CaseEvals::from_holdem_at_flop(game.board.flop, &game.hands).wins();
+            Ok(Results::from_wins(&wins, game.hands.len()))
+        }
+    }
+
+    fn river(game: &Game) -> Result<Results, PKError> {
+        let wins = CaseEvals::from(vec![game.river_case_eval()?]).wins();
+        Ok(Results::from_wins(&wins, game.hands.len()))
+    }
+
+    /// Checks memory, and then the database, in case something else has stored it since the
+    /// tables were loaded.
+    fn find_hup(&self, hero: Two, villain: Two) -> Result<Option<HupOdds>, PKError> {
+        let hups = self.hups.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(odds) = hups.find(None, hero, villain)? {
+            return Ok(Some(odds));
+        }
+        hups.find(Some(&self.conn()), hero, villain)
+    }
+
+    /// Runs every board out for the canonical version of the matchup, without holding on to any
+    /// of the locks while it does, and then saves it and adds it to the index.
+    fn calculate_hup(&self, hero: Two, villain: Two) -> Result<HupOdds, PKError> {
+        let (canonical, _) = HupLookup::canonical(hero, villain);
+        log::info!("EquityService::calculate_hup() calculating {canonical}");
+        let results = Game::new(
+            HoleCards::from(vec![canonical.higher, canonical.lower]),
+            Board::default(),
+        )
+        .preflop_results();
+        let count = |n: usize| u64::try_from(n).unwrap_or_default();
+        let result = HUPResult {
+            higher: canonical.higher_as_bard(),
+            lower: canonical.lower_as_bard(),
+            higher_wins: count(results.wins_and_ties(0).0),
+            lower_wins: count(results.wins_and_ties(1).0),
+            ties: count(results.wins_and_ties(0).1),
+        };
+        {
+            let conn = self.conn();
+            if HUPResult::select(&conn, &canonical)
+                .map_err(|_| PKError::SqlError)?
+                .is_none()
+            {
+                HUPResult::insert(&conn, &result).map_err(|_| PKError::SqlError)?;
+            }
+        }
+        let mut hups = self.hups.write().unwrap_or_else(PoisonError::into_inner);
+        hups.add(&result);
+        hups.find(None, hero, villain)?.ok_or(PKError::Fubar)
+    }
+
+    fn find_multiway(&self, hands: &[Two]) -> Result<Option<MultiwayOdds>, PKError> {
+        let multiway = self.multiway.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(odds) = multiway.find(None, hands)? {
+            return Ok(Some(odds));
+        }
+        multiway.find(Some(&self.conn()), hands)
+    }
+
+    /// The same as `calculate_hup()`, for three and four hands.
+    fn calculate_multiway(&self, hands: &[Two]) -> Result<MultiwayOdds, PKError> {
+        let (canonical, _) = SortedMultiway::canonical(hands)?;
+        log::info!("EquityService::calculate_multiway() calculating {canonical}");
+        let result = MultiwayResult::from(&canonical);
+        {
+            let conn = self.conn();
+            if MultiwayResult::select(&conn, &canonical)
+                .map_err(|_| PKError::SqlError)?
+                .is_none()
+            {
+                MultiwayResult::insert(&conn, &result).map_err(|_| PKError::SqlError)?;
+            }
+        }
+        let mut multiway = self.multiway.write().unwrap_or_else(PoisonError::into_inner);
+        multiway.add(result);
+        multiway.find(None, hands)?.ok_or(PKError::Fubar)
+    }
+
+    fn conn(&self) -> MutexGuard<'_, Connection> {
+        self.conn.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn parse<T: DeserializeOwned>(request: &HttpRequest) -> Result<T, HttpResponse> {
+        serde_json::from_str(&request.body).map_err(|e| HttpResponse::error(400, &e.to_string()))
+    }
+
+    fn respond<T, R, F>(request: &HttpRequest, f: F) -> HttpResponse
+    where
+        T: DeserializeOwned,
+        R: serde::Serialize,
+        F: FnOnce(T) -> Result<R, PKError>,
+    {
+        match EquityService::parse::<T>(request) {
+            Ok(body) => EquityService::reply(f(body)),
+            Err(response) => response,
+        }
+    }
+
+    fn reply<R: serde::Serialize>(result: Result<R, PKError>) -> HttpResponse {
+        match result {
+            Ok(body) => HttpResponse::json(200, &body),
+            Err(e @ (PKError::SqlError | PKError::Fubar | PKError::StorageError(_))) => {
+                HttpResponse::error(500, &e.to_string())
+            }
+            Err(e) => HttpResponse::error(400, &e.to_string()),
+        }
+    }
+
+    fn loading() -> HttpResponse {
+        HttpResponse::error(503, "Still loading the preflop tables")
+    }
+}
+
+#[cfg(test)]
+#[allow(non_snake_case)]
+mod web__service_tests {
+    use super::*;
+    use crate::util::data::TestData;
+
+    fn service() -> EquityService {
+        let conn = Connection::open_in_memory().unwrap();
+        HUPResult::create_table(&conn).unwrap();
+        HUPResult::insert(&conn, &TestData::the_hand_as_hup_result()).unwrap();
+        let service = EquityService::new(conn).unwrap();
+        service.preload();
+        service
+    }
+
+    fn post(service: &EquityService, path: &str, body: &str) -> HttpResponse {
+        service.handle(&HttpRequest::new("POST", path, body))
+    }
+
+    /// A file from before the indexes is left the way it was.
+    #[test]
+    fn open__old_schema() {
+        let path = std::env::temp_dir()
+            .join(format!("pkcore_service_old_{}.db", std::process::id()))
+            .to_string_lossy()
+            .to_string();
+        let _ = std::fs::remove_file(&path);
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(HUPResult::MIGRATIONS[0].sql).unwrap();
+        HUPResult::insert(&conn, &TestData::the_hand_as_hup_result()).unwrap();
+        conn.close().unwrap();
+
+        let service = EquityService::open(&path);
+        let version = Schema::version::<HUPResult>(&Connection::open(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(service, Err(PKError::StorageError(_))));
+        assert_eq!((1, true), version);
+    }
+
+    #[test]
+    fn handle__ready() {
+        let service = EquityService::in_memory().unwrap();
+
+        assert_eq!(200, service.handle(&HttpRequest::new("GET", "/health", "")).status);
+        assert_eq!(503, service.handle(&HttpRequest::new("GET", "/ready", "")).status);
+        assert_eq!(
+            503,
+            post(&service, "/hup", r#"{"hero": "A♠ A♥", "villain": "K♠ K♥"}"#).status
+        );
+
+        service.preload();
+        let response = service.handle(&HttpRequest::new("GET", "/ready", ""));
+        let health: HealthResponse = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(200, response.status);
+        assert!(health.ready);
+    }
+
+    #[test]
+    fn handle__eval() {
+        let response = post(&service(), "/eval", r#"{"cards": "9♠ A♠ K♠ 2♦ Q♠ J♠ T♠"}"#);
+        let eval: EvalResponse = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(200, response.status);
+        assert_eq!("A♠ K♠ Q♠ J♠ T♠", eval.best.to_string());
+        assert_eq!(1, eval.rank);
+        assert_eq!("StraightFlush", eval.name);
+        assert_eq!("RoyalFlush", eval.class);
+    }
+
+    #[test]
+    fn handle__odds() {
+        let response = post(
+            &service(),
+            "/odds",
+            r#"{"game": "HE: 6♠ 6♥ 5♦ 5♣ - 9♣ 6♦ 5♥ 5♠ 8♠", "preflop": true}"#,
+        );
+        let odds: OddsResponse = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(200, response.status);
+        assert_eq!(
+            vec![
+                PhaseHoldem::Preflop,
+                PhaseHoldem::Flop,
+                PhaseHoldem::Turn,
+                PhaseHoldem::River
+            ],
+            odds.streets
+                .iter()
+                .map(|street| street.street)
+                .collect::<Vec<PhaseHoldem>>()
+        );
+        assert_eq!(1_365_284, odds.streets[0].players[0].wins);
+        assert_eq!(990, odds.streets[1].boards);
+        assert_eq!("6♣", odds.streets[2].players[0].outs.clone().unwrap().to_string());
+        assert_eq!(1, odds.streets[3].players[1].wins);
+    }
+
+    #[test]
+    fn handle__outs() {
+        let response = post(
+            &service(),
+            "/outs",
+            r#"{"hands": ["6♠ 6♥", "5♦ 5♣"], "board": "9♣ 6♦ 5♥ 5♠"}"#,
+        );
+        let outs: OutsResponse = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(200, response.status);
+        assert_eq!(1, outs.players[0].count);
+        assert_eq!(43, outs.players[1].count);
+        assert_eq!(
+            400,
+            post(
+                &service(),
+                "/outs",
+                r#"{"hands": ["6♠ 6♥", "5♦ 5♣"], "board": "9♣ 6♦ 5♥"}"#
+            )
+            .status
+        );
+    }
+
+    #[test]
+    fn handle__hup() {
+        let service = service();
+
+        let response = post(&service, "/hup", r#"{"hero": "5♠ 5♥", "villain": "6♦ 6♣"}"#);
+        let hup: HupResponse = serde_json::from_str(&response.body).unwrap();
+
+        assert_eq!(200, response.status);
+        assert_eq!(314_904, hup.odds.wins);
+        assert_eq!(1_365_284, hup.odds.losses);
+        assert!(!hup.calculated);
+        assert_eq!(
+            404,
+            post(&service, "/hup", r#"{"hero": "A♠ A♥", "villain": "K♠ K♥"}"#).status
+        );
+        assert_eq!(
+            400,
+            post(&service, "/hup", r#"{"hero": "A♠ A♥", "villain": "A♠ K♥"}"#).status
+        );
+    }
+
+    #[test]
+    fn handle__range() {
+        let service = service();
+
+        let preflop: RangeResponse = serde_json::from_str(
+            &post(
+                &service,
+                "/range",
+                r#"{"hand": "5♦ 5♣", "range": "66", "partial": true}"#,
+            )
+            .body,
+        )
+        .unwrap();
+        let flop: RangeResponse = serde_json::from_str(
+            &post(
+                &service,
+                "/range",
+                r#"{"hand": "5♦ 5♣", "range": "66", "board": "9♣ 6♦ 5♥"}"#,
+            )
+            .body,
+        )
+        .unwrap();
+
+        assert_eq!(1, preflop.combos);
+        assert_eq!(5, preflop.missing);
+        assert!(preflop.partial);
+        assert_eq!("18.39", format!("{:.2}", preflop.win_percentage));
+        assert!(!flop.partial);
+        assert_eq!(3, flop.combos);
+        assert_eq!(3, flop.blocked);
+        assert!(flop.equity < 50.0);
+    }
+
+    #[test]
+    fn handle__errors() {
+        let service = service();
+
+        assert_eq!(404, service.handle(&HttpRequest::new("GET", "/nope", "")).status);
+        assert_eq!(405, service.handle(&HttpRequest::new("GET", "/eval", "")).status);
+        assert_eq!(405, post(&service, "/health", "").status);
+        assert_eq!(400, post(&service, "/eval", "not json").status);
+        assert_eq!(400, post(&service, "/eval", r#"{"cards": "A♠ K♠"}"#).status);
+        assert_eq!(400, post(&service, "/eval", r#"{"cards": "A♠ A♠ K♠ Q♠ J♠ T♠"}"#).status);
+        assert_eq!(
+            400,
+            post(
+                &service,
+                "/odds",
+                r#"{"hands": ["A♠ K♠", "8♦ 7♦"], "board": "9x 8♠ 2♣"}"#
+            )
+            .status
+        );
+        assert_eq!(
+            404,
+            post(&service, "/range", r#"{"hand": "5♦ 5♣", "range": "66"}"#).status
+        );
+        assert_eq!(
+            400,
+            post(&service, "/range", r#"{"hand": "5♦ 5♣", "range": "ZZ"}"#).status
+        );
+        assert_eq!(
+            r#"{"error":"Not Enough Hands Error"}"#,
+            post(&service, "/odds", r#"{"hands": ["A♠ A♥"]}"#).body
+        );
+    }
+}